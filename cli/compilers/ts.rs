@@ -3,7 +3,7 @@ use std::{
 	collections::HashSet,
 	fs,
 	io,
-	path::PathBuf,
+	path::{Path, PathBuf},
 	pin::Pin,
 	str,
 	sync::{atomic::Ordering, Mutex},
@@ -21,21 +21,49 @@ use crate::{
 	file_fetcher::{SourceFile, SourceFileFetcher},
 	global_state::ThreadSafeGlobalState,
 	msg,
-	source_maps::SourceMapGetter,
+	progress::ProgressEvent,
+	source_maps::{inline_source_map, SourceMapGetter},
 	startup_data,
 	state::*,
 	version,
-	worker::Worker,
+	worker::{Worker, WorkerBuilder},
 };
 
 lazy_static! {
 	static ref CHECK_JS_RE: Regex = Regex::new(r#""checkJs"\s*?:\s*?true"#).unwrap();
+	static ref EXTENDS_RE: Regex = Regex::new(r#""extends"\s*?:\s*?"([^"]*)""#).unwrap();
+}
+
+/// Limits how long a chain of `extends` is followed, which also stops cycles.
+const MAX_EXTENDS_DEPTH:usize = 16;
+
+/// Appends the contents of the configuration files that `config` extends,
+/// transitively, so that editing a base configuration changes the hash too.
+/// Missing files are skipped here; the compiler reports them.
+fn extend_config_hash(hash:&mut Vec<u8>, config:&[u8], config_dir:&Path, depth:usize) {
+	if depth >= MAX_EXTENDS_DEPTH {
+		return;
+	}
+	let config_str = match std::str::from_utf8(config) {
+		Ok(s) => s,
+		Err(_) => return,
+	};
+	let base = match EXTENDS_RE.captures(config_str) {
+		Some(captures) => config_dir.join(&captures[1]),
+		None => return,
+	};
+	if let Ok(base_config) = fs::read(&base) {
+		hash.extend_from_slice(&base_config);
+		let base_dir = base.parent().unwrap_or(config_dir).to_owned();
+		extend_config_hash(hash, &base_config, &base_dir, depth + 1);
+	}
 }
 
 /// Struct which represents the state of the compiler
 /// configuration where the first is canonical name for the configuration file,
 /// second is a vector of the bytes of the contents of the configuration file,
-/// third is bytes of the hash of contents.
+/// third is bytes of the hash of contents, including the contents of any
+/// configuration file it `extends`.
 #[derive(Clone)]
 pub struct CompilerConfig {
 	pub path:Option<PathBuf>,
@@ -88,8 +116,13 @@ impl CompilerConfig {
 			_ => None,
 		};
 
-		let config_hash = match &config {
-			Some(bytes) => bytes.clone(),
+		let config_hash = match (&config, &config_file) {
+			(Some(bytes), Some(config_file)) => {
+				let mut hash = bytes.clone();
+				let config_dir = config_file.parent().unwrap_or_else(|| Path::new("."));
+				extend_config_hash(&mut hash, bytes, config_dir, 0);
+				hash
+			},
 			_ => b"".to_vec(),
 		};
 
@@ -231,8 +264,12 @@ impl TsCompiler {
 		// Count how many times we start the compiler worker.
 		global_state.metrics.compiler_starts.fetch_add(1, Ordering::SeqCst);
 
-		let mut worker =
-			Worker::new("TS".to_string(), startup_data::compiler_isolate_init(), worker_state, ext);
+		// `--max-heap-mb` is for user code, type checking large programs needs more.
+		let mut worker = WorkerBuilder::new(worker_state, ext)
+			.name("TS")
+			.startup_data(startup_data::compiler_isolate_init())
+			.max_heap_mb(None)
+			.build();
 		worker.execute("denoMain()").unwrap();
 		worker.execute("workerMain()").unwrap();
 		worker.execute("compilerMain()").unwrap();
@@ -333,6 +370,7 @@ impl TsCompiler {
 
 		debug!(">>>>> compile_sync START");
 		let module_url = source_file.url.clone();
+		info!("Compiling {}", module_url);
 
 		debug!("Running rust part of compile_sync, module specifier: {}", &source_file.url);
 
@@ -342,6 +380,7 @@ impl TsCompiler {
 		let worker = TsCompiler::setup_worker(global_state.clone());
 		let worker_ = worker.clone();
 		let compiling_job = global_state.progress.add("Compile", &module_url.to_string());
+		global_state.progress.emit(ProgressEvent::CompileStarted { url:module_url.to_string() });
 		let global_state_ = global_state.clone();
 
 		async move {
@@ -349,6 +388,9 @@ impl TsCompiler {
 			worker.await?;
 			debug!("Sent message to worker");
 			let maybe_msg = worker_.get_message().await?;
+			global_state_
+				.progress
+				.emit(ProgressEvent::CompileFinished { url:module_url.to_string() });
 			if let Some(msg) = maybe_msg {
 				let json_str = std::str::from_utf8(&msg).unwrap();
 				debug!("Message: {}", json_str);
@@ -504,16 +546,21 @@ impl TsCompiler {
 
 impl SourceMapGetter for TsCompiler {
 	fn get_source_map(&self, script_name:&str) -> Option<Vec<u8>> {
-		self.try_to_resolve_and_get_source_map(script_name).map(|out| out.source_code)
+		self.try_to_resolve_and_get_source_map(script_name).map(|out| out.source_code).or_else(|| {
+			// Modules that weren't compiled by us, e.g. bundled JavaScript, may
+			// carry their own map inline.
+			self.try_resolve_and_get_source_file(script_name)
+				.and_then(|out| inline_source_map(script_name, &out.source_code))
+		})
 	}
 
 	fn get_source_line(&self, script_name:&str, line:usize) -> Option<String> {
+		// With an inline source map the line refers to the original source rather
+		// than to this file, so it may be out of range.
 		self.try_resolve_and_get_source_file(script_name).and_then(|out| {
-			str::from_utf8(&out.source_code).ok().and_then(|v| {
-				let lines:Vec<&str> = v.lines().collect();
-				assert!(lines.len() > line);
-				Some(lines[line].to_string())
-			})
+			str::from_utf8(&out.source_code)
+				.ok()
+				.and_then(|v| v.lines().nth(line).map(|line| line.to_string()))
 		})
 	}
 }
@@ -555,7 +602,7 @@ mod tests {
 	use tempfile::TempDir;
 
 	use super::*;
-	use crate::{fs as deno_fs, tokio_util};
+	use crate::{deno_dir::DenoDir, flags::DenoFlags, fs as deno_fs, progress::Progress, tokio_util};
 
 	#[test]
 	fn test_compile_async() {
@@ -674,6 +721,79 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_compiler_config_hash_extends() {
+		let temp_dir = TempDir::new().expect("tempdir fail");
+		let temp_dir_path = temp_dir.path();
+		let base_path = temp_dir_path.join("base.json");
+		let path = temp_dir_path.join("tsconfig.json");
+		let path_str = path.to_str().unwrap().to_string();
+
+		deno_fs::write_file(&path, br#"{ "extends": "./base.json" }"#, 0o666).unwrap();
+		deno_fs::write_file(&base_path, br#"{ "compilerOptions": {} }"#, 0o666).unwrap();
+		let before = CompilerConfig::load(Some(path_str.clone())).unwrap();
+
+		// Changing only the extended file invalidates the cache.
+		deno_fs::write_file(&base_path, br#"{ "compilerOptions": { "strict": true } }"#, 0o666)
+			.unwrap();
+		let after = CompilerConfig::load(Some(path_str.clone())).unwrap();
+		assert_ne!(before.hash, after.hash);
+
+		// A cycle doesn't hang.
+		deno_fs::write_file(&base_path, br#"{ "extends": "./tsconfig.json" }"#, 0o666).unwrap();
+		assert!(CompilerConfig::load(Some(path_str)).is_ok());
+	}
+
+	#[test]
+	fn test_compile_cache_config() {
+		let temp_dir = TempDir::new().expect("tempdir fail");
+		let path = temp_dir.path().join("tsconfig.json");
+		let base_path = temp_dir.path().join("base.json");
+		deno_fs::write_file(&path, br#"{ "extends": "./base.json" }"#, 0o666).unwrap();
+		deno_fs::write_file(&base_path, br#"{ "compilerOptions": {} }"#, 0o666).unwrap();
+
+		let p =
+			PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().unwrap().join("tests/002_hello.ts");
+		let source_file = SourceFile {
+			url:ModuleSpecifier::resolve_url_or_path(p.to_str().unwrap()).unwrap().as_url().clone(),
+			filename:p.clone(),
+			media_type:msg::MediaType::TypeScript,
+			source_code:include_bytes!("../tests/002_hello.ts").to_vec(),
+		};
+		// Every run is a fresh program sharing a DENO_DIR, and reports how often
+		// it started the compiler.
+		let deno_dir_root = temp_dir.path().join("deno_dir");
+		let run = || {
+			let state = ThreadSafeGlobalState::with_dir(
+				DenoFlags {
+					argv:vec![String::from("deno")],
+					config_path:Some(path.to_str().unwrap().to_string()),
+					..DenoFlags::default()
+				},
+				Progress::new(),
+				DenoDir::new(Some(deno_dir_root.clone())).unwrap(),
+			)
+			.unwrap();
+			let state_ = state.clone();
+			let source_file = source_file.clone();
+			let fut = async move {
+				state_.ts_compiler.compile_async(state_.clone(), &source_file).await.unwrap();
+				Ok(())
+			};
+			tokio_util::run(fut.boxed());
+			state.metrics.compiler_starts.load(Ordering::SeqCst)
+		};
+
+		assert_eq!(run(), 1);
+		// Nothing changed, the cached output is used.
+		assert_eq!(run(), 0);
+		// Editing only the extended config invalidates the cache.
+		deno_fs::write_file(&base_path, br#"{ "compilerOptions": { "strict": true } }"#, 0o666)
+			.unwrap();
+		assert_eq!(run(), 1);
+		assert_eq!(run(), 0);
+	}
+
 	#[test]
 	fn test_compiler_config_load() {
 		let temp_dir = TempDir::new().expect("tempdir fail");
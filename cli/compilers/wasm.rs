@@ -17,7 +17,7 @@ use crate::{
 	global_state::ThreadSafeGlobalState,
 	startup_data,
 	state::*,
-	worker::Worker,
+	worker::{Worker, WorkerBuilder},
 };
 
 // TODO(kevinkassimo): This is a hack to encode/decode data as base64 string.
@@ -57,12 +57,12 @@ impl WasmCompiler {
 		// Count how many times we start the compiler worker.
 		global_state.metrics.compiler_starts.fetch_add(1, Ordering::SeqCst);
 
-		let mut worker = Worker::new(
-			"WASM".to_string(),
-			startup_data::compiler_isolate_init(),
-			worker_state,
-			ext,
-		);
+		// `--max-heap-mb` is for user code, not the compiler.
+		let mut worker = WorkerBuilder::new(worker_state, ext)
+			.name("WASM")
+			.startup_data(startup_data::compiler_isolate_init())
+			.max_heap_mb(None)
+			.build();
 		worker.execute("denoMain('WASM')").unwrap();
 		worker.execute("workerMain()").unwrap();
 		worker.execute("wasmCompilerMain()").unwrap();
@@ -95,8 +95,7 @@ impl WasmCompiler {
 			.then(move |result| {
 				if let Err(err) = result {
 					// TODO(ry) Need to forward the error instead of exiting.
-					eprintln!("{}", err.to_string());
-					std::process::exit(1);
+					crate::deno_error::print_err_and_exit(err);
 				}
 				debug!("Sent message to worker");
 				worker_.get_message()
@@ -5,6 +5,7 @@ use deno::{AnyError, ErrBox, ModuleResolutionError};
 use dlopen::Error as DlopenError;
 use http::uri;
 use hyper;
+use notify;
 use reqwest;
 use rustyline::error::ReadlineError;
 use url;
@@ -19,11 +20,13 @@ pub struct DenoError {
 }
 
 pub fn print_msg_and_exit(msg:&str) {
+	crate::ops::io::flush_stdio();
 	eprintln!("{}", msg);
 	std::process::exit(1);
 }
 
 pub fn print_err_and_exit(err:ErrBox) {
+	crate::ops::io::flush_stdio();
 	eprintln!("{}", err.to_string());
 	std::process::exit(1);
 }
@@ -87,6 +90,24 @@ pub fn too_many_redirects() -> ErrBox {
 	StaticError(ErrorKind::TooManyRedirects, "too many redirects").into()
 }
 
+pub fn request_timed_out() -> ErrBox {
+	StaticError(ErrorKind::TimedOut, "request timed out").into()
+}
+
+pub fn worker_timeout() -> ErrBox {
+	StaticError(ErrorKind::WorkerTimeout, "worker execution timed out").into()
+}
+
+pub fn too_many_pending_ops() -> ErrBox {
+	StaticError(ErrorKind::TooManyPendingOps, "too many pending ops").into()
+}
+
+pub fn aborted() -> ErrBox { StaticError(ErrorKind::Aborted, "operation was aborted").into() }
+
+pub fn heap_limit_exceeded() -> ErrBox {
+	StaticError(ErrorKind::HeapLimit, "worker exceeded its heap limit").into()
+}
+
 pub fn type_error(msg:String) -> ErrBox { DenoError::new(ErrorKind::TypeError, msg).into() }
 
 pub trait GetErrorKind {
@@ -254,6 +275,8 @@ mod unix {
 				Sys(EPERM) => ErrorKind::PermissionDenied,
 				Sys(EINVAL) => ErrorKind::InvalidInput,
 				Sys(ENOENT) => ErrorKind::NotFound,
+				Sys(EAGAIN) => ErrorKind::WouldBlock,
+				Sys(ESRCH) => ErrorKind::NotFound,
 				Sys(_) => ErrorKind::UnixError,
 				_ => ErrorKind::Other,
 			}
@@ -274,6 +297,18 @@ impl GetErrorKind for DlopenError {
 	}
 }
 
+impl GetErrorKind for notify::Error {
+	fn kind(&self) -> ErrorKind {
+		use notify::Error::*;
+		match self {
+			Io(e) => GetErrorKind::kind(e),
+			PathNotFound => ErrorKind::NotFound,
+			WatchNotFound => ErrorKind::NotFound,
+			Generic(_) => ErrorKind::Other,
+		}
+	}
+}
+
 impl GetErrorKind for dyn AnyError {
 	fn kind(&self) -> ErrorKind {
 		use self::GetErrorKind as Get;
@@ -301,6 +336,7 @@ impl GetErrorKind for dyn AnyError {
 			.or_else(|| self.downcast_ref::<ReadlineError>().map(Get::kind))
 			.or_else(|| self.downcast_ref::<serde_json::error::Error>().map(Get::kind))
 			.or_else(|| self.downcast_ref::<DlopenError>().map(Get::kind))
+			.or_else(|| self.downcast_ref::<notify::Error>().map(Get::kind))
 			.or_else(|| unix_error_kind(self))
 			.unwrap_or_else(|| {
 				panic!("Can't get ErrorKind for {:?}", self);
@@ -501,4 +537,39 @@ mod tests {
 		assert_eq!(err.kind(), ErrorKind::NoSyncSupport);
 		assert_eq!(err.to_string(), "op doesn't support sync calls");
 	}
+
+	#[test]
+	fn test_request_timed_out() {
+		let err = request_timed_out();
+		assert_eq!(err.kind(), ErrorKind::TimedOut);
+		assert_eq!(err.to_string(), "request timed out");
+	}
+
+	#[test]
+	fn test_worker_timeout() {
+		let err = worker_timeout();
+		assert_eq!(err.kind(), ErrorKind::WorkerTimeout);
+		assert_eq!(err.to_string(), "worker execution timed out");
+	}
+
+	#[test]
+	fn test_too_many_pending_ops() {
+		let err = too_many_pending_ops();
+		assert_eq!(err.kind(), ErrorKind::TooManyPendingOps);
+		assert_eq!(err.to_string(), "too many pending ops");
+	}
+
+	#[test]
+	fn test_aborted() {
+		let err = aborted();
+		assert_eq!(err.kind(), ErrorKind::Aborted);
+		assert_eq!(err.to_string(), "operation was aborted");
+	}
+
+	#[test]
+	fn test_heap_limit_exceeded() {
+		let err = heap_limit_exceeded();
+		assert_eq!(err.kind(), ErrorKind::HeapLimit);
+		assert_eq!(err.to_string(), "worker exceeded its heap limit");
+	}
 }
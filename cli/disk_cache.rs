@@ -68,8 +68,19 @@ impl DiskCache {
 
 				out = out.join(remaining_components);
 			},
-			scheme => {
-				unimplemented!("Don't know how to create cache name for scheme: {}", scheme);
+			// Schemes served by embedder provided loaders.
+			_ => {
+				if let Some(host) = url.host_str() {
+					out.push(host);
+				}
+				match url.path_segments() {
+					Some(path_segments) => {
+						for path_seg in path_segments {
+							out.push(path_seg);
+						}
+					},
+					None => out.push(url.path().replace(':', "_")),
+				}
 			},
 		};
 
@@ -136,6 +147,7 @@ mod tests {
 				"https://deno.land/std/http/file_server.ts",
 				"https/deno.land/std/http/file_server.ts",
 			),
+			("ipfs://QmHash/lib/mod.ts", "ipfs/QmHash/lib/mod.ts"),
 		];
 
 		if cfg!(target_os = "windows") {
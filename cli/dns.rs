@@ -0,0 +1,164 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+use std::{
+	collections::HashMap,
+	net::{IpAddr, SocketAddr, ToSocketAddrs},
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+use deno::ErrBox;
+
+use crate::{
+	deno_error::{DenoError, ErrorKind},
+	resolve_addr::parse_scoped_ipv6,
+};
+
+/// Hostname resolution for outbound connections. Names listed with
+/// `--host-override` resolve to the given addresses without asking the
+/// system resolver, other names are looked up and, with `--dns-ttl`, cached.
+#[derive(Debug, Default)]
+pub struct Resolver {
+	overrides:HashMap<String, Vec<IpAddr>>,
+	/// How long looked up addresses are reused. `None` disables the cache.
+	ttl:Option<Duration>,
+	cache:Mutex<HashMap<String, (Instant, Vec<IpAddr>)>>,
+}
+
+impl Resolver {
+	/// Parses `--host-override` entries of the form `host=ip`. A host may be
+	/// listed more than once to give it several addresses.
+	pub fn new(overrides:&[String], ttl:Option<Duration>) -> Result<Self, ErrBox> {
+		let mut map:HashMap<String, Vec<IpAddr>> = HashMap::new();
+		for entry in overrides {
+			let (host, ip) = parse_override(entry)?;
+			map.entry(host).or_default().push(ip);
+		}
+		Ok(Self { overrides:map, ttl, cache:Mutex::new(HashMap::new()) })
+	}
+
+	/// Returns the address `hostname` was overridden with, if any.
+	pub fn override_for(&self, hostname:&str) -> Option<IpAddr> {
+		self.overrides.get(&hostname.to_lowercase()).map(|ips| ips[0])
+	}
+
+	/// Returns all addresses of `hostname`. IP literals, including bracketed
+	/// IPv6 ones, resolve to themselves.
+	pub fn lookup(&self, hostname:&str) -> Result<Vec<IpAddr>, ErrBox> {
+		let host = hostname.trim_start_matches('[').trim_end_matches(']');
+		if let Ok(ip) = host.parse::<IpAddr>() {
+			return Ok(vec![ip]);
+		}
+		let host = host.to_lowercase();
+		if let Some(ips) = self.overrides.get(&host) {
+			return Ok(ips.clone());
+		}
+		if let Some(ttl) = self.ttl {
+			let mut cache = self.cache.lock().unwrap();
+			match cache.get(&host) {
+				Some((resolved_at, ips)) if resolved_at.elapsed() < ttl => return Ok(ips.clone()),
+				Some(_) => {
+					cache.remove(&host);
+				},
+				None => {},
+			}
+		}
+
+		let mut ips:Vec<IpAddr> = Vec::new();
+		for addr in (host.as_str(), 0).to_socket_addrs()? {
+			if !ips.contains(&addr.ip()) {
+				ips.push(addr.ip());
+			}
+		}
+		if ips.is_empty() {
+			return Err(DenoError::new(
+				ErrorKind::NotFound,
+				format!("No addresses found for \"{}\"", hostname),
+			)
+			.into());
+		}
+		if self.ttl.is_some() {
+			self.cache.lock().unwrap().insert(host, (Instant::now(), ips.clone()));
+		}
+		Ok(ips)
+	}
+
+	/// Resolves the address to connect to. An empty hostname means
+	/// `0.0.0.0` and IPv6 addresses may have a zone id, like in
+	/// `resolve_addr()`.
+	pub fn resolve(&self, hostname:&str, port:u16) -> Result<SocketAddr, ErrBox> {
+		let hostname = if hostname.is_empty() { "0.0.0.0" } else { hostname };
+		if let Some(addr) = parse_scoped_ipv6(hostname, port)? {
+			return Ok(addr);
+		}
+		let ips = self.lookup(hostname)?;
+		Ok(SocketAddr::new(ips[0], port))
+	}
+}
+
+fn parse_override(entry:&str) -> Result<(String, IpAddr), ErrBox> {
+	let invalid = || -> ErrBox {
+		DenoError::new(
+			ErrorKind::InvalidInput,
+			format!("Invalid host override \"{}\", expected HOST=IP", entry),
+		)
+		.into()
+	};
+	let mut parts = entry.splitn(2, '=');
+	let host = parts.next().filter(|host| !host.is_empty()).ok_or_else(invalid)?;
+	let ip = parts.next().ok_or_else(invalid)?;
+	let ip = ip.trim_start_matches('[').trim_end_matches(']');
+	let ip = ip.parse::<IpAddr>().map_err(|_| invalid())?;
+	Ok((host.to_lowercase(), ip))
+}
+
+#[cfg(test)]
+mod tests {
+	use std::net::{Ipv4Addr, Ipv6Addr};
+
+	use super::*;
+	use crate::deno_error::GetErrorKind;
+
+	#[test]
+	fn overrides() {
+		let resolver = Resolver::new(
+			&[
+				"staging.example.com=10.0.0.5".to_string(),
+				"Staging.example.com=[::1]".to_string(),
+			],
+			None,
+		)
+		.unwrap();
+		let v4 = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5));
+		let v6 = IpAddr::V6(Ipv6Addr::LOCALHOST);
+		assert_eq!(resolver.lookup("STAGING.example.com").unwrap(), vec![v4, v6]);
+		assert_eq!(resolver.override_for("staging.example.com"), Some(v4));
+		assert_eq!(resolver.override_for("example.com"), None);
+		assert_eq!(
+			resolver.resolve("staging.example.com", 443).unwrap(),
+			SocketAddr::new(v4, 443)
+		);
+	}
+
+	#[test]
+	fn literals_and_localhost() {
+		let resolver = Resolver::new(&[], Some(Duration::from_secs(60))).unwrap();
+		assert_eq!(resolver.lookup("[::1]").unwrap(), vec![IpAddr::V6(Ipv6Addr::LOCALHOST)]);
+		assert_eq!(
+			resolver.resolve("", 80).unwrap(),
+			SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 80)
+		);
+		let ips = resolver.lookup("localhost").unwrap();
+		assert!(ips.iter().all(|ip| ip.is_loopback()));
+		// Served from the cache the second time.
+		assert!(resolver.cache.lock().unwrap().contains_key("localhost"));
+		assert_eq!(resolver.lookup("localhost").unwrap(), ips);
+	}
+
+	#[test]
+	fn invalid_overrides() {
+		for entry in &["example.com", "=10.0.0.1", "example.com=not-an-ip"] {
+			let err = Resolver::new(&[entry.to_string()], None).unwrap_err();
+			assert_eq!(err.kind(), ErrorKind::InvalidInput);
+		}
+	}
+}
@@ -8,7 +8,7 @@ use std::{
 	pin::Pin,
 	result::Result,
 	str,
-	sync::{Arc, Mutex},
+	sync::{Arc, Mutex, RwLock},
 };
 
 use deno::{ErrBox, ModuleSpecifier};
@@ -20,9 +20,9 @@ use crate::{
 	deno_error::{too_many_redirects, DenoError, ErrorKind, GetErrorKind},
 	disk_cache::DiskCache,
 	http_util,
-	http_util::FetchOnceResult,
+	http_util::{FetchOnceResult, HttpConfig, Validators},
 	msg,
-	progress::Progress,
+	progress::{Progress, ProgressEvent},
 };
 
 /// Structure representing local or remote file.
@@ -40,6 +40,34 @@ pub struct SourceFile {
 
 pub type SourceFileFuture = dyn Future<Output = Result<SourceFile, ErrBox>> + Send;
 
+/// Module source produced by a `SchemeLoader`.
+pub struct LoadedSource {
+	pub source_code:Vec<u8>,
+	/// Tells the compilers how to treat `source_code`.
+	pub media_type:msg::MediaType,
+}
+
+pub type LoadedSourceFuture = dyn Future<Output = Result<LoadedSource, ErrBox>> + Send;
+
+/// Serves modules for a URL scheme deno can't fetch by itself, e.g. a content
+/// addressed store. Embedders register one per scheme with
+/// `SourceFileFetcher::register_scheme_loader`; every other scheme keeps using
+/// the built-in file and http loaders.
+pub trait SchemeLoader: Send + Sync {
+	/// Resolves `specifier` as imported by `referrer`, a module of this loader's
+	/// scheme. Returning None falls back to regular URL resolution, which is
+	/// fine for schemes with hierarchical paths.
+	fn resolve(
+		&self,
+		_specifier:&str,
+		_referrer:&ModuleSpecifier,
+	) -> Option<Result<ModuleSpecifier, ErrBox>> {
+		None
+	}
+
+	fn load(&self, url:&Url) -> Pin<Box<LoadedSourceFuture>>;
+}
+
 /// Simple struct implementing in-process caching to prevent multiple
 /// fs reads/net fetches for same file.
 #[derive(Clone, Default)]
@@ -72,7 +100,13 @@ pub struct SourceFileFetcher {
 	cache_blacklist:Vec<String>,
 	use_disk_cache:bool,
 	no_remote:bool,
+	/// Remote modules only come from the cache, even when reloading.
 	cached_only:bool,
+	/// Cached remote modules are revalidated with a conditional request
+	/// instead of used as they are.
+	revalidate:bool,
+	http_config:HttpConfig,
+	scheme_loaders:Arc<RwLock<HashMap<String, Arc<dyn SchemeLoader>>>>,
 }
 
 impl SourceFileFetcher {
@@ -83,6 +117,8 @@ impl SourceFileFetcher {
 		cache_blacklist:Vec<String>,
 		no_remote:bool,
 		cached_only:bool,
+		revalidate:bool,
+		http_config:HttpConfig,
 	) -> std::io::Result<Self> {
 		let file_fetcher = Self {
 			deps_cache,
@@ -92,11 +128,56 @@ impl SourceFileFetcher {
 			use_disk_cache,
 			no_remote,
 			cached_only,
+			revalidate,
+			http_config,
+			scheme_loaders:Arc::new(RwLock::new(HashMap::new())),
 		};
 
 		Ok(file_fetcher)
 	}
 
+	/// Routes modules with URLs of `scheme` to `loader`, replacing any loader
+	/// registered for it before. Built-in schemes can't be overridden.
+	pub fn register_scheme_loader(
+		&self,
+		scheme:&str,
+		loader:Arc<dyn SchemeLoader>,
+	) -> Result<(), ErrBox> {
+		if SUPPORTED_URL_SCHEMES.contains(&scheme) {
+			return Err(DenoError::new(
+				ErrorKind::InvalidInput,
+				format!("Scheme \"{}\" is handled by deno itself", scheme),
+			)
+			.into());
+		}
+		self.scheme_loaders.write().unwrap().insert(scheme.to_string(), loader);
+		Ok(())
+	}
+
+	pub fn scheme_loader(&self, scheme:&str) -> Option<Arc<dyn SchemeLoader>> {
+		self.scheme_loaders.read().unwrap().get(scheme).cloned()
+	}
+
+	fn load_with_scheme_loader(
+		&self,
+		loader:Arc<dyn SchemeLoader>,
+		module_url:&Url,
+	) -> Pin<Box<SourceFileFuture>> {
+		let url = module_url.clone();
+		// Nothing is written there, but the compilers key their caches off it.
+		let filename = self.deps_cache.location.join(self.deps_cache.get_cache_filename(&url));
+		let load = loader.load(&url);
+		Box::pin(async move {
+			let loaded = load.await?;
+			Ok(SourceFile {
+				url,
+				filename,
+				media_type:loaded.media_type,
+				source_code:loaded.source_code,
+			})
+		})
+	}
+
 	fn check_if_supported_scheme(url:&Url) -> Result<(), ErrBox> {
 		if !SUPPORTED_URL_SCHEMES.contains(&url.scheme()) {
 			return Err(DenoError::new(
@@ -149,12 +230,18 @@ impl SourceFileFetcher {
 		let source_file_cache = self.source_file_cache.clone();
 		let specifier_ = specifier.clone();
 
-		let source_file = self.get_source_file_async(
-			&module_url,
-			self.use_disk_cache,
-			self.no_remote,
-			self.cached_only,
-		);
+		let source_file = match self.scheme_loader(module_url.scheme()) {
+			Some(loader) => self.load_with_scheme_loader(loader, &module_url),
+			None => {
+				self.get_source_file_async(
+					&module_url,
+					self.use_disk_cache,
+					self.no_remote,
+					self.cached_only,
+				)
+				.boxed()
+			},
+		};
 
 		Box::pin(async move {
 			match source_file.await {
@@ -182,7 +269,10 @@ impl SourceFileFetcher {
 						DenoError::new(ErrorKind::NotFound, msg).into()
 					} else if err_kind == ErrorKind::PermissionDenied {
 						let msg = format!(
-							r#"Cannot find module "{}"{} in cache, --cached-only is specified"#,
+							concat!(
+								r#"Cannot find module "{}"{}: not in cache and offline mode "#,
+								"(--offline/--cached-only) is set"
+							),
 							module_url, referrer_suffix
 						);
 						DenoError::new(ErrorKind::PermissionDenied, msg).into()
@@ -207,7 +297,8 @@ impl SourceFileFetcher {
 	/// If `no_remote` is true then this method will fail for remote files.
 	///
 	/// If `cached_only` is true then this method will fail for remote files
-	/// not already cached.
+	/// not already cached, and cached ones are used even if `use_disk_cache`
+	/// is false.
 	fn get_source_file_async(
 		self: &Self,
 		module_url:&Url,
@@ -340,8 +431,9 @@ impl SourceFileFetcher {
 		}
 
 		let is_blacklisted = check_cache_blacklist(module_url, self.cache_blacklist.as_ref());
+		let use_disk_cache = use_disk_cache && !is_blacklisted;
 		// First try local cache
-		if use_disk_cache && !is_blacklisted {
+		if (use_disk_cache && !self.revalidate) || cached_only {
 			match self.fetch_cached_remote_source(&module_url) {
 				Ok(Some(source_file)) => {
 					return futures::future::ok(source_file).boxed();
@@ -368,12 +460,22 @@ impl SourceFileFetcher {
 			.boxed();
 		}
 
+		// With `--revalidate` the cached copy is kept if the server answers 304.
+		// Reloaded modules are always downloaded.
+		let validators = if use_disk_cache && self.revalidate {
+			self.cached_validators(module_url)
+		} else {
+			Validators::default()
+		};
+
 		let download_job = self.progress.add("Download", &module_url.to_string());
+		self.progress.emit(ProgressEvent::DownloadStarted { url:module_url.to_string() });
 		let dir = self.clone();
 		let module_url = module_url.clone();
 
 		// Single pass fetch, either yields code or yields redirect.
-		let f = http_util::fetch_string_once(&module_url).and_then(move |r| {
+		let fetch = http_util::fetch_string_once(&module_url, &self.http_config, &validators);
+		let f = fetch.and_then(move |r| {
 			match r {
 				FetchOnceResult::Redirect(new_module_url) => {
 					// If redirects, update module_name and filename for next looped call.
@@ -384,6 +486,10 @@ impl SourceFileFetcher {
 					)
 					.unwrap();
 
+					dir.progress.emit(ProgressEvent::DownloadCompleted {
+						url:module_url.to_string(),
+						bytes:0,
+					});
 					// Explicit drop to keep reference alive until future completes.
 					drop(download_job);
 
@@ -395,10 +501,32 @@ impl SourceFileFetcher {
 						redirect_limit - 1,
 					))
 				},
-				FetchOnceResult::Code(source, maybe_content_type) => {
+				FetchOnceResult::NotModified => {
+					dir.progress.emit(ProgressEvent::DownloadCompleted {
+						url:module_url.to_string(),
+						bytes:0,
+					});
+					// Explicit drop to keep reference alive until future completes.
+					drop(download_job);
+
+					let result = match dir.fetch_cached_remote_source(&module_url) {
+						Ok(Some(source_file)) => Ok(source_file),
+						Ok(None) => {
+							Err(DenoError::new(
+								ErrorKind::NotFound,
+								format!("Cached copy of \"{}\" is missing", module_url),
+							)
+							.into())
+						},
+						Err(err) => Err(err),
+					};
+					Either::Right(futures::future::ready(result))
+				},
+				FetchOnceResult::Code(source, maybe_content_type, validators) => {
 					// We land on the code.
 					dir.save_source_code_headers(&module_url, maybe_content_type.clone(), None)
 						.unwrap();
+					dir.save_validators(&module_url, &validators).unwrap();
 
 					dir.save_source_code(&module_url, &source).unwrap();
 
@@ -419,6 +547,10 @@ impl SourceFileFetcher {
 						source_code:source.as_bytes().to_owned(),
 					};
 
+					dir.progress.emit(ProgressEvent::DownloadCompleted {
+						url:module_url.to_string(),
+						bytes:source.len(),
+					});
 					// Explicit drop to keep reference alive until future completes.
 					drop(download_job);
 
@@ -447,6 +579,43 @@ impl SourceFileFetcher {
 		SourceCodeHeaders::default()
 	}
 
+	/// Validators of the cached copy of a remote file, kept in
+	/// {filename}.validators.json. Empty if there is no copy or the URL
+	/// redirects.
+	fn cached_validators(self: &Self, url:&Url) -> Validators {
+		let filepath = self.deps_cache.location.join(self.deps_cache.get_cache_filename(url));
+		if self.get_source_code_headers(url).redirect_to.is_some() || !filepath.is_file() {
+			return Validators::default();
+		}
+		let cache_key = self.deps_cache.get_cache_filename_with_extension(url, "validators.json");
+		let value:serde_json::Value = match self.deps_cache.get(&cache_key) {
+			Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+			Err(_) => return Validators::default(),
+		};
+		Validators {
+			etag:value[ETAG].as_str().map(String::from),
+			last_modified:value[LAST_MODIFIED].as_str().map(String::from),
+		}
+	}
+
+	/// Save the validators of a downloaded remote file, if it had any, so it
+	/// can be revalidated later.
+	fn save_validators(self: &Self, url:&Url, validators:&Validators) -> std::io::Result<()> {
+		let cache_key = self.deps_cache.get_cache_filename_with_extension(url, "validators.json");
+
+		// May not exist. DON'T unwrap.
+		let _ = self.deps_cache.remove(&cache_key);
+
+		if validators.is_empty() {
+			return Ok(());
+		}
+		let json = json!({
+		  ETAG: validators.etag,
+		  LAST_MODIFIED: validators.last_modified,
+		});
+		self.deps_cache.set(&cache_key, json.to_string().as_bytes())
+	}
+
 	/// Save contents of downloaded remote file in on-disk cache for subsequent
 	/// access.
 	fn save_source_code(self: &Self, url:&Url, source:&str) -> std::io::Result<()> {
@@ -599,6 +768,8 @@ pub struct SourceCodeHeaders {
 
 static MIME_TYPE:&str = "mime_type";
 static REDIRECT_TO:&str = "redirect_to";
+static ETAG:&str = "etag";
+static LAST_MODIFIED:&str = "last_modified";
 
 impl SourceCodeHeaders {
 	pub fn from_json_string(headers_string:String) -> Self {
@@ -669,10 +840,37 @@ mod tests {
 			vec![],
 			false,
 			false,
+			false,
+			HttpConfig::default(),
 		)
 		.expect("setup fail")
 	}
 
+	struct StubLoader;
+
+	impl SchemeLoader for StubLoader {
+		fn load(&self, url:&Url) -> Pin<Box<LoadedSourceFuture>> {
+			let source_code = format!("export const url = \"{}\";", url).into_bytes();
+			futures::future::ok(LoadedSource { source_code, media_type:msg::MediaType::TypeScript })
+				.boxed()
+		}
+	}
+
+	#[test]
+	fn test_fetch_source_file_scheme_loader() {
+		let (_temp_dir, fetcher) = test_setup();
+		assert!(fetcher.register_scheme_loader("https", Arc::new(StubLoader)).is_err());
+		fetcher.register_scheme_loader("ipfs", Arc::new(StubLoader)).unwrap();
+
+		let specifier = ModuleSpecifier::resolve_url("ipfs://QmHash/mod.ts").unwrap();
+		tokio_util::run(fetcher.fetch_source_file_async(&specifier, None).then(|r| {
+			let source_file = r.unwrap();
+			assert_eq!(source_file.media_type, msg::MediaType::TypeScript);
+			assert_eq!(source_file.source_code, b"export const url = \"ipfs://QmHash/mod.ts\";");
+			futures::future::ok(())
+		}));
+	}
+
 	fn test_setup() -> (TempDir, SourceFileFetcher) {
 		let temp_dir = TempDir::new().expect("tempdir fail");
 		let fetcher = setup_file_fetcher(temp_dir.path());
@@ -956,6 +1154,55 @@ mod tests {
 		drop(http_server_guard);
 	}
 
+	#[test]
+	fn test_get_source_code_revalidate() {
+		let http_server_guard = crate::test_util::http_server();
+		let (temp_dir, fetcher) = test_setup();
+		// Relies on external http server. See tools/http_server.py
+		let module_url = Url::parse("http://localhost:4545/etag_script.ts").unwrap();
+		let create_fetcher = |cache_blacklist:Vec<String>, revalidate:bool| {
+			SourceFileFetcher::new(
+				fetcher.deps_cache.clone(),
+				Progress::new(),
+				true,
+				cache_blacklist,
+				false,
+				false,
+				revalidate,
+				HttpConfig::default(),
+			)
+			.unwrap()
+		};
+		let revalidate_fetcher = create_fetcher(vec![], true);
+		let reload_fetcher = create_fetcher(vec![module_url.to_string()], true);
+		let filepath =
+			fetcher.deps_cache.location.join(fetcher.deps_cache.get_cache_filename(&module_url));
+
+		let fut = async move {
+			let r = fetcher.get_source_file_async(&module_url, true, false, false).await.unwrap();
+			assert_eq!(r.source_code, "export const etag = \"33a64df5\";\n".as_bytes());
+			assert_eq!(fetcher.cached_validators(&module_url).etag.unwrap(), "\"33a64df5\"");
+
+			// The server answers 304, so the (modified) cached copy is used.
+			fs::write(&filepath, "export const etag = \"cached\";\n").unwrap();
+			let r = revalidate_fetcher.get_source_file_async(&module_url, true, false, false).await;
+			assert_eq!(r.unwrap().source_code, "export const etag = \"cached\";\n".as_bytes());
+
+			// Reloading a module always downloads it.
+			let r = reload_fetcher.get_source_file_async(&module_url, true, false, false).await;
+			assert_eq!(r.unwrap().source_code, "export const etag = \"33a64df5\";\n".as_bytes());
+			fs::write(&filepath, "export const etag = \"cached\";\n").unwrap();
+			let r =
+				revalidate_fetcher.get_source_file_async(&module_url, false, false, false).await;
+			assert_eq!(r.unwrap().source_code, "export const etag = \"33a64df5\";\n".as_bytes());
+			Ok(())
+		};
+
+		tokio_util::run(fut.boxed());
+		drop(http_server_guard);
+		drop(temp_dir);
+	}
+
 	#[test]
 	fn test_get_source_code_multiple_downloads_of_same_file() {
 		let http_server_guard = crate::test_util::http_server();
@@ -1242,6 +1489,45 @@ mod tests {
 		drop(http_server_guard);
 	}
 
+	#[test]
+	fn test_get_source_cached_only_reload() {
+		let http_server_guard = crate::test_util::http_server();
+		let (temp_dir, fetcher) = test_setup();
+		let module_url = Url::parse("http://localhost:4545/tests/002_hello.ts").unwrap();
+		let specifier = ModuleSpecifier::resolve_url(module_url.as_str()).unwrap();
+		// Cached only and reloading everything.
+		let cached_only_fetcher = SourceFileFetcher::new(
+			fetcher.deps_cache.clone(),
+			Progress::new(),
+			false,
+			vec![],
+			false,
+			true,
+			false,
+			HttpConfig::default(),
+		)
+		.unwrap();
+
+		let fut = async move {
+			let err =
+				cached_only_fetcher.fetch_source_file_async(&specifier, None).await.unwrap_err();
+			assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+			assert!(err
+				.to_string()
+				.contains("not in cache and offline mode (--offline/--cached-only) is set"));
+
+			// Once cached, the module is served from the cache.
+			fetcher.get_source_file_async(&module_url, true, false, false).await.unwrap();
+			let r = cached_only_fetcher.fetch_source_file_async(&specifier, None).await;
+			assert!(r.is_ok());
+			Ok(())
+		};
+
+		tokio_util::run(fut.boxed());
+		drop(http_server_guard);
+		drop(temp_dir);
+	}
+
 	#[test]
 	fn test_fetch_source_async_1() {
 		let http_server_guard = crate::test_util::http_server();
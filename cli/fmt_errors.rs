@@ -134,6 +134,41 @@ impl JSError {
 		let js_error = Self(mapped_exception);
 		ErrBox::from(js_error)
 	}
+
+	/// The source-mapped exception, including its stack frames.
+	pub fn v8_exception(&self) -> &V8Exception { &self.0 }
+
+	/// The error as plain data, for embedders that render errors themselves.
+	/// Errors returned by `Worker` and `execute_mod_async` can be turned into
+	/// this with `err.downcast_ref::<JSError>()`. Lines and columns are 1-based,
+	/// as in the formatted output, and no ANSI escapes are included.
+	pub fn to_json(&self) -> serde_json::Value {
+		let e = &self.0;
+		let frames:Vec<serde_json::Value> = e
+			.frames
+			.iter()
+			.map(|frame| {
+				json!({
+				  "scriptName": frame.script_name,
+				  "functionName": frame.function_name,
+				  "line": frame.line + 1,
+				  "column": frame.column + 1,
+				  "isEval": frame.is_eval,
+				  "isConstructor": frame.is_constructor,
+				  "isWasm": frame.is_wasm,
+				})
+			})
+			.collect();
+		json!({
+		  "message": e.message,
+		  "scriptResourceName": e.script_resource_name,
+		  "lineNumber": e.line_number.map(|line| line + 1),
+		  "startColumn": e.start_column.map(|column| column + 1),
+		  "endColumn": e.end_column.map(|column| column + 1),
+		  "sourceLine": e.source_line,
+		  "frames": frames,
+		})
+	}
 }
 
 impl DisplayFormatter for JSError {
@@ -242,6 +277,26 @@ mod tests {
 		assert_eq!("error: Error: foo bar\n    at foo (foo_bar.ts:5:17)\n    at qat (bar_baz.ts:6:21)\n    at deno_main.js:2:2", strip_ansi_codes(&JSError(e).to_string()));
 	}
 
+	#[test]
+	fn js_error_to_json() {
+		let json = JSError(error1()).to_json();
+		assert_eq!(json["message"], "Error: foo bar");
+		assert_eq!(json["lineNumber"], serde_json::Value::Null);
+		assert_eq!(json["frames"].as_array().unwrap().len(), 3);
+		assert_eq!(
+			json["frames"][0],
+			json!({
+			  "scriptName": "foo_bar.ts",
+			  "functionName": "foo",
+			  "line": 5,
+			  "column": 17,
+			  "isEval": false,
+			  "isConstructor": false,
+			  "isWasm": false,
+			})
+		);
+	}
+
 	#[test]
 	fn test_format_none_source_name() {
 		let actual = format_maybe_source_name(None, None, None);
@@ -3,16 +3,26 @@
 use std::os::unix::fs::DirBuilderExt;
 #[cfg(any(unix))]
 use std::os::unix::fs::PermissionsExt;
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+#[cfg(windows)]
+use std::os::windows::io::AsRawHandle;
 use std::{
 	self,
-	fs::{create_dir, DirBuilder, File, OpenOptions},
-	io::{ErrorKind, Write},
+	cell::RefCell,
+	fs::{self as std_fs, create_dir, DirBuilder, File, Metadata, OpenOptions},
+	io::{self, ErrorKind, Write},
 	path::{Path, PathBuf},
+	time::UNIX_EPOCH,
 };
 
 use deno::ErrBox;
 #[cfg(unix)]
-use nix::unistd::{chown as unix_chown, Gid, Uid};
+use nix::{
+	fcntl::{flock, FlockArg},
+	sys::stat::{umask as unix_umask, Mode},
+	unistd::{chown as unix_chown, Gid, Uid},
+};
 use rand::{self, Rng};
 use url::Url;
 
@@ -43,6 +53,71 @@ pub fn write_file_2<T:AsRef<[u8]>>(
 	file.write_all(data.as_ref())
 }
 
+/// Writes `data` to a temporary file next to `filename` and renames it over
+/// `filename`, so readers see either the old or the new contents. The mode of
+/// an existing `filename` is kept; otherwise `perm` (less the umask) is used.
+/// With `sync` the data and the rename are flushed to disk before returning.
+/// The temporary file is removed if anything fails.
+pub fn write_file_atomic<T:AsRef<[u8]>>(
+	filename:&Path,
+	data:T,
+	perm:u32,
+	sync:bool,
+) -> io::Result<()> {
+	let dir = match filename.parent() {
+		Some(dir) if !dir.as_os_str().is_empty() => dir,
+		_ => Path::new("."),
+	};
+	let file_name = filename
+		.file_name()
+		.ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "Not a file path"))?;
+	let permissions = match std_fs::metadata(filename) {
+		Ok(metadata) => Some(metadata.permissions()),
+		Err(ref e) if e.kind() == ErrorKind::NotFound => None,
+		Err(e) => return Err(e),
+	};
+
+	let prefix = format!(".{}.", file_name.to_string_lossy());
+	let temp_file = RefCell::new(None);
+	let temp_path = make_temp(Some(dir), Some(&prefix), Some(".tmp"), |path| {
+		let mut options = OpenOptions::new();
+		options.write(true).create_new(true);
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::OpenOptionsExt;
+			// Less the umask, which the kernel applies.
+			options.mode(perm & 0o777);
+		}
+		*temp_file.borrow_mut() = Some(options.open(path)?);
+		Ok(())
+	})?;
+	let mut file = temp_file.into_inner().unwrap();
+
+	let result = (|| {
+		file.write_all(data.as_ref())?;
+		if let Some(permissions) = permissions {
+			file.set_permissions(permissions)?;
+		}
+		if sync {
+			file.sync_all()?;
+		}
+		// Windows can't rename a file that is still open.
+		drop(file);
+		std_fs::rename(&temp_path, filename)
+	})();
+	if let Err(e) = result {
+		let _ = std_fs::remove_file(&temp_path);
+		return Err(e);
+	}
+	#[cfg(unix)]
+	{
+		if sync {
+			File::open(dir)?.sync_all()?;
+		}
+	}
+	Ok(())
+}
+
 #[cfg(any(unix))]
 fn set_permissions(file:&mut File, perm:u32) -> std::io::Result<()> {
 	debug!("set file perm to {}", perm);
@@ -54,11 +129,50 @@ fn set_permissions(_file:&mut File, _perm:u32) -> std::io::Result<()> {
 	Ok(())
 }
 
+/// Creates a directory with a random name in `dir`, or the system temp
+/// directory, that only the current user can access.
 pub fn make_temp_dir(
 	dir:Option<&Path>,
 	prefix:Option<&str>,
 	suffix:Option<&str>,
 ) -> std::io::Result<PathBuf> {
+	make_temp(dir, prefix, suffix, |path| {
+		let mut builder = DirBuilder::new();
+		set_dir_permission(&mut builder, 0o700);
+		builder.create(path)
+	})
+}
+
+/// Creates an empty file with a random name in `dir`, or the system temp
+/// directory, that only the current user can read and write. Like `mkstemp`
+/// the name is claimed by creating the file, so no other process can get
+/// there first.
+pub fn make_temp_file(
+	dir:Option<&Path>,
+	prefix:Option<&str>,
+	suffix:Option<&str>,
+) -> std::io::Result<PathBuf> {
+	make_temp(dir, prefix, suffix, |path| {
+		let mut options = OpenOptions::new();
+		options.write(true).create_new(true);
+		#[cfg(unix)]
+		{
+			use std::os::unix::fs::OpenOptionsExt;
+			options.mode(0o600);
+		}
+		options.open(path).map(|_| ())
+	})
+}
+
+/// Calls `create` with random names until one doesn't exist yet.
+fn make_temp<F>(
+	dir:Option<&Path>,
+	prefix:Option<&str>,
+	suffix:Option<&str>,
+	create:F,
+) -> std::io::Result<PathBuf>
+where
+	F: Fn(&Path) -> std::io::Result<()>, {
 	let prefix_ = prefix.unwrap_or("");
 	let suffix_ = suffix.unwrap_or("");
 	let mut buf:PathBuf = match dir {
@@ -70,9 +184,7 @@ pub fn make_temp_dir(
 	loop {
 		let unique = rng.gen::<u32>();
 		buf.set_file_name(format!("{}{:08x}{}", prefix_, unique, suffix_));
-		// TODO: on posix, set mode flags to 0o700.
-		let r = create_dir(buf.as_path());
-		match r {
+		match create(buf.as_path()) {
 			Err(ref e) if e.kind() == ErrorKind::AlreadyExists => continue,
 			Ok(_) => return Ok(buf),
 			Err(e) => return Err(e),
@@ -99,6 +211,107 @@ fn set_dir_permission(_builder:&mut DirBuilder, _perm:u32) {
 	// NOOP on windows
 }
 
+/// Options for `copy_dir`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CopyDirOptions {
+	/// Also copy directory permissions and access/modification times. File
+	/// permissions are always copied.
+	pub preserve:bool,
+	/// Merge into an existing destination, replacing files that are already
+	/// there, instead of failing.
+	pub overwrite:bool,
+	/// Copy what symlinks point to rather than the links themselves.
+	pub follow_symlinks:bool,
+}
+
+/// Recursively copies the directory `from` to `to`.
+pub fn copy_dir(from:&Path, to:&Path, options:CopyDirOptions) -> io::Result<()> {
+	let metadata = std_fs::metadata(from)?;
+	if !metadata.is_dir() {
+		return Err(io::Error::new(ErrorKind::InvalidInput, "Source is not a directory"));
+	}
+	if to.starts_with(from) {
+		return Err(io::Error::new(ErrorKind::InvalidInput, "Cannot copy a directory into itself"));
+	}
+	copy_dir_recursive(from, to, &metadata, options)
+}
+
+fn copy_dir_recursive(
+	from:&Path,
+	to:&Path,
+	metadata:&Metadata,
+	options:CopyDirOptions,
+) -> io::Result<()> {
+	match create_dir(to) {
+		Err(ref e) if e.kind() == ErrorKind::AlreadyExists && options.overwrite && to.is_dir() => {},
+		result => result?,
+	}
+	for entry in std_fs::read_dir(from)? {
+		let entry = entry?;
+		let src = entry.path();
+		let dst = to.join(entry.file_name());
+		if entry.file_type()?.is_symlink() && !options.follow_symlinks {
+			copy_symlink(&src, &dst, options)?;
+			continue;
+		}
+		let metadata = std_fs::metadata(&src)?;
+		if metadata.is_dir() {
+			copy_dir_recursive(&src, &dst, &metadata, options)?;
+		} else {
+			prepare_copy_destination(&dst, options)?;
+			std_fs::copy(&src, &dst)?;
+			if options.preserve {
+				set_times(&dst, &metadata)?;
+			}
+		}
+	}
+	if options.preserve {
+		std_fs::set_permissions(to, metadata.permissions())?;
+		set_times(to, metadata)?;
+	}
+	Ok(())
+}
+
+/// Fails if `dst` exists and overwriting wasn't asked for. Otherwise removes a
+/// symlink at `dst` so the copy doesn't write through it.
+fn prepare_copy_destination(dst:&Path, options:CopyDirOptions) -> io::Result<()> {
+	match std_fs::symlink_metadata(dst) {
+		Ok(_) if !options.overwrite => {
+			Err(io::Error::new(
+				ErrorKind::AlreadyExists,
+				format!("{} already exists", dst.display()),
+			))
+		},
+		Ok(ref metadata) if metadata.file_type().is_symlink() => std_fs::remove_file(dst),
+		_ => Ok(()),
+	}
+}
+
+#[cfg(unix)]
+fn copy_symlink(src:&Path, dst:&Path, options:CopyDirOptions) -> io::Result<()> {
+	prepare_copy_destination(dst, options)?;
+	if dst.is_file() {
+		std_fs::remove_file(dst)?;
+	}
+	std::os::unix::fs::symlink(std_fs::read_link(src)?, dst)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(_src:&Path, _dst:&Path, _options:CopyDirOptions) -> io::Result<()> {
+	// TODO: copy links as links on Windows.
+	Err(io::Error::new(
+		ErrorKind::Other,
+		"Copying symlinks is not supported on this platform, follow them instead",
+	))
+}
+
+fn set_times(path:&Path, metadata:&Metadata) -> io::Result<()> {
+	let seconds = |t:io::Result<std::time::SystemTime>| {
+		t.ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.as_secs()).unwrap_or(0)
+	};
+	utime::set_file_times(path, seconds(metadata.accessed()), seconds(metadata.modified()))
+}
+
 pub fn normalize_path(path:&Path) -> String {
 	let s = String::from(path.to_str().unwrap());
 	if cfg!(windows) {
@@ -123,15 +336,135 @@ pub fn chown(_path:&str, _uid:u32, _gid:u32) -> Result<(), ErrBox> {
 	Err(crate::deno_error::op_not_implemented())
 }
 
+/// Sets the process umask to `mask` and returns the previous one. With `None`
+/// the umask is only read.
+#[cfg(unix)]
+pub fn umask(mask:Option<u32>) -> Result<u32, ErrBox> {
+	lazy_static! {
+		static ref UMASK_LOCK:std::sync::Mutex<()> = std::sync::Mutex::new(());
+	}
+	// Callers of this function never see each other's temporary mask, but
+	// there is no way to read the umask without setting it, so a file that
+	// another thread creates while it is 0 doesn't get the umask applied.
+	let _guard = UMASK_LOCK.lock().unwrap();
+	let new_mask = mask.map(Mode::from_bits_truncate).unwrap_or_else(Mode::empty);
+	let previous = unix_umask(new_mask);
+	if mask.is_none() {
+		unix_umask(previous);
+	}
+	Ok(previous.bits() as u32)
+}
+
+#[cfg(not(unix))]
+pub fn umask(_mask:Option<u32>) -> Result<u32, ErrBox> {
+	Err(crate::deno_error::op_not_implemented())
+}
+
+/// Takes an advisory lock on `file`. With `non_blocking` a contended lock
+/// fails with `ErrorKind::WouldBlock` instead of waiting. The lock belongs to
+/// the open file description, so it outlives clones of `file` and is released
+/// when the last handle is closed.
+#[cfg(unix)]
+pub fn lock_file(file:&File, exclusive:bool, non_blocking:bool) -> Result<(), ErrBox> {
+	let arg = match (exclusive, non_blocking) {
+		(false, false) => FlockArg::LockShared,
+		(false, true) => FlockArg::LockSharedNonblock,
+		(true, false) => FlockArg::LockExclusive,
+		(true, true) => FlockArg::LockExclusiveNonblock,
+	};
+	flock(file.as_raw_fd(), arg)?;
+	Ok(())
+}
+
+#[cfg(unix)]
+pub fn unlock_file(file:&File) -> Result<(), ErrBox> {
+	flock(file.as_raw_fd(), FlockArg::Unlock)?;
+	Ok(())
+}
+
+#[cfg(windows)]
+pub fn lock_file(file:&File, exclusive:bool, non_blocking:bool) -> Result<(), ErrBox> {
+	use winapi::um::{
+		fileapi::LockFileEx,
+		minwinbase::{LOCKFILE_EXCLUSIVE_LOCK, LOCKFILE_FAIL_IMMEDIATELY, OVERLAPPED},
+	};
+
+	let mut flags = 0;
+	if exclusive {
+		flags |= LOCKFILE_EXCLUSIVE_LOCK;
+	}
+	if non_blocking {
+		flags |= LOCKFILE_FAIL_IMMEDIATELY;
+	}
+	// Lock the whole file, like flock(2) does.
+	let ok = unsafe {
+		let mut overlapped:OVERLAPPED = std::mem::zeroed();
+		LockFileEx(file.as_raw_handle() as _, flags, 0, !0, !0, &mut overlapped)
+	};
+	if ok == 0 {
+		return Err(windows_lock_error());
+	}
+	Ok(())
+}
+
+#[cfg(windows)]
+pub fn unlock_file(file:&File) -> Result<(), ErrBox> {
+	use winapi::um::{fileapi::UnlockFileEx, minwinbase::OVERLAPPED};
+
+	let ok = unsafe {
+		let mut overlapped:OVERLAPPED = std::mem::zeroed();
+		UnlockFileEx(file.as_raw_handle() as _, 0, !0, !0, &mut overlapped)
+	};
+	if ok == 0 {
+		return Err(windows_lock_error());
+	}
+	Ok(())
+}
+
+#[cfg(windows)]
+fn windows_lock_error() -> ErrBox {
+	use winapi::shared::winerror::ERROR_LOCK_VIOLATION;
+
+	let err = io::Error::last_os_error();
+	if err.raw_os_error() == Some(ERROR_LOCK_VIOLATION as i32) {
+		crate::deno_error::DenoError::new(
+			crate::deno_error::ErrorKind::WouldBlock,
+			"File is locked".to_string(),
+		)
+		.into()
+	} else {
+		err.into()
+	}
+}
+
+/// Reserves disk space so that `file` is at least `len` bytes long without
+/// writing to it. Where preallocation isn't available the file is extended
+/// with `set_len` instead. Never shrinks the file.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+pub fn fallocate(file:&File, len:u64) -> Result<(), ErrBox> {
+	nix::fcntl::posix_fallocate(file.as_raw_fd(), 0, len as nix::libc::off_t)?;
+	Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd")))]
+pub fn fallocate(file:&File, len:u64) -> Result<(), ErrBox> {
+	if file.metadata()?.len() < len {
+		file.set_len(len)?;
+	}
+	Ok(())
+}
+
 pub fn resolve_from_cwd(path:&str) -> Result<(PathBuf, String), ErrBox> {
+	resolve_from(&std::env::current_dir().unwrap(), path)
+}
+
+/// Like `resolve_from_cwd()`, but relative paths are resolved against `base`,
+/// which must be absolute.
+pub fn resolve_from(base:&Path, path:&str) -> Result<(PathBuf, String), ErrBox> {
 	let candidate_path = Path::new(path);
 
-	let resolved_path = if candidate_path.is_absolute() {
-		candidate_path.to_owned()
-	} else {
-		let cwd = std::env::current_dir().unwrap();
-		cwd.join(path)
-	};
+	let resolved_path =
+		if candidate_path.is_absolute() { candidate_path.to_owned() } else { base.join(path) };
 
 	// HACK: `Url::parse` is used here because it normalizes the path.
 	// Joining `/dev/deno/" with "./tests" using `PathBuf` yields
@@ -154,6 +487,21 @@ pub fn resolve_from_cwd(path:&str) -> Result<(PathBuf, String), ErrBox> {
 	Ok((normalized_path, path_string))
 }
 
+/// Like `fs::canonicalize`, but a symlink in the final component is kept
+/// rather than followed, the way `lstat` treats it. Fails if the path
+/// doesn't exist.
+pub fn canonicalize_no_follow(path:&Path) -> io::Result<PathBuf> {
+	std_fs::symlink_metadata(path)?;
+	match (path.parent(), path.file_name()) {
+		(Some(parent), Some(name)) => {
+			let parent = if parent.as_os_str().is_empty() { Path::new(".") } else { parent };
+			Ok(std_fs::canonicalize(parent)?.join(name))
+		},
+		// The root, or a path ending in `..`, which has no link to keep.
+		_ => std_fs::canonicalize(path),
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use super::*;
@@ -176,6 +524,93 @@ mod tests {
 		assert_eq!(resolve_from_cwd("a/..").unwrap().0, cwd);
 	}
 
+	#[cfg(unix)]
+	#[test]
+	fn umask_read_does_not_change_mask() {
+		let before = umask(None).unwrap();
+		assert_eq!(umask(None).unwrap(), before);
+	}
+
+	#[test]
+	fn write_file_atomic_replaces_contents() {
+		let temp_dir = tempfile::TempDir::new().unwrap();
+		let filename = temp_dir.path().join("file.txt");
+		write_file_atomic(&filename, "first", 0o666, false).unwrap();
+		#[cfg(unix)]
+		std_fs::set_permissions(&filename, PermissionsExt::from_mode(0o640)).unwrap();
+		write_file_atomic(&filename, "second", 0o666, true).unwrap();
+
+		assert_eq!(std_fs::read_to_string(&filename).unwrap(), "second");
+		#[cfg(unix)]
+		assert_eq!(std_fs::metadata(&filename).unwrap().permissions().mode() & 0o777, 0o640);
+		// Only the target is left behind.
+		assert_eq!(std_fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+	}
+
+	#[test]
+	fn copy_dir_tree() {
+		let temp_dir = tempfile::TempDir::new().unwrap();
+		let from = temp_dir.path().join("from");
+		std_fs::create_dir_all(from.join("a/b")).unwrap();
+		std_fs::write(from.join("top.txt"), "top").unwrap();
+		std_fs::write(from.join("a/b/nested.txt"), "nested").unwrap();
+		#[cfg(unix)]
+		std::os::unix::fs::symlink("top.txt", from.join("link")).unwrap();
+
+		let to = temp_dir.path().join("to");
+		copy_dir(&from, &to, CopyDirOptions::default()).unwrap();
+		assert_eq!(std_fs::read_to_string(to.join("top.txt")).unwrap(), "top");
+		assert_eq!(std_fs::read_to_string(to.join("a/b/nested.txt")).unwrap(), "nested");
+		#[cfg(unix)]
+		assert_eq!(std_fs::read_link(to.join("link")).unwrap(), Path::new("top.txt"));
+
+		let err = copy_dir(&from, &to, CopyDirOptions::default()).unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+		let options = CopyDirOptions { overwrite:true, ..CopyDirOptions::default() };
+		copy_dir(&from, &to, options).unwrap();
+	}
+
+	#[test]
+	fn copy_dir_into_itself() {
+		let temp_dir = tempfile::TempDir::new().unwrap();
+		let err =
+			copy_dir(temp_dir.path(), &temp_dir.path().join("sub"), CopyDirOptions::default())
+				.unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::InvalidInput);
+	}
+
+	#[test]
+	fn make_temp_file_is_private() {
+		let temp_dir = tempfile::TempDir::new().unwrap();
+		let file = make_temp_file(Some(temp_dir.path()), Some("pre"), Some(".txt")).unwrap();
+		let dir = make_temp_dir(Some(temp_dir.path()), None, None).unwrap();
+		assert!(file.is_file());
+		assert!(dir.is_dir());
+		let name = file.file_name().unwrap().to_str().unwrap();
+		assert!(name.starts_with("pre") && name.ends_with(".txt"));
+		#[cfg(unix)]
+		{
+			let mode = |path:&Path| std_fs::metadata(path).unwrap().permissions().mode() & 0o777;
+			assert_eq!(mode(&file), 0o600);
+			assert_eq!(mode(&dir), 0o700);
+		}
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn canonicalize_no_follow_keeps_final_symlink() {
+		let temp_dir = tempfile::TempDir::new().unwrap();
+		let root = std_fs::canonicalize(temp_dir.path()).unwrap();
+		std_fs::create_dir(root.join("target")).unwrap();
+		std::os::unix::fs::symlink(root.join("target"), root.join("link")).unwrap();
+
+		let path = root.join("target/../link");
+		assert_eq!(canonicalize_no_follow(&path).unwrap(), root.join("link"));
+		assert_eq!(std_fs::canonicalize(&path).unwrap(), root.join("target"));
+		let err = canonicalize_no_follow(&root.join("missing")).unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::NotFound);
+	}
+
 	// TODO: Get a good expected value here for Windows.
 	#[cfg(not(windows))]
 	#[test]
@@ -183,4 +618,12 @@ mod tests {
 		let expected = Path::new("/a");
 		assert_eq!(resolve_from_cwd("/a").unwrap().0, expected);
 	}
+
+	#[test]
+	fn resolve_from_base() {
+		let base = std::env::current_dir().unwrap().join("a");
+		assert_eq!(resolve_from(&base, "b/../c").unwrap().0, base.join("c"));
+		assert_eq!(resolve_from(&base, "..").unwrap().0, std::env::current_dir().unwrap());
+		assert_eq!(resolve_from(&base, "/d").unwrap().0, Path::new("/d"));
+	}
 }
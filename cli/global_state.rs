@@ -1,11 +1,13 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
 use std::{
 	self,
+	collections::HashMap,
 	env,
 	future::Future,
 	ops::Deref,
 	str,
 	sync::{Arc, Mutex},
+	time::Duration,
 };
 
 use deno::{ErrBox, ModuleSpecifier};
@@ -13,14 +15,18 @@ use deno::{ErrBox, ModuleSpecifier};
 use crate::{
 	compilers::{CompiledModule, JsCompiler, JsonCompiler, TsCompiler, WasmCompiler},
 	deno_dir,
-	deno_error::permission_denied,
+	deno_error::{permission_denied, DenoError, ErrorKind},
+	dns::Resolver,
 	file_fetcher::SourceFileFetcher,
 	flags,
+	http_util::{FetchClients, HttpConfig},
 	lockfile::Lockfile,
 	metrics::Metrics,
 	msg,
+	net_allowlist::NetAllowlist,
 	permissions::DenoPermissions,
 	progress::Progress,
+	worker::Worker,
 };
 
 /// Holds state of the program and can be accessed by V8 isolate.
@@ -40,12 +46,24 @@ pub struct GlobalState {
 	pub dir:deno_dir::DenoDir,
 	pub metrics:Metrics,
 	pub progress:Progress,
+	/// HTTP client settings used for module downloads and `fetch`.
+	pub http_config:HttpConfig,
+	/// Clients shared by all `fetch` calls, holding the connection pool.
+	pub fetch_clients:FetchClients,
 	pub file_fetcher:SourceFileFetcher,
 	pub js_compiler:JsCompiler,
 	pub json_compiler:JsonCompiler,
 	pub ts_compiler:TsCompiler,
 	pub wasm_compiler:WasmCompiler,
 	pub lockfile:Option<Mutex<Lockfile>>,
+	/// Parsed from `--net-allowlist`, `None` when outbound connections are
+	/// unrestricted.
+	pub net_allowlist:Option<NetAllowlist>,
+	/// Resolves hostnames for outbound connections, honoring
+	/// `--host-override` and `--dns-ttl`.
+	pub resolver:Resolver,
+	/// Workers reachable by name from any module, see `register_worker`.
+	named_workers:Mutex<HashMap<String, Worker>>,
 }
 
 impl Clone for ThreadSafeGlobalState {
@@ -62,6 +80,17 @@ impl ThreadSafeGlobalState {
 	pub fn new(flags:flags::DenoFlags, progress:Progress) -> Result<Self, ErrBox> {
 		let custom_root = env::var("DENO_DIR").map(String::into).ok();
 		let dir = deno_dir::DenoDir::new(custom_root)?;
+		Self::with_dir(flags, progress, dir)
+	}
+
+	/// Like `new()`, but caches in `dir` instead of the directory `DENO_DIR`
+	/// points at.
+	pub fn with_dir(
+		flags:flags::DenoFlags,
+		progress:Progress,
+		dir:deno_dir::DenoDir,
+	) -> Result<Self, ErrBox> {
+		let http_config = HttpConfig::from_flags(&flags)?;
 
 		let file_fetcher = SourceFileFetcher::new(
 			dir.deps_cache.clone(),
@@ -70,6 +99,8 @@ impl ThreadSafeGlobalState {
 			flags.cache_blacklist.clone(),
 			flags.no_remote,
 			flags.cached_only,
+			flags.revalidate,
+			http_config.clone(),
 		)?;
 
 		let ts_compiler = TsCompiler::new(
@@ -93,6 +124,14 @@ impl ThreadSafeGlobalState {
 			None
 		};
 
+		let net_allowlist = if flags.net_allowlist.is_empty() {
+			None
+		} else {
+			Some(NetAllowlist::parse(&flags.net_allowlist)?)
+		};
+		let dns_ttl = flags.dns_ttl.filter(|ms| *ms > 0).map(Duration::from_millis);
+		let resolver = Resolver::new(&flags.host_overrides, dns_ttl)?;
+
 		let state = GlobalState {
 			main_module,
 			dir,
@@ -100,12 +139,17 @@ impl ThreadSafeGlobalState {
 			flags,
 			metrics:Metrics::default(),
 			progress,
+			fetch_clients:FetchClients::new(&http_config),
+			http_config,
 			file_fetcher,
 			ts_compiler,
 			js_compiler:JsCompiler {},
 			json_compiler:JsonCompiler {},
 			wasm_compiler:WasmCompiler::default(),
 			lockfile,
+			net_allowlist,
+			resolver,
+			named_workers:Mutex::new(HashMap::new()),
 		};
 
 		Ok(ThreadSafeGlobalState(Arc::new(state)))
@@ -124,6 +168,15 @@ impl ThreadSafeGlobalState {
 
 		async move {
 			let out = source_file.await?;
+			// Checked before compiling, so a tampered module never runs.
+			if let Some(ref lockfile) = state2.lockfile {
+				let mut g = lockfile.lock().unwrap();
+				if state2.flags.lock_write {
+					g.insert(out.url.as_str(), &out.source_code);
+				} else {
+					g.check(out.url.as_str(), &out.source_code)?;
+				}
+			}
 			let compiled_module = match out.media_type {
 				msg::MediaType::Unknown => state1.js_compiler.compile_async(&out),
 				msg::MediaType::Json => state1.json_compiler.compile_async(&out),
@@ -140,27 +193,49 @@ impl ThreadSafeGlobalState {
 				},
 			}
 			.await?;
+			Ok(compiled_module)
+		}
+	}
 
-			if let Some(ref lockfile) = state2.lockfile {
-				let mut g = lockfile.lock().unwrap();
-				if state2.flags.lock_write {
-					g.insert(&compiled_module);
-				} else {
-					let check = match g.check(&compiled_module) {
-						Err(e) => return Err(ErrBox::from(e)),
-						Ok(v) => v,
-					};
-					if !check {
-						eprintln!(
-							"Subresource integrity check failed --lock={}\n{}",
-							g.filename, compiled_module.name
-						);
-						std::process::exit(10);
-					}
-				}
+	/// Makes `worker` reachable by `name`, also from modules that didn't
+	/// create it. A name can't be taken twice while its worker is alive.
+	pub fn register_worker(&self, name:&str, worker:Worker) -> Result<(), ErrBox> {
+		let mut named_workers = self.named_workers.lock().unwrap();
+		if let Some(existing) = named_workers.get(name) {
+			if !existing.is_terminated() {
+				let msg = format!("A worker named \"{}\" already exists", name);
+				return Err(DenoError::new(ErrorKind::AlreadyExists, msg).into());
 			}
-			Ok(compiled_module)
 		}
+		named_workers.insert(name.to_string(), worker);
+		Ok(())
+	}
+
+	/// Removes `worker` from the registry, if it is registered under its name.
+	/// Called when it is terminated or exits.
+	pub fn unregister_worker(&self, worker:&Worker) {
+		let mut named_workers = self.named_workers.lock().unwrap();
+		if named_workers.get(&worker.name).map_or(false, |w| w.ptr_eq(worker)) {
+			named_workers.remove(&worker.name);
+		}
+	}
+
+	/// Returns the live worker registered as `name`.
+	pub fn named_worker(&self, name:&str) -> Option<Worker> {
+		let named_workers = self.named_workers.lock().unwrap();
+		named_workers.get(name).filter(|worker| !worker.is_terminated()).cloned()
+	}
+
+	/// Names of the live registered workers, sorted.
+	pub fn worker_names(&self) -> Vec<String> {
+		let named_workers = self.named_workers.lock().unwrap();
+		let mut names:Vec<String> = named_workers
+			.iter()
+			.filter(|(_, worker)| !worker.is_terminated())
+			.map(|(name, _)| name.clone())
+			.collect();
+		names.sort();
+		names
 	}
 
 	#[inline]
@@ -235,3 +310,86 @@ fn import_map_given_for_repl() {
 		Progress::new(),
 	);
 }
+
+#[test]
+fn named_workers() {
+	use deno::StartupData;
+	use futures::executor::block_on;
+
+	use crate::{deno_error::GetErrorKind, state::ThreadSafeState};
+
+	let global_state = ThreadSafeGlobalState::mock(vec![String::from("./deno")]);
+	let create_worker = |name:&str| {
+		let (int, ext) = ThreadSafeState::create_channels();
+		let state = ThreadSafeState::new(global_state.clone(), None, None, true, int).unwrap();
+		Worker::new(name.to_string(), StartupData::None, state, ext)
+	};
+	let a = create_worker("a");
+	let b = create_worker("b");
+	global_state.register_worker("a", a.clone()).unwrap();
+	global_state.register_worker("b", b.clone()).unwrap();
+	let err = global_state.register_worker("a", create_worker("a")).unwrap_err();
+	assert_eq!(err.kind(), ErrorKind::AlreadyExists);
+	assert_eq!(global_state.worker_names(), vec!["a", "b"]);
+
+	let msg = b"\"hello b\"".to_vec().into_boxed_slice();
+	block_on(global_state.named_worker("b").unwrap().post_message(msg)).unwrap();
+	let mut channels = b.state.worker_channels.lock().unwrap();
+	let received = channels.receiver.try_next().unwrap().unwrap();
+	assert_eq!(*received, *b"\"hello b\"");
+
+	block_on(a.terminate()).unwrap();
+	assert!(global_state.named_worker("a").is_none());
+	assert!(!global_state.named_workers.lock().unwrap().contains_key("a"));
+	assert_eq!(global_state.worker_names(), vec!["b"]);
+	global_state.register_worker("a", create_worker("a")).unwrap();
+}
+
+#[test]
+fn lockfile_tampered_cache() {
+	use tempfile::TempDir;
+
+	use crate::{deno_error::GetErrorKind, tokio_util};
+
+	let http_server_guard = crate::test_util::http_server();
+	let temp_dir = TempDir::new().unwrap();
+	let lock = temp_dir.path().join("lock.json").to_string_lossy().to_string();
+	// A DENO_DIR of its own, the test tampers with the cached module.
+	let specifier =
+		ModuleSpecifier::resolve_url("http://localhost:4545/tests/subdir/mismatch_ext.ts").unwrap();
+	let deno_dir_root = temp_dir.path().join("deno_dir");
+	let create_state = |lock_write:bool| {
+		ThreadSafeGlobalState::with_dir(
+			flags::DenoFlags {
+				argv:vec![String::from("./deno")],
+				lock:Some(lock.clone()),
+				lock_write,
+				..flags::DenoFlags::default()
+			},
+			Progress::new(),
+			deno_dir::DenoDir::new(Some(deno_dir_root.clone())).unwrap(),
+		)
+		.unwrap()
+	};
+
+	let state = create_state(true);
+	let specifier_ = specifier.clone();
+	tokio_util::run(async move {
+		state.fetch_compiled_module(&specifier_, None).await.unwrap();
+		state.lockfile.as_ref().unwrap().lock().unwrap().write().unwrap();
+		Ok(())
+	});
+
+	let state = create_state(false);
+	let deps_cache = &state.dir.deps_cache;
+	let cached = deps_cache.location.join(deps_cache.get_cache_filename(specifier.as_url()));
+	std::fs::write(&cached, "export const loaded = false;\n").unwrap();
+	let specifier_ = specifier.clone();
+	tokio_util::run(async move {
+		let err = state.fetch_compiled_module(&specifier_, None).await.unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::InvalidData);
+		assert!(err.to_string().contains(specifier_.as_str()));
+		Ok(())
+	});
+	drop(http_server_guard);
+}
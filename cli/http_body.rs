@@ -3,25 +3,100 @@
 use std::{
 	cmp::min,
 	io,
-	io::Read,
+	io::{Read, Write},
 	pin::Pin,
 	task::{Context, Poll},
+	time::Instant,
 };
 
-use futures::{io::AsyncRead, stream::StreamExt};
+use brotli::DecompressorWriter;
+use futures::{compat::Compat01As03, future::FutureExt, io::AsyncRead, stream::StreamExt};
 use reqwest::r#async::{Chunk, Decoder};
+use tokio::timer::Delay;
 
 /// Wraps `reqwest::Decoder` so that it can be exposed as an `AsyncRead` and
 /// integrated into resources more easily.
 pub struct HttpBody {
-	decoder:futures::compat::Compat01As03<Decoder>,
+	decoder:Compat01As03<Decoder>,
 	chunk:Option<Chunk>,
 	pos:usize,
+	deadline:Option<Compat01As03<Delay>>,
+	/// Set for `Content-Encoding: br` bodies, which reqwest doesn't decode.
+	/// `decoded` holds output not read yet and is only `Some` for such bodies,
+	/// `brotli` is taken once the stream has ended.
+	brotli:Option<DecompressorWriter<Vec<u8>>>,
+	decoded:Option<Vec<u8>>,
 }
 
 impl HttpBody {
 	pub fn from(body:Decoder) -> Self {
-		Self { decoder:futures::compat::Compat01As03::new(body), chunk:None, pos:0 }
+		Self {
+			decoder:Compat01As03::new(body),
+			chunk:None,
+			pos:0,
+			deadline:None,
+			brotli:None,
+			decoded:None,
+		}
+	}
+
+	/// Decodes the body as a brotli stream.
+	pub fn with_brotli(mut self) -> Self {
+		self.brotli = Some(DecompressorWriter::new(Vec::new(), 4096));
+		self.decoded = Some(Vec::new());
+		self
+	}
+
+	fn poll_read_brotli(
+		&mut self,
+		cx:&mut Context,
+		buf:&mut [u8],
+	) -> Poll<Result<usize, io::Error>> {
+		loop {
+			let decoded = self.decoded.as_mut().unwrap();
+			if self.pos < decoded.len() {
+				let n = min(buf.len(), decoded.len() - self.pos);
+				buf[..n].copy_from_slice(&decoded[self.pos..self.pos + n]);
+				self.pos += n;
+				if self.pos == decoded.len() {
+					decoded.clear();
+					self.pos = 0;
+				}
+				return Poll::Ready(Ok(n));
+			}
+			let writer = match self.brotli.as_mut() {
+				Some(writer) => writer,
+				None => return Poll::Ready(Ok(0)),
+			};
+			match self.decoder.poll_next_unpin(cx) {
+				Poll::Ready(Some(Err(e))) => {
+					return Poll::Ready(Err(io::Error::new(io::ErrorKind::Other, e)));
+				},
+				Poll::Ready(Some(Ok(chunk))) => {
+					writer.write_all(&chunk)?;
+					writer.flush()?;
+					decoded.append(writer.get_mut());
+				},
+				Poll::Ready(None) => {
+					match self.brotli.take().unwrap().into_inner() {
+						Ok(rest) => *decoded = rest,
+						Err(_) => {
+							return Poll::Ready(Err(io::Error::new(
+								io::ErrorKind::InvalidData,
+								"incomplete brotli stream",
+							)));
+						},
+					}
+				},
+				Poll::Pending => return Poll::Pending,
+			}
+		}
+	}
+
+	/// Reads fail with `io::ErrorKind::TimedOut` once `deadline` has passed.
+	pub fn with_deadline(mut self, deadline:Instant) -> Self {
+		self.deadline = Some(Compat01As03::new(Delay::new(deadline)));
+		self
 	}
 }
 
@@ -38,6 +113,14 @@ impl AsyncRead for HttpBody {
 		buf:&mut [u8],
 	) -> Poll<Result<usize, io::Error>> {
 		let mut inner = self.get_mut();
+		if let Some(deadline) = inner.deadline.as_mut() {
+			if let Poll::Ready(_) = deadline.poll_unpin(cx) {
+				return Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, "request timed out")));
+			}
+		}
+		if inner.decoded.is_some() {
+			return inner.poll_read_brotli(cx, buf);
+		}
 		if let Some(chunk) = inner.chunk.take() {
 			debug!("HttpBody Fake Read buf {} chunk {} pos {}", buf.len(), chunk.len(), inner.pos);
 			let n = min(buf.len(), chunk.len() - inner.pos);
@@ -56,31 +139,36 @@ impl AsyncRead for HttpBody {
 			assert_eq!(inner.pos, 0);
 		}
 
-		let p = inner.decoder.poll_next_unpin(cx);
-		match p {
-			Poll::Ready(Some(Err(e))) => {
-				Poll::Ready(Err(
-					// TODO Need to map hyper::Error into std::io::Error.
-					io::Error::new(io::ErrorKind::Other, e),
-				))
-			},
-			Poll::Ready(Some(Ok(chunk))) => {
-				debug!(
-					"HttpBody Real Read buf {} chunk {} pos {}",
-					buf.len(),
-					chunk.len(),
-					inner.pos
-				);
-				let n = min(buf.len(), chunk.len());
-				buf[..n].clone_from_slice(&chunk[..n]);
-				if buf.len() < chunk.len() {
-					inner.pos = n;
-					inner.chunk = Some(chunk);
-				}
-				Poll::Ready(Ok(n))
-			},
-			Poll::Ready(None) => Poll::Ready(Ok(0)),
-			Poll::Pending => Poll::Pending,
+		loop {
+			let p = inner.decoder.poll_next_unpin(cx);
+			match p {
+				Poll::Ready(Some(Err(e))) => {
+					return Poll::Ready(Err(
+						// TODO Need to map hyper::Error into std::io::Error.
+						io::Error::new(io::ErrorKind::Other, e),
+					));
+				},
+				// An empty chunk must not be reported as a zero-length read, that
+				// would be mistaken for EOF.
+				Poll::Ready(Some(Ok(ref chunk))) if chunk.is_empty() => continue,
+				Poll::Ready(Some(Ok(chunk))) => {
+					debug!(
+						"HttpBody Real Read buf {} chunk {} pos {}",
+						buf.len(),
+						chunk.len(),
+						inner.pos
+					);
+					let n = min(buf.len(), chunk.len());
+					buf[..n].clone_from_slice(&chunk[..n]);
+					if buf.len() < chunk.len() {
+						inner.pos = n;
+						inner.chunk = Some(chunk);
+					}
+					return Poll::Ready(Ok(n));
+				},
+				Poll::Ready(None) => return Poll::Ready(Ok(0)),
+				Poll::Pending => return Poll::Pending,
+			}
 		}
 	}
 }
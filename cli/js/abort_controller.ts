@@ -0,0 +1,80 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
+import * as domTypes from "./dom_types.ts";
+import { DenoError, ErrorKind } from "./errors.ts";
+import { Event } from "./event.ts";
+import { EventTarget } from "./event_target.ts";
+import { close } from "./files.ts";
+
+const signalAbort: unique symbol = Symbol();
+
+export class AbortSignal extends EventTarget implements domTypes.AbortSignal {
+	private _aborted = false;
+	onabort: ((this: domTypes.AbortSignal, ev: domTypes.Event) => any) | null =
+		null;
+
+	get aborted(): boolean {
+		return this._aborted;
+	}
+
+	[signalAbort](): void {
+		if (this._aborted) {
+			return;
+		}
+		this._aborted = true;
+		const event = new Event("abort");
+		if (typeof this.onabort === "function") {
+			this.onabort(event);
+		}
+		this.dispatchEvent(event);
+	}
+
+	get [Symbol.toStringTag](): string {
+		return "AbortSignal";
+	}
+}
+
+export class AbortController {
+	readonly signal = new AbortSignal();
+
+	/** Aborts the ops started with `signal`. Their promises reject with
+	 * `ErrorKind.Aborted`. */
+	abort(): void {
+		this.signal[signalAbort]();
+	}
+
+	get [Symbol.toStringTag](): string {
+		return "AbortController";
+	}
+}
+
+function abortError(): DenoError<ErrorKind.Aborted> {
+	return new DenoError(ErrorKind.Aborted, "operation was aborted");
+}
+
+/** Calls `send` with the rid of a cancel handle that is cancelled when
+ * `signal` aborts, so the async op it starts is dropped on the Rust side and
+ * its promise rejects. Without a signal `send` gets no rid. */
+export async function withSignal<T>(
+	signal: domTypes.AbortSignal | null | undefined,
+	send: (cancelRid?: number) => Promise<T>,
+): Promise<T> {
+	if (!signal) {
+		return await send();
+	}
+	if (signal.aborted) {
+		throw abortError();
+	}
+	const cancelRid = sendSync(dispatch.OP_CANCEL_HANDLE) as number;
+	const onAbort = (): void => {
+		sendSync(dispatch.OP_CANCEL, { rid: cancelRid });
+	};
+	signal.addEventListener("abort", onAbort);
+	try {
+		return await send(cancelRid);
+	} finally {
+		signal.removeEventListener("abort", onAbort);
+		close(cancelRid);
+	}
+}
@@ -0,0 +1,44 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, test, testPerm } from "./test_util.ts";
+
+test(function abortControllerSignal(): void {
+	const controller = new AbortController();
+	const { signal } = controller;
+	assert(!signal.aborted);
+	let onabort = 0;
+	let listener = 0;
+	signal.onabort = (): void => {
+		onabort++;
+	};
+	signal.addEventListener("abort", (): void => {
+		listener++;
+	});
+	controller.abort();
+	controller.abort();
+	assert(signal.aborted);
+	assertEquals(onabort, 1);
+	assertEquals(listener, 1);
+	assertEquals(String(controller), "[object AbortController]");
+	assertEquals(String(signal), "[object AbortSignal]");
+});
+
+testPerm({ run: true }, async function abortProcessStatus(): Promise<void> {
+	const p = Deno.run({
+		args: ["python", "-c", "import time; time.sleep(10)"],
+	});
+	const controller = new AbortController();
+	setTimeout((): void => controller.abort(), 50);
+	let err;
+	try {
+		await p.status({ signal: controller.signal });
+	} catch (err_) {
+		err = err_;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.Aborted);
+	// The process is still running and can be waited on again. Any signal
+	// terminates it on Windows.
+	p.kill(9);
+	const status = await p.status();
+	assert(!status.success);
+	p.close();
+});
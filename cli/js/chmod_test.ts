@@ -20,6 +20,18 @@ testPerm({ read: true, write: true }, function chmodSyncSuccess(): void {
 	}
 });
 
+testPerm({ read: true, write: true }, function chmodSyncOwnerOnly(): void {
+	const tempDir = Deno.makeTempDirSync();
+	const filename = tempDir + "/test.txt";
+	Deno.writeFileSync(filename, new Uint8Array([1]), { perm: 0o666 });
+
+	Deno.chmodSync(filename, 0o600);
+
+	if (isNotWindows) {
+		assertEquals(Deno.statSync(filename).mode & 0o777, 0o600);
+	}
+});
+
 // Check symlink when not on windows
 if (isNotWindows) {
 	testPerm(
@@ -12,6 +12,7 @@ import { Diagnostic, fromTypeScriptDiagnostic } from "./diagnostics.ts";
 import { cwd } from "./dir.ts";
 import { sendAsync, sendSync } from "./dispatch_json.ts";
 import * as dispatch from "./dispatch.ts";
+import { base64Decode } from "./encoding.ts";
 import * as os from "./os.ts";
 import { getMappedModuleName, parseTypeDirectives } from "./type_directives.ts";
 import { assert, notImplemented } from "./util.ts";
@@ -707,16 +708,6 @@ window.compilerMain = function compilerMain(): void {
 	};
 };
 
-function base64ToUint8Array(data: string): Uint8Array {
-	const binString = window.atob(data);
-	const size = binString.length;
-	const bytes = new Uint8Array(size);
-	for (let i = 0; i < size; i++) {
-		bytes[i] = binString.charCodeAt(i);
-	}
-	return bytes;
-}
-
 window.wasmCompilerMain = function wasmCompilerMain(): void {
 	// workerMain should have already been called since a compiler is a worker.
 	window.onmessage = async ({
@@ -724,7 +715,7 @@ window.wasmCompilerMain = function wasmCompilerMain(): void {
 	}: {
 		data: string;
 	}): Promise<void> => {
-		const buffer = base64ToUint8Array(binary);
+		const buffer = base64Decode(binary);
 		// @ts-ignore
 		const compiled = await WebAssembly.compile(buffer);
 
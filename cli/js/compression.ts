@@ -0,0 +1,148 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
+import { Reader, Writer, EOF } from "./io.ts";
+import { writeAll } from "./buffer.ts";
+
+/** `deflate` is the zlib format, as in HTTP's `Content-Encoding`, and
+ * `deflate-raw` the bare deflate stream.
+ */
+export type CompressionFormat = "gzip" | "deflate" | "deflate-raw" | "brotli";
+
+export interface DecompressionOptions {
+	/** Preset dictionary. Not supported: neither the deflate nor the brotli
+	 * implementation deno is built with takes one, so passing one throws
+	 * `InvalidInput`.
+	 */
+	dictionary?: Uint8Array;
+	/** Fails with `InvalidData` once the output would grow past this many
+	 * bytes, in total over all `update()` and `finish()` calls. Use it when
+	 * decompressing untrusted input. Unlimited by default.
+	 */
+	maxOutputLength?: number;
+}
+
+export interface CompressionOptions extends DecompressionOptions {
+	/** 0 to 9 for gzip and deflate, defaulting to 6, and 0 to 11 for brotli,
+	 * defaulting to 11. Higher is smaller and slower.
+	 */
+	level?: number;
+}
+
+/** Compresses or decompresses data given in pieces. */
+export interface Compressor {
+	readonly rid: number;
+
+	/** Feeds `data` in and returns the output produced so far, which may be
+	 * empty.
+	 */
+	update(data: Uint8Array): Uint8Array;
+
+	/** Returns the rest of the output and closes `rid`. Throws `InvalidData`
+	 * if decompressed input is corrupt, or truncated gzip or brotli.
+	 */
+	finish(): Uint8Array;
+
+	/** Feeds everything read from `r` until EOF in, writes the output to `w`
+	 * and finishes.
+	 */
+	transform(r: Reader, w: Writer): Promise<void>;
+}
+
+class CompressorImpl implements Compressor {
+	constructor(readonly rid: number) {}
+
+	update(data: Uint8Array): Uint8Array {
+		return sendSync(
+			dispatch.OP_COMPRESSION_UPDATE,
+			{ rid: this.rid },
+			data,
+		);
+	}
+
+	finish(): Uint8Array {
+		return sendSync(dispatch.OP_COMPRESSION_FINISH, { rid: this.rid });
+	}
+
+	async transform(r: Reader, w: Writer): Promise<void> {
+		const buf = new Uint8Array(32 * 1024);
+		while (true) {
+			const result = await r.read(buf);
+			if (result === EOF) {
+				break;
+			}
+			await writeAll(w, this.update(buf.subarray(0, result)));
+		}
+		await writeAll(w, this.finish());
+	}
+}
+
+/** Starts compressing with `format`.
+ *
+ *       const gz = Deno.createCompressor("gzip", { level: 9 });
+ *       await gz.transform(file, out);
+ */
+export function createCompressor(
+	format: CompressionFormat,
+	options: CompressionOptions = {},
+): Compressor {
+	const rid = sendSync(
+		dispatch.OP_COMPRESSION_CREATE,
+		{
+			format,
+			level: options.level,
+			maxOutputLength: options.maxOutputLength,
+		},
+		options.dictionary,
+	);
+	return new CompressorImpl(rid);
+}
+
+/** Starts decompressing `format`.
+ *
+ *       const gunzip = Deno.createDecompressor("gzip", {
+ *         maxOutputLength: 64 * 1024 * 1024,
+ *       });
+ */
+export function createDecompressor(
+	format: CompressionFormat,
+	options: DecompressionOptions = {},
+): Compressor {
+	const rid = sendSync(
+		dispatch.OP_COMPRESSION_CREATE,
+		{
+			format,
+			decompress: true,
+			maxOutputLength: options.maxOutputLength,
+		},
+		options.dictionary,
+	);
+	return new CompressorImpl(rid);
+}
+
+function concat(a: Uint8Array, b: Uint8Array): Uint8Array {
+	const buf = new Uint8Array(a.byteLength + b.byteLength);
+	buf.set(a);
+	buf.set(b, a.byteLength);
+	return buf;
+}
+
+/** Compresses `data` in one go. */
+export function compress(
+	format: CompressionFormat,
+	data: Uint8Array,
+	options: CompressionOptions = {},
+): Uint8Array {
+	const compressor = createCompressor(format, options);
+	return concat(compressor.update(data), compressor.finish());
+}
+
+/** Decompresses `data` in one go. */
+export function decompress(
+	format: CompressionFormat,
+	data: Uint8Array,
+	options: DecompressionOptions = {},
+): Uint8Array {
+	const decompressor = createDecompressor(format, options);
+	return concat(decompressor.update(data), decompressor.finish());
+}
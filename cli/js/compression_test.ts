@@ -0,0 +1,119 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, test } from "./test_util.ts";
+
+const formats: Deno.CompressionFormat[] = [
+	"gzip",
+	"deflate",
+	"deflate-raw",
+	"brotli",
+];
+
+function randomData(n: number): Uint8Array {
+	const data = new Uint8Array(n);
+	// getRandomValues() takes at most 65536 bytes.
+	for (let i = 0; i < n; i += 65536) {
+		crypto.getRandomValues(data.subarray(i, Math.min(n, i + 65536)));
+	}
+	// The second half compresses well.
+	data.fill(97, n >> 1);
+	return data;
+}
+
+test(function compressionRoundTrip(): void {
+	for (const format of formats) {
+		for (const size of [1, 1000, 100000]) {
+			const data = randomData(size);
+			const compressed = Deno.compress(format, data, { level: 1 });
+			assertEquals(Deno.decompress(format, compressed), data);
+		}
+	}
+});
+
+test(function compressionStreaming(): void {
+	const data = randomData(50000);
+	for (const format of formats) {
+		const compressor = Deno.createCompressor(format);
+		assertEquals(Deno.resources()[compressor.rid], "compressor");
+		const parts = [
+			compressor.update(data.subarray(0, 10000)),
+			compressor.update(data.subarray(10000)),
+			compressor.finish(),
+		];
+		assertEquals(Deno.resources()[compressor.rid], undefined);
+
+		const decompressor = Deno.createDecompressor(format);
+		const out = new Deno.Buffer();
+		for (const part of parts) {
+			out.writeSync(decompressor.update(part));
+		}
+		out.writeSync(decompressor.finish());
+		assertEquals(out.bytes(), data);
+	}
+});
+
+test(async function compressionTransform(): Promise<void> {
+	const data = randomData(100000);
+	const compressed = new Deno.Buffer();
+	await Deno.createCompressor("gzip").transform(
+		new Deno.Buffer(data),
+		compressed,
+	);
+	const out = new Deno.Buffer();
+	await Deno.createDecompressor("gzip").transform(compressed, out);
+	assertEquals(out.bytes(), data);
+});
+
+test(function compressionErrors(): void {
+	const compressed = Deno.compress("gzip", randomData(1000));
+	let err;
+	try {
+		Deno.decompress("gzip", compressed.subarray(0, compressed.length >> 1));
+	} catch (e) {
+		err = e;
+	}
+	assert(err);
+	assertEquals(err.kind, Deno.ErrorKind.InvalidData);
+
+	err = undefined;
+	try {
+		Deno.createCompressor("gzip", { level: 10 });
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.InvalidInput);
+
+	err = undefined;
+	try {
+		Deno.createDecompressor("deflate", { dictionary: new Uint8Array(8) });
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.InvalidInput);
+	assertEquals(
+		err.message,
+		'Preset dictionaries are not supported for "deflate"',
+	);
+});
+
+test(function compressionMaxOutputLength(): void {
+	// A MiB of zeros compresses to a few hundred bytes at most.
+	const bomb = Deno.compress("gzip", new Uint8Array(1 << 20));
+	const decompressor = Deno.createDecompressor("gzip", {
+		maxOutputLength: 1000,
+	});
+	let err;
+	try {
+		decompressor.update(bomb);
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.InvalidData);
+	Deno.close(decompressor.rid);
+
+	const data = randomData(1000);
+	const compressed = Deno.compress("brotli", data);
+	assertEquals(
+		Deno.decompress("brotli", compressed, { maxOutputLength: 1000 }),
+		data,
+	);
+});
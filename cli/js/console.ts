@@ -1,5 +1,8 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
 import { cliTable } from "./console_table.ts";
+import { core } from "./core.ts";
+import { sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
 import { File, stdout } from "./files.ts";
 import { TextEncoder } from "./text_encoding.ts";
 import { TypedArray } from "./types.ts";
@@ -487,6 +490,18 @@ export function stringifyArgs(
 
 type PrintFunc = (x: string, isErr?: boolean) => void;
 
+const encoder = new TextEncoder();
+
+/** Prints `s` like `Deno.core.print`, through the line buffering of
+ * `--line-buffered` once the ops are set up. */
+export function print(s: string, isErr = false): void {
+	if (dispatch.OP_PRINT === undefined) {
+		core.print(s, isErr);
+		return;
+	}
+	sendSync(dispatch.OP_PRINT, { stderr: isErr }, encoder.encode(s));
+}
+
 const countMap = new Map<string, number>();
 const timerMap = new Map<string, number>();
 const isConsoleInstance = Symbol("isConsoleInstance");
@@ -0,0 +1,61 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { sendAsync, sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
+
+export interface CopyDirOptions {
+	/** Also copy directory permissions and access/modification times. File
+	 * permissions are always copied. Defaults to `false`. */
+	preserve?: boolean;
+	/** Merge into an existing destination, replacing files that are already
+	 * there. When `false` (the default) an existing target is an error. */
+	overwrite?: boolean;
+	/** Copy what symlinks point to rather than the links themselves.
+	 * Defaults to `false`. */
+	followSymlinks?: boolean;
+}
+
+function req(
+	from: string,
+	to: string,
+	options: CopyDirOptions,
+): {
+	from: string;
+	to: string;
+	preserve: boolean;
+	overwrite: boolean;
+	followSymlinks: boolean;
+} {
+	return {
+		from,
+		to,
+		preserve: !!options.preserve,
+		overwrite: !!options.overwrite,
+		followSymlinks: !!options.followSymlinks,
+	};
+}
+
+/** Recursively copies the directory `from` to `to` synchronously.
+ * Requires read permission for `from` and write permission for `to`.
+ *
+ *       Deno.copyDirSync("src", "backup", { preserve: true });
+ */
+export function copyDirSync(
+	from: string,
+	to: string,
+	options: CopyDirOptions = {},
+): void {
+	sendSync(dispatch.OP_COPY_DIR, req(from, to, options));
+}
+
+/** Recursively copies the directory `from` to `to`.
+ * Requires read permission for `from` and write permission for `to`.
+ *
+ *       await Deno.copyDir("src", "backup", { preserve: true });
+ */
+export async function copyDir(
+	from: string,
+	to: string,
+	options: CopyDirOptions = {},
+): Promise<void> {
+	await sendAsync(dispatch.OP_COPY_DIR, req(from, to, options));
+}
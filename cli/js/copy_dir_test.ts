@@ -0,0 +1,56 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, testPerm } from "./test_util.ts";
+
+function writeFileString(filename: string, s: string): void {
+	Deno.writeFileSync(filename, new TextEncoder().encode(s), { perm: 0o666 });
+}
+
+function readFileString(filename: string): string {
+	return new TextDecoder().decode(Deno.readFileSync(filename));
+}
+
+testPerm({ read: true, write: true }, function copyDirSyncSuccess(): void {
+	const tempDir = Deno.makeTempDirSync();
+	const from = tempDir + "/from";
+	Deno.mkdirSync(from + "/nested", true);
+	writeFileString(from + "/a.txt", "a");
+	writeFileString(from + "/nested/b.txt", "b");
+
+	Deno.copyDirSync(from, tempDir + "/to");
+	assertEquals(readFileString(tempDir + "/to/a.txt"), "a");
+	assertEquals(readFileString(tempDir + "/to/nested/b.txt"), "b");
+});
+
+testPerm({ read: true, write: true }, async function copyDirExists(): Promise<
+	void
+> {
+	const tempDir = Deno.makeTempDirSync();
+	const from = tempDir + "/from";
+	Deno.mkdirSync(from);
+	writeFileString(from + "/a.txt", "new");
+	Deno.mkdirSync(tempDir + "/to");
+	writeFileString(tempDir + "/to/a.txt", "old");
+
+	let err;
+	try {
+		await Deno.copyDir(from, tempDir + "/to");
+	} catch (e) {
+		err = e;
+	}
+	assert(!!err);
+	assertEquals(err.kind, Deno.ErrorKind.AlreadyExists);
+
+	await Deno.copyDir(from, tempDir + "/to", { overwrite: true });
+	assertEquals(readFileString(tempDir + "/to/a.txt"), "new");
+});
+
+testPerm({ write: true, read: false }, function copyDirSyncPerm(): void {
+	let err;
+	try {
+		Deno.copyDirSync("/from", "/to");
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.PermissionDenied);
+	assertEquals(err.name, "PermissionDenied");
+});
@@ -21,6 +21,12 @@ export {
 	templateDir,
 	videoDir,
 	hostname,
+	uptime,
+	loadavg,
+	memoryInfo,
+	MemoryInfo,
+	resourceUsage,
+	ResourceUsage,
 } from "./os.ts";
 export { chdir, cwd } from "./dir.ts";
 export {
@@ -32,10 +38,18 @@ export {
 	stderr,
 	read,
 	readSync,
+	readPartial,
+	ReadPartialOptions,
+	ReadPartialResult,
 	write,
 	writeSync,
 	seek,
 	seekSync,
+	flock,
+	flockSync,
+	funlock,
+	funlockSync,
+	LockOptions,
 	close,
 	OpenMode,
 } from "./files.ts";
@@ -69,17 +83,30 @@ export { mkdirSync, mkdir } from "./mkdir.ts";
 export {
 	makeTempDirSync,
 	makeTempDir,
+	makeTempFileSync,
+	makeTempFile,
+	MakeTempOptions,
 	MakeTempDirOptions,
 } from "./make_temp_dir.ts";
 export { chmodSync, chmod } from "./chmod.ts";
+export { umask } from "./umask.ts";
 export { chownSync, chown } from "./chown.ts";
 export { utimeSync, utime } from "./utime.ts";
 export { removeSync, remove, RemoveOption } from "./remove.ts";
 export { renameSync, rename } from "./rename.ts";
-export { realpathSync, realpath } from "./realpath.ts";
-export { readFileSync, readFile } from "./read_file.ts";
-export { readDirSync, readDir } from "./read_dir.ts";
+export { realpathSync, realpath, RealpathOptions } from "./realpath.ts";
+export { readFileSync, readFile, ReadFileOptions } from "./read_file.ts";
+export {
+	readDirSync,
+	readDir,
+	readDirIter,
+	DirEntry,
+	DirIterator,
+	ReadDirIterOptions,
+} from "./read_dir.ts";
+export { watchFs, FsEvent, FsWatcher, WatchFsOptions } from "./fs_events.ts";
 export { copyFileSync, copyFile } from "./copy_file.ts";
+export { copyDirSync, copyDir, CopyDirOptions } from "./copy_dir.ts";
 export { readlinkSync, readlink } from "./read_link.ts";
 export { statSync, lstatSync, stat, lstat } from "./stat.ts";
 export { linkSync, link } from "./link.ts";
@@ -94,21 +121,85 @@ export {
 	PermissionStatus,
 	Permissions,
 } from "./permissions.ts";
-export { truncateSync, truncate } from "./truncate.ts";
+export {
+	truncateSync,
+	truncate,
+	ftruncateSync,
+	ftruncate,
+	fallocateSync,
+	fallocate,
+} from "./truncate.ts";
 export { FileInfo } from "./file_info.ts";
 export { openPlugin } from "./plugins.ts";
-export { connect, dial, listen, Listener, Conn } from "./net.ts";
-export { dialTLS, listenTLS } from "./tls.ts";
+export {
+	compress,
+	CompressionFormat,
+	CompressionOptions,
+	Compressor,
+	createCompressor,
+	createDecompressor,
+	decompress,
+	DecompressionOptions,
+} from "./compression.ts";
+export {
+	Base64Options,
+	base64Decode,
+	base64Encode,
+	hexDecode,
+	hexEncode,
+} from "./encoding.ts";
+export {
+	bytesEqual,
+	createHash,
+	Hash,
+	HashAlgorithm,
+	hmac,
+} from "./hash.ts";
+export {
+	connect,
+	dial,
+	listen,
+	listenDatagram,
+	listenUnix,
+	dialUnix,
+	resolveDns,
+	ping,
+	shutdown,
+	ShutdownMode,
+	localAddr,
+	peerAddr,
+	NetAddr,
+	UnixAddr,
+	DnsRecord,
+	ResolveDnsOptions,
+	PingOptions,
+	PingResult,
+	Listener,
+	Conn,
+	ConnReadHalf,
+	ConnWriteHalf,
+	TcpOptions,
+	UnixListenOptions,
+	DatagramConn,
+	DatagramAddr,
+} from "./net.ts";
+export { dialTLS, listenTLS, TLSConn } from "./tls.ts";
 export { metrics, Metrics } from "./metrics.ts";
-export { resources } from "./resources.ts";
+export { closeAll, dup, resources, resourceStats } from "./resources.ts";
+export { namedWorkers, postMessageToWorker } from "./workers.ts";
 export {
+	getPriority,
 	kill,
 	run,
 	RunOptions,
 	Process,
 	ProcessStatus,
+	setPriority,
+	StatusOptions,
 	Signal,
 } from "./process.ts";
+export { signal, SignalStream } from "./signals.ts";
+export { interval, IntervalStream } from "./interval.ts";
 export { inspect, customInspect } from "./console.ts";
 export { build, OperatingSystem, Arch } from "./build.ts";
 export { version } from "./version.ts";
@@ -125,6 +216,8 @@ export { setPrepareStackTrace } from "./error_stack.ts";
 // TODO Don't expose Console nor stringifyArgs.
 /** @internal */
 export { Console, stringifyArgs } from "./console.ts";
+/** @internal */
+export { isIncomplete } from "./repl.ts";
 // TODO Don't expose DomIterableMixin.
 /** @internal */
 export { DomIterableMixin } from "./mixins/dom_iterable.ts";
@@ -15,6 +15,8 @@ export function cwd(): string {
 
 /**
  * `chdir()` Change the current working directory to path.
+ * Each worker has its own working directory, so this does not affect
+ * other workers or the directory of the process.
  * throws `NotFound` exception if directory not available
  */
 export function chdir(directory: string): void {
@@ -5,6 +5,8 @@ import { AsyncHandler } from "./plugins.ts";
 
 // These consts are shared with Rust. Update with care.
 export let OP_READ: number;
+export let OP_READ_PARTIAL: number;
+export let OP_PRINT: number;
 export let OP_WRITE: number;
 export let OP_EXIT: number;
 export let OP_IS_TTY: number;
@@ -12,6 +14,7 @@ export let OP_ENV: number;
 export let OP_EXEC_PATH: number;
 export let OP_UTIME: number;
 export let OP_SET_ENV: number;
+export let OP_DELETE_ENV: number;
 export let OP_GET_ENV: number;
 export let OP_GET_DIR: number;
 export let OP_START: number;
@@ -22,6 +25,12 @@ export let OP_FETCH_SOURCE_FILES: number;
 export let OP_OPEN: number;
 export let OP_CLOSE: number;
 export let OP_SEEK: number;
+export let OP_FLOCK: number;
+export let OP_FUNLOCK: number;
+export let OP_FTRUNCATE: number;
+export let OP_FALLOCATE: number;
+export let OP_READ_FILE: number;
+export let OP_WRITE_FILE: number;
 export let OP_FETCH: number;
 export let OP_METRICS: number;
 export let OP_REPL_START: number;
@@ -30,13 +39,45 @@ export let OP_ACCEPT: number;
 export let OP_ACCEPT_TLS: number;
 export let OP_DIAL: number;
 export let OP_SHUTDOWN: number;
+export let OP_SPLIT: number;
+export let OP_LOCAL_ADDR: number;
+export let OP_PEER_ADDR: number;
 export let OP_LISTEN: number;
+export let OP_SET_TCP_OPTIONS: number;
+export let OP_TCP_OPTIONS: number;
 export let OP_LISTEN_TLS: number;
+export let OP_UDP_BIND: number;
+export let OP_UDP_SEND_TO: number;
+export let OP_UDP_RECV_FROM: number;
+export let OP_UNIX_LISTEN: number;
+export let OP_UNIX_ACCEPT: number;
+export let OP_UNIX_DIAL: number;
+export let OP_RESOLVE_DNS: number;
+export let OP_PING: number;
 export let OP_RESOURCES: number;
+export let OP_CLOSE_ALL: number;
+export let OP_DUP: number;
+export let OP_CANCEL_HANDLE: number;
+export let OP_CANCEL: number;
 export let OP_GET_RANDOM_VALUES: number;
+export let OP_HASH_CREATE: number;
+export let OP_HASH_UPDATE: number;
+export let OP_HASH_DIGEST: number;
+export let OP_HMAC: number;
+export let OP_BYTES_EQUAL: number;
+export let OP_BASE64_ENCODE: number;
+export let OP_BASE64_DECODE: number;
+export let OP_HEX_ENCODE: number;
+export let OP_HEX_DECODE: number;
+export let OP_COMPRESSION_CREATE: number;
+export let OP_COMPRESSION_UPDATE: number;
+export let OP_COMPRESSION_FINISH: number;
 export let OP_GLOBAL_TIMER_STOP: number;
 export let OP_GLOBAL_TIMER: number;
 export let OP_NOW: number;
+export let OP_INTERVAL_START: number;
+export let OP_INTERVAL_POLL: number;
+export let OP_INTERVAL_STOP: number;
 export let OP_QUERY_PERMISSION: number;
 export let OP_REVOKE_PERMISSION: number;
 export let OP_REQUEST_PERMISSION: number;
@@ -46,29 +87,48 @@ export let OP_HOST_POST_MESSAGE: number;
 export let OP_HOST_GET_MESSAGE: number;
 export let OP_WORKER_POST_MESSAGE: number;
 export let OP_WORKER_GET_MESSAGE: number;
+export let OP_NAMED_WORKER_POST_MESSAGE: number;
+export let OP_NAMED_WORKERS: number;
 export let OP_RUN: number;
 export let OP_RUN_STATUS: number;
 export let OP_KILL: number;
+export let OP_GET_PRIORITY: number;
+export let OP_SET_PRIORITY: number;
+export let OP_SIGNAL_BIND: number;
+export let OP_SIGNAL_POLL: number;
+export let OP_SIGNAL_UNBIND: number;
 export let OP_CHDIR: number;
 export let OP_MKDIR: number;
 export let OP_CHMOD: number;
+export let OP_UMASK: number;
 export let OP_CHOWN: number;
 export let OP_REMOVE: number;
 export let OP_COPY_FILE: number;
+export let OP_COPY_DIR: number;
 export let OP_STAT: number;
 export let OP_REALPATH: number;
 export let OP_READ_DIR: number;
+export let OP_OPEN_DIR: number;
+export let OP_READ_DIR_BATCH: number;
+export let OP_FS_WATCH: number;
+export let OP_FS_EVENTS_POLL: number;
 export let OP_RENAME: number;
 export let OP_LINK: number;
 export let OP_SYMLINK: number;
 export let OP_READ_LINK: number;
 export let OP_TRUNCATE: number;
 export let OP_MAKE_TEMP_DIR: number;
+export let OP_MAKE_TEMP_FILE: number;
 export let OP_CWD: number;
 export let OP_FETCH_ASSET: number;
 export let OP_DIAL_TLS: number;
 export let OP_HOSTNAME: number;
+export let OP_UPTIME: number;
+export let OP_LOADAVG: number;
+export let OP_RESOURCE_USAGE: number;
+export let OP_MEM_INFO: number;
 export let OP_OPEN_PLUGIN: number;
+export let OP_CLOSE_PLUGIN: number;
 
 const PLUGIN_ASYNC_HANDLER_MAP: Map<number, AsyncHandler> = new Map();
 
@@ -93,31 +153,50 @@ export function asyncMsgFromRust(opId: number, ui8: Uint8Array): void {
 		case OP_UTIME:
 		case OP_OPEN:
 		case OP_SEEK:
+		case OP_FLOCK:
+		case OP_FUNLOCK:
+		case OP_FTRUNCATE:
+		case OP_FALLOCATE:
+		case OP_READ_FILE:
+		case OP_WRITE_FILE:
 		case OP_FETCH:
 		case OP_REPL_START:
 		case OP_REPL_READLINE:
 		case OP_ACCEPT:
 		case OP_ACCEPT_TLS:
 		case OP_DIAL:
+		case OP_UDP_SEND_TO:
+		case OP_UDP_RECV_FROM:
+		case OP_UNIX_ACCEPT:
+		case OP_UNIX_DIAL:
+		case OP_RESOLVE_DNS:
+		case OP_PING:
 		case OP_GLOBAL_TIMER:
+		case OP_INTERVAL_POLL:
 		case OP_HOST_GET_WORKER_CLOSED:
 		case OP_HOST_GET_MESSAGE:
 		case OP_WORKER_GET_MESSAGE:
 		case OP_RUN_STATUS:
+		case OP_SIGNAL_POLL:
 		case OP_MKDIR:
 		case OP_CHMOD:
 		case OP_CHOWN:
 		case OP_REMOVE:
 		case OP_COPY_FILE:
+		case OP_COPY_DIR:
 		case OP_STAT:
 		case OP_REALPATH:
 		case OP_READ_DIR:
+		case OP_READ_DIR_BATCH:
+		case OP_READ_PARTIAL:
+		case OP_FS_EVENTS_POLL:
 		case OP_RENAME:
 		case OP_LINK:
 		case OP_SYMLINK:
 		case OP_READ_LINK:
 		case OP_TRUNCATE:
 		case OP_MAKE_TEMP_DIR:
+		case OP_MAKE_TEMP_FILE:
 		case OP_DIAL_TLS:
 		case OP_FETCH_SOURCE_FILES:
 			json.asyncMsgFromRust(opId, ui8);
@@ -26,6 +26,16 @@ function nextPromiseId(): number {
 }
 
 function decode(ui8: Uint8Array): JsonResponse {
+	// Ops returning raw bytes prefix the JSON part with a zero word and its
+	// length, and the bytes follow it. They are the op's result.
+	if (ui8.byteLength >= 8 && ui8[0] === 0) {
+		const view = new DataView(ui8.buffer, ui8.byteOffset, 8);
+		const jsonLength = view.getUint32(4, true);
+		const json = ui8.subarray(8, 8 + jsonLength);
+		const res = JSON.parse(new TextDecoder().decode(json)) as JsonResponse;
+		res.ok = ui8.subarray(8 + jsonLength);
+		return res;
+	}
 	const s = new TextDecoder().decode(ui8);
 	return JSON.parse(s) as JsonResponse;
 }
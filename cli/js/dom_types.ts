@@ -61,7 +61,7 @@ export interface BlobPropertyBag {
 }
 
 interface AbortSignalEventMap {
-	abort: ProgressEvent;
+	abort: Event;
 }
 
 // https://dom.spec.whatwg.org/#node
@@ -254,7 +254,7 @@ export interface AddEventListenerOptions extends EventListenerOptions {
 
 export interface AbortSignal extends EventTarget {
 	readonly aborted: boolean;
-	onabort: ((this: AbortSignal, ev: ProgressEvent) => any) | null;
+	onabort: ((this: AbortSignal, ev: Event) => any) | null;
 	addEventListener<K extends keyof AbortSignalEventMap>(
 		type: K,
 		listener: (this: AbortSignal, ev: AbortSignalEventMap[K]) => any,
@@ -555,8 +555,49 @@ export interface RequestInit {
 	redirect?: RequestRedirect;
 	referrer?: string;
 	referrerPolicy?: ReferrerPolicy;
+	/** Aborts the request until the response headers have arrived. The
+	 * promise then rejects with `ErrorKind.Aborted`. */
 	signal?: AbortSignal | null;
 	window?: any;
+	/** Deno extension. Milliseconds before the request is aborted, `0`
+	 * disables the timeout. Defaults to the `--fetch-timeout` flag. */
+	timeout?: number;
+	/** Deno extension. Set to `false` to receive gzip and brotli encoded
+	 * bodies as sent, along with their `Content-Encoding` header. */
+	decompress?: boolean;
+	/** Deno extension. Redirects followed before failing with
+	 * `ErrorKind.TooManyRedirects`. Defaults to 20. */
+	maxRedirects?: number;
+	/** Deno extension. Drop the `Authorization`, `Cookie` and
+	 * `Proxy-Authorization` headers when following a redirect to another
+	 * origin. */
+	stripCredentialsOnRedirect?: boolean;
+	/** Deno extension. Send a `multipart/form-data` body made of these
+	 * parts, with `Content-Type` and `Content-Length` set to match. Replaces
+	 * `body`.
+	 *
+	 *       const file = await Deno.open("report.csv");
+	 *       await fetch(url, {
+	 *         method: "POST",
+	 *         multipart: [
+	 *           { name: "title", value: "Report" },
+	 *           { name: "upload", rid: file.rid, filename: "report.csv" }
+	 *         ]
+	 *       });
+	 */
+	multipart?: MultipartPart[];
+}
+
+export interface MultipartPart {
+	name: string;
+	/** Content of a plain field. */
+	value?: string;
+	/** A file opened for reading, sent from its current position to its end
+	 * without being read into memory first. */
+	rid?: number;
+	filename?: string;
+	/** Defaults to `application/octet-stream` for parts with a filename. */
+	contentType?: string;
 }
 
 export interface ResponseInit {
@@ -0,0 +1,50 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
+
+export interface Base64Options {
+	/** Use `-` and `_` instead of `+` and `/`, as in URLs and file names. */
+	urlSafe?: boolean;
+	/** Pad the output with `=` to a multiple of four characters. Defaults to
+	 * true. Decoding accepts input with or without padding.
+	 */
+	pad?: boolean;
+}
+
+/** Encodes `data` as base64.
+ *
+ *       Deno.base64Encode(data, { urlSafe: true, pad: false });
+ */
+export function base64Encode(
+	data: Uint8Array,
+	options: Base64Options = {},
+): string {
+	return sendSync(
+		dispatch.OP_BASE64_ENCODE,
+		{ urlSafe: options.urlSafe, pad: options.pad },
+		data,
+	);
+}
+
+/** Decodes base64 `data`, throwing `InvalidData` if it isn't valid. */
+export function base64Decode(
+	data: string,
+	options: Base64Options = {},
+): Uint8Array {
+	return sendSync(dispatch.OP_BASE64_DECODE, {
+		data,
+		urlSafe: options.urlSafe,
+	});
+}
+
+/** Encodes `data` as lower case hex. */
+export function hexEncode(data: Uint8Array): string {
+	return sendSync(dispatch.OP_HEX_ENCODE, {}, data);
+}
+
+/** Decodes hex `data` of either case, throwing `InvalidData` if it isn't
+ * valid.
+ */
+export function hexDecode(data: string): Uint8Array {
+	return sendSync(dispatch.OP_HEX_DECODE, { data });
+}
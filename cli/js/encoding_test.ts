@@ -0,0 +1,62 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, test } from "./test_util.ts";
+
+function randomBytes(n: number): Uint8Array {
+	const bytes = new Uint8Array(n);
+	if (n > 0) {
+		crypto.getRandomValues(bytes);
+	}
+	return bytes;
+}
+
+test(function encodingRoundTripRandom(): void {
+	for (let i = 0; i < 200; i++) {
+		const bytes = randomBytes(i % 67);
+		const hex = Deno.hexEncode(bytes);
+		assertEquals(hex.length, bytes.length * 2);
+		assertEquals(Deno.hexDecode(hex), bytes);
+		assertEquals(Deno.hexDecode(hex.toUpperCase()), bytes);
+		for (const urlSafe of [false, true]) {
+			for (const pad of [false, true]) {
+				const encoded = Deno.base64Encode(bytes, { urlSafe, pad });
+				const padded = pad && bytes.length % 3 !== 0;
+				assertEquals(encoded.endsWith("="), padded);
+				assert(!/[+/]/.test(encoded) || !urlSafe);
+				assertEquals(Deno.base64Decode(encoded, { urlSafe }), bytes);
+			}
+		}
+	}
+});
+
+test(function encodingKnownValues(): void {
+	const encoder = new TextEncoder();
+	assertEquals(Deno.base64Encode(encoder.encode("f")), "Zg==");
+	assertEquals(Deno.base64Encode(encoder.encode("fo")), "Zm8=");
+	assertEquals(Deno.base64Encode(encoder.encode("foo")), "Zm9v");
+	assertEquals(Deno.base64Encode(encoder.encode("f"), { pad: false }), "Zg");
+	const bytes = new Uint8Array([0xfb, 0xff]);
+	assertEquals(Deno.base64Encode(bytes), "+/8=");
+	assertEquals(Deno.base64Encode(bytes, { urlSafe: true }), "-_8=");
+	assertEquals(Deno.base64Decode("Zg"), encoder.encode("f"));
+	assertEquals(Deno.hexEncode(bytes), "fbff");
+	assertEquals(Deno.base64Encode(new Uint8Array(0)), "");
+	assertEquals(Deno.hexDecode(""), new Uint8Array(0));
+});
+
+test(function encodingInvalidInput(): void {
+	for (const decode of [
+		(): Uint8Array => Deno.base64Decode("Z"),
+		(): Uint8Array => Deno.base64Decode("-_8="),
+		(): Uint8Array => Deno.base64Decode("Zg====="),
+		(): Uint8Array => Deno.hexDecode("abc"),
+		(): Uint8Array => Deno.hexDecode("zz"),
+	]) {
+		let err;
+		try {
+			decode();
+		} catch (e) {
+			err = e;
+		}
+		assertEquals(err.kind, Deno.ErrorKind.InvalidData);
+	}
+});
@@ -83,4 +83,8 @@ export enum ErrorKind {
 
 	/** TODO this is a DomException type, and should be moved out of here when possible */
 	DataCloneError = 52,
+	WorkerTimeout = 53,
+	HeapLimit = 54,
+	TooManyPendingOps = 55,
+	Aborted = 56,
 }
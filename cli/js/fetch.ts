@@ -1,9 +1,11 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { withSignal } from "./abort_controller.ts";
 import { bytesSymbol as blobBytesSymbol, DenoBlob } from "./blob.ts";
 import { Buffer } from "./buffer.ts";
 import { sendAsync } from "./dispatch_json.ts";
 import * as dispatch from "./dispatch.ts";
 import * as domTypes from "./dom_types.ts";
+import { DenoError, ErrorKind } from "./errors.ts";
 import { close, read } from "./files.ts";
 import { FormData } from "./form_data.ts";
 import { Headers } from "./headers.ts";
@@ -37,8 +39,11 @@ function hasHeaderValueOf(s: string, value: string): boolean {
 
 class Body implements domTypes.Body, domTypes.ReadableStream, io.ReadCloser {
 	private _bodyUsed = false;
+	private _closed = false;
 	private _bodyPromise: null | Promise<ArrayBuffer> = null;
 	private _data: ArrayBuffer | null = null;
+	/** Settles once the body has been read to the end, or closed before. */
+	readonly trailer = createResolvable<domTypes.Headers>();
 	readonly locked: boolean = false; // TODO
 	readonly body: null | Body = this;
 
@@ -231,17 +236,38 @@ class Body implements domTypes.Body, domTypes.ReadableStream, io.ReadCloser {
 		return decoder.decode(ab);
 	}
 
-	read(p: Uint8Array): Promise<number | io.EOF> {
+	async read(p: Uint8Array): Promise<number | io.EOF> {
 		this._bodyUsed = true;
-		return read(this.rid, p);
+		if (this._closed) {
+			return io.EOF;
+		}
+		const result = await read(this.rid, p);
+		// Release the connection as soon as the body has been fully read.
+		if (result === io.EOF) {
+			// The body stream doesn't report trailer fields: reqwest doesn't
+			// pass them on and hyper drops HTTP/1 chunked trailers.
+			this.trailer.resolve(new Headers());
+			this.close();
+		}
+		return result;
 	}
 
 	close(): void {
+		if (this._closed) {
+			return;
+		}
+		this._closed = true;
 		close(this.rid);
+		// A no-op once resolved above. Nobody may be waiting for the trailer,
+		// so the rejection is marked as handled.
+		this.trailer.reject(
+			new Error("Response body was closed before it was read to the end"),
+		);
+		this.trailer.catch((): void => {});
 	}
 
 	async cancel(): Promise<void> {
-		return notImplemented();
+		this.close();
 	}
 
 	getReader(): domTypes.ReadableStreamReader {
@@ -265,7 +291,10 @@ export class Response implements domTypes.Response {
 	readonly type = "basic"; // TODO
 	readonly redirected: boolean;
 	headers: domTypes.Headers;
-	readonly trailer: Promise<domTypes.Headers>;
+	/** Deno extension. The headers as received, with repeated ones kept as
+	 * separate entries. Names are lower case, and a repeated header follows
+	 * the first one of the same name. */
+	readonly rawHeaders: Array<[string, string]>;
 	readonly body: Body;
 
 	constructor(
@@ -277,7 +306,7 @@ export class Response implements domTypes.Response {
 		redirected_: boolean,
 		body_: null | Body = null,
 	) {
-		this.trailer = createResolvable();
+		this.rawHeaders = headersList;
 		this.headers = new Headers(headersList);
 		const contentType = this.headers.get("content-type") || "";
 
@@ -290,6 +319,12 @@ export class Response implements domTypes.Response {
 		this.redirected = redirected_;
 	}
 
+	/** Resolves with the trailing headers once the body has been read to the
+	 * end, and rejects if it is closed before that. */
+	get trailer(): Promise<domTypes.Headers> {
+		return this.body.trailer;
+	}
+
 	async arrayBuffer(): Promise<ArrayBuffer> {
 		return this.body.arrayBuffer();
 	}
@@ -326,17 +361,11 @@ export class Response implements domTypes.Response {
 			);
 		}
 
-		const iterators = this.headers.entries();
-		const headersList: Array<[string, string]> = [];
-		for (const header of iterators) {
-			headersList.push(header);
-		}
-
 		return new Response(
 			this.url,
 			this.status,
 			this.statusText,
-			headersList,
+			this.rawHeaders,
 			-1,
 			this.redirected,
 			this.body,
@@ -344,6 +373,44 @@ export class Response implements domTypes.Response {
 	}
 }
 
+/** A `MultipartPart`, or one whose content is `data`, a range of the
+ * request's zero copy buffer. */
+interface MultipartPartArgs extends domTypes.MultipartPart {
+	data?: [number, number];
+}
+
+/** Turns a `FormData` into multipart parts. The content of its files goes
+ * into the returned buffer. */
+function formDataParts(
+	formData: FormData,
+): [MultipartPartArgs[], Uint8Array] {
+	const parts: MultipartPartArgs[] = [];
+	const files: Uint8Array[] = [];
+	let length = 0;
+	for (const [name, value] of formData) {
+		if (typeof value === "string") {
+			parts.push({ name, value });
+			continue;
+		}
+		const bytes = (value as DenoBlob)[blobBytesSymbol];
+		parts.push({
+			name,
+			filename: value.name,
+			contentType: value.type || undefined,
+			data: [length, length + bytes.byteLength],
+		});
+		files.push(bytes);
+		length += bytes.byteLength;
+	}
+	const data = new Uint8Array(length);
+	let offset = 0;
+	for (const bytes of files) {
+		data.set(bytes, offset);
+		offset += bytes.byteLength;
+	}
+	return [parts, data];
+}
+
 interface FetchResponse {
 	bodyRid: number;
 	status: number;
@@ -356,6 +423,10 @@ async function sendFetchReq(
 	method: string | null,
 	headers: domTypes.Headers | null,
 	body: ArrayBufferView | undefined,
+	multipart: MultipartPartArgs[] | undefined,
+	timeout: number | undefined,
+	decompress: boolean | undefined,
+	signal: domTypes.AbortSignal | null | undefined,
 ): Promise<FetchResponse> {
 	let headerArray: Array<[string, string]> = [];
 	if (headers) {
@@ -371,19 +442,30 @@ async function sendFetchReq(
 		);
 	}
 
-	const args = {
-		method,
-		url,
-		headers: headerArray,
-	};
-
-	return (await sendAsync(
-		dispatch.OP_FETCH,
-		args,
-		zeroCopy,
-	)) as FetchResponse;
+	return await withSignal(
+		signal,
+		async (cancelRid?: number): Promise<FetchResponse> => {
+			const args = {
+				method,
+				url,
+				headers: headerArray,
+				timeout,
+				decompress,
+				cancelRid,
+				multipart,
+			};
+			return (await sendAsync(
+				dispatch.OP_FETCH,
+				args,
+				zeroCopy,
+			)) as FetchResponse;
+		},
+	);
 }
 
+/** Request headers dropped by `stripCredentialsOnRedirect`. */
+const credentialHeaders = ["authorization", "cookie", "proxy-authorization"];
+
 /** Fetch a resource from the network. */
 export async function fetch(
 	input: domTypes.Request | URL | string,
@@ -393,14 +475,23 @@ export async function fetch(
 	let method: string | null = null;
 	let headers: domTypes.Headers | null = null;
 	let body: ArrayBufferView | undefined;
+	let multipart: MultipartPartArgs[] | undefined;
+	let timeout: number | undefined;
+	let decompress: boolean | undefined;
+	const signal = init && init.signal;
 	let redirected = false;
-	let remRedirectCount = 20; // TODO: use a better way to handle
+	const redirect = (init && init.redirect) || "follow";
+	const maxRedirects =
+		init && init.maxRedirects != null ? init.maxRedirects : 20;
+	const stripCredentials = !!(init && init.stripCredentialsOnRedirect);
 
 	if (typeof input === "string" || input instanceof URL) {
 		url =
 			typeof input === "string" ? (input as string) : (input as URL).href;
 		if (init != null) {
 			method = init.method || null;
+			timeout = init.timeout;
+			decompress = init.decompress;
 			if (init.headers) {
 				headers =
 					init.headers instanceof Headers
@@ -430,14 +521,21 @@ export async function fetch(
 				} else if (init.body instanceof DenoBlob) {
 					body = init.body[blobBytesSymbol];
 					contentType = init.body.type;
+				} else if (init.body instanceof FormData) {
+					// The op sets the content type, which carries the boundary.
+					[multipart, body] = formDataParts(init.body);
 				} else {
-					// TODO: FormData, ReadableStream
+					// TODO: ReadableStream
 					notImplemented();
 				}
 				if (contentType && !headers.has("content-type")) {
 					headers.set("content-type", contentType);
 				}
 			}
+			if (init.multipart) {
+				multipart = init.multipart;
+				body = undefined;
+			}
 		}
 	} else {
 		url = input.url;
@@ -450,8 +548,17 @@ export async function fetch(
 		}
 	}
 
-	while (remRedirectCount) {
-		const fetchResponse = await sendFetchReq(url, method, headers, body);
+	for (let redirectCount = 0; ; redirectCount++) {
+		const fetchResponse = await sendFetchReq(
+			url,
+			method,
+			headers,
+			body,
+			multipart,
+			timeout,
+			decompress,
+			signal,
+		);
 
 		const response = new Response(
 			url,
@@ -461,37 +568,39 @@ export async function fetch(
 			fetchResponse.bodyRid,
 			redirected,
 		);
-		if ([301, 302, 303, 307, 308].includes(response.status)) {
-			// We're in a redirect status
-			switch ((init && init.redirect) || "follow") {
-				case "error":
-					throw notImplemented();
-				case "manual":
-					throw notImplemented();
-				case "follow":
-				default:
-					let redirectUrl = response.headers.get("Location");
-					if (redirectUrl == null) {
-						return response; // Unspecified
-					}
-					if (
-						!redirectUrl.startsWith("http://") &&
-						!redirectUrl.startsWith("https://")
-					) {
-						redirectUrl =
-							url.split("//")[0] +
-							"//" +
-							url.split("//")[1].split("/")[0] +
-							redirectUrl; // TODO: handle relative redirection more gracefully
-					}
-					url = redirectUrl;
-					redirected = true;
-					remRedirectCount--;
-			}
-		} else {
+		// In manual mode the 3xx response, including its Location header, is
+		// handed to the caller as is.
+		if (
+			redirect === "manual" ||
+			![301, 302, 303, 307, 308].includes(response.status)
+		) {
 			return response;
 		}
+		const location = response.headers.get("Location");
+		if (location == null) {
+			return response; // Unspecified
+		}
+		response.body.close();
+		if (redirect === "error") {
+			// A network error, which fetch reports as a TypeError.
+			throw new TypeError(
+				`Redirected to ${location} with redirect mode "error"`,
+			);
+		}
+		if (redirectCount >= maxRedirects) {
+			throw new DenoError(
+				ErrorKind.TooManyRedirects,
+				`More than ${maxRedirects} redirects`,
+			);
+		}
+		const redirectUrl = new URL(location, url);
+		if (stripCredentials && redirectUrl.origin !== new URL(url).origin) {
+			headers = new Headers(headers || undefined);
+			for (const name of credentialHeaders) {
+				headers.delete(name);
+			}
+		}
+		url = redirectUrl.href;
+		redirected = true;
 	}
-	// Return a network error due to too many redirections
-	throw notImplemented();
 }
@@ -20,6 +20,22 @@ testPerm({ net: true }, async function fetchConnectionError(): Promise<void> {
 	assertStrContains(err.message, "error trying to connect");
 });
 
+testPerm({ net: true }, async function fetchTimeout(): Promise<void> {
+	// Accepts the connection but never responds.
+	const listener = Deno.listen({ port: 4510 });
+	const accepted = listener.accept();
+	let err;
+	try {
+		await fetch("http://localhost:4510", { timeout: 100 });
+	} catch (err_) {
+		err = err_;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.TimedOut);
+	assertEquals(err.name, "TimedOut");
+	(await accepted).close();
+	listener.close();
+});
+
 testPerm({ net: true }, async function fetchJsonSuccess(): Promise<void> {
 	const response = await fetch(
 		"http://localhost:4545/cli/tests/fixture.json",
@@ -98,6 +114,55 @@ testPerm({ net: true }, async function fetchAsyncIterator(): Promise<void> {
 	assertEquals(total, Number(headers.get("Content-Length")));
 });
 
+testPerm({ net: true }, async function fetchBodyClosedAtEOF(): Promise<void> {
+	const response = await fetch(
+		"http://localhost:4545/cli/tests/fixture.json",
+	);
+	const before = Object.keys(Deno.resources()).length;
+	for await (const _chunk of response.body) {
+		// drain
+	}
+	assertEquals(Object.keys(Deno.resources()).length, before - 1);
+	// Closing again after EOF is a no-op.
+	response.body.close();
+});
+
+testPerm({ net: true }, async function fetchBodyCancel(): Promise<void> {
+	const response = await fetch(
+		"http://localhost:4545/cli/tests/fixture.json",
+	);
+	const before = Object.keys(Deno.resources()).length;
+	await response.body.cancel();
+	assertEquals(Object.keys(Deno.resources()).length, before - 1);
+	const buf = new Uint8Array(16);
+	assertEquals(await response.body.read(buf), Deno.EOF);
+});
+
+testPerm({ net: true }, async function fetchGzipDecoded(): Promise<void> {
+	const response = await fetch("http://localhost:4545/encoded/gzip");
+	assertEquals(response.headers.get("content-encoding"), null);
+	assertEquals(await response.text(), "Hello from an encoded body!\n");
+});
+
+testPerm({ net: true }, async function fetchBrotliDecoded(): Promise<void> {
+	const response = await fetch("http://localhost:4545/encoded/br");
+	assertEquals(response.headers.get("content-encoding"), null);
+	assertEquals(await response.text(), "Hello from an encoded body!\n");
+});
+
+testPerm({ net: true }, async function fetchDecompressFalse(): Promise<
+	void
+> {
+	const response = await fetch("http://localhost:4545/encoded/gzip", {
+		decompress: false,
+	});
+	assertEquals(response.headers.get("content-encoding"), "gzip");
+	const bytes = new Uint8Array(await response.arrayBuffer());
+	// gzip magic number
+	assertEquals(bytes[0], 0x1f);
+	assertEquals(bytes[1], 0x8b);
+});
+
 testPerm({ net: true }, async function responseClone(): Promise<void> {
 	const response = await fetch(
 		"http://localhost:4545/cli/tests/fixture.json",
@@ -176,15 +241,55 @@ testPerm(
 	},
 );
 
-// The feature below is not implemented, but the test should work after implementation
-/*
 testPerm({ net: true }, async function fetchWithInfRedirection(): Promise<
-  void
+	void
 > {
-  const response = await fetch("http://localhost:4549/tests"); // will redirect to the same place
-  assertEquals(response.status, 0); // network error
+	let err;
+	try {
+		await fetch("http://localhost:4549/tests"); // will redirect to the same place
+	} catch (err_) {
+		err = err_;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.TooManyRedirects);
+});
+
+testPerm({ net: true }, async function fetchMaxRedirects(): Promise<void> {
+	// 4548 redirects to 4546, which redirects to 4545.
+	let err;
+	try {
+		await fetch("http://localhost:4548/", { maxRedirects: 1 });
+	} catch (err_) {
+		err = err_;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.TooManyRedirects);
+
+	const response = await fetch("http://localhost:4548/", {
+		maxRedirects: 2,
+	});
+	assertEquals(response.status, 200);
+	assertEquals(response.url, "http://localhost:4545/");
+	await response.text();
+});
+
+testPerm({ net: true }, async function fetchManualRedirect(): Promise<void> {
+	const response = await fetch("http://localhost:4546/", {
+		redirect: "manual",
+	});
+	assertEquals(response.status, 301);
+	assertEquals(response.headers.get("location"), "http://localhost:4545/");
+	assertEquals(response.redirected, false);
+	await response.text();
+});
+
+testPerm({ net: true }, async function fetchErrorRedirect(): Promise<void> {
+	let err;
+	try {
+		await fetch("http://localhost:4546/", { redirect: "error" });
+	} catch (err_) {
+		err = err_;
+	}
+	assert(err instanceof TypeError);
 });
-*/
 
 testPerm({ net: true }, async function fetchInitStringBody(): Promise<void> {
 	const data = "Hello World";
@@ -257,6 +362,48 @@ testPerm({ net: true }, async function fetchInitBlobBody(): Promise<void> {
 	assert(response.headers.get("content-type").startsWith("text/javascript"));
 });
 
+testPerm({ net: true }, async function fetchInitFormDataBody(): Promise<void> {
+	const form = new FormData();
+	form.append("title", "Report");
+	const csv = new Blob(["a,b\n1,2\n"], { type: "text/csv" });
+	form.append("upload", csv, "r.csv");
+	const response = await fetch("http://localhost:4545/echo_server", {
+		method: "POST",
+		body: form,
+	});
+	assert(
+		response.headers
+			.get("content-type")
+			.startsWith("multipart/form-data; boundary="),
+	);
+	const parsed = await response.formData();
+	assertEquals(parsed.get("title"), "Report");
+	const upload = parsed.get("upload") as File;
+	assertEquals(upload.name, "r.csv");
+	assertEquals(upload.size, 8);
+});
+
+testPerm(
+	{ net: true, read: true },
+	async function fetchMultipartFile(): Promise<void> {
+		const contents = Deno.readFileSync("tests/hello.txt");
+		const file = await Deno.open("tests/hello.txt");
+		const response = await fetch("http://localhost:4545/echo_server", {
+			method: "POST",
+			multipart: [
+				{ name: "title", value: "Hello" },
+				{ name: "upload", rid: file.rid, filename: "hello.txt" },
+			],
+		});
+		file.close();
+		const parsed = await response.formData();
+		assertEquals(parsed.get("title"), "Hello");
+		const upload = parsed.get("upload") as File;
+		assertEquals(upload.name, "hello.txt");
+		assertEquals(upload.size, contents.byteLength);
+	},
+);
+
 testPerm({ net: true }, async function fetchUserAgent(): Promise<void> {
 	const data = "Hello World";
 	const response = await fetch("http://localhost:4545/echo_server", {
@@ -382,3 +529,99 @@ testPerm({ net: true }, async function fetchPostBodyTypedArray():Promise<void> {
   assertEquals(actual, expected);
 });
 */
+
+testPerm({ net: true }, async function fetchAbortInFlight(): Promise<void> {
+	// Accepts the connection but never answers, so only the abort can end
+	// the request.
+	const listener = Deno.listen({ port: 4511 });
+	const accepted = listener.accept();
+	const controller = new AbortController();
+	const start = Date.now();
+	setTimeout((): void => controller.abort(), 50);
+	let err;
+	try {
+		await fetch("http://127.0.0.1:4511/", { signal: controller.signal });
+	} catch (err_) {
+		err = err_;
+	}
+	assert(err !== undefined);
+	assertEquals(err.kind, Deno.ErrorKind.Aborted);
+	assertEquals(err.name, "Aborted");
+	assert(Date.now() - start < 1000);
+	// The aborted request dropped its connection.
+	const conn = await accepted;
+	const buf = new Uint8Array(4096);
+	let result: number | Deno.EOF;
+	do {
+		result = await conn.read(buf);
+	} while (result !== Deno.EOF);
+	conn.close();
+	listener.close();
+});
+
+testPerm({ net: true }, async function fetchAlreadyAborted(): Promise<void> {
+	const controller = new AbortController();
+	controller.abort();
+	let err;
+	try {
+		await fetch("http://localhost:4545/cli/tests/fixture.json", {
+			signal: controller.signal,
+		});
+	} catch (err_) {
+		err = err_;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.Aborted);
+	// No cancel handle is left behind.
+	assert(!Object.values(Deno.resources()).includes("cancelHandle"));
+});
+
+testPerm({ net: true }, async function fetchRawHeaders(): Promise<void> {
+	const listener = Deno.listen({ port: 4512 });
+	const served = (async (): Promise<void> => {
+		const conn = await listener.accept();
+		await conn.read(new Uint8Array(4096));
+		const response = [
+			"HTTP/1.1 200 OK",
+			"X-Trace: a",
+			"Content-Length: 2",
+			"x-trace: b",
+			"Connection: close",
+			"",
+			"ok",
+		].join("\r\n");
+		await conn.write(new TextEncoder().encode(response));
+		conn.close();
+	})();
+	const response = await fetch("http://127.0.0.1:4512/");
+	const rawHeaders = response.rawHeaders.filter(
+		([name]): boolean => name === "x-trace",
+	);
+	assertEquals(rawHeaders, [
+		["x-trace", "a"],
+		["x-trace", "b"],
+	]);
+	assertEquals(response.headers.get("x-trace"), "a, b");
+	assertEquals(await response.text(), "ok");
+	const trailer = await response.trailer;
+	assertEquals(Array.from(trailer.entries()), []);
+	await served;
+	listener.close();
+});
+
+testPerm({ net: true }, async function fetchTrailerBodyClosed(): Promise<
+	void
+> {
+	const response = await fetch("http://localhost:4545/cli/tests/fixture.json");
+	response.body.close();
+	let err;
+	try {
+		await response.trailer;
+	} catch (e) {
+		err = e;
+	}
+	assert(err instanceof Error);
+	assertEquals(
+		err.message,
+		"Response body was closed before it was read to the end",
+	);
+});
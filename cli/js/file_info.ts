@@ -30,6 +30,29 @@ export interface FileInfo {
 	/** The file or directory name. */
 	name: string | null;
 
+	/** Nanoseconds past the second for `modified`, `accessed` and `created`.
+	 * Null when the corresponding time is not available.
+	 */
+	modifiedNsec: number | null;
+	accessedNsec: number | null;
+	createdNsec: number | null;
+	/** ID of the device containing the file. Unix only, null on Windows. */
+	dev: number | null;
+	/** Inode number. Unix only, null on Windows. */
+	ino: number | null;
+	/** Number of hard links pointing to the file. Unix only, null on Windows. */
+	nlink: number | null;
+	/** User ID of the owner. Unix only, null on Windows. */
+	uid: number | null;
+	/** Group ID of the owner. Unix only, null on Windows. */
+	gid: number | null;
+	/** Device ID, if this is a special file. Unix only, null on Windows. */
+	rdev: number | null;
+	/** Preferred block size for file system I/O. Unix only, null on Windows. */
+	blksize: number | null;
+	/** Number of 512-byte blocks allocated. Unix only, null on Windows. */
+	blocks: number | null;
+
 	/** Returns whether this is info for a regular file. This result is mutually
 	 * exclusive to `FileInfo.isDirectory` and `FileInfo.isSymlink`.
 	 */
@@ -56,6 +79,17 @@ export class FileInfoImpl implements FileInfo {
 	created: number | null;
 	mode: number | null;
 	name: string | null;
+	modifiedNsec: number | null;
+	accessedNsec: number | null;
+	createdNsec: number | null;
+	dev: number | null;
+	ino: number | null;
+	nlink: number | null;
+	uid: number | null;
+	gid: number | null;
+	rdev: number | null;
+	blksize: number | null;
+	blocks: number | null;
 
 	/* @internal */
 	constructor(private _res: StatResponse) {
@@ -75,6 +109,17 @@ export class FileInfoImpl implements FileInfo {
 		// null on Windows
 		this.mode = hasMode ? mode : null;
 		this.name = name ? name : null;
+		this.modifiedNsec = modified ? this._res.modifiedNsec : null;
+		this.accessedNsec = accessed ? this._res.accessedNsec : null;
+		this.createdNsec = created ? this._res.createdNsec : null;
+		this.dev = this._res.dev;
+		this.ino = this._res.ino;
+		this.nlink = this._res.nlink;
+		this.uid = this._res.uid;
+		this.gid = this._res.gid;
+		this.rdev = this._res.rdev;
+		this.blksize = this._res.blksize;
+		this.blocks = this._res.blocks;
 	}
 
 	isFile(): boolean {
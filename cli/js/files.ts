@@ -80,6 +80,41 @@ export async function read(rid: number, p: Uint8Array): Promise<number | EOF> {
 	}
 }
 
+export interface ReadPartialOptions {
+	/** Read at most this many bytes, even if `p` is larger. */
+	maxLen?: number;
+	/** Resolve immediately with `wouldBlock: true` instead of waiting for
+	 * data on a non-blocking resource such as a socket or pipe. */
+	nonBlocking?: boolean;
+}
+
+export interface ReadPartialResult {
+	nread: number;
+	eof: boolean;
+	wouldBlock: boolean;
+}
+
+/** Read at most `options.maxLen` bytes from a resource into `p`. Unlike
+ * `read()`, a zero byte read is reported as either `eof` or `wouldBlock`.
+ *
+ *       const file = await Deno.open("/foo/bar.txt");
+ *       const buf = new Uint8Array(100);
+ *       const { nread, eof } = await Deno.readPartial(file.rid, buf, {
+ *         maxLen: 4
+ *       });
+ */
+export async function readPartial(
+	rid: number,
+	p: Uint8Array,
+	options: ReadPartialOptions = {},
+): Promise<ReadPartialResult> {
+	return await sendAsyncJson(
+		dispatch.OP_READ_PARTIAL,
+		{ rid, maxLen: options.maxLen, nonBlocking: !!options.nonBlocking },
+		p,
+	);
+}
+
 /** Write synchronously to the file ID the contents of the array buffer.
  *
  * Resolves with the number of bytes written.
@@ -118,12 +153,18 @@ export async function write(rid: number, p: Uint8Array): Promise<number> {
 }
 
 /** Seek a file ID synchronously to the given offset under mode given by `whence`.
+ * Returns the new position relative to the start of the file. Throws if the
+ * resource is not seekable, e.g. a socket or pipe.
  *
  *       const file = Deno.openSync("/foo/bar.txt");
  *       Deno.seekSync(file.rid, 0, 0);
  */
-export function seekSync(rid: number, offset: number, whence: SeekMode): void {
-	sendSyncJson(dispatch.OP_SEEK, { rid, offset, whence });
+export function seekSync(
+	rid: number,
+	offset: number,
+	whence: SeekMode,
+): number {
+	return sendSyncJson(dispatch.OP_SEEK, { rid, offset, whence }).position;
 }
 
 /** Seek a file ID to the given offset under mode given by `whence`.
@@ -135,8 +176,58 @@ export async function seek(
 	rid: number,
 	offset: number,
 	whence: SeekMode,
+): Promise<number> {
+	const res = await sendAsyncJson(dispatch.OP_SEEK, { rid, offset, whence });
+	return res.position;
+}
+
+export interface LockOptions {
+	/** Take an exclusive lock instead of a shared one. */
+	exclusive?: boolean;
+	/** Fail with `ErrorKind.WouldBlock` instead of waiting when the lock is
+	 * held by someone else. */
+	nonBlocking?: boolean;
+}
+
+/** Take an advisory lock on a file ID synchronously. The lock is released by
+ * `funlockSync()` or when the file is closed.
+ *
+ *       const file = Deno.openSync("/foo/bar.txt", "r+");
+ *       Deno.flockSync(file.rid, { exclusive: true });
+ */
+export function flockSync(rid: number, options: LockOptions = {}): void {
+	sendSyncJson(dispatch.OP_FLOCK, {
+		rid,
+		exclusive: !!options.exclusive,
+		nonBlocking: !!options.nonBlocking,
+	});
+}
+
+/** Take an advisory lock on a file ID. The lock is released by `funlock()` or
+ * when the file is closed.
+ *
+ *       const file = await Deno.open("/foo/bar.txt", "r+");
+ *       await Deno.flock(file.rid, { exclusive: true });
+ */
+export async function flock(
+	rid: number,
+	options: LockOptions = {},
 ): Promise<void> {
-	await sendAsyncJson(dispatch.OP_SEEK, { rid, offset, whence });
+	await sendAsyncJson(dispatch.OP_FLOCK, {
+		rid,
+		exclusive: !!options.exclusive,
+		nonBlocking: !!options.nonBlocking,
+	});
+}
+
+/** Release an advisory lock on a file ID synchronously. */
+export function funlockSync(rid: number): void {
+	sendSyncJson(dispatch.OP_FUNLOCK, { rid });
+}
+
+/** Release an advisory lock on a file ID. */
+export async function funlock(rid: number): Promise<void> {
+	await sendAsyncJson(dispatch.OP_FUNLOCK, { rid });
 }
 
 /** Close the file ID. */
@@ -173,14 +264,30 @@ export class File
 		return readSync(this.rid, p);
 	}
 
-	seek(offset: number, whence: SeekMode): Promise<void> {
+	seek(offset: number, whence: SeekMode): Promise<number> {
 		return seek(this.rid, offset, whence);
 	}
 
-	seekSync(offset: number, whence: SeekMode): void {
+	seekSync(offset: number, whence: SeekMode): number {
 		return seekSync(this.rid, offset, whence);
 	}
 
+	lock(options?: LockOptions): Promise<void> {
+		return flock(this.rid, options);
+	}
+
+	lockSync(options?: LockOptions): void {
+		flockSync(this.rid, options);
+	}
+
+	unlock(): Promise<void> {
+		return funlock(this.rid);
+	}
+
+	unlockSync(): void {
+		funlockSync(this.rid);
+	}
+
 	close(): void {
 		close(this.rid);
 	}
@@ -311,6 +311,26 @@ testPerm({ read: true }, function seekSyncEnd(): void {
 	assertEquals(decoded, "world!");
 });
 
+testPerm({ read: true }, async function seekReturnsPosition(): Promise<void> {
+	const file = await Deno.open("tests/hello.txt");
+	assertEquals(await file.seek(6, Deno.SeekMode.SEEK_START), 6);
+	assertEquals(file.seekSync(2, Deno.SeekMode.SEEK_CURRENT), 8);
+	const end = file.seekSync(0, Deno.SeekMode.SEEK_END);
+	assertEquals(end, Deno.statSync("tests/hello.txt").len);
+	file.close();
+});
+
+test(function seekNotSeekable(): void {
+	let err;
+	try {
+		Deno.stdin.seekSync(0, Deno.SeekMode.SEEK_START);
+	} catch (e) {
+		err = e;
+	}
+	assert(!!err);
+	assertEquals(err.kind, Deno.ErrorKind.BadResource);
+});
+
 testPerm({ read: true }, async function seekMode(): Promise<void> {
 	const filename = "tests/hello.txt";
 	const file = await Deno.open(filename);
@@ -330,3 +350,45 @@ testPerm({ read: true }, async function seekMode(): Promise<void> {
 	await file.read(buf); // "H"
 	assertEquals(new TextDecoder().decode(buf), "H");
 });
+
+testPerm({ read: true }, async function readPartialChunks(): Promise<void> {
+	const filename = "tests/hello.txt";
+	const file = await Deno.open(filename);
+	const buf = new Uint8Array(64);
+	const counts = [];
+	for (;;) {
+		const result = await Deno.readPartial(file.rid, buf, { maxLen: 4 });
+		assert(!result.wouldBlock);
+		if (result.eof) {
+			assertEquals(result.nread, 0);
+			break;
+		}
+		counts.push(result.nread);
+	}
+	assertEquals(counts, [4, 4, 4]);
+	file.close();
+});
+
+testPerm({ read: true, write: true }, async function flockNonBlocking(): Promise<
+	void
+> {
+	const filename = Deno.makeTempDirSync() + "/lock.txt";
+	const first = await Deno.open(filename, "w+");
+	const second = await Deno.open(filename, "r+");
+	await first.lock({ exclusive: true });
+
+	let err;
+	try {
+		await second.lock({ exclusive: true, nonBlocking: true });
+	} catch (e) {
+		err = e;
+	}
+	assert(!!err);
+	assertEquals(err.kind, Deno.ErrorKind.WouldBlock);
+
+	// Closing the holder releases its lock.
+	first.close();
+	second.lockSync({ exclusive: true, nonBlocking: true });
+	second.unlockSync();
+	second.close();
+});
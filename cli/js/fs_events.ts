@@ -0,0 +1,68 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { sendAsync, sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
+import { close } from "./files.ts";
+
+export interface FsEvent {
+	kind: "create" | "modify" | "remove" | "rename";
+	/** For "rename" the old path comes first, when it is known. */
+	paths: string[];
+}
+
+export interface WatchFsOptions {
+	/** Also watch subdirectories. Defaults to `true`. */
+	recursive?: boolean;
+	/** Coalesce repeated events for the same path that happen within this
+	 * many milliseconds. Off by default. */
+	debounce?: number;
+}
+
+export interface FsWatcher extends AsyncIterableIterator<FsEvent> {
+	readonly rid: number;
+	/** Stops watching. Pending `next()` calls resolve with `done: true`. */
+	close(): void;
+}
+
+class FsWatcherImpl implements FsWatcher {
+	readonly rid: number;
+
+	constructor(paths: string[], options: WatchFsOptions) {
+		const { recursive = true, debounce } = options;
+		this.rid = sendSync(dispatch.OP_FS_WATCH, {
+			paths,
+			recursive,
+			debounce,
+		}).rid;
+	}
+
+	next(): Promise<IteratorResult<FsEvent>> {
+		return sendAsync(dispatch.OP_FS_EVENTS_POLL, { rid: this.rid });
+	}
+
+	async return(value?: FsEvent): Promise<IteratorResult<FsEvent>> {
+		this.close();
+		return { value, done: true };
+	}
+
+	close(): void {
+		close(this.rid);
+	}
+
+	[Symbol.asyncIterator](): AsyncIterableIterator<FsEvent> {
+		return this;
+	}
+}
+
+/** Watches `paths` for changes. Requires read permission for each path.
+ *
+ *       const watcher = Deno.watchFs("/");
+ *       for await (const event of watcher) {
+ *         console.log(">>>> event", event);
+ *       }
+ */
+export function watchFs(
+	paths: string | string[],
+	options: WatchFsOptions = {},
+): FsWatcher {
+	return new FsWatcherImpl(Array.isArray(paths) ? paths : [paths], options);
+}
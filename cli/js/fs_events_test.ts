@@ -0,0 +1,51 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, testPerm } from "./test_util.ts";
+
+testPerm({ read: false }, function watchFsPermissions(): void {
+	let err;
+	try {
+		Deno.watchFs(".");
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.PermissionDenied);
+	assertEquals(err.name, "PermissionDenied");
+});
+
+testPerm({ read: true }, function watchFsInvalidPath(): void {
+	let err;
+	try {
+		Deno.watchFs("non-existant.file");
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.NotFound);
+});
+
+testPerm({ read: true, write: true }, async function watchFsEvents(): Promise<
+	void
+> {
+	const testDir = Deno.makeTempDirSync();
+	const watcher = Deno.watchFs(testDir);
+	const next = watcher.next();
+
+	Deno.writeFileSync(testDir + "/file.txt", new Uint8Array([1, 2, 3]));
+
+	const result = await next;
+	assert(!result.done);
+	assert(["create", "modify"].includes(result.value.kind));
+	assert(result.value.paths[0].endsWith("file.txt"));
+
+	watcher.close();
+	assertEquals(await watcher.next(), { done: true });
+});
+
+testPerm(
+	{ read: true, write: true },
+	async function watchFsCloseWhilePending(): Promise<void> {
+		const watcher = Deno.watchFs(Deno.makeTempDirSync());
+		const next = watcher.next();
+		watcher.close();
+		assertEquals(await next, { done: true });
+	},
+);
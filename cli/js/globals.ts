@@ -7,11 +7,9 @@
 // Modules which will make up part of the global public API surface should be
 // imported as namespaces, so when the runtime type library is generated they
 // can be expressed as a namespace in the type library.
+import * as abortController from "./abort_controller.ts";
 import * as blob from "./blob.ts";
 import * as consoleTypes from "./console.ts";
-// These imports are not exposed and therefore are fine to just import the
-// symbols required.
-import { core } from "./core.ts";
 import * as customEvent from "./custom_event.ts";
 import * as Deno from "./deno.ts";
 import * as domFile from "./dom_file.ts";
@@ -80,7 +78,7 @@ window.btoa = textEncoding.btoa;
 window.fetch = fetchTypes.fetch;
 window.clearTimeout = timers.clearTimeout;
 window.clearInterval = timers.clearInterval;
-window.console = new consoleTypes.Console(core.print);
+window.console = new consoleTypes.Console(consoleTypes.print);
 window.setTimeout = timers.setTimeout;
 window.setInterval = timers.setInterval;
 window.location = undefined as unknown as domTypes.Location;
@@ -98,6 +96,11 @@ window.crypto = csprng as unknown as Crypto;
 // won't be able to refer to instances.
 // We have to export the type aliases, so that TypeScript _knows_ they are
 // being used, which it cannot statically determine within this module.
+window.AbortController = abortController.AbortController;
+export type AbortController = abortController.AbortController;
+window.AbortSignal = abortController.AbortSignal;
+export type AbortSignal = domTypes.AbortSignal;
+
 window.Blob = blob.DenoBlob;
 export type Blob = domTypes.Blob;
 
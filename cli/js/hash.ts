@@ -0,0 +1,108 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
+import { Reader, EOF } from "./io.ts";
+import { TextEncoder } from "./text_encoding.ts";
+
+export type HashAlgorithm = "sha256" | "sha512" | "blake3";
+
+/** A hash computed natively from data given in pieces. */
+export interface Hash {
+	readonly rid: number;
+
+	/** Feeds `data` to the hash. Strings are hashed as UTF-8. */
+	update(data: Uint8Array | string): this;
+
+	/** Feeds everything read from `r` until EOF to the hash and returns the
+	 * number of bytes read.
+	 */
+	updateFrom(r: Reader): Promise<number>;
+
+	/** Returns the digest. The hash can't be updated after this. */
+	digest(): Uint8Array;
+}
+
+function toBytes(data: Uint8Array | string): Uint8Array {
+	return typeof data === "string" ? new TextEncoder().encode(data) : data;
+}
+
+class HashImpl implements Hash {
+	constructor(readonly rid: number) {}
+
+	update(data: Uint8Array | string): this {
+		data = toBytes(data);
+		// An empty buffer isn't passed to ops.
+		if (data.byteLength === 0) {
+			return this;
+		}
+		sendSync(dispatch.OP_HASH_UPDATE, { rid: this.rid }, data);
+		return this;
+	}
+
+	async updateFrom(r: Reader): Promise<number> {
+		const buf = new Uint8Array(32 * 1024);
+		let n = 0;
+		while (true) {
+			const result = await r.read(buf);
+			if (result === EOF) {
+				return n;
+			}
+			this.update(buf.subarray(0, result));
+			n += result;
+		}
+	}
+
+	digest(): Uint8Array {
+		return sendSync(dispatch.OP_HASH_DIGEST, { rid: this.rid });
+	}
+}
+
+/** Starts computing a hash with `algorithm`.
+ *
+ *       const digest = Deno.createHash("sha256").update("hello").digest();
+ */
+export function createHash(algorithm: HashAlgorithm): Hash {
+	const rid = sendSync(dispatch.OP_HASH_CREATE, { algorithm });
+	return new HashImpl(rid);
+}
+
+/** Both operands in one buffer, since an op takes only one. */
+function concat(a: Uint8Array, b: Uint8Array): Uint8Array {
+	const buf = new Uint8Array(a.byteLength + b.byteLength);
+	buf.set(a);
+	buf.set(b, a.byteLength);
+	return buf;
+}
+
+/** Returns the HMAC (RFC 2104) of `message` under `key`. Strings are taken
+ * as UTF-8.
+ *
+ *       const tag = Deno.hmac("sha256", secret, body);
+ */
+export function hmac(
+	algorithm: "sha256" | "sha512",
+	key: Uint8Array | string,
+	message: Uint8Array | string,
+): Uint8Array {
+	const keyBytes = toBytes(key);
+	return sendSync(
+		dispatch.OP_HMAC,
+		{ algorithm, keyLength: keyBytes.byteLength },
+		concat(keyBytes, toBytes(message)),
+	);
+}
+
+/** Returns whether `a` and `b` hold the same bytes, taking time that depends
+ * only on their lengths. Use it to compare secrets like signatures.
+ *
+ *       if (!Deno.bytesEqual(Deno.hmac("sha256", secret, body), signature)) {
+ *         throw new Error("Bad signature");
+ *       }
+ */
+export function bytesEqual(a: Uint8Array, b: Uint8Array): boolean {
+	return sendSync(
+		dispatch.OP_BYTES_EQUAL,
+		{ length: a.byteLength },
+		concat(a, b),
+	);
+}
@@ -0,0 +1,103 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, test } from "./test_util.ts";
+
+function hex(digest: Uint8Array): string {
+	return Array.from(digest)
+		.map((b): string => b.toString(16).padStart(2, "0"))
+		.join("");
+}
+
+test(function hashKnownDigests(): void {
+	assertEquals(
+		hex(Deno.createHash("sha256").update("abc").digest()),
+		"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+	);
+	assertEquals(
+		hex(Deno.createHash("sha512").update("abc").digest()),
+		"ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a" +
+			"2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+	);
+	assertEquals(
+		hex(Deno.createHash("blake3").update("abc").digest()),
+		"6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85",
+	);
+	assertEquals(
+		hex(Deno.createHash("blake3").digest()),
+		"af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+	);
+});
+
+test(async function hashStreaming(): Promise<void> {
+	const whole = Deno.createHash("sha256").update("hello world").digest();
+	const pieces = Deno.createHash("sha256")
+		.update("hello")
+		.update(new TextEncoder().encode(" world"))
+		.digest();
+	assertEquals(pieces, whole);
+
+	const hash = Deno.createHash("sha256");
+	const n = await hash.updateFrom(
+		new Deno.Buffer(new TextEncoder().encode("hello world")),
+	);
+	assertEquals(n, 11);
+	assertEquals(hash.digest(), whole);
+});
+
+test(function hashDigestClosesResource(): void {
+	const hash = Deno.createHash("sha512");
+	assertEquals(Deno.resources()[hash.rid], "hasher");
+	hash.digest();
+	assertEquals(Deno.resources()[hash.rid], undefined);
+
+	let err;
+	try {
+		Deno.createHash("md5" as Deno.HashAlgorithm);
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.InvalidInput);
+});
+
+function repeat(byte: number, n: number): Uint8Array {
+	return new Uint8Array(n).fill(byte);
+}
+
+// Test cases 1, 2 and 6 of RFC 4231.
+test(function hmacRfc4231(): void {
+	const key1 = repeat(0x0b, 20);
+	assertEquals(
+		hex(Deno.hmac("sha256", key1, "Hi There")),
+		"b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+	);
+	assertEquals(
+		hex(Deno.hmac("sha512", key1, "Hi There")),
+		"87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde" +
+			"daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+	);
+
+	const message2 = "what do ya want for nothing?";
+	assertEquals(
+		hex(Deno.hmac("sha256", "Jefe", message2)),
+		"5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+	);
+	assertEquals(
+		hex(Deno.hmac("sha512", "Jefe", message2)),
+		"164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554" +
+			"9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+	);
+
+	const message6 = "Test Using Larger Than Block-Size Key - Hash Key First";
+	assertEquals(
+		hex(Deno.hmac("sha256", repeat(0xaa, 131), message6)),
+		"60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+	);
+});
+
+test(function bytesEqual(): void {
+	const a = new Uint8Array([1, 2, 3]);
+	assert(Deno.bytesEqual(a, new Uint8Array([1, 2, 3])));
+	assert(!Deno.bytesEqual(a, new Uint8Array([1, 2, 4])));
+	assert(!Deno.bytesEqual(a, new Uint8Array([1, 2])));
+	assert(!Deno.bytesEqual(new Uint8Array(0), a));
+	assert(Deno.bytesEqual(new Uint8Array(0), new Uint8Array(0)));
+});
@@ -0,0 +1,53 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { sendAsync, sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
+
+/** Ticks of a repeating timer. Iterate it with `for await`, or `await` it to
+ * wait for the next tick. */
+export class IntervalStream
+	implements AsyncIterableIterator<void>, PromiseLike<void>
+{
+	readonly rid: number;
+
+	constructor(readonly period: number) {
+		this.rid = sendSync(dispatch.OP_INTERVAL_START, { period }).rid;
+	}
+
+	next(): Promise<IteratorResult<void>> {
+		return sendAsync(dispatch.OP_INTERVAL_POLL, { rid: this.rid });
+	}
+
+	async return(): Promise<IteratorResult<void>> {
+		this.clear();
+		return { value: undefined, done: true };
+	}
+
+	then<T = void, S = never>(
+		f: (v: void) => T | Promise<T>,
+		g?: (v: Error) => S | Promise<S>,
+	): Promise<T | S> {
+		return this.next().then((): void => {}).then(f, g);
+	}
+
+	/** Cancels the timer. Pending `next()` calls resolve with `done: true`.
+	 * Clearing an already cleared timer does nothing. */
+	clear(): void {
+		sendSync(dispatch.OP_INTERVAL_STOP, { rid: this.rid });
+	}
+
+	[Symbol.asyncIterator](): AsyncIterableIterator<void> {
+		return this;
+	}
+}
+
+/** Starts a timer that ticks every `period` milliseconds. Unlike
+ * `setInterval` the ticks are scheduled by the runtime, so they keep to the
+ * original schedule even when the event loop is busy.
+ *
+ *       for await (const _ of Deno.interval(1000)) {
+ *         console.log("tick");
+ *       }
+ */
+export function interval(period: number): IntervalStream {
+	return new IntervalStream(period);
+}
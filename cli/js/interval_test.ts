@@ -0,0 +1,35 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, test } from "./test_util.ts";
+
+function delay(ms: number): Promise<void> {
+	return new Promise((resolve): void => {
+		setTimeout(resolve, ms);
+	});
+}
+
+test(async function intervalFiresRepeatedly(): Promise<void> {
+	const timer = Deno.interval(10);
+	let count = 0;
+	for await (const _ of timer) {
+		count++;
+		if (count === 3) {
+			break;
+		}
+	}
+	assertEquals(count, 3);
+
+	// Breaking out of the loop cleared the timer.
+	const result = await timer.next();
+	assert(result.done);
+	await delay(30);
+	assert((await timer.next()).done);
+});
+
+test(async function intervalClearEndsPendingTick(): Promise<void> {
+	const timer = Deno.interval(1000);
+	const pending = timer.next();
+	timer.clear();
+	assert((await pending).done);
+	// Clearing twice is harmless.
+	timer.clear();
+});
@@ -28,18 +28,68 @@ declare namespace Deno {
 	 *       console.log(Deno.hostname());
 	 */
 	export function hostname(): string;
+	/** Get the number of seconds since the system booted.
+	 *
+	 *       console.log(Deno.uptime());
+	 */
+	export function uptime(): number;
+	/** Get the 1, 5 and 15 minute load averages, or `null` on Windows which has
+	 * no load average.
+	 *
+	 *       const [one, five, fifteen] = Deno.loadavg()!;
+	 */
+	export function loadavg(): [number, number, number] | null;
+	/** System memory, all in bytes. */
+	export interface MemoryInfo {
+		total: number;
+		free: number;
+		/** Memory available for new processes without swapping, including
+		 * reclaimable caches. */
+		available: number;
+		swapTotal: number;
+		swapFree: number;
+	}
+	/** Get system memory usage in bytes.
+	 *
+	 *       console.log(Deno.memoryInfo().available);
+	 */
+	export function memoryInfo(): MemoryInfo;
+	/** CPU time and memory used by the current process. Fields a platform
+	 * doesn't report are `null`. */
+	export interface ResourceUsage {
+		/** CPU time spent in user code, in microseconds. */
+		userTime: number;
+		/** CPU time spent in the kernel on behalf of the process, in
+		 * microseconds. */
+		systemTime: number;
+		/** Peak resident set size in bytes. */
+		maxRss: number;
+		/** All page faults, including those served without I/O. */
+		pageFaults: number;
+		/** Page faults that required I/O. Not available on Windows. */
+		majorPageFaults: number | null;
+	}
+
+	/** Get the resource usage of the current process, so benchmarks can measure
+	 * CPU time and peak memory without an external tool.
+	 *
+	 *       const { userTime, maxRss } = Deno.resourceUsage();
+	 */
+	export function resourceUsage(): ResourceUsage;
 	/** Exit the Deno process with optional exit code. */
 	export function exit(code?: number): never;
 	/** Returns a snapshot of the environment variables at invocation. Mutating a
 	 * property in the object will set that variable in the environment for
-	 * the process. The environment object will only accept `string`s
-	 * as values.
+	 * the process, and deleting it unsets the variable. Subprocesses started
+	 * afterwards see the changes. The environment object will only accept
+	 * `string`s as values.
 	 *
 	 *       const myEnv = Deno.env();
 	 *       console.log(myEnv.SHELL);
 	 *       myEnv.TEST_VAR = "HELLO";
 	 *       const newEnv = Deno.env();
 	 *       console.log(myEnv.TEST_VAR == newEnv.TEST_VAR);
+	 *       delete myEnv.TEST_VAR;
 	 */
 	export function env(): {
 		[index: string]: string;
@@ -221,6 +271,8 @@ declare namespace Deno {
 	export function cwd(): string;
 	/**
 	 * `chdir()` Change the current working directory to path.
+	 * Each worker has its own working directory, so this does not affect
+	 * other workers or the directory of the process.
 	 * throws `NotFound` exception if directory not available
 	 */
 	export function chdir(directory: string): void;
@@ -285,10 +337,10 @@ declare namespace Deno {
 		 * any positive offset is legal, but the behavior of subsequent I/O operations
 		 * on the underlying object is implementation-dependent.
 		 */
-		seek(offset: number, whence: SeekMode): Promise<void>;
+		seek(offset: number, whence: SeekMode): Promise<number>;
 	}
 	export interface SyncSeeker {
-		seekSync(offset: number, whence: SeekMode): void;
+		seekSync(offset: number, whence: SeekMode): number;
 	}
 	export interface ReadCloser extends Reader, Closer {}
 	export interface WriteCloser extends Writer, Closer {}
@@ -352,6 +404,34 @@ declare namespace Deno {
 	 *       })();
 	 */
 	export function read(rid: number, p: Uint8Array): Promise<number | EOF>;
+	export interface ReadPartialOptions {
+		/** Read at most this many bytes, even if `p` is larger. */
+		maxLen?: number;
+		/** Resolve immediately with `wouldBlock: true` instead of waiting for
+		 * data on a non-blocking resource such as a socket or pipe. */
+		nonBlocking?: boolean;
+	}
+	export interface ReadPartialResult {
+		nread: number;
+		eof: boolean;
+		wouldBlock: boolean;
+	}
+	/** Read at most `options.maxLen` bytes from a resource into `p`. Unlike
+	 * `read()`, a zero byte read is reported as either `eof` or `wouldBlock`.
+	 *
+	 *       (async () => {
+	 *         const file = await Deno.open("/foo/bar.txt");
+	 *         const buf = new Uint8Array(100);
+	 *         const { nread, eof } = await Deno.readPartial(file.rid, buf, {
+	 *           maxLen: 4
+	 *         });
+	 *       })();
+	 */
+	export function readPartial(
+		rid: number,
+		p: Uint8Array,
+		options?: ReadPartialOptions,
+	): Promise<ReadPartialResult>;
 	/** Write synchronously to the file ID the contents of the array buffer.
 	 *
 	 * Resolves with the number of bytes written.
@@ -376,6 +456,8 @@ declare namespace Deno {
 	 */
 	export function write(rid: number, p: Uint8Array): Promise<number>;
 	/** Seek a file ID synchronously to the given offset under mode given by `whence`.
+	 * Returns the new position relative to the start of the file. Throws if the
+	 * resource is not seekable, e.g. a socket or pipe.
 	 *
 	 *       const file = Deno.openSync("/foo/bar.txt");
 	 *       Deno.seekSync(file.rid, 0, 0);
@@ -384,7 +466,7 @@ declare namespace Deno {
 		rid: number,
 		offset: number,
 		whence: SeekMode,
-	): void;
+	): number;
 	/** Seek a file ID to the given offset under mode given by `whence`.
 	 *
 	 *      (async () => {
@@ -396,7 +478,34 @@ declare namespace Deno {
 		rid: number,
 		offset: number,
 		whence: SeekMode,
-	): Promise<void>;
+	): Promise<number>;
+	export interface LockOptions {
+		/** Take an exclusive lock instead of a shared one. */
+		exclusive?: boolean;
+		/** Fail with `ErrorKind.WouldBlock` instead of waiting when the lock is
+		 * held by someone else. */
+		nonBlocking?: boolean;
+	}
+	/** Take an advisory lock on a file ID synchronously. The lock is released by
+	 * `funlockSync()` or when the file is closed.
+	 *
+	 *       const file = Deno.openSync("/foo/bar.txt", "r+");
+	 *       Deno.flockSync(file.rid, { exclusive: true });
+	 */
+	export function flockSync(rid: number, options?: LockOptions): void;
+	/** Take an advisory lock on a file ID. The lock is released by `funlock()`
+	 * or when the file is closed.
+	 *
+	 *      (async () => {
+	 *        const file = await Deno.open("/foo/bar.txt", "r+");
+	 *        await Deno.flock(file.rid, { exclusive: true });
+	 *      })();
+	 */
+	export function flock(rid: number, options?: LockOptions): Promise<void>;
+	/** Release an advisory lock on a file ID synchronously. */
+	export function funlockSync(rid: number): void;
+	/** Release an advisory lock on a file ID. */
+	export function funlock(rid: number): Promise<void>;
 	/** Close the file ID. */
 	export function close(rid: number): void;
 	/** The Deno abstraction for reading and writing files. */
@@ -416,8 +525,12 @@ declare namespace Deno {
 		writeSync(p: Uint8Array): number;
 		read(p: Uint8Array): Promise<number | EOF>;
 		readSync(p: Uint8Array): number | EOF;
-		seek(offset: number, whence: SeekMode): Promise<void>;
-		seekSync(offset: number, whence: SeekMode): void;
+		seek(offset: number, whence: SeekMode): Promise<number>;
+		seekSync(offset: number, whence: SeekMode): number;
+		lock(options?: LockOptions): Promise<void>;
+		lockSync(options?: LockOptions): void;
+		unlock(): Promise<void>;
+		unlockSync(): void;
 		close(): void;
 	}
 	/** An instance of `File` for stdin. */
@@ -576,29 +689,50 @@ declare namespace Deno {
 
 	// @url js/make_temp_dir.d.ts
 
-	export interface MakeTempDirOptions {
+	export interface MakeTempOptions {
 		dir?: string;
 		prefix?: string;
 		suffix?: string;
 	}
+	export type MakeTempDirOptions = MakeTempOptions;
 	/** makeTempDirSync is the synchronous version of `makeTempDir`.
 	 *
 	 *       const tempDirName0 = Deno.makeTempDirSync();
 	 *       const tempDirName1 = Deno.makeTempDirSync({ prefix: 'my_temp' });
 	 */
-	export function makeTempDirSync(options?: MakeTempDirOptions): string;
+	export function makeTempDirSync(options?: MakeTempOptions): string;
 	/** makeTempDir creates a new temporary directory in the directory `dir`, its
 	 * name beginning with `prefix` and ending with `suffix`.
 	 * It returns the full path to the newly created directory.
 	 * If `dir` is unspecified, tempDir uses the default directory for temporary
 	 * files. Multiple programs calling tempDir simultaneously will not choose the
 	 * same directory. It is the caller's responsibility to remove the directory
-	 * when no longer needed.
+	 * when no longer needed. On Unix the directory is created with mode 0o700.
+	 * Requires write permission for the directory it is created in.
 	 *
 	 *       const tempDirName0 = await Deno.makeTempDir();
 	 *       const tempDirName1 = await Deno.makeTempDir({ prefix: 'my_temp' });
 	 */
-	export function makeTempDir(options?: MakeTempDirOptions): Promise<string>;
+	export function makeTempDir(options?: MakeTempOptions): Promise<string>;
+	/** makeTempFileSync is the synchronous version of `makeTempFile`.
+	 *
+	 *       const tempFileName0 = Deno.makeTempFileSync();
+	 *       const tempFileName1 = Deno.makeTempFileSync({ suffix: '.json' });
+	 */
+	export function makeTempFileSync(options?: MakeTempOptions): string;
+	/** makeTempFile creates a new empty file in the directory `dir`, its name
+	 * beginning with `prefix` and ending with `suffix`, and returns its full
+	 * path. If `dir` is unspecified, the default directory for temporary files
+	 * is used. The file is created in the same step that picks its name, so
+	 * another process can't claim or replace it in between. On Unix it is
+	 * created with mode 0o600. It is the caller's responsibility to remove the
+	 * file when no longer needed. Requires write permission for the directory
+	 * it is created in.
+	 *
+	 *       const tempFileName0 = await Deno.makeTempFile();
+	 *       const tempFileName1 = await Deno.makeTempFile({ suffix: '.json' });
+	 */
+	export function makeTempFile(options?: MakeTempOptions): Promise<string>;
 
 	// @url js/chmod.d.ts
 
@@ -635,6 +769,16 @@ declare namespace Deno {
 		gid: number,
 	): Promise<void>;
 
+	// @url js/umask.d.ts
+
+	/** Returns the process umask. If `mask` is given it becomes the new umask
+	 * and the previous one is returned. Setting it requires write permission
+	 * for all paths. Unix only at the moment.
+	 *
+	 *       const previous = Deno.umask(0o077);
+	 */
+	export function umask(mask?: number): number;
+
 	// @url js/utime.d.ts
 
 	/** Synchronously changes the access and modification times of a file system
@@ -702,20 +846,33 @@ declare namespace Deno {
 
 	// @url js/read_file.d.ts
 
+	/** Options for reading a file.
+	 * `maxSize` fails the read with `ErrorKind.TooLarge` rather than read more
+	 * than this many bytes (default: no limit)
+	 */
+	export interface ReadFileOptions {
+		maxSize?: number;
+	}
 	/** Read the entire contents of a file synchronously.
 	 *
 	 *       const decoder = new TextDecoder("utf-8");
 	 *       const data = Deno.readFileSync("hello.txt");
 	 *       console.log(decoder.decode(data));
 	 */
-	export function readFileSync(filename: string): Uint8Array;
+	export function readFileSync(
+		filename: string,
+		options?: ReadFileOptions,
+	): Uint8Array;
 	/** Read the entire contents of a file.
 	 *
 	 *       const decoder = new TextDecoder("utf-8");
 	 *       const data = await Deno.readFile("hello.txt");
 	 *       console.log(decoder.decode(data));
 	 */
-	export function readFile(filename: string): Promise<Uint8Array>;
+	export function readFile(
+		filename: string,
+		options?: ReadFileOptions,
+	): Promise<Uint8Array>;
 
 	// @url js/file_info.d.ts
 
@@ -746,6 +903,28 @@ declare namespace Deno {
 		mode: number | null;
 		/** The file or directory name. */
 		name: string | null;
+		/** Nanoseconds past the second for `modified`, `accessed` and `created`.
+		 * Null when the corresponding time is not available.
+		 */
+		modifiedNsec: number | null;
+		accessedNsec: number | null;
+		createdNsec: number | null;
+		/** ID of the device containing the file. Unix only, null on Windows. */
+		dev: number | null;
+		/** Inode number. Unix only, null on Windows. */
+		ino: number | null;
+		/** Number of hard links pointing to the file. Unix only, null on Windows. */
+		nlink: number | null;
+		/** User ID of the owner. Unix only, null on Windows. */
+		uid: number | null;
+		/** Group ID of the owner. Unix only, null on Windows. */
+		gid: number | null;
+		/** Device ID, if this is a special file. Unix only, null on Windows. */
+		rdev: number | null;
+		/** Preferred block size for file system I/O. Unix only, null on Windows. */
+		blksize: number | null;
+		/** Number of 512-byte blocks allocated. Unix only, null on Windows. */
+		blocks: number | null;
 		/** Returns whether this is info for a regular file. This result is mutually
 		 * exclusive to `FileInfo.isDirectory` and `FileInfo.isSymlink`.
 		 */
@@ -762,18 +941,32 @@ declare namespace Deno {
 
 	// @url js/realpath.d.ts
 
+	export interface RealpathOptions {
+		/** Set to `false` to keep a symlink in the final component instead of
+		 * resolving it, like `lstat`. Defaults to `true`. */
+		followFinalSymlink?: boolean;
+	}
+
 	/** Returns absolute normalized path with symbolic links resolved
-	 * synchronously.
+	 * synchronously. Requires read permission for both `path` and the
+	 * resolved path.
 	 *
 	 *       const realPath = Deno.realpathSync("./some/path");
 	 */
-	export function realpathSync(path: string): string;
+	export function realpathSync(
+		path: string,
+		options?: RealpathOptions,
+	): string;
 
 	/** Returns absolute normalized path with symbolic links resolved.
+	 * Requires read permission for both `path` and the resolved path.
 	 *
 	 *       const realPath = await Deno.realpath("./some/path");
 	 */
-	export function realpath(path: string): Promise<string>;
+	export function realpath(
+		path: string,
+		options?: RealpathOptions,
+	): Promise<string>;
 
 	// @url js/read_dir.d.ts
 
@@ -789,6 +982,76 @@ declare namespace Deno {
 	 */
 	export function readDir(path: string): Promise<FileInfo[]>;
 
+	export interface DirEntry {
+		name: string;
+		type: "file" | "dir" | "symlink" | "other";
+		/** Inode number, only set with the `inode` option and only on Unix. */
+		ino?: number | null;
+	}
+
+	export interface ReadDirIterOptions {
+		/** Entries fetched per op. Defaults to 1024. */
+		batchSize?: number;
+		/** Also return inode numbers. They come with the directory entries on
+		 * Unix, so this doesn't cost a `stat` per entry. */
+		inode?: boolean;
+	}
+
+	export interface DirIterator extends AsyncIterableIterator<DirEntry> {
+		readonly rid: number;
+		/** Closes the directory handle. Pending `next()` calls fail. */
+		close(): void;
+	}
+
+	/** Iterates over the entries of a directory in batches, so that large
+	 * directories don't have to be read into memory at once. Entries only
+	 * carry their name and type, use `Deno.lstat` for the rest. The directory
+	 * handle is closed at the end or when the loop is left early. Requires
+	 * `allow-read`.
+	 *
+	 *       for await (const entry of Deno.readDirIter("/")) {
+	 *         console.log(entry.name, entry.type);
+	 *       }
+	 */
+	export function readDirIter(
+		path: string,
+		options?: ReadDirIterOptions,
+	): DirIterator;
+
+	// @url js/fs_events.d.ts
+
+	export interface FsEvent {
+		kind: "create" | "modify" | "remove" | "rename";
+		/** For "rename" the old path comes first, when it is known. */
+		paths: string[];
+	}
+
+	export interface WatchFsOptions {
+		/** Also watch subdirectories. Defaults to `true`. */
+		recursive?: boolean;
+		/** Coalesce repeated events for the same path that happen within this
+		 * many milliseconds. Off by default. */
+		debounce?: number;
+	}
+
+	export interface FsWatcher extends AsyncIterableIterator<FsEvent> {
+		readonly rid: number;
+		/** Stops watching. Pending `next()` calls resolve with `done: true`. */
+		close(): void;
+	}
+
+	/** Watches `paths` for changes. Requires read permission for each path.
+	 *
+	 *       const watcher = Deno.watchFs("/");
+	 *       for await (const event of watcher) {
+	 *         console.log(">>>> event", event);
+	 *       }
+	 */
+	export function watchFs(
+		paths: string | string[],
+		options?: WatchFsOptions,
+	): FsWatcher;
+
 	// @url js/copy_file.d.ts
 
 	/** Copies the contents of a file to another by name synchronously.
@@ -813,6 +1076,41 @@ declare namespace Deno {
 	 */
 	export function copyFile(from: string, to: string): Promise<void>;
 
+	// @url js/copy_dir.d.ts
+
+	export interface CopyDirOptions {
+		/** Also copy directory permissions and access/modification times. File
+		 * permissions are always copied. Defaults to `false`. */
+		preserve?: boolean;
+		/** Merge into an existing destination, replacing files that are already
+		 * there. When `false` (the default) an existing target is an error. */
+		overwrite?: boolean;
+		/** Copy what symlinks point to rather than the links themselves.
+		 * Defaults to `false`. */
+		followSymlinks?: boolean;
+	}
+
+	/** Recursively copies the directory `from` to `to` synchronously.
+	 * Requires read permission for `from` and write permission for `to`.
+	 *
+	 *       Deno.copyDirSync("src", "backup", { preserve: true });
+	 */
+	export function copyDirSync(
+		from: string,
+		to: string,
+		options?: CopyDirOptions,
+	): void;
+	/** Recursively copies the directory `from` to `to`.
+	 * Requires read permission for `from` and write permission for `to`.
+	 *
+	 *       await Deno.copyDir("src", "backup", { preserve: true });
+	 */
+	export function copyDir(
+		from: string,
+		to: string,
+		options?: CopyDirOptions,
+	): Promise<void>;
+
 	// @url js/read_link.d.ts
 
 	/** Returns the destination of the named symbolic link synchronously.
@@ -884,27 +1182,29 @@ declare namespace Deno {
 
 	// @url js/symlink.d.ts
 
-	/** Synchronously creates `newname` as a symbolic link to `oldname`. The type
-	 * argument can be set to `dir` or `file` and is only available on Windows
-	 * (ignored on other platforms).
+	/** Synchronously creates `newname` as a symbolic link to `oldname`. On
+	 * Windows the type argument picks a file or directory link, without it the
+	 * type is taken from `oldname`. Other platforms ignore it. Requires
+	 * `allow-write` for `newname`.
 	 *
 	 *       Deno.symlinkSync("old/name", "new/name");
 	 */
 	export function symlinkSync(
 		oldname: string,
 		newname: string,
-		type?: string,
+		type?: "file" | "dir",
 	): void;
-	/** Creates `newname` as a symbolic link to `oldname`. The type argument can be
-	 * set to `dir` or `file` and is only available on Windows (ignored on other
-	 * platforms).
+	/** Creates `newname` as a symbolic link to `oldname`. On Windows the type
+	 * argument picks a file or directory link, without it the type is taken
+	 * from `oldname`. Other platforms ignore it. Requires `allow-write` for
+	 * `newname`.
 	 *
 	 *       await Deno.symlink("old/name", "new/name");
 	 */
 	export function symlink(
 		oldname: string,
 		newname: string,
-		type?: string,
+		type?: "file" | "dir",
 	): Promise<void>;
 
 	// @url js/write_file.d.ts
@@ -913,11 +1213,16 @@ declare namespace Deno {
 	 * `perm` would change the file's permission if set.
 	 * `create` decides if the file should be created if not exists (default: true)
 	 * `append` decides if the file should be appended (default: false)
+	 * `atomic` writes to a temporary file in the same directory and renames it
+	 * over the target, keeping the target's mode if it exists (default: false)
+	 * `sync` flushes an atomic write to disk before returning (default: false)
 	 */
 	export interface WriteFileOptions {
 		perm?: number;
 		create?: boolean;
 		append?: boolean;
+		atomic?: boolean;
+		sync?: boolean;
 	}
 	/** Write a new file, with given filename and data synchronously.
 	 *
@@ -1063,7 +1368,8 @@ declare namespace Deno {
 		| "net"
 		| "env"
 		| "plugin"
-		| "hrtime";
+		| "hrtime"
+		| "ping";
 	/** https://w3c.github.io/permissions/#status-of-a-permission */
 	export type PermissionState = "granted" | "denied" | "prompt";
 	interface RunPermissionDescriptor {
@@ -1086,6 +1392,9 @@ declare namespace Deno {
 	interface HrtimePermissionDescriptor {
 		name: "hrtime";
 	}
+	interface PingPermissionDescriptor {
+		name: "ping";
+	}
 	/** See: https://w3c.github.io/permissions/#permission-descriptor */
 	type PermissionDescriptor =
 		| RunPermissionDescriptor
@@ -1093,7 +1402,8 @@ declare namespace Deno {
 		| NetPermissionDescriptor
 		| EnvPermissionDescriptor
 		| PluginPermissionDescriptor
-		| HrtimePermissionDescriptor;
+		| HrtimePermissionDescriptor
+		| PingPermissionDescriptor;
 
 	export class Permissions {
 		/** Queries the permission.
@@ -1103,7 +1413,8 @@ declare namespace Deno {
 		 *       }
 		 */
 		query(d: PermissionDescriptor): Promise<PermissionStatus>;
-		/** Revokes the permission.
+		/** Revokes the permission. With a `path` or `url` every grant covering
+		 * it is dropped, including a blanket `--allow-*` flag.
 		 *       const status = await Deno.permissions.revoke({ name: "run" });
 		 *       assert(status.state !== "granted")
 		 */
@@ -1141,6 +1452,36 @@ declare namespace Deno {
 	 *       await Deno.truncate("hello.txt", 10);
 	 */
 	export function truncate(name: string, len?: number): Promise<void>;
+	/** Truncates or extends the file with the given ID synchronously to exactly
+	 * `len` bytes. New bytes are zero.
+	 *
+	 *       const file = Deno.openSync("hello.txt", "r+");
+	 *       Deno.ftruncateSync(file.rid, 10);
+	 */
+	export function ftruncateSync(rid: number, len?: number): void;
+	/** Truncates or extends the file with the given ID to exactly `len` bytes.
+	 * New bytes are zero.
+	 *
+	 *       const file = await Deno.open("hello.txt", "r+");
+	 *       await Deno.ftruncate(file.rid, 10);
+	 */
+	export function ftruncate(rid: number, len?: number): Promise<void>;
+	/** Reserves disk space synchronously so the file with the given ID is at
+	 * least `len` bytes long. Uses `posix_fallocate` where available and
+	 * otherwise extends the file. Never shrinks the file.
+	 *
+	 *       const file = Deno.openSync("data.db", "r+");
+	 *       Deno.fallocateSync(file.rid, 1 << 20);
+	 */
+	export function fallocateSync(rid: number, len: number): void;
+	/** Reserves disk space so the file with the given ID is at least `len` bytes
+	 * long. Uses `posix_fallocate` where available and otherwise extends the
+	 * file. Never shrinks the file.
+	 *
+	 *       const file = await Deno.open("data.db", "r+");
+	 *       await Deno.fallocate(file.rid, 1 << 20);
+	 */
+	export function fallocate(rid: number, len: number): Promise<void>;
 
 	// @url js/plugins.d.ts
 
@@ -1157,9 +1498,14 @@ declare namespace Deno {
 	}
 
 	export interface Plugin {
+		readonly rid: number;
 		ops: {
 			[name: string]: PluginOp;
 		};
+		/** Unregisters the plugin's ops and unloads it once calls still in
+		 * progress have finished. Dispatching one of its ops afterwards throws,
+		 * including through `PluginOp` objects obtained earlier. */
+		close(): void;
 	}
 
 	/** Open and initalize a plugin.
@@ -1172,14 +1518,196 @@ declare namespace Deno {
 	 */
 	export function openPlugin(filename: string): Plugin;
 
+	// @url js/compression.d.ts
+
+	/** `deflate` is the zlib format, as in HTTP's `Content-Encoding`, and
+	 * `deflate-raw` the bare deflate stream.
+	 */
+	export type CompressionFormat =
+		| "gzip"
+		| "deflate"
+		| "deflate-raw"
+		| "brotli";
+
+	export interface DecompressionOptions {
+		/** Preset dictionary. Not supported: neither the deflate nor the
+		 * brotli implementation deno is built with takes one, so passing one
+		 * throws `InvalidInput`.
+		 */
+		dictionary?: Uint8Array;
+		/** Fails with `InvalidData` once the output would grow past this many
+		 * bytes, in total over all `update()` and `finish()` calls. Use it when
+		 * decompressing untrusted input. Unlimited by default.
+		 */
+		maxOutputLength?: number;
+	}
+
+	export interface CompressionOptions extends DecompressionOptions {
+		/** 0 to 9 for gzip and deflate, defaulting to 6, and 0 to 11 for
+		 * brotli, defaulting to 11. Higher is smaller and slower.
+		 */
+		level?: number;
+	}
+
+	/** Compresses or decompresses data given in pieces. */
+	export interface Compressor {
+		readonly rid: number;
+
+		/** Feeds `data` in and returns the output produced so far, which may
+		 * be empty.
+		 */
+		update(data: Uint8Array): Uint8Array;
+
+		/** Returns the rest of the output and closes `rid`. Throws
+		 * `InvalidData` if decompressed input is corrupt, or truncated gzip or
+		 * brotli.
+		 */
+		finish(): Uint8Array;
+
+		/** Feeds everything read from `r` until EOF in, writes the output to
+		 * `w` and finishes.
+		 */
+		transform(r: Reader, w: Writer): Promise<void>;
+	}
+
+	/** Starts compressing with `format`.
+	 *
+	 *       const gz = Deno.createCompressor("gzip", { level: 9 });
+	 *       await gz.transform(file, out);
+	 */
+	export function createCompressor(
+		format: CompressionFormat,
+		options?: CompressionOptions,
+	): Compressor;
+
+	/** Starts decompressing `format`.
+	 *
+	 *       const gunzip = Deno.createDecompressor("gzip", {
+	 *         maxOutputLength: 64 * 1024 * 1024,
+	 *       });
+	 */
+	export function createDecompressor(
+		format: CompressionFormat,
+		options?: DecompressionOptions,
+	): Compressor;
+
+	/** Compresses `data` in one go. */
+	export function compress(
+		format: CompressionFormat,
+		data: Uint8Array,
+		options?: CompressionOptions,
+	): Uint8Array;
+
+	/** Decompresses `data` in one go. */
+	export function decompress(
+		format: CompressionFormat,
+		data: Uint8Array,
+		options?: DecompressionOptions,
+	): Uint8Array;
+
+	// @url js/encoding.d.ts
+
+	export interface Base64Options {
+		/** Use `-` and `_` instead of `+` and `/`, as in URLs and file names. */
+		urlSafe?: boolean;
+		/** Pad the output with `=` to a multiple of four characters. Defaults
+		 * to true. Decoding accepts input with or without padding.
+		 */
+		pad?: boolean;
+	}
+
+	/** Encodes `data` as base64.
+	 *
+	 *       Deno.base64Encode(data, { urlSafe: true, pad: false });
+	 */
+	export function base64Encode(
+		data: Uint8Array,
+		options?: Base64Options,
+	): string;
+
+	/** Decodes base64 `data`, throwing `InvalidData` if it isn't valid. */
+	export function base64Decode(
+		data: string,
+		options?: Base64Options,
+	): Uint8Array;
+
+	/** Encodes `data` as lower case hex. */
+	export function hexEncode(data: Uint8Array): string;
+
+	/** Decodes hex `data` of either case, throwing `InvalidData` if it isn't
+	 * valid.
+	 */
+	export function hexDecode(data: string): Uint8Array;
+
+	// @url js/hash.d.ts
+
+	export type HashAlgorithm = "sha256" | "sha512" | "blake3";
+
+	/** A hash computed natively from data given in pieces. */
+	export interface Hash {
+		readonly rid: number;
+
+		/** Feeds `data` to the hash. Strings are hashed as UTF-8. */
+		update(data: Uint8Array | string): this;
+
+		/** Feeds everything read from `r` until EOF to the hash and returns
+		 * the number of bytes read.
+		 */
+		updateFrom(r: Reader): Promise<number>;
+
+		/** Returns the digest. The hash can't be updated after this. */
+		digest(): Uint8Array;
+	}
+
+	/** Starts computing a hash with `algorithm`.
+	 *
+	 *       const digest = Deno.createHash("sha256").update("hello").digest();
+	 */
+	export function createHash(algorithm: HashAlgorithm): Hash;
+
+	/** Returns the HMAC (RFC 2104) of `message` under `key`. Strings are
+	 * taken as UTF-8.
+	 *
+	 *       const tag = Deno.hmac("sha256", secret, body);
+	 */
+	export function hmac(
+		algorithm: "sha256" | "sha512",
+		key: Uint8Array | string,
+		message: Uint8Array | string,
+	): Uint8Array;
+
+	/** Returns whether `a` and `b` hold the same bytes, taking time that
+	 * depends only on their lengths. Use it to compare secrets like
+	 * signatures.
+	 *
+	 *       if (!Deno.bytesEqual(Deno.hmac("sha256", secret, body), signature)) {
+	 *         throw new Error("Bad signature");
+	 *       }
+	 */
+	export function bytesEqual(a: Uint8Array, b: Uint8Array): boolean;
+
 	// @url js/net.d.ts
 
-	type Transport = "tcp";
+	type Transport = "tcp" | "unix";
 	interface Addr {
 		transport: Transport;
 		address: string;
 	}
 
+	export enum ShutdownMode {
+		Read = 0,
+		Write,
+		ReadWrite,
+	}
+
+	/** Shuts down (`shutdown(2)`) reading, writing or both directions of a TCP
+	 * or Unix connection without closing `rid`. After shutting down writing the
+	 * peer reads EOF, while whatever it still sends can be read.
+	 *
+	 *       Deno.shutdown(conn.rid, Deno.ShutdownMode.Write);
+	 */
+	export function shutdown(rid: number, how: ShutdownMode): void;
+
 	/** A Listener is a generic network listener for stream-oriented protocols. */
 	export interface Listener extends AsyncIterator<Conn> {
 		/** Waits for and resolves to the next connection to the `Listener`. */
@@ -1207,12 +1735,72 @@ declare namespace Deno {
 		 * callers should just use `close()`.
 		 */
 		closeWrite(): void;
+		/** Enables or disables Nagle's algorithm (`TCP_NODELAY`). Only valid on
+		 * plain TCP connections.
+		 */
+		setNoDelay(noDelay: boolean): void;
+		/** Enables `SO_KEEPALIVE` with the given interval in milliseconds, or
+		 * disables it when `interval` is 0. Only valid on plain TCP connections.
+		 */
+		setKeepAlive(interval: number): void;
+		/** Returns the socket options currently applied to the connection. */
+		tcpOptions(): TcpOptions;
+		/** Splits the connection into a readable and a writable half with rids of
+		 * their own, so that a read doesn't wait for a pending write or the other
+		 * way round. The connection's own rid is closed. Closing the writable half
+		 * shuts down the writing side while the readable half keeps receiving. Only
+		 * valid on plain TCP and Unix connections.
+		 *
+		 *       const [reader, writer] = conn.split();
+		 */
+		split(): [ConnReadHalf, ConnWriteHalf];
+	}
+
+	/** The readable half of a connection, see `Conn.split()`. */
+	export interface ConnReadHalf extends Reader, Closer {
+		readonly rid: number;
+	}
+
+	/** The writable half of a connection, see `Conn.split()`. */
+	export interface ConnWriteHalf extends Writer, Closer {
+		readonly rid: number;
+	}
+
+	export interface TcpOptions {
+		noDelay: boolean;
+		/** Keepalive interval in milliseconds, or `null` if keepalive is off. */
+		keepAlive: number | null;
 	}
 
 	export interface ListenOptions {
 		port: number;
 		hostname?: string;
 		transport?: Transport;
+		/** Applied to every accepted connection, see `Conn.setNoDelay()`. */
+		noDelay?: boolean;
+		/** Applied to every accepted connection, see `Conn.setKeepAlive()`. */
+		keepAlive?: number;
+		/** Whether the address can be bound again while connections of a closed
+		 * listener linger in `TIME_WAIT`, e.g. when restarting a server. Defaults
+		 * to true. This and the options below are not supported on Windows.
+		 */
+		reuseAddress?: boolean;
+		/** Lets several listeners bind the same address and port, with incoming
+		 * connections spread among them, e.g. to start a new server before
+		 * stopping the old one. Any process running as the same user can then
+		 * bind the port too and receive part of the connections, so only use it
+		 * where all of that user's processes are trusted. Defaults to false.
+		 */
+		reusePort?: boolean;
+		/** Maximum number of connections waiting to be accepted. The OS may
+		 * lower it. Defaults to 1024.
+		 */
+		backlog?: number;
+		/** Whether a listener on an IPv6 address such as `::` refuses IPv4
+		 * connections. With false it accepts both, IPv4 clients showing up with
+		 * mapped addresses like `::ffff:192.0.2.1`. Defaults to the OS setting.
+		 */
+		v6Only?: boolean;
 	}
 
 	/** Listen announces on the local transport address.
@@ -1240,6 +1828,8 @@ declare namespace Deno {
 		transport?: Transport;
 		certFile: string;
 		keyFile: string;
+		clientCaFile?: string;
+		alpnProtocols?: string[];
 	}
 
 	/** Listen announces on the local transport address over TLS (transport layer security).
@@ -1250,6 +1840,10 @@ declare namespace Deno {
 	 *   resolved to an IP address. If not specified, defaults to 0.0.0.0
 	 * @param options.certFile Server certificate file
 	 * @param options.keyFile Server public key file
+	 * @param options.clientCaFile If set, clients must present a certificate
+	 *   signed by one of the CAs in this file
+	 * @param options.alpnProtocols ALPN protocols the server supports, in order
+	 *   of preference
 	 *
 	 * Examples:
 	 *
@@ -1261,6 +1855,12 @@ declare namespace Deno {
 		port: number;
 		hostname?: string;
 		transport?: Transport;
+		/** See `Conn.setNoDelay()`. Defaults to off. */
+		noDelay?: boolean;
+		/** See `Conn.setKeepAlive()`. Defaults to the OS setting. */
+		keepAlive?: number;
+		/** Aborts connecting, the promise then rejects with `ErrorKind.Aborted`. */
+		signal?: AbortSignal;
 	}
 
 	/** Dial connects to the address on the named transport.
@@ -1285,13 +1885,181 @@ declare namespace Deno {
 	export interface DialTLSOptions {
 		port: number;
 		hostname?: string;
+		/** Additional root CA certificates to trust, in PEM format. */
 		certFile?: string;
+		/** Client certificate chain for mutual TLS, in PEM format. */
+		clientCertFile?: string;
+		/** Private key for `clientCertFile`, in PEM format. */
+		clientKeyFile?: string;
+		/** ALPN protocols to offer, in order of preference. */
+		alpnProtocols?: string[];
+		/** Resume a session from an earlier connection to the same host to
+		 * skip the full handshake. Defaults to `true`. */
+		sessionResumption?: boolean;
+		/** Send the first write as TLS 1.3 early data (0-RTT) when resuming.
+		 * Early data can be replayed by an attacker, so only enable this for
+		 * idempotent requests. Defaults to `false`. */
+		earlyData?: boolean;
+	}
+
+	export interface TLSConn extends Conn {
+		/** The ALPN protocol both sides agreed on, or `null` if none was. */
+		alpnProtocol: string | null;
+		/** Hex SHA-256 fingerprints of the peer's certificate chain, leaf first. */
+		peerCertFingerprints: string[];
+		/** True if the server resumed a session from an earlier connection
+		 * instead of doing a full handshake. Always false for accepted
+		 * connections. */
+		resumed: boolean;
 	}
 
 	/**
 	 * dialTLS establishes a secure connection over TLS (transport layer security).
 	 */
-	export function dialTLS(options: DialTLSOptions): Promise<Conn>;
+	export function dialTLS(options: DialTLSOptions): Promise<TLSConn>;
+
+	/** The address of one end of a datagram socket. */
+	export interface DatagramAddr {
+		hostname: string;
+		port: number;
+	}
+
+	export interface DatagramReceiveResult {
+		/** Number of bytes copied into the buffer. */
+		nread: number;
+		/** True if the datagram didn't fit into the buffer and the rest of it
+		 * was discarded. */
+		truncated: boolean;
+		/** Where the datagram came from. */
+		remoteAddr: DatagramAddr;
+	}
+
+	/** A bound UDP socket. */
+	export interface DatagramConn extends Closer {
+		/** The resource ID of the socket. */
+		readonly rid: number;
+		/** The address the socket is bound to. When bound to port 0 this holds
+		 * the port picked by the operating system. */
+		readonly localAddr: DatagramAddr;
+		/** Sends `p` as a single datagram to `addr`. */
+		send(p: Uint8Array, addr: DatagramAddr): Promise<number>;
+		/** Waits for the next datagram and copies it into `p`. */
+		receive(p: Uint8Array): Promise<DatagramReceiveResult>;
+	}
+
+	export interface ListenDatagramOptions {
+		port: number;
+		hostname?: string;
+	}
+
+	/** Binds a UDP socket. Requires the `--allow-net` permission.
+	 *
+	 *     const conn = Deno.listenDatagram({ port: 0 });
+	 *     await conn.send(data, { hostname: "127.0.0.1", port: 53 });
+	 *     const { nread, remoteAddr } = await conn.receive(buf);
+	 */
+	export function listenDatagram(options: ListenDatagramOptions): DatagramConn;
+
+	/** The address of a TCP or UDP socket, see `Deno.localAddr()`. */
+	export interface NetAddr {
+		transport: "tcp" | "udp";
+		hostname: string;
+		port: number;
+		family: "ipv4" | "ipv6";
+	}
+
+	/** The address of a Unix domain socket, see `Deno.localAddr()`. */
+	export interface UnixAddr {
+		transport: "unix";
+		/** `null` for unnamed sockets, e.g. the dialing end of a connection. */
+		path: string | null;
+	}
+
+	/** Returns the address the socket behind `rid` is bound to. Works for
+	 * connections, listeners and datagram sockets.
+	 *
+	 *       const { hostname, port } = Deno.localAddr(listener.rid) as Deno.NetAddr;
+	 */
+	export function localAddr(rid: number): NetAddr | UnixAddr;
+
+	/** Returns the address the connection behind `rid` is connected to, e.g.
+	 * to log or authorize clients of a server.
+	 *
+	 *       const { hostname } = Deno.peerAddr(conn.rid) as Deno.NetAddr;
+	 */
+	export function peerAddr(rid: number): NetAddr | UnixAddr;
+
+	export interface UnixListenOptions {
+		/** Maximum number of connections waiting to be accepted. The OS may
+		 * lower it. Defaults to 128.
+		 */
+		backlog?: number;
+	}
+
+	/** Listens on a Unix domain socket at `path`. The socket file is removed
+	 * when the listener is closed. Requires `--allow-read` and `--allow-write`
+	 * for `path`. Not available on Windows.
+	 *
+	 *     const listener = Deno.listenUnix("/tmp/app.sock");
+	 */
+	export function listenUnix(
+		path: string,
+		options?: UnixListenOptions,
+	): Listener;
+
+	/** Connects to the Unix domain socket at `path`. Requires `--allow-read`
+	 * and `--allow-write` for `path`. Not available on Windows.
+	 */
+	export function dialUnix(path: string): Promise<Conn>;
+
+	export interface DnsRecord {
+		type: "A" | "AAAA";
+		address: string;
+	}
+
+	export interface ResolveDnsOptions {
+		/** Only return records of this address family. */
+		family?: "ipv4" | "ipv6";
+	}
+
+	/** Looks up the addresses of `hostname`. Names given to `--host-override`
+	 * resolve to the overriding addresses. Requires `allow-net` for
+	 * `hostname`.
+	 *
+	 *     const records = await Deno.resolveDns("deno.land", { family: "ipv4" });
+	 */
+	export function resolveDns(
+		hostname: string,
+		options?: ResolveDnsOptions,
+	): Promise<DnsRecord[]>;
+
+	export interface PingOptions {
+		/** Number of echo requests, sent one after another. Defaults to 1. */
+		count?: number;
+		/** Milliseconds to wait for each reply. Defaults to 1000. */
+		timeout?: number;
+	}
+
+	export interface PingResult {
+		/** The address `hostname` resolved to. */
+		address: string;
+		/** Round-trip time of each request in milliseconds, `null` where no
+		 * reply came in time.
+		 */
+		rtts: Array<number | null>;
+	}
+
+	/** Sends ICMP echo requests to `hostname`. Requires `--allow-ping`, and
+	 * the process needs root or `CAP_NET_RAW`, or on Linux a group listed in
+	 * `net.ipv4.ping_group_range`, or it throws `PermissionDenied`. Not
+	 * available on Windows.
+	 *
+	 *     const { rtts } = await Deno.ping("192.0.2.1", { count: 3 });
+	 */
+	export function ping(
+		hostname: string,
+		options?: PingOptions,
+	): Promise<PingResult>;
 
 	// @url js/metrics.d.ts
 	export interface Metrics {
@@ -1300,6 +2068,8 @@ declare namespace Deno {
 		bytesSentControl: number;
 		bytesSentData: number;
 		bytesReceived: number;
+		/** Async ops dispatched and not completed yet. */
+		opsPending: number;
 	}
 	/** Receive metrics from the privileged side of Deno.
 	 *
@@ -1312,6 +2082,7 @@ declare namespace Deno {
 	 *      │ bytesSentControl │  504   │
 	 *      │  bytesSentData   │   0    │
 	 *      │  bytesReceived   │  856   │
+	 *      │    opsPending    │   0    │
 	 *      └──────────────────┴────────┘
 	 */
 	export function metrics(): Metrics;
@@ -1321,10 +2092,73 @@ declare namespace Deno {
 	interface ResourceMap {
 		[rid: number]: string;
 	}
+	interface ResourcesOptions {
+		/** Append what is known about each resource to its name, e.g.
+		 * `"fsFile:/tmp/log.txt"`, `"child:1234"` or
+		 * `"tcpStream:127.0.0.1:4500"`. */
+		details?: boolean;
+	}
 	/** Returns a map of open _file like_ resource ids along with their string
 	 * representation.
 	 */
-	export function resources(): ResourceMap;
+	export function resources(options?: ResourcesOptions): ResourceMap;
+	interface ResourceStats {
+		name: string;
+		/** Bytes read from the resource with `read()` since it was opened. */
+		bytesRead: number;
+		/** Bytes written to the resource with `write()` since it was opened. */
+		bytesWritten: number;
+	}
+	interface ResourceStatsMap {
+		[rid: number]: ResourceStats;
+	}
+	/** Like `resources()`, but also returns how many bytes were read from and
+	 * written to each resource so far.
+	 *
+	 *       const file = await Deno.open("log.txt", "w");
+	 *       await file.write(new TextEncoder().encode("hello"));
+	 *       Deno.resourceStats()[file.rid].bytesWritten; // 5
+	 */
+	export function resourceStats(options?: ResourcesOptions): ResourceStatsMap;
+	interface CloseAllOptions {
+		/** Leave stdin, stdout and stderr (rids 0, 1 and 2) open. Defaults to
+		 * true. */
+		keepStdio?: boolean;
+	}
+	/** Closes every open resource and returns how many were closed. Useful in a
+	 * top level error handler or between test cases. Pending operations on the
+	 * closed resources fail or finish as if each had been closed on its own.
+	 *
+	 *       Deno.closeAll();
+	 */
+	export function closeAll(options?: CloseAllOptions): number;
+	/** Returns a new rid for the file or socket behind `rid`, like dup(2). Both
+	 * can be used and closed independently, the underlying file or socket stays
+	 * open until both are closed. Duplicated files share their position.
+	 *
+	 *       const file = await Deno.open("log.txt");
+	 *       const copy = Deno.dup(file.rid);
+	 *       file.close();
+	 *       await Deno.read(copy, new Uint8Array(16));
+	 */
+	export function dup(rid: number): number;
+
+	// @url js/workers.d.ts
+
+	/** Posts a message to the worker created with `{ name }`, from any module.
+	 * Its replies go to the worker that created it, as usual. Throws a
+	 * `WouldBlock` error if the worker has too many messages queued.
+	 *
+	 *       new Worker("./logger.ts", { name: "logger" });
+	 *       Deno.postMessageToWorker("logger", { level: "info", msg: "hi" });
+	 */
+	export function postMessageToWorker(
+		name: string,
+		data: any,
+		transfer?: ArrayBuffer[],
+	): void;
+	/** Returns the names of the live workers created with `{ name }`. */
+	export function namedWorkers(): string[];
 
 	// @url js/process.d.ts
 
@@ -1350,19 +2184,42 @@ declare namespace Deno {
 		stderr?: ProcessStdio | number;
 		stdin?: ProcessStdio | number;
 	}
-	/** Send a signal to process under given PID. Unix only at this moment.
+
+	export interface StatusOptions {
+		signal?: AbortSignal;
+	}
+
+	/** Send a signal to process under given PID. On Windows the process is
+	 * terminated whatever the signal.
 	 * If pid is negative, the signal will be sent to the process group identified
 	 * by -pid.
 	 * Requires the `--allow-run` flag.
 	 */
 	export function kill(pid: number, signo: number): void;
+	/** Returns the scheduling priority of the process under given PID, or of the
+	 * current process if PID is omitted, as a nice value from -20 (highest) to
+	 * 19 (lowest). On Windows it is derived from the priority class.
+	 * Requires the `--allow-run` flag.
+	 */
+	export function getPriority(pid?: number): number;
+	/** Sets the scheduling priority of the process under given PID, or of the
+	 * current process if PID is omitted. `priority` is a nice value from -20
+	 * (highest) to 19 (lowest); on Windows it selects the closest priority class.
+	 * Raising the priority usually needs elevated privileges and throws
+	 * `PermissionDenied` otherwise.
+	 * Requires the `--allow-run` flag.
+	 */
+	export function setPriority(priority: number, pid?: number): void;
 	export class Process {
 		readonly rid: number;
 		readonly pid: number;
 		readonly stdin?: WriteCloser;
 		readonly stdout?: ReadCloser;
 		readonly stderr?: ReadCloser;
-		status(): Promise<ProcessStatus>;
+		/** Waits for the process to exit. Aborting `signal` stops waiting, the
+		 * promise then rejects with `ErrorKind.Aborted` while the process keeps
+		 * running. */
+		status(options?: StatusOptions): Promise<ProcessStatus>;
 		/** Buffer the stdout and return it as Uint8Array after EOF.
 		 * You must set stdout to "piped" when creating the process.
 		 * This calls close() on stdout after its done.
@@ -1374,18 +2231,26 @@ declare namespace Deno {
 		 */
 		stderrOutput(): Promise<Uint8Array>;
 		close(): void;
+		/** Sends `signo` to the process. Throws `NotFound` if it has already
+		 * exited and been reaped by `status()`. */
 		kill(signo: number): void;
+		/** Returns the process' priority, see `Deno.getPriority()`. */
+		getPriority(): number;
+		/** Sets the process' priority, see `Deno.setPriority()`. */
+		setPriority(priority: number): void;
 	}
 	export interface ProcessStatus {
 		success: boolean;
 		code?: number;
 		signal?: number;
+		/** Whether the signal that terminated the process produced a core dump. */
+		coreDumped: boolean;
 	}
 	/**
 	 * Spawns new subprocess.
 	 *
-	 * Subprocess uses same working directory as parent process unless `opt.cwd`
-	 * is specified.
+	 * Subprocess uses the working directory of the calling worker unless
+	 * `opt.cwd` is specified.
 	 *
 	 * Environmental variables for subprocess can be specified using `opt.env`
 	 * mapping.
@@ -1466,6 +2331,71 @@ declare namespace Deno {
 	export const Signal: typeof MacOSSignal | typeof LinuxSignal;
 	export {};
 
+	// @url js/signals.d.ts
+
+	/** Occurrences of a signal. Iterate it with `for await`, or `await` it to
+	 * wait for the next occurrence. */
+	export class SignalStream
+		implements AsyncIterableIterator<void>, PromiseLike<void>
+	{
+		readonly rid: number;
+		readonly signo: number;
+		constructor(signo: number);
+		next(): Promise<IteratorResult<void>>;
+		return(): Promise<IteratorResult<void>>;
+		then<T = void, S = never>(
+			f: (v: void) => T | Promise<T>,
+			g?: (v: Error) => S | Promise<S>,
+		): Promise<T | S>;
+		/** Stops listening. Pending `next()` calls resolve with `done: true`.
+		 * Once nothing listens for the signal any more it has its default effect
+		 * again. */
+		dispose(): void;
+		[Symbol.asyncIterator](): AsyncIterableIterator<void>;
+	}
+
+	/** Listens for the signal `signo`. While the stream is alive the signal no
+	 * longer has its default effect, e.g. SIGINT doesn't terminate the process.
+	 * On Windows only SIGINT (Ctrl-C) is supported.
+	 *
+	 *       for await (const _ of Deno.signal(Deno.Signal.SIGTERM)) {
+	 *         console.log("got SIGTERM");
+	 *       }
+	 */
+	export function signal(signo: number): SignalStream;
+
+	// @url js/interval.d.ts
+
+	/** Ticks of a repeating timer. Iterate it with `for await`, or `await` it to
+	 * wait for the next tick. */
+	export class IntervalStream
+		implements AsyncIterableIterator<void>, PromiseLike<void>
+	{
+		readonly rid: number;
+		readonly period: number;
+		constructor(period: number);
+		next(): Promise<IteratorResult<void>>;
+		return(): Promise<IteratorResult<void>>;
+		then<T = void, S = never>(
+			f: (v: void) => T | Promise<T>,
+			g?: (v: Error) => S | Promise<S>,
+		): Promise<T | S>;
+		/** Cancels the timer. Pending `next()` calls resolve with `done: true`.
+		 * Clearing an already cleared timer does nothing. */
+		clear(): void;
+		[Symbol.asyncIterator](): AsyncIterableIterator<void>;
+	}
+
+	/** Starts a timer that ticks every `period` milliseconds. Unlike
+	 * `setInterval` the ticks are scheduled by the runtime, so they keep to the
+	 * original schedule even when the event loop is busy.
+	 *
+	 *       for await (const _ of Deno.interval(1000)) {
+	 *         console.log("tick");
+	 *       }
+	 */
+	export function interval(period: number): IntervalStream;
+
 	// @url js/console.d.ts
 
 	type ConsoleOptions = Partial<{
@@ -1528,6 +2458,8 @@ declare interface Window {
 	onload: Function | undefined;
 	onunload: Function | undefined;
 	crypto: Crypto;
+	AbortController: typeof __abortController.AbortController;
+	AbortSignal: typeof __abortController.AbortSignal;
 	Blob: typeof __blob.DenoBlob;
 	File: __domTypes.DomFileConstructor;
 	CustomEvent: typeof __customEvent.CustomEvent;
@@ -1575,6 +2507,8 @@ declare const location: __domTypes.Location;
 declare const onload: Function | undefined;
 declare const onunload: Function | undefined;
 declare const crypto: Crypto;
+declare const AbortController: typeof __abortController.AbortController;
+declare const AbortSignal: typeof __abortController.AbortSignal;
 declare const Blob: typeof __blob.DenoBlob;
 declare const File: __domTypes.DomFileConstructor;
 declare const CustomEventInit: typeof __customEvent.CustomEventInit;
@@ -1609,6 +2543,8 @@ declare const removeEventListener: (
 	options?: boolean | __domTypes.EventListenerOptions | undefined,
 ) => void;
 
+declare type AbortController = __abortController.AbortController;
+declare type AbortSignal = __domTypes.AbortSignal;
 declare type Blob = __domTypes.Blob;
 declare type Body = __domTypes.Body;
 declare type File = __domTypes.DomFile;
@@ -1694,7 +2630,7 @@ declare namespace __domTypes {
 		ending?: EndingType;
 	}
 	interface AbortSignalEventMap {
-		abort: ProgressEvent;
+		abort: Event;
 	}
 	export enum NodeType {
 		ELEMENT_NODE = 1,
@@ -1860,9 +2796,9 @@ declare namespace __domTypes {
 		once: boolean;
 		passive: boolean;
 	}
-	interface AbortSignal extends EventTarget {
+	export interface AbortSignal extends EventTarget {
 		readonly aborted: boolean;
-		onabort: ((this: AbortSignal, ev: ProgressEvent) => any) | null;
+		onabort: ((this: AbortSignal, ev: Event) => any) | null;
 		addEventListener<K extends keyof AbortSignalEventMap>(
 			type: K,
 			listener: (this: AbortSignal, ev: AbortSignalEventMap[K]) => any,
@@ -2041,8 +2977,49 @@ declare namespace __domTypes {
 		redirect?: RequestRedirect;
 		referrer?: string;
 		referrerPolicy?: ReferrerPolicy;
+		/** Aborts the request until the response headers have arrived. The
+		 * promise then rejects with `ErrorKind.Aborted`. */
 		signal?: AbortSignal | null;
 		window?: any;
+		/** Deno extension. Milliseconds before the request is aborted, `0`
+		 * disables the timeout. Defaults to the `--fetch-timeout` flag. */
+		timeout?: number;
+		/** Deno extension. Set to `false` to receive gzip and brotli encoded
+		 * bodies as sent, along with their `Content-Encoding` header. */
+		decompress?: boolean;
+		/** Deno extension. Redirects followed before failing with
+		 * `ErrorKind.TooManyRedirects`. Defaults to 20. */
+		maxRedirects?: number;
+		/** Deno extension. Drop the `Authorization`, `Cookie` and
+		 * `Proxy-Authorization` headers when following a redirect to another
+		 * origin. */
+		stripCredentialsOnRedirect?: boolean;
+		/** Deno extension. Send a `multipart/form-data` body made of these
+		 * parts, with `Content-Type` and `Content-Length` set to match. Replaces
+		 * `body`.
+		 *
+		 *       const file = await Deno.open("report.csv");
+		 *       await fetch(url, {
+		 *         method: "POST",
+		 *         multipart: [
+		 *           { name: "title", value: "Report" },
+		 *           { name: "upload", rid: file.rid, filename: "report.csv" }
+		 *         ]
+		 *       });
+		 */
+		multipart?: MultipartPart[];
+	}
+
+	export interface MultipartPart {
+		name: string;
+		/** Content of a plain field. */
+		value?: string;
+		/** A file opened for reading, sent from its current position to its end
+		 * without being read into memory first. */
+		rid?: number;
+		filename?: string;
+		/** Defaults to `application/octet-stream` for parts with a filename. */
+		contentType?: string;
 	}
 	export interface ResponseInit {
 		headers?: HeadersInit;
@@ -2402,6 +3379,29 @@ declare namespace __event {
 	}
 }
 
+declare namespace __abortController {
+	// @url js/abort_controller.d.ts
+
+	export class AbortSignal
+		extends __eventTarget.EventTarget
+		implements __domTypes.AbortSignal
+	{
+		private _aborted;
+		onabort:
+			| ((this: __domTypes.AbortSignal, ev: __domTypes.Event) => any)
+			| null;
+		readonly aborted: boolean;
+		readonly [Symbol.toStringTag]: string;
+	}
+	export class AbortController {
+		readonly signal: AbortSignal;
+		/** Aborts the ops started with `signal`. Their promises reject with
+		 * `ErrorKind.Aborted`. */
+		abort(): void;
+		readonly [Symbol.toStringTag]: string;
+	}
+}
+
 declare namespace __customEvent {
 	// @url js/custom_event.d.ts
 
@@ -2565,10 +3565,10 @@ declare namespace __io {
 		 * any positive offset is legal, but the behavior of subsequent I/O operations
 		 * on the underlying object is implementation-dependent.
 		 */
-		seek(offset: number, whence: SeekMode): Promise<void>;
+		seek(offset: number, whence: SeekMode): Promise<number>;
 	}
 	export interface SyncSeeker {
-		seekSync(offset: number, whence: SeekMode): void;
+		seekSync(offset: number, whence: SeekMode): number;
 	}
 	export interface ReadCloser extends Reader, Closer {}
 	export interface WriteCloser extends Writer, Closer {}
@@ -2606,6 +3606,8 @@ declare namespace __fetch {
 		bodyUsed: boolean;
 		private _bodyPromise;
 		private _data;
+		/** Settles once the body has been read to the end, or closed before. */
+		readonly trailer: Promise<__domTypes.Headers>;
 		readonly locked: boolean;
 		readonly body: null | Body;
 		constructor(rid: number, contentType: string);
@@ -2629,6 +3631,10 @@ declare namespace __fetch {
 		readonly type = "basic";
 		readonly redirected: boolean;
 		headers: __domTypes.Headers;
+		/** Deno extension. The headers as received, with repeated ones kept as
+		 * separate entries. Names are lower case, and a repeated header follows
+		 * the first one of the same name. */
+		readonly rawHeaders: Array<[string, string]>;
 		readonly trailer: Promise<__domTypes.Headers>;
 		bodyUsed: boolean;
 		readonly body: Body;
@@ -2858,39 +3864,76 @@ declare namespace __url {
 declare namespace __workers {
 	// @url js/workers.d.ts
 
-	export function encodeMessage(data: any): Uint8Array;
+	/** Encodes `data` as a worker message. The contents of the buffers in
+	 * `transfer` are moved to the receiver without copying, and the buffers
+	 * are detached, as with `postMessage` in web Workers. */
+	export function encodeMessage(
+		data: any,
+		transfer?: ArrayBuffer[],
+	): Uint8Array;
 	export function decodeMessage(dataIntArray: Uint8Array): any;
 	export let onmessage: (e: { data: any }) => void;
-	export function postMessage(data: any): void;
+	export function postMessage(data: any, transfer?: ArrayBuffer[]): void;
+	/** Posts a message to the worker created with `{ name }`, from any module.
+	 * Its replies go to the worker that created it, as usual. Throws a
+	 * `WouldBlock` error if the worker has too many messages queued. */
+	export function postMessageToWorker(
+		name: string,
+		data: any,
+		transfer?: ArrayBuffer[],
+	): void;
+	/** Returns the names of the live workers created with `{ name }`. */
+	export function namedWorkers(): string[];
 	export function getMessage(): Promise<any>;
 	export let isClosing: boolean;
 	export function workerClose(): void;
 	export function workerMain(): Promise<void>;
 	export interface Worker {
-		onerror?: () => void;
+		onerror?: (e: Error) => void;
 		onmessage?: (e: { data: any }) => void;
 		onmessageerror?: () => void;
-		postMessage(data: any): void;
+		postMessage(data: any, transfer?: ArrayBuffer[]): void;
 		closed: Promise<void>;
 	}
 	export interface WorkerOptions {}
+	/** Permissions a worker keeps when created with reduced permissions. Each
+	 * one is kept only if the creating worker has it, the others are denied.
+	 */
+	export interface WorkerPermissions {
+		read?: boolean;
+		write?: boolean;
+		net?: boolean;
+		env?: boolean;
+		run?: boolean;
+		plugin?: boolean;
+		hrtime?: boolean;
+		ping?: boolean;
+	}
 	/** Extended Deno Worker initialization options.
 	 * `noDenoNamespace` hides global `window.Deno` namespace for
 	 * spawned worker and nested workers spawned by it (default: false).
+	 * `name` registers the worker so that any module can message it with
+	 * `Deno.postMessageToWorker()`. Names must be unique among live workers.
+	 * `permissions` gives the worker its own, reduced set of permissions
+	 * instead of sharing the creating worker's, e.g.
+	 *
+	 *       new Worker("./parser.ts", { permissions: { net: true } });
 	 */
 	export interface DenoWorkerOptions extends WorkerOptions {
 		noDenoNamespace?: boolean;
+		name?: string;
+		permissions?: WorkerPermissions;
 	}
 	export class WorkerImpl implements Worker {
 		private readonly id;
 		private isClosing;
 		private readonly isClosedPromise;
-		onerror?: () => void;
+		onerror?: (e: Error) => void;
 		onmessage?: (data: any) => void;
 		onmessageerror?: () => void;
 		constructor(specifier: string, options?: DenoWorkerOptions);
 		readonly closed: Promise<void>;
-		postMessage(data: any): void;
+		postMessage(data: any, transfer?: ArrayBuffer[]): void;
 		private run;
 	}
 }
@@ -2901,7 +3944,8 @@ declare namespace __performanceUtil {
 	export class Performance {
 		/** Returns a current time from Deno's start in milliseconds.
 		 *
-		 * Use the flag --allow-hrtime return a precise value.
+		 * The value comes from a monotonic clock, so it is not affected by changes
+		 * to the system time. Use the flag --allow-hrtime return a precise value.
 		 *
 		 *       const t = performance.now();
 		 *       console.log(`${t} ms since start!`);
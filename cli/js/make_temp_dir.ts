@@ -2,18 +2,20 @@
 import { sendAsync, sendSync } from "./dispatch_json.ts";
 import * as dispatch from "./dispatch.ts";
 
-export interface MakeTempDirOptions {
+export interface MakeTempOptions {
 	dir?: string;
 	prefix?: string;
 	suffix?: string;
 }
 
+export type MakeTempDirOptions = MakeTempOptions;
+
 /** makeTempDirSync is the synchronous version of `makeTempDir`.
  *
  *       const tempDirName0 = Deno.makeTempDirSync();
  *       const tempDirName1 = Deno.makeTempDirSync({ prefix: 'my_temp' });
  */
-export function makeTempDirSync(options: MakeTempDirOptions = {}): string {
+export function makeTempDirSync(options: MakeTempOptions = {}): string {
 	return sendSync(dispatch.OP_MAKE_TEMP_DIR, options);
 }
 
@@ -23,13 +25,41 @@ export function makeTempDirSync(options: MakeTempDirOptions = {}): string {
  * If `dir` is unspecified, tempDir uses the default directory for temporary
  * files. Multiple programs calling tempDir simultaneously will not choose the
  * same directory. It is the caller's responsibility to remove the directory
- * when no longer needed.
+ * when no longer needed. On Unix the directory is created with mode 0o700.
+ * Requires write permission for the directory it is created in.
  *
  *       const tempDirName0 = await Deno.makeTempDir();
  *       const tempDirName1 = await Deno.makeTempDir({ prefix: 'my_temp' });
  */
 export async function makeTempDir(
-	options: MakeTempDirOptions = {},
+	options: MakeTempOptions = {},
 ): Promise<string> {
 	return await sendAsync(dispatch.OP_MAKE_TEMP_DIR, options);
 }
+
+/** makeTempFileSync is the synchronous version of `makeTempFile`.
+ *
+ *       const tempFileName0 = Deno.makeTempFileSync();
+ *       const tempFileName1 = Deno.makeTempFileSync({ suffix: '.json' });
+ */
+export function makeTempFileSync(options: MakeTempOptions = {}): string {
+	return sendSync(dispatch.OP_MAKE_TEMP_FILE, options);
+}
+
+/** makeTempFile creates a new empty file in the directory `dir`, its name
+ * beginning with `prefix` and ending with `suffix`, and returns its full path.
+ * If `dir` is unspecified, the default directory for temporary files is used.
+ * The file is created in the same step that picks its name, so another
+ * process can't claim or replace it in between. On Unix it is created with
+ * mode 0o600. It is the caller's responsibility to remove the file when no
+ * longer needed. Requires write permission for the directory it is created
+ * in.
+ *
+ *       const tempFileName0 = await Deno.makeTempFile();
+ *       const tempFileName1 = await Deno.makeTempFile({ suffix: '.json' });
+ */
+export async function makeTempFile(
+	options: MakeTempOptions = {},
+): Promise<string> {
+	return await sendAsync(dispatch.OP_MAKE_TEMP_FILE, options);
+}
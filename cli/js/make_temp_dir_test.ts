@@ -64,3 +64,53 @@ testPerm({ write: true }, async function makeTempDirSuccess(): Promise<void> {
 	assertEquals(err.kind, Deno.ErrorKind.NotFound);
 	assertEquals(err.name, "NotFound");
 });
+
+testPerm(
+	{ read: true, write: true },
+	function makeTempFileSyncSuccess(): void {
+		const file1 = Deno.makeTempFileSync({ prefix: "hello", suffix: ".txt" });
+		const file2 = Deno.makeTempFileSync({ prefix: "hello", suffix: ".txt" });
+		assert(file1 !== file2);
+		for (const file of [file1, file2]) {
+			const lastPart = file.replace(/^.*[\\\/]/, "");
+			assert(lastPart.startsWith("hello"));
+			assert(lastPart.endsWith(".txt"));
+			const info = Deno.statSync(file);
+			assert(info.isFile());
+			assertEquals(info.len, 0);
+			if (Deno.build.os !== "win") {
+				assertEquals(info.mode! & 0o777, 0o600);
+			}
+		}
+		const dir = Deno.makeTempDirSync();
+		if (Deno.build.os !== "win") {
+			assertEquals(Deno.statSync(dir).mode! & 0o777, 0o700);
+		}
+		const file3 = Deno.makeTempFileSync({ dir });
+		assert(file3.startsWith(dir));
+	},
+);
+
+test(function makeTempFileSyncPerm(): void {
+	let err;
+	try {
+		Deno.makeTempFileSync();
+	} catch (err_) {
+		err = err_;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.PermissionDenied);
+});
+
+testPerm({ write: true }, async function makeTempFileSuccess(): Promise<
+	void
+> {
+	const file = await Deno.makeTempFile({ prefix: "hello" });
+	assert(file.replace(/^.*[\\\/]/, "").startsWith("hello"));
+	let err;
+	try {
+		await Deno.makeTempFile({ dir: "/baddir" });
+	} catch (err_) {
+		err = err_;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.NotFound);
+});
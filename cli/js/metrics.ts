@@ -8,6 +8,8 @@ export interface Metrics {
 	bytesSentControl: number;
 	bytesSentData: number;
 	bytesReceived: number;
+	/** Async ops dispatched and not completed yet. */
+	opsPending: number;
 }
 
 /** Receive metrics from the privileged side of Deno.
@@ -21,6 +23,7 @@ export interface Metrics {
  *      │ bytesSentControl │  504   │
  *      │  bytesSentData   │   0    │
  *      │  bytesReceived   │  856   │
+ *      │    opsPending    │   0    │
  *      └──────────────────┴────────┘
  */
 export function metrics(): Metrics {
@@ -8,6 +8,7 @@ test(async function metrics(): Promise<void> {
 	assert(m1.bytesSentControl > 0);
 	assert(m1.bytesSentData >= 0);
 	assert(m1.bytesReceived > 0);
+	assert(m1.opsPending >= 0);
 
 	// Write to stdout to ensure a "data" message gets sent instead of just
 	// control messages.
@@ -44,3 +45,9 @@ testPerm(
 		assert(metrics.opsDispatched === metrics.opsCompleted);
 	},
 );
+
+test(async function metricsOpsPending(): Promise<void> {
+	const timeout = new Promise((resolve): number => setTimeout(resolve, 10));
+	assert(Deno.metrics().opsPending > 0);
+	await timeout;
+});
@@ -1,11 +1,12 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { AbortSignal, withSignal } from "./abort_controller.ts";
 import { sendAsync, sendSync } from "./dispatch_json.ts";
 import * as dispatch from "./dispatch.ts";
 import { close, read, write } from "./files.ts";
 import { Closer, EOF, Reader, Writer } from "./io.ts";
 import { notImplemented } from "./util.ts";
 
-export type Transport = "tcp";
+export type Transport = "tcp" | "unix";
 // TODO support other types:
 // export type Transport = "tcp" | "tcp4" | "tcp6" | "unix" | "unixpacket";
 
@@ -32,15 +33,21 @@ export interface Listener extends AsyncIterator<Conn> {
 	[Symbol.asyncIterator](): AsyncIterator<Conn>;
 }
 
-enum ShutdownMode {
+export enum ShutdownMode {
 	// See http://man7.org/linux/man-pages/man2/shutdown.2.html
 	// Corresponding to SHUT_RD, SHUT_WR, SHUT_RDWR
 	Read = 0,
 	Write,
-	ReadWrite, // unused
+	ReadWrite,
 }
 
-function shutdown(rid: number, how: ShutdownMode): void {
+/** Shuts down (`shutdown(2)`) reading, writing or both directions of a TCP
+ * or Unix connection without closing `rid`. After shutting down writing the
+ * peer reads EOF, while whatever it still sends can be read.
+ *
+ *       Deno.shutdown(conn.rid, Deno.ShutdownMode.Write);
+ */
+export function shutdown(rid: number, how: ShutdownMode): void {
 	sendSync(dispatch.OP_SHUTDOWN, { rid, how });
 }
 
@@ -76,6 +83,52 @@ export class ConnImpl implements Conn {
 	closeWrite(): void {
 		shutdown(this.rid, ShutdownMode.Write);
 	}
+
+	setNoDelay(noDelay: boolean): void {
+		sendSync(dispatch.OP_SET_TCP_OPTIONS, { rid: this.rid, noDelay });
+	}
+
+	setKeepAlive(interval: number): void {
+		sendSync(dispatch.OP_SET_TCP_OPTIONS, {
+			rid: this.rid,
+			keepAlive: interval,
+		});
+	}
+
+	tcpOptions(): TcpOptions {
+		return sendSync(dispatch.OP_TCP_OPTIONS, { rid: this.rid });
+	}
+
+	split(): [ConnReadHalf, ConnWriteHalf] {
+		const { readRid, writeRid } = sendSync(dispatch.OP_SPLIT, {
+			rid: this.rid,
+		});
+		return [new ConnReadHalfImpl(readRid), new ConnWriteHalfImpl(writeRid)];
+	}
+}
+
+class ConnReadHalfImpl implements ConnReadHalf {
+	constructor(readonly rid: number) {}
+
+	read(p: Uint8Array): Promise<number | EOF> {
+		return read(this.rid, p);
+	}
+
+	close(): void {
+		close(this.rid);
+	}
+}
+
+class ConnWriteHalfImpl implements ConnWriteHalf {
+	constructor(readonly rid: number) {}
+
+	write(p: Uint8Array): Promise<number> {
+		return write(this.rid, p);
+	}
+
+	close(): void {
+		close(this.rid);
+	}
 }
 
 export class ListenerImpl implements Listener {
@@ -87,7 +140,11 @@ export class ListenerImpl implements Listener {
 	) {}
 
 	async accept(): Promise<Conn> {
-		const res = await sendAsync(dispatch.OP_ACCEPT, { rid: this.rid });
+		const opId =
+			this.transport === "unix"
+				? dispatch.OP_UNIX_ACCEPT
+				: dispatch.OP_ACCEPT;
+		const res = await sendAsync(opId, { rid: this.rid });
 		return new ConnImpl(res.rid, res.remoteAddr, res.localAddr);
 	}
 
@@ -140,12 +197,72 @@ export interface Conn extends Reader, Writer, Closer {
 	 * callers should just use `close()`.
 	 */
 	closeWrite(): void;
+	/** Enables or disables Nagle's algorithm (`TCP_NODELAY`). Only valid on
+	 * plain TCP connections.
+	 */
+	setNoDelay(noDelay: boolean): void;
+	/** Enables `SO_KEEPALIVE` with the given interval in milliseconds, or
+	 * disables it when `interval` is 0. Only valid on plain TCP connections.
+	 */
+	setKeepAlive(interval: number): void;
+	/** Returns the socket options currently applied to the connection. */
+	tcpOptions(): TcpOptions;
+	/** Splits the connection into a readable and a writable half with rids of
+	 * their own, so that a read doesn't wait for a pending write or the other
+	 * way round. The connection's own rid is closed. Closing the writable half
+	 * shuts down the writing side while the readable half keeps receiving. Only
+	 * valid on plain TCP and Unix connections.
+	 *
+	 *       const [reader, writer] = conn.split();
+	 */
+	split(): [ConnReadHalf, ConnWriteHalf];
+}
+
+/** The readable half of a connection, see `Conn.split()`. */
+export interface ConnReadHalf extends Reader, Closer {
+	readonly rid: number;
+}
+
+/** The writable half of a connection, see `Conn.split()`. */
+export interface ConnWriteHalf extends Writer, Closer {
+	readonly rid: number;
+}
+
+export interface TcpOptions {
+	noDelay: boolean;
+	/** Keepalive interval in milliseconds, or `null` if keepalive is off. */
+	keepAlive: number | null;
 }
 
 export interface ListenOptions {
 	port: number;
 	hostname?: string;
 	transport?: Transport;
+	/** Applied to every accepted connection, see `Conn.setNoDelay()`. */
+	noDelay?: boolean;
+	/** Applied to every accepted connection, see `Conn.setKeepAlive()`. */
+	keepAlive?: number;
+	/** Whether the address can be bound again while connections of a closed
+	 * listener linger in `TIME_WAIT`, e.g. when restarting a server. Defaults
+	 * to true. This and the options below are not supported on Windows.
+	 */
+	reuseAddress?: boolean;
+	/** Lets several listeners bind the same address and port, with incoming
+	 * connections spread among them, e.g. to start a new server before
+	 * stopping the old one. Any process running as the same user can then
+	 * bind the port too and receive part of the connections, so only use it
+	 * where all of that user's processes are trusted. Defaults to false.
+	 */
+	reusePort?: boolean;
+	/** Maximum number of connections waiting to be accepted. The OS may
+	 * lower it. Defaults to 1024.
+	 */
+	backlog?: number;
+	/** Whether a listener on an IPv6 address such as `::` refuses IPv4
+	 * connections. With false it accepts both, IPv4 clients showing up with
+	 * mapped addresses like `::ffff:192.0.2.1`. Defaults to the OS setting.
+	 */
+	v6Only?: boolean;
 }
 
 /** Listen announces on the local transport address.
@@ -163,6 +280,7 @@ export interface ListenOptions {
  *     listen({ port: 80 })
  *     listen({ hostname: "192.0.2.1", port: 80 })
  *     listen({ hostname: "[2001:db8::1]", port: 80 });
+ *     listen({ hostname: "::", port: 80, v6Only: false });
  *     listen({ hostname: "golang.org", port: 80, transport: "tcp" })
  */
 export function listen(options: ListenOptions): Listener {
@@ -172,6 +290,12 @@ export function listen(options: ListenOptions): Listener {
 		hostname,
 		port: options.port,
 		transport,
+		noDelay: options.noDelay,
+		keepAlive: options.keepAlive,
+		reuseAddress: options.reuseAddress,
+		reusePort: options.reusePort,
+		backlog: options.backlog,
+		v6Only: options.v6Only,
 	});
 	return new ListenerImpl(res.rid, transport, res.localAddr);
 }
@@ -180,6 +304,18 @@ export interface DialOptions {
 	port: number;
 	hostname?: string;
 	transport?: Transport;
+	/** See `Conn.setNoDelay()`. Defaults to off. */
+	noDelay?: boolean;
+	/** See `Conn.setKeepAlive()`. Defaults to the OS setting. */
+	keepAlive?: number;
+	/** Aborts connecting, the promise then rejects with `ErrorKind.Aborted`. */
+	signal?: AbortSignal;
+}
+
+interface DialResponse {
+	rid: number;
+	localAddr: string;
+	remoteAddr: string;
 }
 
 /** Dial connects to the address on the named transport.
@@ -197,17 +333,149 @@ export interface DialOptions {
  *     dial({ port: 80 })
  *     dial({ hostname: "192.0.2.1", port: 80 })
  *     dial({ hostname: "[2001:db8::1]", port: 80 });
+ *     dial({ hostname: "fe80::1%eth0", port: 80 });
  *     dial({ hostname: "golang.org", port: 80, transport: "tcp" })
  */
 export async function dial(options: DialOptions): Promise<Conn> {
-	const res = await sendAsync(dispatch.OP_DIAL, {
-		hostname: options.hostname || "127.0.0.1",
-		port: options.port,
-		transport: options.transport || "tcp",
-	});
+	const res = await withSignal(
+		options.signal,
+		async (cancelRid?: number): Promise<DialResponse> =>
+			await sendAsync(dispatch.OP_DIAL, {
+				hostname: options.hostname || "127.0.0.1",
+				port: options.port,
+				transport: options.transport || "tcp",
+				noDelay: options.noDelay,
+				keepAlive: options.keepAlive,
+				cancelRid,
+			}),
+	);
 	return new ConnImpl(res.rid, res.remoteAddr!, res.localAddr!);
 }
 
+/** The address of a TCP or UDP socket, see `Deno.localAddr()`. */
+export interface NetAddr {
+	transport: "tcp" | "udp";
+	hostname: string;
+	port: number;
+	family: "ipv4" | "ipv6";
+}
+
+/** The address of a Unix domain socket, see `Deno.localAddr()`. */
+export interface UnixAddr {
+	transport: "unix";
+	/** `null` for unnamed sockets, e.g. the dialing end of a connection. */
+	path: string | null;
+}
+
+/** Returns the address the socket behind `rid` is bound to. Works for
+ * connections, listeners and datagram sockets.
+ *
+ *       const { hostname, port } = Deno.localAddr(listener.rid) as Deno.NetAddr;
+ */
+export function localAddr(rid: number): NetAddr | UnixAddr {
+	return sendSync(dispatch.OP_LOCAL_ADDR, { rid });
+}
+
+/** Returns the address the connection behind `rid` is connected to, e.g.
+ * to log or authorize clients of a server.
+ *
+ *       const { hostname } = Deno.peerAddr(conn.rid) as Deno.NetAddr;
+ */
+export function peerAddr(rid: number): NetAddr | UnixAddr {
+	return sendSync(dispatch.OP_PEER_ADDR, { rid });
+}
+
+export interface UnixListenOptions {
+	/** Maximum number of connections waiting to be accepted. The OS may
+	 * lower it. Defaults to 128.
+	 */
+	backlog?: number;
+}
+
+/** Listens on a Unix domain socket at `path`. The socket file is removed
+ * when the listener is closed. Requires `--allow-read` and `--allow-write`
+ * for `path`. Not available on Windows.
+ *
+ *     const listener = listenUnix("/tmp/app.sock");
+ */
+export function listenUnix(
+	path: string,
+	options: UnixListenOptions = {},
+): Listener {
+	const res = sendSync(dispatch.OP_UNIX_LISTEN, {
+		path,
+		backlog: options.backlog,
+	});
+	return new ListenerImpl(res.rid, "unix", res.localAddr);
+}
+
+/** Connects to the Unix domain socket at `path`. Requires `--allow-read` and
+ * `--allow-write` for `path`. Not available on Windows.
+ */
+export async function dialUnix(path: string): Promise<Conn> {
+	const res = await sendAsync(dispatch.OP_UNIX_DIAL, { path });
+	return new ConnImpl(res.rid, res.remoteAddr, res.localAddr);
+}
+
+export interface DnsRecord {
+	type: "A" | "AAAA";
+	address: string;
+}
+
+export interface ResolveDnsOptions {
+	/** Only return records of this address family. */
+	family?: "ipv4" | "ipv6";
+}
+
+/** Looks up the addresses of `hostname`. Names given to `--host-override`
+ * resolve to the overriding addresses. Requires `allow-net` for `hostname`.
+ *
+ *     const records = await resolveDns("deno.land", { family: "ipv4" });
+ */
+export async function resolveDns(
+	hostname: string,
+	options: ResolveDnsOptions = {},
+): Promise<DnsRecord[]> {
+	return await sendAsync(dispatch.OP_RESOLVE_DNS, {
+		hostname,
+		family: options.family,
+	});
+}
+
+export interface PingOptions {
+	/** Number of echo requests, sent one after another. Defaults to 1. */
+	count?: number;
+	/** Milliseconds to wait for each reply. Defaults to 1000. */
+	timeout?: number;
+}
+
+export interface PingResult {
+	/** The address `hostname` resolved to. */
+	address: string;
+	/** Round-trip time of each request in milliseconds, `null` where no reply
+	 * came in time.
+	 */
+	rtts: Array<number | null>;
+}
+
+/** Sends ICMP echo requests to `hostname`. Requires `--allow-ping`, and the
+ * process needs root or `CAP_NET_RAW`, or on Linux a group listed in
+ * `net.ipv4.ping_group_range`, or it throws `PermissionDenied`. Not available
+ * on Windows.
+ *
+ *     const { rtts } = await ping("192.0.2.1", { count: 3 });
+ */
+export async function ping(
+	hostname: string,
+	options: PingOptions = {},
+): Promise<PingResult> {
+	return await sendAsync(dispatch.OP_PING, {
+		hostname,
+		count: options.count,
+		timeout: options.timeout,
+	});
+}
+
 /** **RESERVED** */
 export async function connect(
 	_transport: Transport,
@@ -215,3 +483,87 @@ export async function connect(
 ): Promise<Conn> {
 	return notImplemented();
 }
+
+/** The address of one end of a datagram socket. */
+export interface DatagramAddr {
+	hostname: string;
+	port: number;
+}
+
+export interface DatagramReceiveResult {
+	/** Number of bytes copied into the buffer. */
+	nread: number;
+	/** True if the datagram didn't fit into the buffer and the rest of it was
+	 * discarded. */
+	truncated: boolean;
+	/** Where the datagram came from. */
+	remoteAddr: DatagramAddr;
+}
+
+/** A bound UDP socket. */
+export interface DatagramConn extends Closer {
+	/** The resource ID of the socket. */
+	readonly rid: number;
+	/** The address the socket is bound to. When bound to port 0 this holds
+	 * the port picked by the operating system. */
+	readonly localAddr: DatagramAddr;
+	/** Sends `p` as a single datagram to `addr`. */
+	send(p: Uint8Array, addr: DatagramAddr): Promise<number>;
+	/** Waits for the next datagram and copies it into `p`. */
+	receive(p: Uint8Array): Promise<DatagramReceiveResult>;
+}
+
+class DatagramConnImpl implements DatagramConn {
+	constructor(
+		readonly rid: number,
+		readonly localAddr: DatagramAddr,
+	) {}
+
+	async send(p: Uint8Array, addr: DatagramAddr): Promise<number> {
+		const res = await sendAsync(
+			dispatch.OP_UDP_SEND_TO,
+			{ rid: this.rid, hostname: addr.hostname, port: addr.port },
+			p,
+		);
+		return res.nwritten;
+	}
+
+	async receive(p: Uint8Array): Promise<DatagramReceiveResult> {
+		const res = await sendAsync(
+			dispatch.OP_UDP_RECV_FROM,
+			{ rid: this.rid },
+			p,
+		);
+		return {
+			nread: res.nread,
+			truncated: res.truncated,
+			remoteAddr: { hostname: res.hostname, port: res.port },
+		};
+	}
+
+	close(): void {
+		close(this.rid);
+	}
+}
+
+export interface ListenDatagramOptions {
+	port: number;
+	hostname?: string;
+}
+
+/** Binds a UDP socket. Requires the `--allow-net` permission.
+ *
+ *     const conn = listenDatagram({ port: 0 });
+ *     await conn.send(data, { hostname: "127.0.0.1", port: 53 });
+ *     const { nread, remoteAddr } = await conn.receive(buf);
+ */
+export function listenDatagram(options: ListenDatagramOptions): DatagramConn {
+	const res = sendSync(dispatch.OP_UDP_BIND, {
+		hostname: options.hostname || "0.0.0.0",
+		port: options.port,
+	});
+	return new DatagramConnImpl(res.rid, {
+		hostname: res.hostname,
+		port: res.port,
+	});
+}
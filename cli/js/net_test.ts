@@ -1,5 +1,5 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
-import { assert, assertEquals, testPerm } from "./test_util.ts";
+import { assert, assertEquals, test, testPerm } from "./test_util.ts";
 
 testPerm({ net: true }, function netListenClose(): void {
 	const listener = Deno.listen({ hostname: "127.0.0.1", port: 4500 });
@@ -75,6 +75,153 @@ testPerm({ net: true }, async function netDialListen(): Promise<void> {
 	conn.close();
 });
 
+testPerm({ net: true }, async function netSplitEcho(): Promise<void> {
+	const listener = Deno.listen({ port: 4500 });
+	const echoed = listener.accept().then(async (conn): Promise<void> => {
+		const [reader, writer] = conn.split();
+		assertEquals(Deno.resources()[reader.rid], "tcpReadHalf");
+		assertEquals(Deno.resources()[writer.rid], "tcpWriteHalf");
+		await Deno.copy(writer, reader);
+		reader.close();
+		writer.close();
+	});
+	const conn = await Deno.dial({ port: 4500 });
+	const [reader, writer] = conn.split();
+	assertEquals(Deno.resources()[conn.rid], undefined);
+
+	// Reads and writes are pending at the same time.
+	const received = Deno.readAll(reader);
+	for (let i = 0; i < 3; i++) {
+		await writer.write(new Uint8Array([i, i, i]));
+	}
+	// Sends FIN, the echoes keep arriving on the read half.
+	writer.close();
+	assertEquals(await received, new Uint8Array([0, 0, 0, 1, 1, 1, 2, 2, 2]));
+	await echoed;
+	reader.close();
+	listener.close();
+});
+
+testPerm({ net: true }, async function netListenReusePort(): Promise<void> {
+	if (Deno.build.os === "win") {
+		return;
+	}
+	const options = { port: 4500, reusePort: true, backlog: 16 };
+	const listener1 = Deno.listen(options);
+	const listener2 = Deno.listen(options);
+	const conn = await Deno.dial({ port: 4500 });
+	conn.close();
+	listener1.close();
+	listener2.close();
+
+	// Without the option the port can't be shared.
+	const listener3 = Deno.listen({ port: 4500, reusePort: false });
+	let err;
+	try {
+		Deno.listen({ port: 4500 });
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.AddrInUse);
+	listener3.close();
+});
+
+testPerm(
+	{ net: true },
+	async function netCloseWriteReadsTrailing(): Promise<void> {
+		const listener = Deno.listen({ port: 4500 });
+		listener.accept().then(async (conn): Promise<void> => {
+			// Answers once the whole request has been read.
+			const request = await Deno.readAll(conn);
+			await conn.write(request.map((b): number => b * 2));
+			conn.close();
+		});
+		const conn = await Deno.dial({ port: 4500 });
+		await conn.write(new Uint8Array([1, 2, 3]));
+		Deno.shutdown(conn.rid, Deno.ShutdownMode.Write);
+		assertEquals(await Deno.readAll(conn), new Uint8Array([2, 4, 6]));
+
+		let err;
+		try {
+			Deno.shutdown(conn.rid, 3);
+		} catch (e) {
+			err = e;
+		}
+		assertEquals(err.kind, Deno.ErrorKind.InvalidInput);
+		listener.close();
+		conn.close();
+	},
+);
+
+testPerm({ net: true }, async function netSocketAddrs(): Promise<void> {
+	const listener = Deno.listen({ hostname: "127.0.0.1", port: 4500 });
+	const accepted = listener.accept();
+	const conn = await Deno.dial({ hostname: "127.0.0.1", port: 4500 });
+	const serverConn = await accepted;
+
+	assertEquals(Deno.localAddr(listener.rid), {
+		transport: "tcp",
+		hostname: "127.0.0.1",
+		port: 4500,
+		family: "ipv4",
+	});
+	assertEquals(Deno.peerAddr(conn.rid), Deno.localAddr(listener.rid));
+	assertEquals(Deno.peerAddr(serverConn.rid), Deno.localAddr(conn.rid));
+	const { port } = Deno.localAddr(conn.rid) as Deno.NetAddr;
+	assertEquals(conn.localAddr, `127.0.0.1:${port}`);
+
+	let err;
+	try {
+		Deno.peerAddr(listener.rid);
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.BadResource);
+	serverConn.close();
+	conn.close();
+	listener.close();
+});
+
+testPerm({ net: true }, async function netListenDualStack(): Promise<void> {
+	if (Deno.build.os === "win") {
+		return;
+	}
+	const listener = Deno.listen({ hostname: "::", port: 4500, v6Only: false });
+	for (const hostname of ["127.0.0.1", "::1"]) {
+		const accepted = listener.accept();
+		const conn = await Deno.dial({ hostname, port: 4500 });
+		const serverConn = await accepted;
+		const peer = Deno.peerAddr(serverConn.rid) as Deno.NetAddr;
+		assertEquals(peer.family, "ipv6");
+		const mapped = hostname === "::1" ? "::1" : "::ffff:127.0.0.1";
+		assertEquals(peer.hostname, mapped);
+		serverConn.close();
+		conn.close();
+	}
+	listener.close();
+});
+
+testPerm({ net: true }, async function netTcpOptions(): Promise<void> {
+	const listener = Deno.listen({ port: 4500, noDelay: true });
+	const accepted = listener.accept();
+	const conn = await Deno.dial({ port: 4500 });
+	assertEquals(conn.tcpOptions().noDelay, false);
+	conn.setNoDelay(true);
+	conn.setKeepAlive(60000);
+	const options = conn.tcpOptions();
+	assertEquals(options.noDelay, true);
+	assert(options.keepAlive != null);
+	conn.setKeepAlive(0);
+	assertEquals(conn.tcpOptions().keepAlive, null);
+
+	const serverConn = await accepted;
+	assertEquals(serverConn.tcpOptions().noDelay, true);
+
+	serverConn.close();
+	conn.close();
+	listener.close();
+});
+
 testPerm(
 	{ net: true },
 	async function netListenCloseWhileIterating(): Promise<void> {
@@ -238,3 +385,150 @@ testPerm({ net: true }, async function netDoubleCloseWrite() {
   conn.close();
 });
 */
+
+testPerm({ net: true }, async function netDatagramSendReceive(): Promise<void> {
+	const alice = Deno.listenDatagram({ hostname: "127.0.0.1", port: 0 });
+	const bob = Deno.listenDatagram({ hostname: "127.0.0.1", port: 0 });
+	assert(alice.localAddr.port > 0);
+	assert(alice.localAddr.port !== bob.localAddr.port);
+
+	const sent = new Uint8Array([1, 2, 3]);
+	const nwritten = await alice.send(sent, bob.localAddr);
+	assertEquals(nwritten, 3);
+
+	const buf = new Uint8Array(16);
+	const { nread, truncated, remoteAddr } = await bob.receive(buf);
+	assertEquals(nread, 3);
+	assertEquals(truncated, false);
+	assertEquals(buf.subarray(0, nread), sent);
+	assertEquals(remoteAddr, alice.localAddr);
+
+	alice.close();
+	bob.close();
+});
+
+testPerm({ net: true }, async function netDatagramTruncated(): Promise<void> {
+	const alice = Deno.listenDatagram({ hostname: "127.0.0.1", port: 0 });
+	const bob = Deno.listenDatagram({ hostname: "127.0.0.1", port: 0 });
+
+	await alice.send(new Uint8Array([1, 2, 3, 4, 5]), bob.localAddr);
+	const buf = new Uint8Array(2);
+	const { nread, truncated } = await bob.receive(buf);
+	assertEquals(nread, 2);
+	assertEquals(truncated, true);
+	assertEquals(buf, new Uint8Array([1, 2]));
+
+	alice.close();
+	bob.close();
+});
+
+test(function netDatagramPerm(): void {
+	let err;
+	try {
+		Deno.listenDatagram({ port: 0 });
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.PermissionDenied);
+});
+
+testPerm(
+	{ read: true, write: true },
+	async function netUnixEcho(): Promise<void> {
+		if (Deno.build.os === "win") {
+			return;
+		}
+		const dir = Deno.makeTempDirSync();
+		const path = `${dir}/echo.sock`;
+		const listener = Deno.listenUnix(path);
+		assertEquals(listener.addr().transport, "unix");
+		assertEquals(listener.addr().address, path);
+
+		const served = listener.accept().then(
+			async (conn): Promise<string> => {
+				const details = Deno.resources({ details: true })[conn.rid];
+				const buf = new Uint8Array(16);
+				const nread = await conn.read(buf);
+				await conn.write(buf.subarray(0, nread as number));
+				conn.close();
+				return details;
+			},
+		);
+
+		const conn = await Deno.dialUnix(path);
+		assertEquals(conn.remoteAddr, path);
+		const details = Deno.resources({ details: true })[conn.rid];
+		assertEquals(details, `unixStream:${path}`);
+		assertEquals(Deno.peerAddr(conn.rid), { transport: "unix", path });
+		assertEquals(Deno.localAddr(conn.rid), { transport: "unix", path: null });
+		await conn.write(new Uint8Array([1, 2, 3]));
+		const buf = new Uint8Array(16);
+		const nread = await conn.read(buf);
+		assertEquals(nread, 3);
+		assertEquals(buf.subarray(0, 3), new Uint8Array([1, 2, 3]));
+		conn.close();
+		assertEquals(await served, `unixStream:${path}`);
+
+		listener.close();
+		let err;
+		try {
+			Deno.statSync(path);
+		} catch (e) {
+			err = e;
+		}
+		assertEquals(err.kind, Deno.ErrorKind.NotFound);
+		Deno.removeSync(dir, { recursive: true });
+	},
+);
+
+testPerm({ net: true }, async function netResolveDns(): Promise<void> {
+	const records = await Deno.resolveDns("127.0.0.1");
+	assertEquals(records, [{ type: "A", address: "127.0.0.1" }]);
+
+	const v6 = await Deno.resolveDns("[::1]", { family: "ipv6" });
+	assertEquals(v6, [{ type: "AAAA", address: "::1" }]);
+	const none = await Deno.resolveDns("[::1]", { family: "ipv4" });
+	assertEquals(none, []);
+
+	const localhost = await Deno.resolveDns("localhost");
+	assert(localhost.length > 0);
+});
+
+test(async function netResolveDnsPerm(): Promise<void> {
+	let err;
+	try {
+		await Deno.resolveDns("localhost");
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.PermissionDenied);
+});
+
+testPerm({ ping: true }, async function netPingLoopback(): Promise<void> {
+	if (Deno.build.os === "win") {
+		return;
+	}
+	let result;
+	try {
+		result = await Deno.ping("127.0.0.1", { count: 2, timeout: 500 });
+	} catch (e) {
+		// Without root or CAP_NET_RAW, and outside ping_group_range.
+		assertEquals(e.kind, Deno.ErrorKind.PermissionDenied);
+		return;
+	}
+	assertEquals(result.address, "127.0.0.1");
+	assertEquals(result.rtts.length, 2);
+	for (const rtt of result.rtts) {
+		assert(rtt !== null && rtt >= 0);
+	}
+});
+
+test(async function netPingPerm(): Promise<void> {
+	let err;
+	try {
+		await Deno.ping("127.0.0.1");
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.PermissionDenied);
+});
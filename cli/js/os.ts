@@ -24,6 +24,67 @@ export function hostname(): string {
 	return sendSync(dispatch.OP_HOSTNAME);
 }
 
+/** Get the number of seconds since the system booted.
+ *
+ *       console.log(Deno.uptime());
+ */
+export function uptime(): number {
+	return sendSync(dispatch.OP_UPTIME);
+}
+
+/** Get the 1, 5 and 15 minute load averages, or `null` on Windows which has
+ * no load average.
+ *
+ *       const [one, five, fifteen] = Deno.loadavg()!;
+ */
+export function loadavg(): [number, number, number] | null {
+	return sendSync(dispatch.OP_LOADAVG);
+}
+
+/** System memory, all in bytes. */
+export interface MemoryInfo {
+	total: number;
+	free: number;
+	/** Memory available for new processes without swapping, including
+	 * reclaimable caches. */
+	available: number;
+	swapTotal: number;
+	swapFree: number;
+}
+
+/** Get system memory usage in bytes.
+ *
+ *       console.log(Deno.memoryInfo().available);
+ */
+export function memoryInfo(): MemoryInfo {
+	return sendSync(dispatch.OP_MEM_INFO);
+}
+
+/** CPU time and memory used by the current process. Fields a platform
+ * doesn't report are `null`. */
+export interface ResourceUsage {
+	/** CPU time spent in user code, in microseconds. */
+	userTime: number;
+	/** CPU time spent in the kernel on behalf of the process, in
+	 * microseconds. */
+	systemTime: number;
+	/** Peak resident set size in bytes. */
+	maxRss: number;
+	/** All page faults, including those served without I/O. */
+	pageFaults: number;
+	/** Page faults that required I/O. Not available on Windows. */
+	majorPageFaults: number | null;
+}
+
+/** Get the resource usage of the current process, so benchmarks can measure
+ * CPU time and peak memory without an external tool.
+ *
+ *       const { userTime, maxRss } = Deno.resourceUsage();
+ */
+export function resourceUsage(): ResourceUsage {
+	return sendSync(dispatch.OP_RESOURCE_USAGE);
+}
+
 /** Exit the Deno process with optional exit code. */
 export function exit(code = 0): never {
 	sendSync(dispatch.OP_EXIT, { code });
@@ -34,14 +95,19 @@ function setEnv(key: string, value: string): void {
 	sendSync(dispatch.OP_SET_ENV, { key, value });
 }
 
+function deleteEnv(key: string): void {
+	sendSync(dispatch.OP_DELETE_ENV, { key });
+}
+
 function getEnv(key: string): string | undefined {
 	return sendSync(dispatch.OP_GET_ENV, { key })[0];
 }
 
 /** Returns a snapshot of the environment variables at invocation. Mutating a
  * property in the object will set that variable in the environment for
- * the process. The environment object will only accept `string`s
- * as values.
+ * the process, and deleting it unsets the variable. Subprocesses started
+ * afterwards see the changes. The environment object will only accept
+ * `string`s as values.
  *
  *       console.log(Deno.env("SHELL"));
  *       const myEnv = Deno.env();
@@ -49,6 +115,7 @@ function getEnv(key: string): string | undefined {
  *       myEnv.TEST_VAR = "HELLO";
  *       const newEnv = Deno.env();
  *       console.log(myEnv.TEST_VAR == newEnv.TEST_VAR);
+ *       delete myEnv.TEST_VAR;
  */
 export function env(): { [index: string]: string };
 export function env(key: string): string | undefined;
@@ -64,6 +131,10 @@ export function env(
 			setEnv(prop, value);
 			return Reflect.set(obj, prop, value);
 		},
+		deleteProperty(obj, prop: string): boolean {
+			deleteEnv(prop);
+			return Reflect.deleteProperty(obj, prop);
+		},
 	});
 }
 
@@ -18,6 +18,49 @@ testPerm({ env: true }, function envSuccess(): void {
 	assertEquals(Deno.env("test_var"), env.test_var);
 });
 
+testPerm({ env: true }, function envDelete(): void {
+	const env = Deno.env();
+	// eslint-disable-next-line @typescript-eslint/camelcase
+	env.test_delete_var = "Hello";
+	assertEquals(Deno.env("test_delete_var"), "Hello");
+	// eslint-disable-next-line @typescript-eslint/camelcase
+	delete env.test_delete_var;
+	assertEquals(Deno.env("test_delete_var"), undefined);
+	assert(!("test_delete_var" in Deno.env()));
+});
+
+testPerm({ env: true }, function envInvalidName(): void {
+	let err;
+	try {
+		Deno.env()["A=B"] = "C";
+	} catch (e) {
+		err = e;
+	}
+	assert(!!err);
+	assertEquals(err.kind, Deno.ErrorKind.InvalidInput);
+});
+
+testPerm({ env: true, run: true }, async function envVisibleToChild(): Promise<
+	void
+> {
+	const env = Deno.env();
+	// eslint-disable-next-line @typescript-eslint/camelcase
+	env.test_child_var = "from parent";
+	const p = Deno.run({
+		args: [
+			"python",
+			"-c",
+			"import os, sys; sys.stdout.write(os.environ['test_child_var'])",
+		],
+		stdout: "piped",
+	});
+	const output = await p.output();
+	assertEquals(new TextDecoder().decode(output), "from parent");
+	p.close();
+	// eslint-disable-next-line @typescript-eslint/camelcase
+	delete env.test_child_var;
+});
+
 testPerm({ env: true }, function envNotFound(): void {
 	const r = Deno.env("env_var_does_not_exist!");
 	assertEquals(r, undefined);
@@ -346,3 +389,46 @@ testPerm({ env: false }, function hostnamePerm(): void {
 	}
 	assert(caughtError);
 });
+
+test(function osUptime(): void {
+	const uptime = Deno.uptime();
+	assert(Number.isInteger(uptime));
+	assert(uptime > 0);
+});
+
+test(function osLoadavg(): void {
+	const load = Deno.loadavg();
+	if (Deno.build.os === "win") {
+		assertEquals(load, null);
+	} else {
+		assertEquals(load!.length, 3);
+		assert(load!.every((n): boolean => n >= 0));
+	}
+});
+
+test(function osMemoryInfo(): void {
+	const info = Deno.memoryInfo();
+	assert(info.total > 0);
+	assert(info.free <= info.total);
+	assert(info.available <= info.total);
+	assert(info.swapFree <= info.swapTotal);
+});
+
+test(function osResourceUsage(): void {
+	const before = Deno.resourceUsage();
+	let x = 0;
+	for (let i = 0; i < 1e6; i++) {
+		x += Math.sqrt(i);
+	}
+	assert(x > 0);
+	const after = Deno.resourceUsage();
+	assert(after.userTime >= before.userTime);
+	assert(after.systemTime >= 0);
+	assert(after.maxRss > 0);
+	assert(after.pageFaults >= before.pageFaults);
+	if (Deno.build.os === "win") {
+		assertEquals(after.majorPageFaults, null);
+	} else {
+		assert(after.majorPageFaults! <= after.pageFaults);
+	}
+});
@@ -10,7 +10,8 @@ interface NowResponse {
 export class Performance {
 	/** Returns a current time from Deno's start in milliseconds.
 	 *
-	 * Use the flag --allow-hrtime return a precise value.
+	 * The value comes from a monotonic clock, so it is not affected by changes
+	 * to the system time. Use the flag --allow-hrtime return a precise value.
 	 *
 	 *       const t = performance.now();
 	 *       console.log(`${t} ms since start!`);
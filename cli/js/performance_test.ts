@@ -8,3 +8,12 @@ testPerm({ hrtime: false }, function now(): void {
 		assert(end - start >= 10);
 	}, 10);
 });
+
+testPerm({ hrtime: true }, function nowMonotonic(): void {
+	let last = performance.now();
+	for (let i = 0; i < 10000; i++) {
+		const t = performance.now();
+		assert(t >= last);
+		last = t;
+	}
+});
@@ -12,7 +12,8 @@ export type PermissionName =
 	| "env"
 	| "run"
 	| "plugin"
-	| "hrtime";
+	| "hrtime"
+	| "ping";
 // NOTE: Keep in sync with cli/permissions.rs
 
 /** https://w3c.github.io/permissions/#status-of-a-permission */
@@ -38,6 +39,9 @@ interface PluginPermissionDescriptor {
 interface HrtimePermissionDescriptor {
 	name: "hrtime";
 }
+interface PingPermissionDescriptor {
+	name: "ping";
+}
 /** See: https://w3c.github.io/permissions/#permission-descriptor */
 type PermissionDescriptor =
 	| RunPermissionDescriptor
@@ -45,7 +49,8 @@ type PermissionDescriptor =
 	| NetPermissionDescriptor
 	| EnvPermissionDescriptor
 	| PluginPermissionDescriptor
-	| HrtimePermissionDescriptor;
+	| HrtimePermissionDescriptor
+	| PingPermissionDescriptor;
 
 /** https://w3c.github.io/permissions/#permissionstatus */
 export class PermissionStatus {
@@ -65,7 +70,8 @@ export class Permissions {
 		return new PermissionStatus(state);
 	}
 
-	/** Revokes the permission.
+	/** Revokes the permission. With a `path` or `url` every grant covering it
+	 * is dropped, including a blanket `--allow-*` flag.
 	 *       const status = await Deno.permissions.revoke({ name: "run" });
 	 *       assert(status.state !== "granted")
 	 */
@@ -9,6 +9,7 @@ const knownPermissions: Deno.PermissionName[] = [
 	"env",
 	"plugin",
 	"hrtime",
+	"ping",
 ];
 
 function genFunc(grant: Deno.PermissionName): () => Promise<void> {
@@ -47,3 +48,23 @@ test(async function permissionNetInvalidUrl(): Promise<void> {
 		assert(e.name === "TypeError");
 	}
 });
+
+testPerm({ read: true }, async function permissionRevokeReadScope(): Promise<
+	void
+> {
+	Deno.statSync("cli/tests");
+	const status = await Deno.permissions.revoke({
+		name: "read",
+		path: "cli/tests",
+	});
+	assertEquals(status.state, "prompt");
+
+	let err;
+	try {
+		Deno.statSync("cli/tests");
+	} catch (e) {
+		err = e;
+	}
+	assert(!!err);
+	assertEquals(err.kind, Deno.ErrorKind.PermissionDenied);
+});
@@ -1,6 +1,10 @@
 import { core } from "./core.ts";
 import { sendSync } from "./dispatch_json.ts";
-import { OP_OPEN_PLUGIN, setPluginAsyncHandler } from "./dispatch.ts";
+import {
+	OP_CLOSE_PLUGIN,
+	OP_OPEN_PLUGIN,
+	setPluginAsyncHandler,
+} from "./dispatch.ts";
 
 export interface AsyncHandler {
 	(msg: Uint8Array): void;
@@ -29,19 +33,19 @@ class PluginOpImpl implements PluginOp {
 	}
 }
 
-// TODO(afinch7): add close method.
-
 interface Plugin {
+	readonly rid: number;
 	ops: {
 		[name: string]: PluginOp;
 	};
+	close(): void;
 }
 
 class PluginImpl implements Plugin {
 	private _ops: { [name: string]: PluginOp } = {};
 
 	constructor(
-		private readonly rid: number,
+		readonly rid: number,
 		ops: { [name: string]: number },
 	) {
 		for (const op in ops) {
@@ -52,6 +56,14 @@ class PluginImpl implements Plugin {
 	get ops(): { [name: string]: PluginOp } {
 		return Object.assign({}, this._ops);
 	}
+
+	/** Unregisters the plugin's ops and unloads it once calls still in
+	 * progress have finished. Dispatching one of its ops afterwards throws,
+	 * including through `PluginOp` objects obtained earlier. */
+	close(): void {
+		sendSync(OP_CLOSE_PLUGIN, { rid: this.rid });
+		this._ops = {};
+	}
 }
 
 interface OpenPluginResponse {
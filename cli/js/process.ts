@@ -1,4 +1,5 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { AbortSignal, withSignal } from "./abort_controller.ts";
 import { readAll } from "./buffer.ts";
 import { build } from "./build.ts";
 import { sendAsync, sendSync } from "./dispatch_json.ts";
@@ -31,27 +32,41 @@ export interface RunOptions {
 	stdin?: ProcessStdio | number;
 }
 
+export interface StatusOptions {
+	signal?: AbortSignal;
+}
+
 interface RunStatusResponse {
 	gotSignal: boolean;
 	exitCode: number;
 	exitSignal: number;
+	coreDumped: boolean;
 }
 
-async function runStatus(rid: number): Promise<ProcessStatus> {
-	const res = (await sendAsync(dispatch.OP_RUN_STATUS, {
-		rid,
-	})) as RunStatusResponse;
+async function runStatus(
+	rid: number,
+	signal?: AbortSignal,
+): Promise<ProcessStatus> {
+	const res = await withSignal(
+		signal,
+		async (cancelRid?: number): Promise<RunStatusResponse> =>
+			(await sendAsync(dispatch.OP_RUN_STATUS, {
+				rid,
+				cancelRid,
+			})) as RunStatusResponse,
+	);
 
 	if (res.gotSignal) {
 		const signal = res.exitSignal;
-		return { signal, success: false };
+		return { signal, coreDumped: res.coreDumped, success: false };
 	} else {
 		const code = res.exitCode;
-		return { code, success: code === 0 };
+		return { code, coreDumped: false, success: code === 0 };
 	}
 }
 
-/** Send a signal to process under given PID. Unix only at this moment.
+/** Send a signal to process under given PID. On Windows the process is
+ * terminated whatever the signal.
  * If pid is negative, the signal will be sent to the process group identified
  * by -pid.
  * Requires the `--allow-run` flag.
@@ -60,6 +75,26 @@ export function kill(pid: number, signo: number): void {
 	sendSync(dispatch.OP_KILL, { pid, signo });
 }
 
+/** Returns the scheduling priority of the process under given PID, or of the
+ * current process if PID is omitted, as a nice value from -20 (highest) to
+ * 19 (lowest). On Windows it is derived from the priority class.
+ * Requires the `--allow-run` flag.
+ */
+export function getPriority(pid = 0): number {
+	return sendSync(dispatch.OP_GET_PRIORITY, { pid });
+}
+
+/** Sets the scheduling priority of the process under given PID, or of the
+ * current process if PID is omitted. `priority` is a nice value from -20
+ * (highest) to 19 (lowest); on Windows it selects the closest priority class.
+ * Raising the priority usually needs elevated privileges and throws
+ * `PermissionDenied` otherwise.
+ * Requires the `--allow-run` flag.
+ */
+export function setPriority(priority: number, pid = 0): void {
+	sendSync(dispatch.OP_SET_PRIORITY, { pid, priority });
+}
+
 export class Process {
 	readonly rid: number;
 	readonly pid: number;
@@ -85,8 +120,11 @@ export class Process {
 		}
 	}
 
-	async status(): Promise<ProcessStatus> {
-		return await runStatus(this.rid);
+	/** Waits for the process to exit. Aborting `signal` stops waiting, the
+	 * promise then rejects with `ErrorKind.Aborted` while the process keeps
+	 * running. */
+	async status(options: StatusOptions = {}): Promise<ProcessStatus> {
+		return await runStatus(this.rid, options.signal);
 	}
 
 	/** Buffer the stdout and return it as Uint8Array after EOF.
@@ -123,8 +161,20 @@ export class Process {
 		close(this.rid);
 	}
 
+	/** Sends `signo` to the process. Throws `NotFound` if it has already
+	 * exited and been reaped by `status()`. */
 	kill(signo: number): void {
-		kill(this.pid, signo);
+		sendSync(dispatch.OP_KILL, { rid: this.rid, signo });
+	}
+
+	/** Returns the process' priority, see `Deno.getPriority()`. */
+	getPriority(): number {
+		return sendSync(dispatch.OP_GET_PRIORITY, { rid: this.rid });
+	}
+
+	/** Sets the process' priority, see `Deno.setPriority()`. */
+	setPriority(priority: number): void {
+		sendSync(dispatch.OP_SET_PRIORITY, { rid: this.rid, priority });
 	}
 }
 
@@ -132,6 +182,8 @@ export interface ProcessStatus {
 	success: boolean;
 	code?: number;
 	signal?: number; // TODO: Make this a string, e.g. 'SIGTERM'.
+	/** Whether the signal that terminated the process produced a core dump. */
+	coreDumped: boolean;
 }
 
 // TODO: this method is only used to validate proper option, probably can be renamed
@@ -160,8 +212,8 @@ interface RunResponse {
 /**
  * Spawns new subprocess.
  *
- * Subprocess uses same working directory as parent process unless `opt.cwd`
- * is specified.
+ * Subprocess uses the working directory of the calling worker unless
+ * `opt.cwd` is specified.
  *
  * Environmental variables for subprocess can be specified using `opt.env`
  * mapping.
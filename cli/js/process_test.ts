@@ -52,6 +52,7 @@ testPerm(
 		assertEquals(status.success, false);
 		assertEquals(status.code, 42);
 		assertEquals(status.signal, undefined);
+		assertEquals(status.coreDumped, false);
 		p.close();
 	},
 );
@@ -69,6 +70,8 @@ testPerm(
 		assertEquals(status.success, false);
 		assertEquals(status.code, undefined);
 		assertEquals(status.signal, 9);
+		// SIGKILL never dumps core.
+		assertEquals(status.coreDumped, false);
 		p.close();
 	},
 );
@@ -153,6 +156,24 @@ testPerm({ run: true }, async function runStdinPiped(): Promise<void> {
 	p.close();
 });
 
+if (Deno.build.os !== "win") {
+	testPerm({ run: true }, async function runStdinStdoutPipedCat(): Promise<
+		void
+	> {
+		const p = run({ args: ["cat"], stdin: "piped", stdout: "piped" });
+		const msg = new TextEncoder().encode("hello");
+		assertEquals(await p.stdin.write(msg), msg.byteLength);
+		// cat only exits once closing stdin has delivered EOF.
+		p.stdin.close();
+
+		const output = await p.output();
+		assertEquals(new TextDecoder().decode(output), "hello");
+		const status = await p.status();
+		assertEquals(status.success, true);
+		p.close();
+	});
+}
+
 testPerm({ run: true }, async function runStdoutPiped(): Promise<void> {
 	const p = run({
 		args: ["python", "-c", "import sys; sys.stdout.write('hello')"],
@@ -385,4 +406,69 @@ if (Deno.build.os !== "win") {
 
 		p.close();
 	});
+
+	testPerm({ run: true }, async function killSigterm(): Promise<void> {
+		const p = run({
+			args: ["python", "-c", "from time import sleep; sleep(10000)"],
+		});
+		p.kill(Deno.Signal.SIGTERM);
+		const status = await p.status();
+		assertEquals(status.success, false);
+		assertEquals(status.signal, Deno.Signal.SIGTERM);
+		p.close();
+	});
+
+	testPerm({ run: true }, async function killReapedChild(): Promise<void> {
+		const p = run({ args: ["python", "-c", "pass"] });
+		await p.status();
+
+		let err;
+		try {
+			p.kill(Deno.Signal.SIGTERM);
+		} catch (e) {
+			err = e;
+		}
+		assert(!!err);
+		assertEquals(err.kind, Deno.ErrorKind.NotFound);
+		p.close();
+	});
 }
+
+testPerm({ run: true }, async function processPriority(): Promise<void> {
+	const p = run({
+		args: ["python", "-c", "from time import sleep; sleep(10000)"],
+	});
+	const priority = p.getPriority();
+	assertEquals(Deno.getPriority(p.pid), priority);
+	// Lowering the priority needs no privileges.
+	p.setPriority(Math.min(priority + 10, 19));
+	if (Deno.build.os !== "win") {
+		assertEquals(p.getPriority(), Math.min(priority + 10, 19));
+	} else {
+		assert(p.getPriority() > priority);
+	}
+
+	let err;
+	try {
+		p.setPriority(20);
+	} catch (e) {
+		err = e;
+	}
+	assert(!!err);
+	assertEquals(err.kind, Deno.ErrorKind.InvalidInput);
+
+	p.kill(Deno.Signal.SIGKILL);
+	await p.status();
+	p.close();
+});
+
+test(function priorityPermissions(): void {
+	let caughtError = false;
+	try {
+		Deno.getPriority();
+	} catch (e) {
+		caughtError = true;
+		assertEquals(e.kind, Deno.ErrorKind.PermissionDenied);
+	}
+	assert(caughtError);
+});
@@ -2,6 +2,7 @@
 import { sendAsync, sendSync } from "./dispatch_json.ts";
 import * as dispatch from "./dispatch.ts";
 import { FileInfo, FileInfoImpl } from "./file_info.ts";
+import { close } from "./files.ts";
 import { StatResponse } from "./stat.ts";
 
 interface ReadDirResponse {
@@ -30,3 +31,85 @@ export function readDirSync(path: string): FileInfo[] {
 export async function readDir(path: string): Promise<FileInfo[]> {
 	return res(await sendAsync(dispatch.OP_READ_DIR, { path }));
 }
+
+export interface DirEntry {
+	name: string;
+	type: "file" | "dir" | "symlink" | "other";
+	/** Inode number, only set with the `inode` option and only on Unix. */
+	ino?: number | null;
+}
+
+export interface ReadDirIterOptions {
+	/** Entries fetched per op. Defaults to 1024. */
+	batchSize?: number;
+	/** Also return inode numbers. They come with the directory entries on
+	 * Unix, so this doesn't cost a `stat` per entry. */
+	inode?: boolean;
+}
+
+export interface DirIterator extends AsyncIterableIterator<DirEntry> {
+	readonly rid: number;
+	/** Closes the directory handle. Pending `next()` calls fail. */
+	close(): void;
+}
+
+class DirIteratorImpl implements DirIterator {
+	readonly rid: number;
+	private batch: DirEntry[] = [];
+	private done = false;
+
+	constructor(path: string, private options: ReadDirIterOptions) {
+		this.rid = sendSync(dispatch.OP_OPEN_DIR, { path }).rid;
+	}
+
+	async next(): Promise<IteratorResult<DirEntry>> {
+		if (this.batch.length === 0 && !this.done) {
+			const res = await sendAsync(dispatch.OP_READ_DIR_BATCH, {
+				rid: this.rid,
+				batchSize: this.options.batchSize,
+				inode: this.options.inode,
+			});
+			this.batch = res.entries;
+			if (this.batch.length === 0) {
+				this.close();
+			}
+		}
+		const value = this.batch.shift();
+		if (value === undefined) {
+			return { value: undefined, done: true };
+		}
+		return { value, done: false };
+	}
+
+	async return(value?: DirEntry): Promise<IteratorResult<DirEntry>> {
+		this.close();
+		return { value, done: true };
+	}
+
+	close(): void {
+		if (!this.done) {
+			this.done = true;
+			close(this.rid);
+		}
+	}
+
+	[Symbol.asyncIterator](): AsyncIterableIterator<DirEntry> {
+		return this;
+	}
+}
+
+/** Iterates over the entries of a directory in batches, so that large
+ * directories don't have to be read into memory at once. Entries only carry
+ * their name and type, use `Deno.lstat` for the rest. The directory handle
+ * is closed at the end or when the loop is left early. Requires `allow-read`.
+ *
+ *       for await (const entry of Deno.readDirIter("/")) {
+ *         console.log(entry.name, entry.type);
+ *       }
+ */
+export function readDirIter(
+	path: string,
+	options: ReadDirIterOptions = {},
+): DirIterator {
+	return new DirIteratorImpl(path, options);
+}
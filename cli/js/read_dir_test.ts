@@ -82,3 +82,51 @@ testPerm({ read: false }, async function readDirPerm(): Promise<void> {
 	}
 	assert(caughtError);
 });
+
+testPerm(
+	{ read: true, write: true },
+	async function readDirIterBatches(): Promise<void> {
+		const testDir = Deno.makeTempDirSync();
+		const data = new TextEncoder().encode("x");
+		for (let i = 0; i < 5; i++) {
+			Deno.writeFileSync(`${testDir}/file${i}`, data);
+		}
+		Deno.mkdirSync(`${testDir}/subdir`);
+		const before = Object.keys(Deno.resources()).length;
+
+		const entries: Deno.DirEntry[] = [];
+		for await (const entry of Deno.readDirIter(testDir, {
+			batchSize: 2,
+			inode: true,
+		})) {
+			entries.push(entry);
+		}
+		entries.sort((a, b): number => a.name.localeCompare(b.name));
+		assertEquals(
+			entries.map((entry): string => entry.name),
+			["file0", "file1", "file2", "file3", "file4", "subdir"],
+		);
+		assertEquals(entries[0].type, "file");
+		assertEquals(entries[5].type, "dir");
+		if (Deno.build.os !== "win") {
+			assertEquals(typeof entries[0].ino, "number");
+		}
+		assertEquals(Object.keys(Deno.resources()).length, before);
+
+		// Leaving the loop early closes the handle too.
+		for await (const _entry of Deno.readDirIter(testDir)) {
+			break;
+		}
+		assertEquals(Object.keys(Deno.resources()).length, before);
+	},
+);
+
+testPerm({ read: false }, function readDirIterPerm(): void {
+	let err;
+	try {
+		Deno.readDirIter("tests/");
+	} catch (e) {
+		err = e;
+	}
+	assertEquals(err.kind, Deno.ErrorKind.PermissionDenied);
+});
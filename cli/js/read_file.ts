@@ -1,6 +1,14 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
-import { readAll, readAllSync } from "./buffer.ts";
-import { open, openSync } from "./files.ts";
+import { sendAsync, sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
+
+/** Options for reading a file.
+ * `maxSize` fails the read with `ErrorKind.TooLarge` rather than read more
+ * than this many bytes (default: no limit)
+ */
+export interface ReadFileOptions {
+	maxSize?: number;
+}
 
 /** Read the entire contents of a file synchronously.
  *
@@ -8,11 +16,14 @@ import { open, openSync } from "./files.ts";
  *       const data = Deno.readFileSync("hello.txt");
  *       console.log(decoder.decode(data));
  */
-export function readFileSync(filename: string): Uint8Array {
-	const file = openSync(filename);
-	const contents = readAllSync(file);
-	file.close();
-	return contents;
+export function readFileSync(
+	filename: string,
+	options: ReadFileOptions = {},
+): Uint8Array {
+	return sendSync(dispatch.OP_READ_FILE, {
+		filename,
+		maxSize: options.maxSize,
+	});
 }
 
 /** Read the entire contents of a file.
@@ -21,9 +32,12 @@ export function readFileSync(filename: string): Uint8Array {
  *       const data = await Deno.readFile("hello.txt");
  *       console.log(decoder.decode(data));
  */
-export async function readFile(filename: string): Promise<Uint8Array> {
-	const file = await open(filename);
-	const contents = await readAll(file);
-	file.close();
-	return contents;
+export async function readFile(
+	filename: string,
+	options: ReadFileOptions = {},
+): Promise<Uint8Array> {
+	return await sendAsync(dispatch.OP_READ_FILE, {
+		filename,
+		maxSize: options.maxSize,
+	});
 }
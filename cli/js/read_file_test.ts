@@ -55,3 +55,28 @@ testPerm({ read: false }, async function readFilePerm(): Promise<void> {
 	}
 	assert(caughtError);
 });
+
+testPerm({ read: true }, function readFileSyncMaxSize(): void {
+	const size = Deno.statSync("cli/tests/fixture.json").len;
+	const data = Deno.readFileSync("cli/tests/fixture.json", { maxSize: size });
+	assertEquals(data.byteLength, size);
+	let caughtError = false;
+	try {
+		Deno.readFileSync("cli/tests/fixture.json", { maxSize: size - 1 });
+	} catch (e) {
+		caughtError = true;
+		assertEquals(e.kind, Deno.ErrorKind.TooLarge);
+	}
+	assert(caughtError);
+});
+
+testPerm({ read: true }, async function readFileMaxSize(): Promise<void> {
+	let caughtError = false;
+	try {
+		await Deno.readFile("cli/tests/fixture.json", { maxSize: 1 });
+	} catch (e) {
+		caughtError = true;
+		assertEquals(e.kind, Deno.ErrorKind.TooLarge);
+	}
+	assert(caughtError);
+});
@@ -2,18 +2,38 @@
 import { sendAsync, sendSync } from "./dispatch_json.ts";
 import * as dispatch from "./dispatch.ts";
 
+export interface RealpathOptions {
+	/** Set to `false` to keep a symlink in the final component instead of
+	 * resolving it, like `lstat`. Defaults to `true`. */
+	followFinalSymlink?: boolean;
+}
+
 /** Returns absolute normalized path with symbolic links resolved synchronously.
+ * Requires read permission for both `path` and the resolved path.
  *
  *       const realPath = Deno.realpathSync("./some/path");
  */
-export function realpathSync(path: string): string {
-	return sendSync(dispatch.OP_REALPATH, { path });
+export function realpathSync(
+	path: string,
+	options: RealpathOptions = {},
+): string {
+	return sendSync(dispatch.OP_REALPATH, {
+		path,
+		followFinalSymlink: options.followFinalSymlink,
+	});
 }
 
 /** Returns absolute normalized path with symbolic links resolved.
+ * Requires read permission for both `path` and the resolved path.
  *
  *       const realPath = await Deno.realpath("./some/path");
  */
-export async function realpath(path: string): Promise<string> {
-	return await sendAsync(dispatch.OP_REALPATH, { path });
+export async function realpath(
+	path: string,
+	options: RealpathOptions = {},
+): Promise<string> {
+	return await sendAsync(dispatch.OP_REALPATH, {
+		path,
+		followFinalSymlink: options.followFinalSymlink,
+	});
 }
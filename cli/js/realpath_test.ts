@@ -23,6 +23,21 @@ if (Deno.build.os !== "win") {
 		assert(targetPath.startsWith("/"));
 		assert(targetPath.endsWith("/target"));
 	});
+
+	testPerm(
+		{ read: true, write: true },
+		function realpathSyncNoFollowFinalSymlink(): void {
+			const testDir = Deno.makeTempDirSync();
+			const target = testDir + "/target";
+			const symlink = testDir + "/symln";
+			Deno.mkdirSync(target);
+			Deno.symlinkSync(target, symlink);
+			const linkPath = Deno.realpathSync(target + "/../symln", {
+				followFinalSymlink: false,
+			});
+			assertEquals(linkPath, Deno.realpathSync(testDir) + "/symln");
+		},
+	);
 }
 
 testPerm({ read: false }, function realpathSyncPerm(): void {
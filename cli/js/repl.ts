@@ -71,6 +71,89 @@ function isRecoverableError(e: Error): boolean {
 	return recoverableErrorMessages.includes(e.message);
 }
 
+// Operators that can't end a statement, e.g. `a +` or `foo.`. `++` and `--`
+// are excluded since `a++` is complete.
+const trailingOperator = /[-+*/%&|^=<>!?:,.]$/;
+
+// @internal
+/** Returns true if `code` looks unfinished: brackets that were never closed,
+ * an open template literal or block comment, or a dangling operator. Used on
+ * top of the error message check so that input like `${` inside a template,
+ * which V8 reports with a message that also covers real errors, keeps
+ * reading instead of failing. */
+export function isIncomplete(code: string): boolean {
+	const stack: string[] = [];
+	// End of the code outside comments, for the trailing operator check.
+	let end = 0;
+	let i = 0;
+	while (i < code.length) {
+		const c = code[i];
+		const top = stack[stack.length - 1];
+		if (top === "`") {
+			if (c === "\\") {
+				i += 2;
+				continue;
+			}
+			if (c === "`") {
+				stack.pop();
+			} else if (c === "$" && code[i + 1] === "{") {
+				stack.push("${");
+				i++;
+			}
+			i++;
+			end = i;
+			continue;
+		}
+		if (c === "/" && code[i + 1] === "/") {
+			const newline = code.indexOf("\n", i);
+			if (newline === -1) {
+				break;
+			}
+			i = newline + 1;
+			continue;
+		}
+		if (c === "/" && code[i + 1] === "*") {
+			const closing = code.indexOf("*/", i + 2);
+			if (closing === -1) {
+				return true;
+			}
+			i = closing + 2;
+			continue;
+		}
+		if (c === "'" || c === '"') {
+			// Strings can't span lines, so an unterminated one is an error.
+			i++;
+			while (i < code.length && code[i] !== c && code[i] !== "\n") {
+				i += code[i] === "\\" ? 2 : 1;
+			}
+			i++;
+			end = i;
+			continue;
+		}
+		if (c === "`" || c === "(" || c === "[" || c === "{") {
+			stack.push(c);
+		} else if (c === ")" || c === "]" || c === "}") {
+			const open = stack.pop();
+			if (open === undefined) {
+				return false; // Unbalanced the other way, a real error.
+			}
+		}
+		i++;
+		if (c.trim() !== "") {
+			end = i;
+		}
+	}
+	if (stack.length > 0) {
+		return true;
+	}
+	const trimmed = code.slice(0, end);
+	return (
+		trailingOperator.test(trimmed) &&
+		!trimmed.endsWith("++") &&
+		!trimmed.endsWith("--")
+	);
+}
+
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
 type Value = any;
 
@@ -85,7 +168,10 @@ function evaluate(code: string): boolean {
 	if (!errInfo) {
 		lastEvalResult = result;
 		replLog(result);
-	} else if (errInfo.isCompileError && isRecoverableError(errInfo.thrown)) {
+	} else if (
+		errInfo.isCompileError &&
+		(isRecoverableError(errInfo.thrown) || isIncomplete(code))
+	) {
 		// Recoverable compiler error
 		return false; // don't consume code.
 	} else {
@@ -171,7 +257,9 @@ export async function replLoop(): Promise<void> {
 				quitRepl(1);
 			}
 		}
-		// Start continued read
+		// Start continued read. With bracketed paste a pasted block arrives as a
+		// single multi-line read and is evaluated as one unit; otherwise the
+		// pasted lines arrive one at a time and are joined here until they parse.
 		while (!evaluate(code)) {
 			code += "\n";
 			try {
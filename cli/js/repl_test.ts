@@ -0,0 +1,50 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { assert, test } from "./test_util.ts";
+
+// isIncomplete isn't exposed in the types.
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+const { isIncomplete } = Deno as any;
+
+test(function replIsIncompleteBrackets(): void {
+	assert(isIncomplete("function f() {"));
+	assert(isIncomplete("console.log(1,"));
+	assert(isIncomplete("[1, [2"));
+	assert(isIncomplete("if (a) {\n  b();"));
+	assert(!isIncomplete("function f() {}"));
+	assert(!isIncomplete("[1, [2]]"));
+	// Unbalanced the other way is a real error.
+	assert(!isIncomplete("f())"));
+});
+
+test(function replIsIncompleteTemplateLiterals(): void {
+	assert(isIncomplete("`abc"));
+	assert(isIncomplete("`a ${b"));
+	assert(isIncomplete("`a ${{ b: 1 }"));
+	assert(!isIncomplete("`a ${b} c`"));
+	assert(!isIncomplete("`(`"));
+	assert(!isIncomplete("`\\``"));
+});
+
+test(function replIsIncompleteStrings(): void {
+	assert(!isIncomplete('"("'));
+	assert(!isIncomplete("'{['"));
+	assert(!isIncomplete('f("}")'));
+	assert(!isIncomplete("'\\'('"));
+	assert(isIncomplete('f("(",'));
+});
+
+test(function replIsIncompleteComments(): void {
+	assert(!isIncomplete("a // {"));
+	assert(isIncomplete("f( // )\n"));
+	assert(isIncomplete("a /* open"));
+	assert(!isIncomplete("a /* { */"));
+	assert(isIncomplete("{ /* } */"));
+});
+
+test(function replIsIncompleteTrailingOperator(): void {
+	assert(isIncomplete("a +"));
+	assert(isIncomplete("a.b."));
+	assert(isIncomplete("x = cond ?"));
+	assert(!isIncomplete("a++"));
+	assert(!isIncomplete("a--"));
+});
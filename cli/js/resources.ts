@@ -6,14 +6,86 @@ export interface ResourceMap {
 	[rid: number]: string;
 }
 
+export interface ResourcesOptions {
+	/** Append what is known about each resource to its name, e.g.
+	 * `"fsFile:/tmp/log.txt"`, `"child:1234"` or
+	 * `"tcpStream:127.0.0.1:4500"`. */
+	details?: boolean;
+}
+
 /** Returns a map of open _file like_ resource ids along with their string
  * representation.
  */
-export function resources(): ResourceMap {
-	const res = sendSync(dispatch.OP_RESOURCES) as Array<[number, string]>;
+export function resources(options: ResourcesOptions = {}): ResourceMap {
+	const res = sendSync(dispatch.OP_RESOURCES, {
+		details: !!options.details,
+	}) as Array<[number, string]>;
 	const resources: ResourceMap = {};
 	for (const resourceTuple of res) {
 		resources[resourceTuple[0]] = resourceTuple[1];
 	}
 	return resources;
 }
+
+export interface ResourceStats {
+	name: string;
+	/** Bytes read from the resource with `read()` since it was opened. */
+	bytesRead: number;
+	/** Bytes written to the resource with `write()` since it was opened. */
+	bytesWritten: number;
+}
+
+export interface ResourceStatsMap {
+	[rid: number]: ResourceStats;
+}
+
+/** Like `resources()`, but also returns how many bytes were read from and
+ * written to each resource so far.
+ *
+ *       const file = await Deno.open("log.txt", "w");
+ *       await file.write(new TextEncoder().encode("hello"));
+ *       Deno.resourceStats()[file.rid].bytesWritten; // 5
+ */
+export function resourceStats(
+	options: ResourcesOptions = {},
+): ResourceStatsMap {
+	const res = sendSync(dispatch.OP_RESOURCES, {
+		details: !!options.details,
+		stats: true,
+	}) as Array<[number, string, number, number]>;
+	const stats: ResourceStatsMap = {};
+	for (const [rid, name, bytesRead, bytesWritten] of res) {
+		stats[rid] = { name, bytesRead, bytesWritten };
+	}
+	return stats;
+}
+
+export interface CloseAllOptions {
+	/** Leave stdin, stdout and stderr (rids 0, 1 and 2) open. Defaults to
+	 * true. */
+	keepStdio?: boolean;
+}
+
+/** Closes every open resource and returns how many were closed. Useful in a
+ * top level error handler or between test cases. Pending operations on the
+ * closed resources fail or finish as if each had been closed on its own.
+ *
+ *       Deno.closeAll();
+ */
+export function closeAll(options: CloseAllOptions = {}): number {
+	const { keepStdio = true } = options;
+	return sendSync(dispatch.OP_CLOSE_ALL, { keepStdio }).closed;
+}
+
+/** Returns a new rid for the file or socket behind `rid`, like dup(2). Both
+ * can be used and closed independently, the underlying file or socket stays
+ * open until both are closed. Duplicated files share their position.
+ *
+ *       const file = await Deno.open("log.txt");
+ *       const copy = Deno.dup(file.rid);
+ *       file.close();
+ *       await Deno.read(copy, new Uint8Array(16));
+ */
+export function dup(rid: number): number {
+	return sendSync(dispatch.OP_DUP, { rid });
+}
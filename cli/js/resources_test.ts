@@ -1,5 +1,5 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
-import { assertEquals, test, testPerm } from "./test_util.ts";
+import { assert, assertEquals, test, testPerm } from "./test_util.ts";
 
 test(function resourcesStdio(): void {
 	const res = Deno.resources();
@@ -44,3 +44,91 @@ testPerm({ read: true }, async function resourcesFile(): Promise<void> {
 	});
 	assertEquals(resourcesAfter[newRid], "fsFile");
 });
+
+testPerm({ read: true }, async function resourcesDetails(): Promise<void> {
+	const file = await Deno.open("tests/hello.txt");
+	const res = Deno.resources({ details: true });
+	assertEquals(res[0], "stdin");
+	const name = res[file.rid];
+	assert(name.startsWith("fsFile:"));
+	assert(name.replace(/\\/g, "/").endsWith("tests/hello.txt"));
+	assertEquals(Deno.resources()[file.rid], "fsFile");
+	file.close();
+});
+
+testPerm({ read: true }, async function resourcesCloseAll(): Promise<void> {
+	await Deno.open("tests/hello.txt");
+	await Deno.open("tests/hello.txt");
+	const timer = Deno.interval(1000);
+	const pending = timer.next();
+
+	const closed = Deno.closeAll();
+	assert(closed >= 3);
+	assertEquals(Object.keys(Deno.resources()).sort(), ["0", "1", "2"]);
+	// A pending tick of the closed timer ends instead of hanging.
+	assert((await pending).done);
+});
+
+testPerm(
+	{ read: true, write: true },
+	async function resourcesStats(): Promise<void> {
+		const filename = Deno.makeTempDirSync() + "/stats.txt";
+		const file = await Deno.open(filename, "w+");
+		assertEquals(Deno.resourceStats()[file.rid], {
+			name: "fsFile",
+			bytesRead: 0,
+			bytesWritten: 0,
+		});
+		await file.write(new TextEncoder().encode("hello world"));
+		await file.seek(0, Deno.SeekMode.SEEK_START);
+		await file.read(new Uint8Array(5));
+		const stats = Deno.resourceStats({ details: true })[file.rid];
+		assert(stats.name.startsWith("fsFile:"));
+		assertEquals(stats.bytesWritten, 11);
+		assertEquals(stats.bytesRead, 5);
+		file.close();
+	},
+);
+
+testPerm({ read: true }, async function resourcesDup(): Promise<void> {
+	const file = await Deno.open("tests/hello.txt");
+	const rid = Deno.dup(file.rid);
+	assert(rid !== file.rid);
+	assertEquals(Deno.resources()[rid], "fsFile");
+	file.close();
+
+	// Still readable after the original is closed.
+	const buf = new Uint8Array(5);
+	assertEquals(await Deno.read(rid, buf), 5);
+	assertEquals(new TextDecoder().decode(buf), "Hello");
+	Deno.close(rid);
+});
+
+testPerm({ net: true }, async function resourcesDupSocket(): Promise<void> {
+	const listener = Deno.listen({ port: 4502 });
+	const dialerConn = await Deno.dial({ port: 4502 });
+	const listenerConn = await listener.accept();
+	const rid = Deno.dup(listenerConn.rid);
+	listenerConn.close();
+
+	await dialerConn.write(new Uint8Array([1, 2, 3]));
+	const buf = new Uint8Array(3);
+	assertEquals(await Deno.read(rid, buf), 3);
+	assertEquals(buf, new Uint8Array([1, 2, 3]));
+
+	Deno.close(rid);
+	dialerConn.close();
+	listener.close();
+});
+
+test(function resourcesDupStdio(): void {
+	let err;
+	try {
+		Deno.dup(0);
+	} catch (e) {
+		err = e;
+	}
+	assert(err instanceof Deno.DenoError);
+	assertEquals(err.kind, Deno.ErrorKind.BadResource);
+});
+
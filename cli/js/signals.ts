@@ -0,0 +1,54 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { sendAsync, sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
+
+/** Occurrences of a signal. Iterate it with `for await`, or `await` it to wait
+ * for the next occurrence. */
+export class SignalStream
+	implements AsyncIterableIterator<void>, PromiseLike<void>
+{
+	readonly rid: number;
+
+	constructor(readonly signo: number) {
+		this.rid = sendSync(dispatch.OP_SIGNAL_BIND, { signo }).rid;
+	}
+
+	next(): Promise<IteratorResult<void>> {
+		return sendAsync(dispatch.OP_SIGNAL_POLL, { rid: this.rid });
+	}
+
+	async return(): Promise<IteratorResult<void>> {
+		this.dispose();
+		return { value: undefined, done: true };
+	}
+
+	then<T = void, S = never>(
+		f: (v: void) => T | Promise<T>,
+		g?: (v: Error) => S | Promise<S>,
+	): Promise<T | S> {
+		return this.next().then((): void => {}).then(f, g);
+	}
+
+	/** Stops listening. Pending `next()` calls resolve with `done: true`. Once
+	 * nothing listens for the signal any more it has its default effect
+	 * again. */
+	dispose(): void {
+		sendSync(dispatch.OP_SIGNAL_UNBIND, { rid: this.rid });
+	}
+
+	[Symbol.asyncIterator](): AsyncIterableIterator<void> {
+		return this;
+	}
+}
+
+/** Listens for the signal `signo`. While the stream is alive the signal no
+ * longer has its default effect, e.g. SIGINT doesn't terminate the process.
+ * On Windows only SIGINT (Ctrl-C) is supported.
+ *
+ *       for await (const _ of Deno.signal(Deno.Signal.SIGTERM)) {
+ *         console.log("got SIGTERM");
+ *       }
+ */
+export function signal(signo: number): SignalStream {
+	return new SignalStream(signo);
+}
@@ -0,0 +1,41 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { assert, assertEquals, test, testPerm } from "./test_util.ts";
+
+const isWindows = Deno.build.os === "win";
+
+if (!isWindows) {
+	testPerm({ run: true }, async function signalStreamReceivesSignal(): Promise<
+		void
+	> {
+		const stream = Deno.signal(Deno.Signal.SIGUSR1);
+		let count = 0;
+		const received = (async (): Promise<void> => {
+			for await (const _ of stream) {
+				count++;
+				break;
+			}
+		})();
+		Deno.kill(Deno.pid, Deno.Signal.SIGUSR1);
+		await received;
+		assertEquals(count, 1);
+	});
+
+	test(async function signalStreamDisposeEndsIteration(): Promise<void> {
+		const stream = Deno.signal(Deno.Signal.SIGUSR2);
+		const pending = stream.next();
+		stream.dispose();
+		const result = await pending;
+		assert(result.done);
+	});
+} else {
+	test(function signalUnsupportedOnWindows(): void {
+		let err;
+		try {
+			Deno.signal(15);
+		} catch (e) {
+			err = e;
+		}
+		assert(!!err);
+		assertEquals(err.kind, Deno.ErrorKind.InvalidInput);
+	});
+}
@@ -13,6 +13,18 @@ export interface StatResponse {
 	mode: number;
 	hasMode: boolean; // false on windows
 	name: string | null;
+	modifiedNsec: number | null;
+	accessedNsec: number | null;
+	createdNsec: number | null;
+	// Unix only, null on windows
+	dev: number | null;
+	ino: number | null;
+	nlink: number | null;
+	uid: number | null;
+	gid: number | null;
+	rdev: number | null;
+	blksize: number | null;
+	blocks: number | null;
 }
 
 /** Queries the file system for information on the path provided. If the given
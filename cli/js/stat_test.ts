@@ -170,3 +170,30 @@ testPerm({ read: true }, async function lstatNotFound(): Promise<void> {
 	assert(caughtError);
 	assertEquals(badInfo, undefined);
 });
+
+if (Deno.build.os !== "win") {
+	testPerm(
+		{ read: true, write: true },
+		function statSyncUnixFields(): void {
+			const tempDir = Deno.makeTempDirSync();
+			const filename = tempDir + "/a.txt";
+			Deno.writeFileSync(filename, new Uint8Array(10));
+			Deno.linkSync(filename, tempDir + "/b.txt");
+
+			const a = Deno.statSync(filename);
+			const b = Deno.statSync(tempDir + "/b.txt");
+			assertEquals(a.ino, b.ino);
+			assertEquals(a.dev, b.dev);
+			assertEquals(a.nlink, 2);
+			assert(a.blksize! > 0);
+			assert(a.blocks !== null);
+			assert(a.modifiedNsec! >= 0 && a.modifiedNsec! < 1e9);
+		},
+	);
+} else {
+	testPerm({ read: true }, function statSyncWindowsFields(): void {
+		const info = Deno.statSync("README.md");
+		assertEquals(info.ino, null);
+		assertEquals(info.nlink, null);
+	});
+}
@@ -1,39 +1,32 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
-import { build } from "./build.ts";
 import { sendAsync, sendSync } from "./dispatch_json.ts";
 import * as dispatch from "./dispatch.ts";
-import * as util from "./util.ts";
 
-/** Synchronously creates `newname` as a symbolic link to `oldname`. The type
- * argument can be set to `dir` or `file` and is only available on Windows
- * (ignored on other platforms).
+/** Synchronously creates `newname` as a symbolic link to `oldname`. On
+ * Windows the type argument picks a file or directory link, without it the
+ * type is taken from `oldname`. Other platforms ignore it. Requires
+ * `allow-write` for `newname`.
  *
  *       Deno.symlinkSync("old/name", "new/name");
  */
 export function symlinkSync(
 	oldname: string,
 	newname: string,
-	type?: string,
+	type?: "file" | "dir",
 ): void {
-	if (build.os === "win" && type) {
-		return util.notImplemented();
-	}
-	sendSync(dispatch.OP_SYMLINK, { oldname, newname });
+	sendSync(dispatch.OP_SYMLINK, { oldname, newname, type });
 }
 
-/** Creates `newname` as a symbolic link to `oldname`. The type argument can be
- * set to `dir` or `file` and is only available on Windows (ignored on other
- * platforms).
+/** Creates `newname` as a symbolic link to `oldname`. On Windows the type
+ * argument picks a file or directory link, without it the type is taken from
+ * `oldname`. Other platforms ignore it. Requires `allow-write` for `newname`.
  *
  *       await Deno.symlink("old/name", "new/name");
  */
 export async function symlink(
 	oldname: string,
 	newname: string,
-	type?: string,
+	type?: "file" | "dir",
 ): Promise<void> {
-	if (build.os === "win" && type) {
-		return util.notImplemented();
-	}
-	await sendAsync(dispatch.OP_SYMLINK, { oldname, newname });
+	await sendAsync(dispatch.OP_SYMLINK, { oldname, newname, type });
 }
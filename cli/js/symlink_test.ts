@@ -6,23 +6,11 @@ testPerm({ read: true, write: true }, function symlinkSyncSuccess(): void {
 	const oldname = testDir + "/oldname";
 	const newname = testDir + "/newname";
 	Deno.mkdirSync(oldname);
-	let errOnWindows;
-	// Just for now, until we implement symlink for Windows.
-	try {
-		Deno.symlinkSync(oldname, newname);
-	} catch (e) {
-		errOnWindows = e;
-	}
-	if (errOnWindows) {
-		assertEquals(Deno.build.os, "win");
-		assertEquals(errOnWindows.kind, Deno.ErrorKind.Other);
-		assertEquals(errOnWindows.message, "Not implemented");
-	} else {
-		const newNameInfoLStat = Deno.lstatSync(newname);
-		const newNameInfoStat = Deno.statSync(newname);
-		assert(newNameInfoLStat.isSymlink());
-		assert(newNameInfoStat.isDirectory());
-	}
+	Deno.symlinkSync(oldname, newname);
+	const newNameInfoLStat = Deno.lstatSync(newname);
+	const newNameInfoStat = Deno.statSync(newname);
+	assert(newNameInfoLStat.isSymlink());
+	assert(newNameInfoStat.isDirectory());
 });
 
 test(function symlinkSyncPerm(): void {
@@ -36,24 +24,44 @@ test(function symlinkSyncPerm(): void {
 	assertEquals(err.name, "PermissionDenied");
 });
 
-// Just for now, until we implement symlink for Windows.
-// Symlink with type should succeed on other platforms with type ignored
-testPerm({ write: true }, function symlinkSyncNotImplemented(): void {
+// The type hint lets Windows create links to targets that don't exist yet,
+// other platforms ignore it.
+testPerm({ read: true, write: true }, function symlinkSyncWithType(): void {
 	const testDir = Deno.makeTempDirSync();
 	const oldname = testDir + "/oldname";
 	const newname = testDir + "/newname";
+	Deno.symlinkSync(oldname, newname, "dir");
+	assert(Deno.lstatSync(newname).isSymlink());
+	Deno.mkdirSync(oldname);
+	assert(Deno.statSync(newname).isDirectory());
+});
+
+testPerm({ write: true }, function symlinkSyncInvalidType(): void {
+	const testDir = Deno.makeTempDirSync();
 	let err;
 	try {
-		Deno.symlinkSync(oldname, newname, "dir");
+		// eslint-disable-next-line @typescript-eslint/no-explicit-any
+		Deno.symlinkSync(testDir, testDir + "/link", "junction" as any);
 	} catch (e) {
 		err = e;
 	}
-	if (err) {
-		assertEquals(Deno.build.os, "win");
-		assertEquals(err.message, "Not implemented");
-	}
+	assertEquals(err.kind, Deno.ErrorKind.InvalidInput);
 });
 
+testPerm(
+	{ read: true, write: true },
+	function symlinkSyncReadlinkRoundTrip(): void {
+		const testDir = Deno.makeTempDirSync();
+		const target = testDir + "/target.txt";
+		const link = testDir + "/link.txt";
+		Deno.writeFileSync(target, new TextEncoder().encode("hello"));
+		Deno.symlinkSync(target, link, "file");
+		const normalize = (path: string): string => path.replace(/\\/g, "/");
+		assertEquals(normalize(Deno.readlinkSync(link)), normalize(target));
+		assertEquals(new TextDecoder().decode(Deno.readFileSync(link)), "hello");
+	},
+);
+
 testPerm(
 	{ read: true, write: true },
 	async function symlinkSuccess(): Promise<void> {
@@ -61,21 +69,10 @@ testPerm(
 		const oldname = testDir + "/oldname";
 		const newname = testDir + "/newname";
 		Deno.mkdirSync(oldname);
-		let errOnWindows;
-		// Just for now, until we implement symlink for Windows.
-		try {
-			await Deno.symlink(oldname, newname);
-		} catch (e) {
-			errOnWindows = e;
-		}
-		if (errOnWindows) {
-			assertEquals(errOnWindows.kind, Deno.ErrorKind.Other);
-			assertEquals(errOnWindows.message, "Not implemented");
-		} else {
-			const newNameInfoLStat = Deno.lstatSync(newname);
-			const newNameInfoStat = Deno.statSync(newname);
-			assert(newNameInfoLStat.isSymlink());
-			assert(newNameInfoStat.isDirectory());
-		}
+		await Deno.symlink(oldname, newname);
+		const newNameInfoLStat = Deno.lstatSync(newname);
+		const newNameInfoStat = Deno.statSync(newname);
+		assert(newNameInfoLStat.isSymlink());
+		assert(newNameInfoStat.isDirectory());
 	},
 );
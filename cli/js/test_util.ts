@@ -29,6 +29,7 @@ interface TestPermissions {
 	run?: boolean;
 	plugin?: boolean;
 	hrtime?: boolean;
+	ping?: boolean;
 }
 
 export interface Permissions {
@@ -39,6 +40,7 @@ export interface Permissions {
 	run: boolean;
 	plugin: boolean;
 	hrtime: boolean;
+	ping: boolean;
 }
 
 const isGranted = async (name: Deno.PermissionName): Promise<boolean> =>
@@ -53,6 +55,7 @@ async function getProcessPermissions(): Promise<Permissions> {
 		env: await isGranted("env"),
 		plugin: await isGranted("plugin"),
 		hrtime: await isGranted("hrtime"),
+		ping: await isGranted("ping"),
 	};
 }
 
@@ -81,7 +84,8 @@ function permToString(perms: Permissions): string {
 	const u = perms.run ? 1 : 0;
 	const p = perms.plugin ? 1 : 0;
 	const h = perms.hrtime ? 1 : 0;
-	return `permR${r}W${w}N${n}E${e}U${u}P${p}H${h}`;
+	const i = perms.ping ? 1 : 0;
+	return `permR${r}W${w}N${n}E${e}U${u}P${p}H${h}I${i}`;
 }
 
 function registerPermCombination(perms: Permissions): void {
@@ -100,6 +104,7 @@ function normalizeTestPermissions(perms: TestPermissions): Permissions {
 		env: !!perms.env,
 		plugin: !!perms.plugin,
 		hrtime: !!perms.hrtime,
+		ping: !!perms.ping,
 	};
 }
 
@@ -128,6 +133,7 @@ export function test(fn: testing.TestFunction): void {
 			run: false,
 			plugin: false,
 			hrtime: false,
+			ping: false,
 		},
 		fn,
 	);
@@ -185,6 +191,7 @@ test(function permissionsMatches(): void {
 				run: false,
 				plugin: false,
 				hrtime: false,
+				ping: false,
 			},
 			normalizeTestPermissions({ read: true }),
 		),
@@ -200,6 +207,7 @@ test(function permissionsMatches(): void {
 				run: false,
 				plugin: false,
 				hrtime: false,
+				ping: false,
 			},
 			normalizeTestPermissions({}),
 		),
@@ -215,6 +223,7 @@ test(function permissionsMatches(): void {
 				run: true,
 				plugin: true,
 				hrtime: true,
+				ping: true,
 			},
 			normalizeTestPermissions({ read: true }),
 		),
@@ -231,6 +240,7 @@ test(function permissionsMatches(): void {
 				run: false,
 				plugin: false,
 				hrtime: false,
+				ping: false,
 			},
 			normalizeTestPermissions({ read: true }),
 		),
@@ -247,6 +257,7 @@ test(function permissionsMatches(): void {
 				run: true,
 				plugin: true,
 				hrtime: true,
+				ping: true,
 			},
 			{
 				read: true,
@@ -256,6 +267,7 @@ test(function permissionsMatches(): void {
 				run: true,
 				plugin: true,
 				hrtime: true,
+				ping: true,
 			},
 		),
 	);
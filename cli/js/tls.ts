@@ -9,22 +9,63 @@ interface DialTLSOptions {
 	port: number;
 	hostname?: string;
 	certFile?: string;
+	clientCertFile?: string;
+	clientKeyFile?: string;
+	alpnProtocols?: string[];
+	sessionResumption?: boolean;
+	earlyData?: boolean;
 }
 const dialTLSDefaults = { hostname: "127.0.0.1", transport: "tcp" };
 
+export interface TLSConn extends Conn {
+	/** The ALPN protocol both sides agreed on, or `null` if none was. */
+	alpnProtocol: string | null;
+	/** Hex SHA-256 fingerprints of the peer's certificate chain, leaf first. */
+	peerCertFingerprints: string[];
+	/** True if the server resumed a session from an earlier connection
+	 * instead of doing a full handshake. Always false for accepted
+	 * connections. */
+	resumed: boolean;
+}
+
+class TLSConnImpl extends ConnImpl implements TLSConn {
+	constructor(
+		rid: number,
+		remoteAddr: string,
+		localAddr: string,
+		readonly alpnProtocol: string | null,
+		readonly peerCertFingerprints: string[],
+		readonly resumed: boolean,
+	) {
+		super(rid, remoteAddr, localAddr);
+	}
+}
+
+// eslint-disable-next-line @typescript-eslint/no-explicit-any
+function tlsConnFromResponse(res: any): TLSConn {
+	return new TLSConnImpl(
+		res.rid,
+		res.remoteAddr,
+		res.localAddr,
+		res.handshake.alpnProtocol,
+		res.handshake.peerCertFingerprints,
+		!!res.handshake.resumed,
+	);
+}
+
 /**
  * dialTLS establishes a secure connection over TLS (transport layer security).
  */
-export async function dialTLS(options: DialTLSOptions): Promise<Conn> {
+export async function dialTLS(options: DialTLSOptions): Promise<TLSConn> {
 	options = Object.assign(dialTLSDefaults, options);
 	const res = await sendAsync(dispatch.OP_DIAL_TLS, options);
-	return new ConnImpl(res.rid, res.remoteAddr!, res.localAddr!);
+	return tlsConnFromResponse(res);
 }
 
 class TLSListenerImpl extends ListenerImpl {
-	async accept(): Promise<Conn> {
+	async accept(): Promise<TLSConn> {
 		const res = await sendAsync(dispatch.OP_ACCEPT_TLS, { rid: this.rid });
-		return new ConnImpl(res.rid, res.remoteAddr, res.localAddr);
+		return tlsConnFromResponse(res);
 	}
 }
 
@@ -34,6 +75,8 @@ export interface ListenTLSOptions {
 	transport?: Transport;
 	certFile: string;
 	keyFile: string;
+	clientCaFile?: string;
+	alpnProtocols?: string[];
 }
 
 /** Listen announces on the local transport address over TLS (transport layer security).
@@ -44,6 +87,10 @@ export interface ListenTLSOptions {
  *   resolved to an IP address. If not specified, defaults to 0.0.0.0
  * @param options.certFile Server certificate file
  * @param options.keyFile Server public key file
+ * @param options.clientCaFile If set, clients must present a certificate
+ *   signed by one of the CAs in this file
+ * @param options.alpnProtocols ALPN protocols the server supports, in order
+ *   of preference
  *
  * Examples:
  *
@@ -58,6 +105,8 @@ export function listenTLS(options: ListenTLSOptions): Listener {
 		transport,
 		certFile: options.certFile,
 		keyFile: options.keyFile,
+		clientCaFile: options.clientCaFile,
+		alpnProtocols: options.alpnProtocols,
 	});
 	return new TLSListenerImpl(res.rid, transport, res.localAddr);
 }
@@ -202,4 +202,131 @@ testPerm(
 	},
 );
 
+testPerm(
+	{ read: true, net: true },
+	async function dialTLSClientCertWithoutKey(): Promise<void> {
+		let err;
+		try {
+			await Deno.dialTLS({
+				hostname: "localhost",
+				port: 4500,
+				clientCertFile: "cli/tests/tls/localhost.crt",
+			});
+		} catch (e) {
+			err = e;
+		}
+		assertEquals(err.kind, Deno.ErrorKind.InvalidInput);
+	},
+);
+
+testPerm(
+	{ read: true, net: true },
+	async function dialAndListenMutualTLS(): Promise<void> {
+		const hostname = "localhost";
+		const port = 4500;
+
+		const listener = Deno.listenTLS({
+			hostname,
+			port,
+			certFile: "cli/tests/tls/localhost.crt",
+			keyFile: "cli/tests/tls/localhost.key",
+			clientCaFile: "cli/tests/tls/RootCA.pem",
+		});
+
+		listener.accept().then(async (conn): Promise<void> => {
+			await conn.write(encoder.encode("hello"));
+			conn.close();
+		});
+
+		const conn = await Deno.dialTLS({
+			hostname,
+			port,
+			certFile: "cli/tests/tls/RootCA.pem",
+			clientCertFile: "cli/tests/tls/localhost.crt",
+			clientKeyFile: "cli/tests/tls/localhost.key",
+		});
+		const buf = new Uint8Array(5);
+		await new BufReader(conn).readFull(buf);
+		assertEquals(decoder.decode(buf), "hello");
+		conn.close();
+		listener.close();
+	},
+);
+
+testPerm(
+	{ read: true, net: true },
+	async function dialTLSNegotiatesALPN(): Promise<void> {
+		const hostname = "localhost";
+		const port = 4500;
+
+		const listener = Deno.listenTLS({
+			hostname,
+			port,
+			certFile: "cli/tests/tls/localhost.crt",
+			keyFile: "cli/tests/tls/localhost.key",
+			alpnProtocols: ["h2", "http/1.1"],
+		});
+		const accepted = listener.accept();
+
+		const conn = await Deno.dialTLS({
+			hostname,
+			port,
+			certFile: "cli/tests/tls/RootCA.pem",
+			alpnProtocols: ["http/1.1"],
+		});
+		assertEquals(conn.alpnProtocol, "http/1.1");
+		assert(conn.peerCertFingerprints.length > 0);
+		assert(/^[0-9a-f]{64}$/.test(conn.peerCertFingerprints[0]));
+
+		const serverConn = await accepted;
+		conn.close();
+		serverConn.close();
+		listener.close();
+	},
+);
+
+testPerm(
+	{ read: true, net: true },
+	async function dialTLSSessionResumption(): Promise<void> {
+		const hostname = "localhost";
+		const port = 4500;
+
+		const listener = Deno.listenTLS({
+			hostname,
+			port,
+			certFile: "cli/tests/tls/localhost.crt",
+			keyFile: "cli/tests/tls/localhost.key",
+		});
+		const dial = async (
+			sessionResumption: boolean,
+			alpnProtocols: string[] = [],
+		): Promise<boolean> => {
+			const accepted = listener.accept();
+			const conn = await Deno.dialTLS({
+				hostname,
+				port,
+				certFile: "cli/tests/tls/RootCA.pem",
+				alpnProtocols,
+				sessionResumption,
+			});
+			const serverConn = await accepted;
+			await serverConn.write(encoder.encode("hello"));
+			// Reading also processes the session ticket sent after the handshake.
+			const buf = new Uint8Array(5);
+			await new BufReader(conn).readFull(buf);
+			assertEquals(decoder.decode(buf), "hello");
+			conn.close();
+			serverConn.close();
+			return conn.resumed;
+		};
+
+		await dial(true);
+		assertEquals(await dial(true), true);
+		assertEquals(await dial(false), false);
+		// Sessions aren't shared with a different config.
+		assertEquals(await dial(true, ["x-other"]), false);
+		listener.close();
+	},
+);
+
 runIfMain(import.meta);
@@ -32,3 +32,45 @@ export function truncateSync(name: string, len?: number): void {
 export async function truncate(name: string, len?: number): Promise<void> {
 	await sendAsync(dispatch.OP_TRUNCATE, { name, len: coerceLen(len) });
 }
+
+/** Truncates or extends the file with the given ID synchronously to exactly
+ * `len` bytes. New bytes are zero.
+ *
+ *       const file = Deno.openSync("hello.txt", "r+");
+ *       Deno.ftruncateSync(file.rid, 10);
+ */
+export function ftruncateSync(rid: number, len?: number): void {
+	sendSync(dispatch.OP_FTRUNCATE, { rid, len: coerceLen(len) });
+}
+
+/** Truncates or extends the file with the given ID to exactly `len` bytes.
+ * New bytes are zero.
+ *
+ *       const file = await Deno.open("hello.txt", "r+");
+ *       await Deno.ftruncate(file.rid, 10);
+ */
+export async function ftruncate(rid: number, len?: number): Promise<void> {
+	await sendAsync(dispatch.OP_FTRUNCATE, { rid, len: coerceLen(len) });
+}
+
+/** Reserves disk space synchronously so the file with the given ID is at least
+ * `len` bytes long. Uses `posix_fallocate` where available and otherwise
+ * extends the file. Never shrinks the file.
+ *
+ *       const file = Deno.openSync("data.db", "r+");
+ *       Deno.fallocateSync(file.rid, 1 << 20);
+ */
+export function fallocateSync(rid: number, len: number): void {
+	sendSync(dispatch.OP_FALLOCATE, { rid, len: coerceLen(len) });
+}
+
+/** Reserves disk space so the file with the given ID is at least `len` bytes
+ * long. Uses `posix_fallocate` where available and otherwise extends the
+ * file. Never shrinks the file.
+ *
+ *       const file = await Deno.open("data.db", "r+");
+ *       await Deno.fallocate(file.rid, 1 << 20);
+ */
+export async function fallocate(rid: number, len: number): Promise<void> {
+	await sendAsync(dispatch.OP_FALLOCATE, { rid, len: coerceLen(len) });
+}
@@ -73,3 +73,32 @@ testPerm({ write: false }, async function truncatePerm(): Promise<void> {
 	assertEquals(err.kind, Deno.ErrorKind.PermissionDenied);
 	assertEquals(err.name, "PermissionDenied");
 });
+
+testPerm({ read: true, write: true }, function ftruncateSyncSuccess(): void {
+	const filename = Deno.makeTempDirSync() + "/test_ftruncateSync.txt";
+	Deno.writeFileSync(filename, new TextEncoder().encode("Hello"));
+	const file = Deno.openSync(filename, "r+");
+	Deno.ftruncateSync(file.rid, 20);
+	assertEquals(Deno.statSync(filename).len, 20);
+	Deno.ftruncateSync(file.rid, 2);
+	assertEquals(readDataSync(filename), "He");
+	file.close();
+	Deno.removeSync(filename);
+});
+
+testPerm(
+	{ read: true, write: true },
+	async function fallocateSuccess(): Promise<void> {
+		const filename = Deno.makeTempDirSync() + "/test_fallocate.txt";
+		await Deno.writeFile(filename, new TextEncoder().encode("Hello"));
+		const file = await Deno.open(filename, "r+");
+		await Deno.fallocate(file.rid, 4096);
+		assertEquals(Deno.statSync(filename).len, 4096);
+		// Preallocating less than the current length leaves the file alone.
+		await Deno.fallocate(file.rid, 1);
+		assertEquals(Deno.statSync(filename).len, 4096);
+		assertEquals((await readData(filename)).slice(0, 5), "Hello");
+		file.close();
+		await Deno.remove(filename);
+	},
+);
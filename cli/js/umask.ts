@@ -0,0 +1,13 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
+
+/** Returns the process umask. If `mask` is given it becomes the new umask
+ * and the previous one is returned. Setting it requires write permission for
+ * all paths. Unix only at the moment.
+ *
+ *       const previous = Deno.umask(0o077);
+ */
+export function umask(mask?: number): number {
+	return sendSync(dispatch.OP_UMASK, { mask }).previous;
+}
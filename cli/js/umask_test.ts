@@ -0,0 +1,27 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+import { assertEquals, test, testPerm } from "./test_util.ts";
+
+if (Deno.build.os !== "win") {
+	test(function umaskRead(): void {
+		const mask = Deno.umask();
+		assertEquals(Deno.umask(), mask);
+	});
+
+	testPerm({ write: true }, function umaskSet(): void {
+		const previous = Deno.umask(0o077);
+		assertEquals(Deno.umask(previous), 0o077);
+		assertEquals(Deno.umask(), previous);
+	});
+
+	testPerm({ write: false }, function umaskSetPerm(): void {
+		const previous = Deno.umask();
+		let err;
+		try {
+			Deno.umask(0o077);
+		} catch (e) {
+			err = e;
+		}
+		assertEquals(err.kind, Deno.ErrorKind.PermissionDenied);
+		assertEquals(Deno.umask(), previous);
+	});
+}
@@ -3,17 +3,21 @@
 // This test is executed as part of tools/test.py
 // But it can also be run manually: ./target/debug/deno cli/js/unit_tests.ts
 
+import "./abort_controller_test.ts";
 import "./blob_test.ts";
 import "./body_test.ts";
 import "./buffer_test.ts";
 import "./build_test.ts";
 import "./chmod_test.ts";
 import "./chown_test.ts";
+import "./compression_test.ts";
 import "./console_test.ts";
 import "./copy_file_test.ts";
+import "./copy_dir_test.ts";
 import "./custom_event_test.ts";
 import "./dir_test.ts";
 import "./dispatch_json_test.ts";
+import "./encoding_test.ts";
 import "./error_stack_test.ts";
 import "./event_test.ts";
 import "./event_target_test.ts";
@@ -21,9 +25,12 @@ import "./fetch_test.ts";
 import "./file_test.ts";
 import "./files_test.ts";
 import "./form_data_test.ts";
+import "./fs_events_test.ts";
 import "./get_random_values_test.ts";
 import "./globals_test.ts";
+import "./hash_test.ts";
 import "./headers_test.ts";
+import "./interval_test.ts";
 import "./link_test.ts";
 import "./location_test.ts";
 import "./make_temp_dir_test.ts";
@@ -38,14 +45,17 @@ import "./read_dir_test.ts";
 import "./read_file_test.ts";
 import "./read_link_test.ts";
 import "./rename_test.ts";
+import "./repl_test.ts";
 import "./request_test.ts";
 import "./resources_test.ts";
+import "./signals_test.ts";
 import "./stat_test.ts";
 import "./symlink_test.ts";
 import "./text_encoding_test.ts";
 import "./timers_test.ts";
 import "./tls_test.ts";
 import "./truncate_test.ts";
+import "./umask_test.ts";
 import "./url_test.ts";
 import "./url_search_params_test.ts";
 import "./utime_test.ts";
@@ -1,8 +1,10 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
 /* eslint-disable @typescript-eslint/no-explicit-any */
 import { blobBytesWeakMap } from "./blob.ts";
+import { core } from "./core.ts";
 import { sendAsync, sendSync } from "./dispatch_json.ts";
 import * as dispatch from "./dispatch.ts";
+import { DenoError, ErrorKind } from "./errors.ts";
 import { TextDecoder, TextEncoder } from "./text_encoding.ts";
 import { blobURLMap } from "./url.ts";
 import { log } from "./util.ts";
@@ -11,14 +13,128 @@ import { window } from "./window.ts";
 const encoder = new TextEncoder();
 const decoder = new TextDecoder();
 
-export function encodeMessage(data: any): Uint8Array {
-	const dataJson = JSON.stringify(data);
-	return encoder.encode(dataJson);
+// Messages with transferred buffers start with a zero byte, which plain JSON
+// never does, followed by the JSON list of transfer ids, another zero byte and
+// the JSON of the data. The data refers to buffer `i` of the list as
+// `{ [TRANSFER_KEY]: i }`, plus the view type and bounds for typed arrays.
+// The buffers' contents are moved by `core.transferArrayBuffer`, not copied.
+const TRANSFER_KEY = "\u0000transfer";
+
+const viewConstructors: { [name: string]: any } = {
+	Int8Array,
+	Uint8Array,
+	Uint8ClampedArray,
+	Int16Array,
+	Uint16Array,
+	Int32Array,
+	Uint32Array,
+	Float32Array,
+	Float64Array,
+	DataView,
+};
+
+function replaceTransferred(
+	transfer: ArrayBuffer[],
+): (key: string, value: any) => any {
+	return (_key: string, value: any): any => {
+		if (value instanceof ArrayBuffer) {
+			const index = transfer.indexOf(value);
+			return index < 0 ? value : { [TRANSFER_KEY]: index };
+		}
+		if (ArrayBuffer.isView(value)) {
+			const index = transfer.indexOf(value.buffer as ArrayBuffer);
+			if (index >= 0) {
+				return {
+					[TRANSFER_KEY]: index,
+					view: value.constructor.name,
+					byteOffset: value.byteOffset,
+					length:
+						value instanceof DataView
+							? value.byteLength
+							: (value as any).length,
+				};
+			}
+		}
+		return value;
+	};
+}
+
+/** Encodes `data` as a worker message. The contents of the buffers in
+ * `transfer` are moved to the receiver without copying, and the buffers are
+ * detached, as with `postMessage` in web Workers. */
+export function encodeMessage(
+	data: any,
+	transfer: ArrayBuffer[] = [],
+): Uint8Array {
+	if (transfer.length === 0) {
+		const dataJson = JSON.stringify(data);
+		return encoder.encode(dataJson);
+	}
+	transfer.forEach((buffer, i): void => {
+		if (!(buffer instanceof ArrayBuffer)) {
+			throw new DenoError(
+				ErrorKind.DataCloneError,
+				"Only ArrayBuffers can be transferred",
+			);
+		}
+		if (transfer.indexOf(buffer) !== i) {
+			throw new DenoError(
+				ErrorKind.DataCloneError,
+				"An ArrayBuffer is listed more than once in the transfer list",
+			);
+		}
+	});
+
+	// Views are described before their buffers are detached.
+	const dataJson = JSON.stringify(data, replaceTransferred(transfer));
+	const ids = transfer.map((buffer): number => {
+		try {
+			return core.transferArrayBuffer(buffer);
+		} catch (e) {
+			throw new DenoError(
+				ErrorKind.DataCloneError,
+				"An ArrayBuffer in the transfer list can't be transferred",
+			);
+		}
+	});
+	return encoder.encode(`\u0000${JSON.stringify(ids)}\u0000${dataJson}`);
 }
 
 export function decodeMessage(dataIntArray: Uint8Array): any {
-	const dataJson = decoder.decode(dataIntArray);
-	return JSON.parse(dataJson);
+	if (dataIntArray[0] !== 0) {
+		const dataJson = decoder.decode(dataIntArray);
+		return JSON.parse(dataJson);
+	}
+
+	const separator = dataIntArray.indexOf(0, 1);
+	const ids: number[] = JSON.parse(
+		decoder.decode(dataIntArray.subarray(1, separator)),
+	);
+	const dataJson = decoder.decode(dataIntArray.subarray(separator + 1));
+	const buffers: ArrayBuffer[] = [];
+	const buffer = (index: number): ArrayBuffer => {
+		if (buffers[index] === undefined) {
+			buffers[index] = core.receiveArrayBuffer(ids[index]);
+		}
+		return buffers[index];
+	};
+	return JSON.parse(dataJson, (_key: string, value: any): any => {
+		if (value === null || typeof value !== "object") {
+			return value;
+		}
+		if (!(TRANSFER_KEY in value)) {
+			return value;
+		}
+		if (value.view === undefined) {
+			return buffer(value[TRANSFER_KEY]);
+		}
+		const ctor = viewConstructors[value.view];
+		return new ctor(
+			buffer(value[TRANSFER_KEY]),
+			value.byteOffset,
+			value.length,
+		);
+	});
 }
 
 function createWorker(
@@ -26,12 +142,16 @@ function createWorker(
 	includeDenoNamespace: boolean,
 	hasSourceCode: boolean,
 	sourceCode: Uint8Array,
+	name?: string,
+	permissions?: WorkerPermissions,
 ): number {
 	return sendSync(dispatch.OP_CREATE_WORKER, {
 		specifier,
 		includeDenoNamespace,
 		hasSourceCode,
 		sourceCode: new TextDecoder().decode(sourceCode),
+		name,
+		permissions,
 	});
 }
 
@@ -39,8 +159,12 @@ async function hostGetWorkerClosed(id: number): Promise<void> {
 	await sendAsync(dispatch.OP_HOST_GET_WORKER_CLOSED, { id });
 }
 
-function hostPostMessage(id: number, data: any): void {
-	const dataIntArray = encodeMessage(data);
+function hostPostMessage(
+	id: number,
+	data: any,
+	transfer?: ArrayBuffer[],
+): void {
+	const dataIntArray = encodeMessage(data, transfer);
 	sendSync(dispatch.OP_HOST_POST_MESSAGE, { id }, dataIntArray);
 }
 
@@ -57,8 +181,8 @@ async function hostGetMessage(id: number): Promise<any> {
 // Stuff for workers
 export const onmessage: (e: { data: any }) => void = (): void => {};
 
-export function postMessage(data: any): void {
-	const dataIntArray = encodeMessage(data);
+export function postMessage(data: any, transfer?: ArrayBuffer[]): void {
+	const dataIntArray = encodeMessage(data, transfer);
 	sendSync(dispatch.OP_WORKER_POST_MESSAGE, {}, dataIntArray);
 }
 
@@ -72,6 +196,23 @@ export async function getMessage(): Promise<any> {
 	}
 }
 
+/** Posts a message to the worker created with `{ name }`, from any module.
+ * Its replies go to the worker that created it, as usual. Throws a
+ * `WouldBlock` error if the worker has too many messages queued. */
+export function postMessageToWorker(
+	name: string,
+	data: any,
+	transfer?: ArrayBuffer[],
+): void {
+	const dataIntArray = encodeMessage(data, transfer);
+	sendSync(dispatch.OP_NAMED_WORKER_POST_MESSAGE, { name }, dataIntArray);
+}
+
+/** Returns the names of the live workers created with `{ name }`. */
+export function namedWorkers(): string[] {
+	return sendSync(dispatch.OP_NAMED_WORKERS);
+}
+
 export let isClosing = false;
 
 export function workerClose(): void {
@@ -103,10 +244,10 @@ export async function workerMain(): Promise<void> {
 }
 
 export interface Worker {
-	onerror?: () => void;
+	onerror?: (e: Error) => void;
 	onmessage?: (e: { data: any }) => void;
 	onmessageerror?: () => void;
-	postMessage(data: any): void;
+	postMessage(data: any, transfer?: ArrayBuffer[]): void;
 	closed: Promise<void>;
 }
 
@@ -114,19 +255,41 @@ export interface Worker {
 // eslint-disable-next-line @typescript-eslint/no-empty-interface
 export interface WorkerOptions {}
 
+/** Permissions a worker keeps when created with reduced permissions. Each
+ * one is kept only if the creating worker has it, the others are denied.
+ */
+export interface WorkerPermissions {
+	read?: boolean;
+	write?: boolean;
+	net?: boolean;
+	env?: boolean;
+	run?: boolean;
+	plugin?: boolean;
+	hrtime?: boolean;
+	ping?: boolean;
+}
+
 /** Extended Deno Worker initialization options.
  * `noDenoNamespace` hides global `window.Deno` namespace for
  * spawned worker and nested workers spawned by it (default: false).
+ * `name` registers the worker so that any module can message it with
+ * `Deno.postMessageToWorker()`. Names must be unique among live workers.
+ * `permissions` gives the worker its own, reduced set of permissions
+ * instead of sharing the creating worker's, e.g.
+ *
+ *       new Worker("./parser.ts", { permissions: { net: true } });
  */
 export interface DenoWorkerOptions extends WorkerOptions {
 	noDenoNamespace?: boolean;
+	name?: string;
+	permissions?: WorkerPermissions;
 }
 
 export class WorkerImpl implements Worker {
 	private readonly id: number;
 	private isClosing = false;
 	private readonly isClosedPromise: Promise<void>;
-	public onerror?: () => void;
+	public onerror?: (e: Error) => void;
 	public onmessage?: (data: any) => void;
 	public onmessageerror?: () => void;
 
@@ -159,20 +322,30 @@ export class WorkerImpl implements Worker {
 			includeDenoNamespace,
 			hasSourceCode,
 			sourceCode,
+			options && options.name,
+			options && options.permissions,
 		);
 		this.run();
 		this.isClosedPromise = hostGetWorkerClosed(this.id);
-		this.isClosedPromise.then((): void => {
-			this.isClosing = true;
-		});
+		this.isClosedPromise.then(
+			(): void => {
+				this.isClosing = true;
+			},
+			(e: Error): void => {
+				this.isClosing = true;
+				if (this.onerror) {
+					this.onerror(e);
+				}
+			},
+		);
 	}
 
 	get closed(): Promise<void> {
 		return this.isClosedPromise;
 	}
 
-	postMessage(data: any): void {
-		hostPostMessage(this.id, data);
+	postMessage(data: any, transfer?: ArrayBuffer[]): void {
+		hostPostMessage(this.id, data, transfer);
 	}
 
 	private async run(): Promise<void> {
@@ -1,18 +1,46 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
-import { writeAll, writeAllSync } from "./buffer.ts";
-import { chmod, chmodSync } from "./chmod.ts";
-import { open, openSync } from "./files.ts";
-import { stat, statSync } from "./stat.ts";
+import { sendAsync, sendSync } from "./dispatch_json.ts";
+import * as dispatch from "./dispatch.ts";
 
 /** Options for writing to a file.
  * `perm` would change the file's permission if set.
  * `create` decides if the file should be created if not exists (default: true)
  * `append` decides if the file should be appended (default: false)
+ * `atomic` writes to a temporary file in the same directory and renames it
+ * over the target, keeping the target's mode if it exists (default: false)
+ * `sync` flushes an atomic write to disk before returning (default: false)
  */
 export interface WriteFileOptions {
 	perm?: number;
 	create?: boolean;
 	append?: boolean;
+	atomic?: boolean;
+	sync?: boolean;
+}
+
+function writeFileArgs(
+	filename: string,
+	options: WriteFileOptions,
+): {
+	filename: string;
+	create: boolean;
+	append: boolean;
+	perm?: number;
+	atomic: boolean;
+	sync: boolean;
+} {
+	if (options.append && options.atomic) {
+		throw new TypeError("append and atomic can't be used together");
+	}
+	const hasPerm = options.perm !== undefined && options.perm !== null;
+	return {
+		filename,
+		create: options.create !== false,
+		append: !!options.append,
+		perm: hasPerm ? options.perm! : undefined,
+		atomic: !!options.atomic,
+		sync: !!options.sync,
+	};
 }
 
 /** Write a new file, with given filename and data synchronously.
@@ -26,23 +54,7 @@ export function writeFileSync(
 	data: Uint8Array,
 	options: WriteFileOptions = {},
 ): void {
-	if (options.create !== undefined) {
-		const create = !!options.create;
-		if (!create) {
-			// verify that file exists
-			statSync(filename);
-		}
-	}
-
-	const openMode = !!options.append ? "a" : "w";
-	const file = openSync(filename, openMode);
-
-	if (options.perm !== undefined && options.perm !== null) {
-		chmodSync(filename, options.perm);
-	}
-
-	writeAllSync(file, data);
-	file.close();
+	sendSync(dispatch.OP_WRITE_FILE, writeFileArgs(filename, options), data);
 }
 
 /** Write a new file, with given filename and data.
@@ -56,21 +68,9 @@ export async function writeFile(
 	data: Uint8Array,
 	options: WriteFileOptions = {},
 ): Promise<void> {
-	if (options.create !== undefined) {
-		const create = !!options.create;
-		if (!create) {
-			// verify that file exists
-			await stat(filename);
-		}
-	}
-
-	const openMode = !!options.append ? "a" : "w";
-	const file = await open(filename, openMode);
-
-	if (options.perm !== undefined && options.perm !== null) {
-		await chmod(filename, options.perm);
-	}
-
-	await writeAll(file, data);
-	file.close();
+	await sendAsync(
+		dispatch.OP_WRITE_FILE,
+		writeFileArgs(filename, options),
+		data,
+	);
 }
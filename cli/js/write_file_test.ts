@@ -220,3 +220,61 @@ testPerm(
 		assertEquals("Hello", actual);
 	},
 );
+
+testPerm(
+	{ read: true, write: true },
+	async function writeFileAtomic(): Promise<void> {
+		const enc = new TextEncoder();
+		const dec = new TextDecoder("utf-8");
+		const tempDir = Deno.makeTempDirSync();
+		const filename = tempDir + "/test.txt";
+		await Deno.writeFile(filename, enc.encode("Hello"), { atomic: true });
+		assertEquals(dec.decode(Deno.readFileSync(filename)), "Hello");
+
+		if (Deno.build.os !== "win") {
+			Deno.chmodSync(filename, 0o640);
+		}
+		Deno.writeFileSync(filename, enc.encode("World"), {
+			atomic: true,
+			sync: true,
+		});
+		assertEquals(dec.decode(Deno.readFileSync(filename)), "World");
+		if (Deno.build.os !== "win") {
+			assertEquals(Deno.statSync(filename).mode! & 0o777, 0o640);
+		}
+		// No temporary files are left behind.
+		assertEquals(Deno.readDirSync(tempDir).length, 1);
+	},
+);
+
+testPerm({ read: true, write: true }, function writeFileAtomicAppend(): void {
+	const filename = Deno.makeTempDirSync() + "/test.txt";
+	let err;
+	try {
+		Deno.writeFileSync(filename, new Uint8Array([1]), {
+			atomic: true,
+			append: true,
+		});
+	} catch (e) {
+		err = e;
+	}
+	assert(err instanceof TypeError);
+});
+
+testPerm(
+	{ read: true, write: true },
+	function writeFileSyncAtomicNotCreate(): void {
+		const filename = Deno.makeTempDirSync() + "/test.txt";
+		let caughtError = false;
+		try {
+			Deno.writeFileSync(filename, new Uint8Array([1]), {
+				atomic: true,
+				create: false,
+			});
+		} catch (e) {
+			caughtError = true;
+			assertEquals(e.kind, Deno.ErrorKind.NotFound);
+		}
+		assert(caughtError);
+	},
+);
@@ -24,7 +24,8 @@ pub mod deno_dir;
 pub mod deno_error;
 pub mod diagnostics;
 mod disk_cache;
-mod file_fetcher;
+mod dns;
+pub mod file_fetcher;
 pub mod flags;
 pub mod fmt_errors;
 mod fs;
@@ -35,11 +36,16 @@ mod http_util;
 mod import_map;
 mod js;
 mod lockfile;
-mod metrics;
+pub mod logger;
+pub mod metrics;
 pub mod msg;
+mod multipart;
+mod net_allowlist;
 pub mod ops;
 pub mod permissions;
-mod progress;
+mod ping;
+mod priority;
+pub mod progress;
 mod repl;
 pub mod resolve_addr;
 mod shell;
@@ -49,14 +55,15 @@ mod startup_data;
 pub mod state;
 pub mod test_util;
 mod tokio_util;
+mod vendor;
 pub mod version;
 pub mod worker;
+pub mod worker_pool;
 
-use std::env;
+use std::{env, path::Path};
 
 use deno::{v8_set_flags, ErrBox, ModuleSpecifier};
 use flags::{DenoFlags, DenoSubcommand};
-use log::{Level, Metadata, Record};
 
 use crate::{
 	deno_error::{js_check, print_err_and_exit, print_msg_and_exit},
@@ -67,29 +74,6 @@ use crate::{
 	worker::Worker,
 };
 
-static LOGGER:Logger = Logger;
-
-struct Logger;
-
-impl log::Log for Logger {
-	fn enabled(&self, metadata:&Metadata) -> bool { metadata.level() <= log::max_level() }
-
-	fn log(&self, record:&Record) {
-		if self.enabled(record.metadata()) {
-			let mut target = record.target().to_string();
-
-			if let Some(line_no) = record.line() {
-				target.push_str(":");
-				target.push_str(&line_no.to_string());
-			}
-
-			println!("{} RS - {} - {}", record.level(), target, record.args());
-		}
-	}
-
-	fn flush(&self) {}
-}
-
 fn create_worker_and_state(flags:DenoFlags) -> (Worker, ThreadSafeGlobalState) {
 	use std::sync::{Arc, Mutex};
 
@@ -129,11 +113,32 @@ fn create_worker_and_state(flags:DenoFlags) -> (Worker, ThreadSafeGlobalState) {
 		resource_table.add("stderr", Box::new(stderr));
 	}
 
-	let worker = Worker::new("main".to_string(), startup_data::deno_isolate_init(), state, ext);
+	let startup_data = startup_data::user_isolate_init(&global_state.flags);
+	let worker = Worker::new("main".to_string(), startup_data, state, ext);
 
 	(worker, global_state)
 }
 
+/// Starts the `--metrics-addr` endpoint, if requested. Op and module counters
+/// come from the worker, compiler starts from the global state.
+fn start_metrics_server(worker:&Worker) -> Option<metrics::MetricsServer> {
+	let state = worker.state.clone();
+	let addr = state.global_state.flags.metrics_addr.as_ref()?;
+	let addr = addr.parse().expect("validated by the flags parser");
+	let sample = move || {
+		let mut snapshot = state.metrics.snapshot();
+		snapshot.compiler_starts = state.global_state.metrics.snapshot().compiler_starts;
+		snapshot
+	};
+	match metrics::serve(&addr, sample) {
+		Ok(server) => Some(server),
+		Err(err) => {
+			print_err_and_exit(err);
+			None
+		},
+	}
+}
+
 fn types_command() {
 	let content = crate::js::get_asset("lib.deno_runtime.d.ts").unwrap();
 	println!("{}", content);
@@ -179,6 +184,7 @@ async fn print_file_info(worker:Worker, module_specifier:ModuleSpecifier) {
 	let maybe_compiled = global_state_.clone().fetch_compiled_module(&module_specifier, None).await;
 	if let Err(e) = maybe_compiled {
 		debug!("compiler error exiting!");
+		ops::io::flush_stdio();
 		eprintln!("\n{}", e.to_string());
 		std::process::exit(1);
 	}
@@ -254,6 +260,11 @@ fn fetch_command(flags:DenoFlags) {
 	let main_future = async move {
 		let result = worker.execute_mod_async(&main_module, None, true).await;
 		js_check(result);
+		if let Some(ref dir) = state.flags.vendor_dir {
+			if let Err(err) = vendor::vendor(&worker.state, &main_module, Path::new(dir)) {
+				print_err_and_exit(err);
+			}
+		}
 		Ok(())
 	};
 
@@ -341,6 +352,7 @@ fn run_script(flags:DenoFlags) {
 	let mut worker_ = worker.clone();
 
 	let main_future = async move {
+		let _metrics_server = start_metrics_server(&worker);
 		let mod_result = worker.execute_mod_async(&main_module, None, false).await;
 		if let Err(err) = mod_result {
 			print_err_and_exit(err);
@@ -352,6 +364,7 @@ fn run_script(flags:DenoFlags) {
 					print_err_and_exit(ErrBox::from(e));
 				}
 			} else {
+				ops::io::flush_stdio();
 				eprintln!("--lock flag must be specified when using --lock-write");
 				std::process::exit(11);
 			}
@@ -374,9 +387,10 @@ pub fn main() {
 	#[cfg(windows)]
 	ansi_term::enable_ansi_support().ok(); // For Windows 10
 
-	log::set_logger(&LOGGER).unwrap();
 	let args:Vec<String> = env::args().collect();
 	let flags = flags::flags_from_vec(args);
+	logger::init(&flags);
+	ops::io::set_line_buffered(flags.line_buffered);
 
 	if let Some(ref v8_flags) = flags.v8_flags {
 		let mut v8_flags_ = v8_flags.clone();
@@ -384,12 +398,6 @@ pub fn main() {
 		v8_set_flags(v8_flags_);
 	}
 
-	let log_level = match flags.log_level {
-		Some(level) => level,
-		None => Level::Warn,
-	};
-	log::set_max_level(log_level.to_level_filter());
-
 	match flags.subcommand {
 		DenoSubcommand::Bundle => bundle_command(flags),
 		DenoSubcommand::Completions => {},
@@ -401,4 +409,5 @@ pub fn main() {
 		DenoSubcommand::Types => types_command(),
 		_ => panic!("bad subcommand"),
 	}
+	ops::io::flush_stdio();
 }
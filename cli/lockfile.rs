@@ -1,10 +1,18 @@
 use std::{collections::HashMap, io::Result};
 
+use deno::ErrBox;
 use serde_json::json;
 pub use serde_json::Value;
 
-use crate::compilers::CompiledModule;
+use crate::deno_error::{DenoError, ErrorKind};
 
+/// Version of the lock file format. Version 1 files were a bare map of
+/// checksums of the compiled output, which can't be checked against fetched
+/// sources, so they are rejected and have to be written again.
+const VERSION:u64 = 2;
+
+/// Maps remote module specifiers to the SHA-256 of their source, so that a
+/// module changing on the server or in the cache is noticed on load.
 pub struct Lockfile {
 	need_read:bool,
 	map:HashMap<String, String>,
@@ -17,7 +25,10 @@ impl Lockfile {
 	}
 
 	pub fn write(&self) -> Result<()> {
-		let j = json!(self.map);
+		let j = json!({
+		  "version": VERSION,
+		  "remote": self.map,
+		});
 		let s = serde_json::to_string_pretty(&j).unwrap();
 		let mut f = std::fs::OpenOptions::new()
 			.write(true)
@@ -30,38 +41,94 @@ impl Lockfile {
 		Ok(())
 	}
 
-	pub fn read(&mut self) -> Result<()> {
+	pub fn read(&mut self) -> std::result::Result<(), ErrBox> {
 		debug!("lockfile read {}", self.filename);
 		let s = std::fs::read_to_string(&self.filename)?;
-		self.map = serde_json::from_str(&s)?;
+		let mut value:Value = serde_json::from_str(&s)?;
+		if value.get("version").and_then(Value::as_u64) != Some(VERSION) {
+			return Err(DenoError::new(
+				ErrorKind::InvalidData,
+				format!(
+					"Lock file {} has an unsupported format, write it again with --lock-write",
+					self.filename
+				),
+			)
+			.into());
+		}
+		self.map = serde_json::from_value(value["remote"].take())?;
 		self.need_read = false;
 		Ok(())
 	}
 
-	/// Lazily reads the filename, checks the given module is included.
-	/// Returns Ok(true) if check passed
-	pub fn check(&mut self, m:&CompiledModule) -> Result<bool> {
-		if m.name.starts_with("file:") {
-			return Ok(true);
+	/// Lazily reads the filename, checks that `source_code` has the checksum
+	/// recorded for `specifier`. A module missing from the lockfile fails the
+	/// check too. Local modules are not checked.
+	pub fn check(&mut self, specifier:&str, source_code:&[u8]) -> std::result::Result<(), ErrBox> {
+		if specifier.starts_with("file:") {
+			return Ok(());
 		}
 		if self.need_read {
 			self.read()?;
 		}
 		assert!(!self.need_read);
-		Ok(if let Some(lockfile_checksum) = self.map.get(&m.name) {
-			let compiled_checksum = crate::checksum::gen2(&m.code);
-			lockfile_checksum == &compiled_checksum
-		} else {
-			false
-		})
+		let msg = match self.map.get(specifier) {
+			Some(checksum) if *checksum == crate::checksum::gen(vec![source_code]) => return Ok(()),
+			Some(_) => "Subresource integrity check failed",
+			None => "Module is missing from the lock file",
+		};
+		Err(DenoError::new(
+			ErrorKind::InvalidData,
+			format!("{} --lock={}\n{}", msg, self.filename, specifier),
+		)
+		.into())
 	}
 
 	// Returns true if module was not already inserted.
-	pub fn insert(&mut self, m:&CompiledModule) -> bool {
-		if m.name.starts_with("file:") {
+	pub fn insert(&mut self, specifier:&str, source_code:&[u8]) -> bool {
+		if specifier.starts_with("file:") {
 			return false;
 		}
-		let checksum = crate::checksum::gen2(&m.code);
-		self.map.insert(m.name.clone(), checksum).is_none()
+		let checksum = crate::checksum::gen(vec![source_code]);
+		self.map.insert(specifier.to_string(), checksum).is_none()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use tempfile::TempDir;
+
+	use super::*;
+	use crate::deno_error::GetErrorKind;
+
+	#[test]
+	fn check_written_lockfile() {
+		let temp_dir = TempDir::new().unwrap();
+		let filename = temp_dir.path().join("lock.json").to_string_lossy().to_string();
+		let specifier = "https://deno.land/std/fmt/colors.ts";
+		let mut lockfile = Lockfile::new(filename.clone());
+		assert!(lockfile.insert(specifier, b"export {};\n"));
+		assert!(!lockfile.insert("file:///main.ts", b"import \"./dep.ts\";\n"));
+		lockfile.write().unwrap();
+
+		let mut lockfile = Lockfile::new(filename);
+		lockfile.check(specifier, b"export {};\n").unwrap();
+		lockfile.check("file:///main.ts", b"anything").unwrap();
+		let err = lockfile.check(specifier, b"export const evil = 1;\n").unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::InvalidData);
+		assert!(err.to_string().contains(specifier));
+		let err = lockfile.check("https://deno.land/std/path/mod.ts", b"").unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::InvalidData);
+	}
+
+	#[test]
+	fn check_old_format() {
+		let temp_dir = TempDir::new().unwrap();
+		let filename = temp_dir.path().join("lock.json").to_string_lossy().to_string();
+		let specifier = "https://deno.land/std/fmt/colors.ts";
+		std::fs::write(&filename, format!("{{\"{}\": \"00\"}}", specifier)).unwrap();
+		let mut lockfile = Lockfile::new(filename);
+		let err = lockfile.check(specifier, b"export {};\n").unwrap_err();
+		assert_eq!(err.kind(), ErrorKind::InvalidData);
+		assert!(err.to_string().contains("--lock-write"));
 	}
 }
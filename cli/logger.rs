@@ -0,0 +1,191 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+//! Leveled logging for the `log` macros used throughout the crate. Records go
+//! to a `LogSink`, by default stderr as text or JSON lines, and embedders may
+//! install their own with `set_sink()`.
+use std::{
+	io::Write,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		RwLock,
+	},
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{Level, LevelFilter, Metadata, Record};
+
+use crate::flags::DenoFlags;
+
+/// How the default sink renders records.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogFormat {
+	/// `LEVEL RS - target:line - message`.
+	Text,
+	/// One JSON object per line, so records can be parsed by other tools.
+	Json,
+}
+
+impl Default for LogFormat {
+	fn default() -> Self { LogFormat::Text }
+}
+
+/// A log record handed to a `LogSink`.
+#[derive(Debug)]
+pub struct LogRecord<'a> {
+	pub level:Level,
+	/// Module path of the emitting code, e.g. `deno_cli::worker`.
+	pub target:&'a str,
+	pub file:Option<&'a str>,
+	pub line:Option<u32>,
+	pub message:String,
+	/// Milliseconds since the Unix epoch.
+	pub timestamp:u64,
+}
+
+impl<'a> LogRecord<'a> {
+	pub fn to_json(&self) -> String {
+		json!({
+		  "level": self.level.to_string(),
+		  "target": self.target,
+		  "file": self.file,
+		  "line": self.line,
+		  "message": self.message,
+		  "timestamp": self.timestamp,
+		})
+		.to_string()
+	}
+
+	pub fn to_text(&self) -> String {
+		match self.line {
+			Some(line) => {
+				format!("{} RS - {}:{} - {}", self.level, self.target, line, self.message)
+			},
+			None => format!("{} RS - {} - {}", self.level, self.target, self.message),
+		}
+	}
+}
+
+/// Destination of log records.
+pub trait LogSink: Send + Sync {
+	fn log(&self, record:&LogRecord);
+
+	fn flush(&self) {}
+}
+
+/// The sink used unless `set_sink()` replaced it.
+pub struct StdioSink {
+	pub format:LogFormat,
+}
+
+impl LogSink for StdioSink {
+	fn log(&self, record:&LogRecord) {
+		let line = match self.format {
+			LogFormat::Text => record.to_text(),
+			LogFormat::Json => record.to_json(),
+		};
+		// Logging must not fail the program, e.g. when stderr is a closed pipe.
+		let _ = writeln!(std::io::stderr(), "{}", line);
+	}
+
+	fn flush(&self) {
+		let _ = std::io::stderr().flush();
+	}
+}
+
+lazy_static! {
+	static ref SINK:RwLock<Box<dyn LogSink>> =
+		RwLock::new(Box::new(StdioSink { format:LogFormat::Text }));
+}
+
+/// Set once `set_sink()` was called, so that `init()` keeps that sink.
+static CUSTOM_SINK:AtomicBool = AtomicBool::new(false);
+
+static LOGGER:Logger = Logger;
+
+struct Logger;
+
+impl log::Log for Logger {
+	fn enabled(&self, metadata:&Metadata) -> bool { metadata.level() <= log::max_level() }
+
+	fn log(&self, record:&Record) {
+		if !self.enabled(record.metadata()) {
+			return;
+		}
+		let timestamp = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|d| d.as_millis() as u64)
+			.unwrap_or(0);
+		let record = LogRecord {
+			level:record.level(),
+			target:record.target(),
+			file:record.file(),
+			line:record.line(),
+			message:record.args().to_string(),
+			timestamp,
+		};
+		SINK.read().unwrap().log(&record);
+	}
+
+	fn flush(&self) { SINK.read().unwrap().flush(); }
+}
+
+/// Installs the logger with the level and format from `flags`. Without
+/// `--log-level` only warnings and errors are logged. Calling it again only
+/// updates the level and format. A sink installed with `set_sink()` is kept.
+pub fn init(flags:&DenoFlags) {
+	// Fails if a logger is already installed, which is fine.
+	let _ = log::set_logger(&LOGGER);
+	let level = flags.log_level.unwrap_or(Level::Warn);
+	log::set_max_level(level.to_level_filter());
+	if !CUSTOM_SINK.load(Ordering::SeqCst) {
+		*SINK.write().unwrap() = Box::new(StdioSink { format:flags.log_format });
+	}
+}
+
+/// Routes all further records to `sink`, e.g. to forward them to an
+/// embedder's own logging. The level is still set by `init()` or
+/// `set_max_level()`.
+pub fn set_sink(sink:Box<dyn LogSink>) {
+	*SINK.write().unwrap() = sink;
+	CUSTOM_SINK.store(true, Ordering::SeqCst);
+}
+
+pub fn set_max_level(level:LevelFilter) { log::set_max_level(level); }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn record(line:Option<u32>) -> LogRecord<'static> {
+		LogRecord {
+			level:Level::Warn,
+			target:"deno_cli::worker",
+			file:Some("cli/worker.rs"),
+			line,
+			message:"worker \"a\" exited".to_string(),
+			timestamp:1_500_000_000_000,
+		}
+	}
+
+	#[test]
+	fn text_format() {
+		let text = record(Some(12)).to_text();
+		assert_eq!(text, "WARN RS - deno_cli::worker:12 - worker \"a\" exited");
+		assert_eq!(record(None).to_text(), "WARN RS - deno_cli::worker - worker \"a\" exited");
+	}
+
+	#[test]
+	fn json_format() {
+		let value:serde_json::Value = serde_json::from_str(&record(Some(12)).to_json()).unwrap();
+		assert_eq!(
+			value,
+			json!({
+			  "level": "WARN",
+			  "target": "deno_cli::worker",
+			  "file": "cli/worker.rs",
+			  "line": 12,
+			  "message": "worker \"a\" exited",
+			  "timestamp": 1_500_000_000_000u64,
+			})
+		);
+	}
+}
@@ -1,5 +1,20 @@
-use std::sync::atomic::AtomicUsize;
+use std::{
+	collections::{BTreeMap, HashSet},
+	fmt::Write,
+	net::SocketAddr,
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Mutex,
+	},
+	time::Duration,
+};
 
+use deno::ErrBox;
+use hyper::{header, service::service_fn_ok, Body, Request, Response, Server, StatusCode};
+use tokio::{prelude::Future, sync::oneshot};
+
+/// Counters collected by a worker. The atomic fields stay public for
+/// compatibility, but prefer `snapshot()` to reading them one by one.
 #[derive(Default)]
 pub struct Metrics {
 	pub ops_dispatched:AtomicUsize,
@@ -9,4 +24,276 @@ pub struct Metrics {
 	pub bytes_received:AtomicUsize,
 	pub resolve_count:AtomicUsize,
 	pub compiler_starts:AtomicUsize,
+	/// Modules already counted in `resolve_count`, so that loads racing for a
+	/// shared dependency count it once.
+	resolved_modules:Mutex<HashSet<String>>,
+	/// Per op latencies, only recorded with `--profile-ops`.
+	op_latencies:Mutex<BTreeMap<String, OpLatency>>,
+}
+
+/// Upper bounds of the op latency histogram buckets in microseconds.
+pub const OP_LATENCY_BUCKETS_US:[u64; 6] = [10, 100, 1_000, 10_000, 100_000, 1_000_000];
+
+/// Latencies of one op, from dispatch until its result is ready.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OpLatency {
+	pub count:u64,
+	pub sum:Duration,
+	/// Calls per bucket of `OP_LATENCY_BUCKETS_US`, the last counting calls
+	/// slower than all of them. Unlike OpenMetrics buckets these aren't
+	/// cumulative.
+	pub buckets:[u64; 7],
+}
+
+impl OpLatency {
+	fn record(&mut self, elapsed:Duration) {
+		let us = elapsed.as_micros();
+		let bucket = OP_LATENCY_BUCKETS_US
+			.iter()
+			.position(|&bound| us <= u128::from(bound))
+			.unwrap_or(OP_LATENCY_BUCKETS_US.len());
+		self.count += 1;
+		self.sum += elapsed;
+		self.buckets[bucket] += 1;
+	}
+}
+
+/// Plain copy of `Metrics` taken by `Metrics::snapshot()`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetricsSnapshot {
+	pub ops_dispatched:usize,
+	pub ops_completed:usize,
+	pub bytes_sent_control:usize,
+	pub bytes_sent_data:usize,
+	pub bytes_received:usize,
+	pub resolve_count:usize,
+	pub compiler_starts:usize,
+	/// Keyed by op name, empty unless `--profile-ops` is on.
+	pub op_latencies:BTreeMap<String, OpLatency>,
+}
+
+impl Metrics {
+	/// Counts `url` as resolved unless it already was. Returns whether it was
+	/// counted.
+	pub fn record_resolve(&self, url:&str) -> bool {
+		if !self.resolved_modules.lock().unwrap().insert(url.to_string()) {
+			return false;
+		}
+		self.resolve_count.fetch_add(1, Ordering::SeqCst);
+		true
+	}
+
+	pub fn record_op_latency(&self, name:&str, elapsed:Duration) {
+		let mut latencies = self.op_latencies.lock().unwrap();
+		match latencies.get_mut(name) {
+			Some(latency) => latency.record(elapsed),
+			None => {
+				let mut latency = OpLatency::default();
+				latency.record(elapsed);
+				latencies.insert(name.to_string(), latency);
+			},
+		}
+	}
+
+	/// Reads all counters. The completion side is read before the dispatch
+	/// side, so a snapshot never reports more ops completed than dispatched
+	/// even while ops are in flight.
+	pub fn snapshot(&self) -> MetricsSnapshot {
+		let ops_completed = self.ops_completed.load(Ordering::SeqCst);
+		let bytes_received = self.bytes_received.load(Ordering::SeqCst);
+		MetricsSnapshot {
+			ops_dispatched:self.ops_dispatched.load(Ordering::SeqCst),
+			ops_completed,
+			bytes_sent_control:self.bytes_sent_control.load(Ordering::SeqCst),
+			bytes_sent_data:self.bytes_sent_data.load(Ordering::SeqCst),
+			bytes_received,
+			resolve_count:self.resolve_count.load(Ordering::SeqCst),
+			compiler_starts:self.compiler_starts.load(Ordering::SeqCst),
+			op_latencies:self.op_latencies.lock().unwrap().clone(),
+		}
+	}
+
+	/// Zeroes all counters and returns their values from just before, e.g. to
+	/// measure benchmark phases separately. Ops in flight during the reset
+	/// complete into the new phase, and modules are counted again when
+	/// resolved after it.
+	pub fn reset(&self) -> MetricsSnapshot {
+		self.resolved_modules.lock().unwrap().clear();
+		let op_latencies =
+			std::mem::replace(&mut *self.op_latencies.lock().unwrap(), BTreeMap::new());
+		let ops_completed = self.ops_completed.swap(0, Ordering::SeqCst);
+		let bytes_received = self.bytes_received.swap(0, Ordering::SeqCst);
+		MetricsSnapshot {
+			ops_dispatched:self.ops_dispatched.swap(0, Ordering::SeqCst),
+			ops_completed,
+			bytes_sent_control:self.bytes_sent_control.swap(0, Ordering::SeqCst),
+			bytes_sent_data:self.bytes_sent_data.swap(0, Ordering::SeqCst),
+			bytes_received,
+			resolve_count:self.resolve_count.swap(0, Ordering::SeqCst),
+			compiler_starts:self.compiler_starts.swap(0, Ordering::SeqCst),
+			op_latencies,
+		}
+	}
+}
+
+impl MetricsSnapshot {
+	/// Renders the counters in the OpenMetrics text exposition format.
+	pub fn to_openmetrics(&self) -> String {
+		let counters = [
+			("deno_ops_dispatched", "Ops dispatched from JavaScript.", self.ops_dispatched),
+			("deno_ops_completed", "Ops completed.", self.ops_completed),
+			("deno_bytes_sent_control", "Control bytes sent to ops.", self.bytes_sent_control),
+			("deno_bytes_sent_data", "Zero-copy bytes sent to ops.", self.bytes_sent_data),
+			("deno_bytes_received", "Bytes received from ops.", self.bytes_received),
+			("deno_modules_resolved", "Modules resolved.", self.resolve_count),
+			("deno_compiler_starts", "Compiler workers started.", self.compiler_starts),
+		];
+		let mut out = String::new();
+		for (name, help, value) in counters.iter() {
+			writeln!(out, "# TYPE {} counter", name).unwrap();
+			writeln!(out, "# HELP {} {}", name, help).unwrap();
+			writeln!(out, "{}_total {}", name, value).unwrap();
+		}
+		if !self.op_latencies.is_empty() {
+			let name = "deno_op_latency_seconds";
+			writeln!(out, "# TYPE {} histogram", name).unwrap();
+			writeln!(out, "# HELP {} Time from dispatch until an op's result is ready.", name)
+				.unwrap();
+			for (op, latency) in &self.op_latencies {
+				let mut cumulative = 0;
+				for (bound, count) in OP_LATENCY_BUCKETS_US.iter().zip(&latency.buckets) {
+					cumulative += count;
+					let le = *bound as f64 / 1e6;
+					writeln!(out, "{}_bucket{{op=\"{}\",le=\"{}\"}} {}", name, op, le, cumulative)
+						.unwrap();
+				}
+				writeln!(out, "{}_bucket{{op=\"{}\",le=\"+Inf\"}} {}", name, op, latency.count)
+					.unwrap();
+				writeln!(out, "{}_count{{op=\"{}\"}} {}", name, op, latency.count).unwrap();
+				let sum = latency.sum.as_secs_f64();
+				writeln!(out, "{}_sum{{op=\"{}\"}} {}", name, op, sum).unwrap();
+			}
+		}
+		out.push_str("# EOF\n");
+		out
+	}
+}
+
+/// Handle to the endpoint started by `serve()`. Dropping it shuts the server
+/// down, which lets the runtime exit once the main module is done.
+pub struct MetricsServer {
+	pub local_addr:SocketAddr,
+	shutdown:Option<oneshot::Sender<()>>,
+}
+
+impl Drop for MetricsServer {
+	fn drop(&mut self) {
+		if let Some(shutdown) = self.shutdown.take() {
+			let _ = shutdown.send(());
+		}
+	}
+}
+
+/// Binds `addr` and serves `sample()` at `/metrics` on the current Tokio
+/// runtime. Binding errors are returned right away rather than from the
+/// spawned server.
+pub fn serve<F>(addr:&SocketAddr, sample:F) -> Result<MetricsServer, ErrBox>
+where
+	F: Fn() -> MetricsSnapshot + Clone + Send + Sync + 'static, {
+	let builder = Server::try_bind(addr)?;
+	let server = builder.serve(move || {
+		let sample = sample.clone();
+		service_fn_ok(move |req:Request<Body>| {
+			if req.uri().path() != "/metrics" {
+				return Response::builder()
+					.status(StatusCode::NOT_FOUND)
+					.body(Body::empty())
+					.unwrap();
+			}
+			Response::builder()
+				.header(
+					header::CONTENT_TYPE,
+					"application/openmetrics-text; version=1.0.0; charset=utf-8",
+				)
+				.body(Body::from(sample().to_openmetrics()))
+				.unwrap()
+		})
+	});
+	let local_addr = server.local_addr();
+	let (shutdown, signal) = oneshot::channel::<()>();
+	tokio::spawn(
+		server
+			.with_graceful_shutdown(signal.map_err(|_| ()))
+			.map_err(|err| error!("metrics server error: {}", err)),
+	);
+	debug!("metrics server listening on {}", local_addr);
+	Ok(MetricsServer { local_addr, shutdown:Some(shutdown) })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn snapshot_and_reset() {
+		let metrics = Metrics::default();
+		metrics.ops_dispatched.fetch_add(3, Ordering::SeqCst);
+		metrics.ops_completed.fetch_add(2, Ordering::SeqCst);
+		metrics.bytes_received.fetch_add(10, Ordering::SeqCst);
+		assert!(metrics.record_resolve("file:///a.js"));
+		assert!(!metrics.record_resolve("file:///a.js"));
+
+		let expected = MetricsSnapshot {
+			ops_dispatched:3,
+			ops_completed:2,
+			bytes_received:10,
+			resolve_count:1,
+			..MetricsSnapshot::default()
+		};
+		assert_eq!(metrics.snapshot(), expected);
+		assert_eq!(metrics.reset(), expected);
+		assert_eq!(metrics.snapshot(), MetricsSnapshot::default());
+		assert!(metrics.record_resolve("file:///a.js"));
+		assert_eq!(metrics.snapshot().resolve_count, 1);
+	}
+
+	#[test]
+	fn openmetrics_text() {
+		let snapshot =
+			MetricsSnapshot { ops_dispatched:3, compiler_starts:1, ..MetricsSnapshot::default() };
+		let text = snapshot.to_openmetrics();
+		assert!(text.contains("# TYPE deno_ops_dispatched counter\n"));
+		assert!(text.contains("\ndeno_ops_dispatched_total 3\n"));
+		assert!(text.contains("\ndeno_compiler_starts_total 1\n"));
+		assert!(text.contains("\ndeno_bytes_received_total 0\n"));
+		assert!(text.ends_with("# EOF\n"));
+		assert!(!text.contains("deno_op_latency_seconds"));
+	}
+
+	#[test]
+	fn op_latencies() {
+		let metrics = Metrics::default();
+		metrics.record_op_latency("read", Duration::from_micros(5));
+		metrics.record_op_latency("read", Duration::from_micros(50));
+		metrics.record_op_latency("fetch", Duration::from_secs(2));
+
+		let snapshot = metrics.snapshot();
+		let read = &snapshot.op_latencies["read"];
+		assert_eq!(read.count, 2);
+		assert_eq!(read.sum, Duration::from_micros(55));
+		assert_eq!(read.buckets, [1, 1, 0, 0, 0, 0, 0]);
+		assert_eq!(snapshot.op_latencies["fetch"].buckets, [0, 0, 0, 0, 0, 0, 1]);
+
+		let text = snapshot.to_openmetrics();
+		assert!(text.contains("# TYPE deno_op_latency_seconds histogram\n"));
+		assert!(text.contains("\ndeno_op_latency_seconds_bucket{op=\"read\",le=\"0.00001\"} 1\n"));
+		assert!(text.contains("\ndeno_op_latency_seconds_bucket{op=\"read\",le=\"0.0001\"} 2\n"));
+		assert!(text.contains("\ndeno_op_latency_seconds_bucket{op=\"fetch\",le=\"1\"} 0\n"));
+		assert!(text.contains("\ndeno_op_latency_seconds_bucket{op=\"fetch\",le=\"+Inf\"} 1\n"));
+		assert!(text.contains("\ndeno_op_latency_seconds_count{op=\"read\"} 2\n"));
+		assert!(text.contains("\ndeno_op_latency_seconds_sum{op=\"fetch\"} 2\n"));
+
+		assert_eq!(metrics.reset().op_latencies.len(), 2);
+		assert!(metrics.snapshot().op_latencies.is_empty());
+	}
 }
@@ -62,6 +62,10 @@ pub enum ErrorKind {
 	/// TODO this is a DomException type, and should be moved out of here when
 	/// possible
 	DataCloneError = 52,
+	WorkerTimeout = 53,
+	HeapLimit = 54,
+	TooManyPendingOps = 55,
+	Aborted = 56,
 }
 
 // Warning! The values in this enum are duplicated in js/compiler.ts
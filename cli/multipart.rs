@@ -0,0 +1,192 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+
+use std::{
+	fs::File,
+	io,
+	io::{Read, Seek, SeekFrom},
+};
+
+use rand::Rng;
+use reqwest::r#async::{Body, Chunk};
+use tokio::{
+	codec::{BytesCodec, FramedRead},
+	prelude::{stream, Stream},
+};
+
+/// Content of a `multipart/form-data` part.
+pub enum PartBody {
+	Bytes(Vec<u8>),
+	/// Sent from the file's current position to its end, without reading it
+	/// into memory first.
+	File(File),
+}
+
+pub struct Part {
+	pub name:String,
+	pub filename:Option<String>,
+	pub content_type:Option<String>,
+	pub body:PartBody,
+}
+
+type ChunkStream = Box<dyn Stream<Item = Chunk, Error = io::Error> + Send>;
+
+/// A `multipart/form-data` request body, streamed part by part.
+pub struct Multipart {
+	boundary:String,
+	parts:Vec<Part>,
+}
+
+impl Multipart {
+	pub fn new(parts:Vec<Part>) -> Self {
+		let mut rng = rand::thread_rng();
+		let boundary = format!("deno-{:016x}{:016x}", rng.gen::<u64>(), rng.gen::<u64>());
+		Self { boundary, parts }
+	}
+
+	/// The `Content-Type` header value, which carries the boundary.
+	pub fn content_type(&self) -> String {
+		format!("multipart/form-data; boundary={}", self.boundary)
+	}
+
+	/// Length of the encoded body, so that it can be sent with a
+	/// `Content-Length` header rather than chunked.
+	pub fn content_length(&self) -> io::Result<u64> {
+		let mut len = 0;
+		for part in &self.parts {
+			len += self.part_header(part).len() as u64 + 2;
+			len += match &part.body {
+				PartBody::Bytes(bytes) => bytes.len() as u64,
+				PartBody::File(file) => file_remaining(file)?,
+			};
+		}
+		Ok(len + self.closing().len() as u64)
+	}
+
+	pub fn into_body(self) -> io::Result<Body> { Ok(Body::wrap_stream(self.into_stream()?)) }
+
+	fn into_stream(self) -> io::Result<ChunkStream> {
+		let headers:Vec<String> = self.parts.iter().map(|part| self.part_header(part)).collect();
+		let closing = self.closing();
+		let mut streams:Vec<ChunkStream> = Vec::new();
+		for (part, header) in self.parts.into_iter().zip(headers) {
+			streams.push(Box::new(stream::once(Ok(Chunk::from(header)))));
+			streams.push(match part.body {
+				PartBody::Bytes(bytes) => Box::new(stream::once(Ok(Chunk::from(bytes)))),
+				PartBody::File(file) => {
+					// Bounded, so that a file growing meanwhile doesn't overrun
+					// `Content-Length`.
+					let len = file_remaining(&file)?;
+					let file = tokio::fs::File::from_std(file).take(len);
+					let chunks = FramedRead::new(file, BytesCodec::new());
+					Box::new(chunks.map(|bytes| Chunk::from(bytes.freeze())))
+				},
+			});
+			streams.push(Box::new(stream::once(Ok(Chunk::from("\r\n")))));
+		}
+		streams.push(Box::new(stream::once(Ok(Chunk::from(closing)))));
+		Ok(Box::new(stream::iter_ok(streams).flatten()))
+	}
+
+	fn part_header(&self, part:&Part) -> String {
+		let mut header = format!(
+			"--{}\r\nContent-Disposition: form-data; name=\"{}\"",
+			self.boundary,
+			escape(&part.name)
+		);
+		if let Some(filename) = &part.filename {
+			header.push_str(&format!("; filename=\"{}\"", escape(filename)));
+		}
+		header.push_str("\r\n");
+		let content_type = match (&part.content_type, &part.filename) {
+			(Some(content_type), _) => Some(content_type.as_str()),
+			(None, Some(_)) => Some("application/octet-stream"),
+			(None, None) => None,
+		};
+		if let Some(content_type) = content_type {
+			header.push_str(&format!("Content-Type: {}\r\n", content_type));
+		}
+		header.push_str("\r\n");
+		header
+	}
+
+	fn closing(&self) -> String { format!("--{}--\r\n", self.boundary) }
+}
+
+fn file_remaining(mut file:&File) -> io::Result<u64> {
+	let pos = file.seek(SeekFrom::Current(0))?;
+	Ok(file.metadata()?.len().saturating_sub(pos))
+}
+
+/// Escapes a name the way browsers do in `Content-Disposition`.
+fn escape(name:&str) -> String {
+	name.replace('"', "%22").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+	use std::io::Write;
+
+	use tokio::prelude::Future;
+
+	use super::*;
+
+	fn encode(multipart:Multipart) -> String {
+		let body = multipart.into_stream().unwrap().concat2().wait().unwrap();
+		String::from_utf8(body.to_vec()).unwrap()
+	}
+
+	#[test]
+	fn fields() {
+		let multipart = Multipart::new(vec![
+			Part {
+				name:"title".to_string(),
+				filename:None,
+				content_type:None,
+				body:PartBody::Bytes(b"report".to_vec()),
+			},
+			Part {
+				name:"a\"b".to_string(),
+				filename:Some("data.csv".to_string()),
+				content_type:Some("text/csv".to_string()),
+				body:PartBody::Bytes(b"1,2\n".to_vec()),
+			},
+		]);
+		let boundary = multipart.boundary.clone();
+		let len = multipart.content_length().unwrap();
+		assert_eq!(
+			multipart.content_type(),
+			format!("multipart/form-data; boundary={}", boundary)
+		);
+		let body = encode(multipart);
+		assert_eq!(body.len() as u64, len);
+		assert_eq!(
+			body,
+			format!(
+				"--{b}\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nreport\r\n\
+				 --{b}\r\nContent-Disposition: form-data; name=\"a%22b\"; \
+				 filename=\"data.csv\"\r\nContent-Type: text/csv\r\n\r\n1,2\n\r\n--{b}--\r\n",
+				b = boundary
+			)
+		);
+	}
+
+	#[test]
+	fn file_length_from_position() {
+		let mut file = tempfile::tempfile().unwrap();
+		file.write_all(b"hello world").unwrap();
+		file.seek(SeekFrom::Start(6)).unwrap();
+		assert_eq!(file_remaining(&file).unwrap(), 5);
+		let multipart = Multipart::new(vec![Part {
+			name:"upload".to_string(),
+			filename:Some("hello.txt".to_string()),
+			content_type:None,
+			body:PartBody::File(file),
+		}]);
+		let header_len = multipart.part_header(&multipart.parts[0]).len() as u64;
+		let closing_len = multipart.closing().len() as u64;
+		assert_eq!(multipart.content_length().unwrap(), header_len + 5 + 2 + closing_len);
+		assert!(multipart.part_header(&multipart.parts[0]).ends_with(
+			"filename=\"hello.txt\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+		));
+	}
+}
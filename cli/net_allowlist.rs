@@ -0,0 +1,274 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+use std::net::{IpAddr, SocketAddr};
+
+use deno::ErrBox;
+
+use crate::deno_error::{permission_denied_msg, DenoError, ErrorKind};
+
+/// Outbound connection targets allowed by `--net-allowlist`. Unlike
+/// `--allow-net` this restricts where `Deno.dial` and `fetch` may connect to
+/// even when network access is granted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetAllowlist {
+	entries:Vec<Entry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Entry {
+	/// A hostname, or `*.example.com` for any subdomain of example.com.
+	Host { pattern:String, port:Option<u16> },
+	/// An IP range. A single address is a range with a full-length prefix.
+	Cidr { addr:IpAddr, prefix:u8, port:Option<u16> },
+}
+
+impl NetAllowlist {
+	/// Parses entries such as `deno.land`, `*.deno.land`, `github.com:443`,
+	/// `10.0.0.0/8`, `127.0.0.1:8000` and `[::1]:8000`.
+	pub fn parse(entries:&[String]) -> Result<Self, ErrBox> {
+		let entries = entries.iter().map(|entry| parse_entry(entry)).collect::<Result<_, _>>()?;
+		Ok(Self { entries })
+	}
+
+	/// Checks a target before it is resolved. IP literals must fall in one of
+	/// the ranges, hostnames must match one of the host patterns.
+	pub fn check_host(&self, hostname:&str, port:u16) -> Result<(), ErrBox> {
+		let host = hostname.trim_start_matches('[').trim_end_matches(']');
+		let allowed = match host.parse::<IpAddr>() {
+			Ok(ip) => self.contains_ip(ip, port),
+			Err(_) => {
+				let host = host.to_lowercase();
+				self.entries.iter().any(|entry| {
+					match entry {
+						Entry::Host { pattern, port:entry_port } => {
+							port_matches(*entry_port, port) && host_matches(pattern, &host)
+						},
+						Entry::Cidr { .. } => false,
+					}
+				})
+			},
+		};
+		if allowed {
+			Ok(())
+		} else {
+			Err(permission_denied_msg(format!(
+				"network access to \"{}:{}\" is not in the net allowlist",
+				hostname, port
+			)))
+		}
+	}
+
+	/// Checks the address `hostname` resolved to. Addresses in one of the
+	/// ranges are allowed. A hostname that matches a host pattern may resolve
+	/// to any public address, but internal ones (loopback, private, link-local
+	/// and the like) have to be in a range themselves, so that a name whose
+	/// DNS answer changes can't be used to reach the local network.
+	pub fn check_addr(&self, hostname:&str, addr:&SocketAddr) -> Result<(), ErrBox> {
+		let port = addr.port();
+		let ip = addr.ip();
+		if self.contains_ip(ip, port)
+			|| (!is_internal(ip) && self.check_host(hostname, port).is_ok())
+		{
+			Ok(())
+		} else {
+			Err(permission_denied_msg(format!(
+				"network access to \"{}\" is not in the net allowlist",
+				addr
+			)))
+		}
+	}
+
+	fn contains_ip(&self, ip:IpAddr, port:u16) -> bool {
+		self.entries.iter().any(|entry| {
+			match entry {
+				Entry::Cidr { addr, prefix, port:entry_port } => {
+					port_matches(*entry_port, port) && cidr_contains(*addr, *prefix, ip)
+				},
+				Entry::Host { .. } => false,
+			}
+		})
+	}
+}
+
+/// Addresses that aren't reachable on the public internet.
+fn is_internal(ip:IpAddr) -> bool {
+	match ip {
+		IpAddr::V4(ip) => {
+			let [a, b, ..] = ip.octets();
+			ip.is_loopback()
+				|| ip.is_private()
+				|| ip.is_link_local()
+				|| ip.is_unspecified()
+				|| ip.is_broadcast()
+				// Shared address space, RFC 6598.
+				|| (a == 100 && b & 0xc0 == 64)
+		},
+		IpAddr::V6(ip) => {
+			let first = ip.segments()[0];
+			match ip.to_ipv4() {
+				// IPv4-mapped and -compatible addresses, but not `::` and `::1`.
+				Some(v4) if !ip.is_loopback() && !ip.is_unspecified() => {
+					is_internal(IpAddr::V4(v4))
+				},
+				_ => {
+					ip.is_loopback()
+						|| ip.is_unspecified()
+						// Unique local, fc00::/7.
+						|| first & 0xfe00 == 0xfc00
+						// Link-local, fe80::/10.
+						|| first & 0xffc0 == 0xfe80
+				},
+			}
+		},
+	}
+}
+
+fn parse_entry(entry:&str) -> Result<Entry, ErrBox> {
+	let invalid =
+		|| DenoError::new(ErrorKind::InvalidInput, format!("Invalid net allowlist entry: {}", entry));
+
+	if let Some(slash) = entry.find('/') {
+		let addr:IpAddr = entry[..slash].parse().map_err(|_| invalid())?;
+		let prefix:u8 = entry[slash + 1..].parse().map_err(|_| invalid())?;
+		let max_prefix = if addr.is_ipv4() { 32 } else { 128 };
+		if prefix > max_prefix {
+			return Err(invalid().into());
+		}
+		return Ok(Entry::Cidr { addr, prefix, port:None });
+	}
+	if let Ok(addr) = entry.trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>() {
+		return Ok(Entry::Cidr { addr, prefix:full_prefix(addr), port:None });
+	}
+	if let Ok(addr) = entry.parse::<SocketAddr>() {
+		let ip = addr.ip();
+		return Ok(Entry::Cidr { addr:ip, prefix:full_prefix(ip), port:Some(addr.port()) });
+	}
+
+	let (host, port) = match entry.rfind(':') {
+		Some(colon) => (&entry[..colon], Some(entry[colon + 1..].parse().map_err(|_| invalid())?)),
+		None => (entry, None),
+	};
+	if host.is_empty() || host.contains(':') {
+		return Err(invalid().into());
+	}
+	Ok(Entry::Host { pattern:host.to_lowercase(), port })
+}
+
+fn full_prefix(addr:IpAddr) -> u8 { if addr.is_ipv4() { 32 } else { 128 } }
+
+fn port_matches(entry_port:Option<u16>, port:u16) -> bool {
+	entry_port.map_or(true, |entry_port| entry_port == port)
+}
+
+fn host_matches(pattern:&str, host:&str) -> bool {
+	if pattern.starts_with("*.") {
+		host.ends_with(&pattern[1..])
+	} else {
+		pattern == host
+	}
+}
+
+fn cidr_contains(network:IpAddr, prefix:u8, ip:IpAddr) -> bool {
+	match (network, ip) {
+		(IpAddr::V4(network), IpAddr::V4(ip)) => {
+			let mask = if prefix == 0 { 0 } else { !0u32 << (32 - prefix) };
+			u32::from(network) & mask == u32::from(ip) & mask
+		},
+		(IpAddr::V6(network), IpAddr::V6(ip)) => {
+			let mask = if prefix == 0 { 0 } else { !0u128 << (128 - prefix) };
+			u128::from(network) & mask == u128::from(ip) & mask
+		},
+		_ => false,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	macro_rules! svec {
+    ($($x:expr),*) => (vec![$($x.to_string()),*]);
+  }
+
+	fn allowlist() -> NetAllowlist {
+		NetAllowlist::parse(&svec![
+			"deno.land",
+			"*.github.com",
+			"example.com:443",
+			"10.0.0.0/8",
+			"127.0.0.1:8000",
+			"[::1]:8000",
+			"fd00::/8"
+		])
+		.unwrap()
+	}
+
+	#[test]
+	fn check_host() {
+		let allowlist = allowlist();
+		let tests = vec![
+			("deno.land", 80, true),
+			("DENO.land", 443, true),
+			("sub.deno.land", 80, false),
+			("api.github.com", 443, true),
+			("github.com", 443, false),
+			("example.com", 443, true),
+			("example.com", 80, false),
+			("10.1.2.3", 22, true),
+			("11.1.2.3", 22, false),
+			("127.0.0.1", 8000, true),
+			("127.0.0.1", 8001, false),
+			("[::1]", 8000, true),
+			("[fd12::1]", 80, true),
+			("[fe80::1]", 80, false),
+		];
+		for (host, port, allowed) in tests {
+			assert_eq!(allowlist.check_host(host, port).is_ok(), allowed, "{}:{}", host, port);
+		}
+	}
+
+	#[test]
+	fn check_addr() {
+		let allowlist = allowlist();
+		let private = "10.0.0.1:443".parse().unwrap();
+		let public = "93.184.216.34:443".parse().unwrap();
+		assert!(allowlist.check_addr("internal.example", &private).is_ok());
+		// Allowed names may resolve outside the ranges.
+		assert!(allowlist.check_addr("deno.land", &public).is_ok());
+		assert!(allowlist.check_addr("api.github.com", &public).is_ok());
+		assert!(allowlist.check_addr("evil.example", &public).is_err());
+		let public_http = "93.184.216.34:80".parse().unwrap();
+		assert!(allowlist.check_addr("example.com", &public_http).is_err());
+	}
+
+	#[test]
+	fn check_addr_internal() {
+		let allowlist = allowlist();
+		// An allowed name doesn't extend to internal addresses outside the ranges.
+		for addr in &[
+			"127.0.0.1:443",
+			"192.168.1.1:443",
+			"169.254.169.254:443",
+			"100.64.0.1:443",
+			"0.0.0.0:443",
+			"[::1]:443",
+			"[fc00::1]:443",
+			"[fe80::1]:443",
+			"[::ffff:127.0.0.1]:443",
+		] {
+			let addr = addr.parse().unwrap();
+			assert!(allowlist.check_addr("deno.land", &addr).is_err(), "{}", addr);
+		}
+		// Unless they are in a range.
+		let private = "10.0.0.1:443".parse().unwrap();
+		assert!(allowlist.check_addr("deno.land", &private).is_ok());
+		let public6 = "[2606:4700::1]:443".parse().unwrap();
+		assert!(allowlist.check_addr("deno.land", &public6).is_ok());
+	}
+
+	#[test]
+	fn parse_invalid() {
+		assert!(NetAllowlist::parse(&svec!["10.0.0.0/33"]).is_err());
+		assert!(NetAllowlist::parse(&svec!["deno.land:http"]).is_err());
+		assert!(NetAllowlist::parse(&svec![":80"]).is_err());
+	}
+}
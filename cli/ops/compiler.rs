@@ -10,9 +10,9 @@ use crate::{
 };
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("cache", s.core_op(json_op(s.stateful_op(op_cache))));
-	i.register_op("fetch_source_files", s.core_op(json_op(s.stateful_op(op_fetch_source_files))));
-	i.register_op("fetch_asset", s.core_op(json_op(s.stateful_op(op_fetch_asset))));
+	s.register_op(i, "cache", json_op(s.stateful_op(op_cache)));
+	s.register_op(i, "fetch_source_files", json_op(s.stateful_op(op_fetch_source_files)));
+	s.register_op(i, "fetch_asset", json_op(s.stateful_op(op_fetch_asset)));
 }
 
 #[derive(Deserialize)]
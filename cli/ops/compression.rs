@@ -0,0 +1,326 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+//! Streaming gzip, deflate and brotli (de)compression of buffers. Preset
+//! dictionaries are rejected, flate2 with the miniz backend and the brotli
+//! writers don't take one.
+use std::{
+	io,
+	io::Write,
+	sync::{Arc, Mutex},
+};
+
+use brotli::{CompressorWriter, DecompressorWriter};
+use deno::*;
+use flate2::{
+	write::{DeflateDecoder, DeflateEncoder, GzDecoder, GzEncoder, ZlibDecoder, ZlibEncoder},
+	Compression,
+};
+
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::{
+	deno_error::{bad_resource, DenoError, ErrorKind},
+	ops::json_op,
+	state::ThreadSafeState,
+};
+
+pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
+	s.register_op(i, "compression_create", json_op(s.stateful_op(op_compression_create)));
+	s.register_op(i, "compression_update", json_op(s.stateful_op(op_compression_update)));
+	s.register_op(i, "compression_finish", json_op(s.stateful_op(op_compression_finish)));
+}
+
+const BROTLI_BUFFER_SIZE:usize = 4096;
+/// Window size of brotli output, the default of the reference encoder.
+const BROTLI_LGWIN:u32 = 22;
+
+/// Where a codec writes to. A clone is kept to take the output, since not
+/// every codec gives mutable access to its writer.
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<OutputBuf>>);
+
+#[derive(Default)]
+struct OutputBuf {
+	buf:Vec<u8>,
+	/// Total written, taken or not.
+	len:usize,
+	/// Writes fail once `len` would exceed it, which stops a decompression
+	/// bomb while it is being decoded rather than after.
+	max_len:Option<usize>,
+}
+
+impl Output {
+	fn with_max_len(max_len:Option<usize>) -> Self {
+		Output(Arc::new(Mutex::new(OutputBuf { max_len, ..OutputBuf::default() })))
+	}
+
+	fn take(&self) -> Vec<u8> { std::mem::replace(&mut self.0.lock().unwrap().buf, Vec::new()) }
+}
+
+impl Write for Output {
+	fn write(&mut self, buf:&[u8]) -> io::Result<usize> {
+		let mut output = self.0.lock().unwrap();
+		if let Some(max_len) = output.max_len {
+			if output.len + buf.len() > max_len {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					format!("output is larger than maxOutputLength ({} bytes)", max_len),
+				));
+			}
+		}
+		output.len += buf.len();
+		output.buf.extend_from_slice(buf);
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+enum Codec {
+	GzipEncoder(GzEncoder<Output>),
+	GzipDecoder(GzDecoder<Output>),
+	/// "deflate" is zlib-wrapped, as in HTTP's `Content-Encoding`.
+	ZlibEncoder(ZlibEncoder<Output>),
+	ZlibDecoder(ZlibDecoder<Output>),
+	DeflateEncoder(DeflateEncoder<Output>),
+	DeflateDecoder(DeflateDecoder<Output>),
+	BrotliEncoder(Box<CompressorWriter<Output>>),
+	BrotliDecoder(Box<DecompressorWriter<Output>>),
+}
+
+impl Codec {
+	fn new(
+		format:&str,
+		decompress:bool,
+		level:Option<u32>,
+		output:Output,
+	) -> Result<Self, ErrBox> {
+		let max_level = if format == "brotli" { 11 } else { 9 };
+		if let Some(level) = level.filter(|level| *level > max_level) {
+			return Err(DenoError::new(
+				ErrorKind::InvalidInput,
+				format!("Level {} of \"{}\" is not between 0 and {}", level, format, max_level),
+			)
+			.into());
+		}
+		let flate_level = level.map(Compression::new).unwrap_or_default();
+		Ok(match (format, decompress) {
+			("gzip", false) => Codec::GzipEncoder(GzEncoder::new(output, flate_level)),
+			("gzip", true) => Codec::GzipDecoder(GzDecoder::new(output)),
+			("deflate", false) => Codec::ZlibEncoder(ZlibEncoder::new(output, flate_level)),
+			("deflate", true) => Codec::ZlibDecoder(ZlibDecoder::new(output)),
+			("deflate-raw", false) => {
+				Codec::DeflateEncoder(DeflateEncoder::new(output, flate_level))
+			},
+			("deflate-raw", true) => Codec::DeflateDecoder(DeflateDecoder::new(output)),
+			("brotli", false) => {
+				let quality = level.unwrap_or(11);
+				let writer =
+					CompressorWriter::new(output, BROTLI_BUFFER_SIZE, quality, BROTLI_LGWIN);
+				Codec::BrotliEncoder(Box::new(writer))
+			},
+			("brotli", true) => {
+				Codec::BrotliDecoder(Box::new(DecompressorWriter::new(output, BROTLI_BUFFER_SIZE)))
+			},
+			_ => {
+				return Err(DenoError::new(
+					ErrorKind::InvalidInput,
+					format!("Unsupported compression format \"{}\"", format),
+				)
+				.into());
+			},
+		})
+	}
+
+	fn write_all(&mut self, data:&[u8]) -> io::Result<()> {
+		match self {
+			Codec::GzipEncoder(w) => w.write_all(data),
+			Codec::GzipDecoder(w) => w.write_all(data),
+			Codec::ZlibEncoder(w) => w.write_all(data),
+			Codec::ZlibDecoder(w) => w.write_all(data),
+			Codec::DeflateEncoder(w) => w.write_all(data),
+			Codec::DeflateDecoder(w) => w.write_all(data),
+			Codec::BrotliEncoder(w) => w.write_all(data),
+			Codec::BrotliDecoder(w) => w.write_all(data),
+		}
+	}
+
+	/// Writes what is still buffered. Fails for gzip and brotli input that
+	/// ended early.
+	fn finish(self) -> io::Result<()> {
+		match self {
+			Codec::GzipEncoder(w) => w.finish().map(drop),
+			Codec::GzipDecoder(w) => w.finish().map(drop),
+			Codec::ZlibEncoder(w) => w.finish().map(drop),
+			Codec::ZlibDecoder(w) => w.finish().map(drop),
+			Codec::DeflateEncoder(w) => w.finish().map(drop),
+			Codec::DeflateDecoder(w) => w.finish().map(drop),
+			Codec::BrotliEncoder(w) => {
+				w.into_inner();
+				Ok(())
+			},
+			Codec::BrotliDecoder(w) => {
+				w.into_inner().map(drop).map_err(|_| {
+					io::Error::new(io::ErrorKind::InvalidData, "incomplete brotli stream")
+				})
+			},
+		}
+	}
+}
+
+/// Taken by `compression_finish`, so that it can consume the codec.
+struct CompressionResource {
+	codec:Option<Codec>,
+	output:Output,
+}
+
+impl Resource for CompressionResource {}
+
+fn invalid_data(e:io::Error) -> ErrBox {
+	match e.kind() {
+		io::ErrorKind::InvalidInput | io::ErrorKind::InvalidData | io::ErrorKind::Other => {
+			DenoError::new(ErrorKind::InvalidData, e.to_string()).into()
+		},
+		_ => e.into(),
+	}
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CompressionCreateArgs {
+	format:String,
+	#[serde(default)]
+	decompress:bool,
+	level:Option<u32>,
+	max_output_length:Option<usize>,
+}
+
+/// A preset dictionary comes in as the zero copy buffer.
+fn op_compression_create(
+	state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:CompressionCreateArgs = serde_json::from_value(args)?;
+	if zero_copy.is_some() {
+		return Err(DenoError::new(
+			ErrorKind::InvalidInput,
+			format!("Preset dictionaries are not supported for \"{}\"", args.format),
+		)
+		.into());
+	}
+	let output = Output::with_max_len(args.max_output_length);
+	let codec = Codec::new(&args.format, args.decompress, args.level, output.clone())?;
+	let name = if args.decompress { "decompressor" } else { "compressor" };
+	let mut table = state.lock_resource_table();
+	let rid = table.add(name, Box::new(CompressionResource { codec:Some(codec), output }));
+	Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+struct CompressionArgs {
+	rid:i32,
+}
+
+/// Feeds the buffer to the codec and returns the output produced so far.
+fn op_compression_update(
+	state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:CompressionArgs = serde_json::from_value(args)?;
+	let mut table = state.lock_resource_table();
+	let resource =
+		table.get_mut::<CompressionResource>(args.rid as u32).ok_or_else(bad_resource)?;
+	let codec = resource.codec.as_mut().ok_or_else(bad_resource)?;
+	if let Some(data) = zero_copy {
+		codec.write_all(&data).map_err(invalid_data)?;
+	}
+	Ok(JsonOp::SyncBuf(resource.output.take().into_boxed_slice()))
+}
+
+/// Returns the rest of the output and closes the resource.
+fn op_compression_finish(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:CompressionArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let mut table = state.lock_resource_table();
+	let resource = table.get_mut::<CompressionResource>(rid).ok_or_else(bad_resource)?;
+	let codec = resource.codec.take().ok_or_else(bad_resource)?;
+	let output = resource.output.clone();
+	table.close(rid);
+	codec.finish().map_err(invalid_data)?;
+	Ok(JsonOp::SyncBuf(output.take().into_boxed_slice()))
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::{Rng, RngCore};
+
+	use super::*;
+
+	fn run(format:&str, decompress:bool, level:Option<u32>, chunks:&[&[u8]]) -> Vec<u8> {
+		let output = Output::default();
+		let mut codec = Codec::new(format, decompress, level, output.clone()).unwrap();
+		let mut out = Vec::new();
+		for chunk in chunks {
+			codec.write_all(chunk).unwrap();
+			out.extend(output.take());
+		}
+		codec.finish().unwrap();
+		out.extend(output.take());
+		out
+	}
+
+	#[test]
+	fn round_trip_random_data() {
+		let mut rng = rand::thread_rng();
+		for format in &["gzip", "deflate", "deflate-raw", "brotli"] {
+			for &level in &[None, Some(0), Some(9)] {
+				let mut data = vec![0u8; rng.gen_range(0, 20_000)];
+				rng.fill_bytes(&mut data);
+				// Half random, half compressible.
+				let half = data.len() / 2;
+				data[half..].iter_mut().for_each(|b| *b = b'a');
+				let (a, b) = data.split_at(data.len() / 3);
+				let compressed = run(format, false, level, &[a, b]);
+				assert_eq!(run(format, true, None, &[&compressed]), data, "{}", format);
+			}
+		}
+	}
+
+	#[test]
+	fn errors() {
+		let output = Output::default();
+		assert!(Codec::new("zip", false, None, output.clone()).is_err());
+		assert!(Codec::new("gzip", false, Some(10), output.clone()).is_err());
+		assert!(Codec::new("brotli", false, Some(11), output.clone()).is_ok());
+
+		let compressed = run("gzip", false, None, &[b"hello world"]);
+		let mut codec = Codec::new("gzip", true, None, output).unwrap();
+		codec.write_all(&compressed[..compressed.len() / 2]).unwrap();
+		assert!(codec.finish().is_err());
+	}
+
+	#[test]
+	fn max_output_len() {
+		for format in &["gzip", "brotli"] {
+			// A MiB of zeros compresses to a few hundred bytes at most.
+			let bomb = run(format, false, None, &[&vec![0u8; 1 << 20]]);
+			let output = Output::with_max_len(Some(1000));
+			let mut codec = Codec::new(format, true, None, output.clone()).unwrap();
+			let err = codec.write_all(&bomb).and_then(|()| codec.finish()).unwrap_err();
+			assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", format);
+			assert!(output.take().len() <= 1000);
+		}
+
+		// Output up to the limit is fine.
+		let compressed = run("deflate", false, None, &[&[1u8; 1000]]);
+		let output = Output::with_max_len(Some(1000));
+		let mut codec = Codec::new("deflate", true, None, output.clone()).unwrap();
+		codec.write_all(&compressed).unwrap();
+		codec.finish().unwrap();
+		assert_eq!(output.take(), vec![1u8; 1000]);
+	}
+}
@@ -0,0 +1,175 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+//! Hashing, HMAC and constant-time comparison, kept apart from the CSPRNG in
+//! `ops::random`.
+use deno::*;
+use ring::{constant_time, digest, hmac};
+
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::{
+	deno_error::{bad_resource, no_buffer_specified, DenoError, ErrorKind},
+	ops::json_op,
+	state::ThreadSafeState,
+};
+
+pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
+	s.register_op(i, "hash_create", json_op(s.stateful_op(op_hash_create)));
+	s.register_op(i, "hash_update", json_op(s.stateful_op(op_hash_update)));
+	s.register_op(i, "hash_digest", json_op(s.stateful_op(op_hash_digest)));
+	s.register_op(i, "hmac", json_op(s.stateful_op(op_hmac)));
+	s.register_op(i, "bytes_equal", json_op(s.stateful_op(op_bytes_equal)));
+}
+
+fn unsupported_algorithm(algorithm:&str) -> ErrBox {
+	DenoError::new(
+		ErrorKind::InvalidInput,
+		format!("Unsupported hash algorithm \"{}\"", algorithm),
+	)
+	.into()
+}
+
+/// Empty buffers arrive as `None`, both operands being empty then.
+fn operands(zero_copy:&Option<PinnedBuf>) -> &[u8] {
+	zero_copy.as_ref().map(|buf| &buf[..]).unwrap_or(&[])
+}
+
+/// Splits a buffer holding two operands, the first `len` bytes long.
+fn split_operands(buf:&[u8], len:usize) -> Result<(&[u8], &[u8]), ErrBox> {
+	if len > buf.len() {
+		return Err(DenoError::new(
+			ErrorKind::InvalidInput,
+			format!("Operand length {} exceeds the buffer of {} bytes", len, buf.len()),
+		)
+		.into());
+	}
+	Ok(buf.split_at(len))
+}
+
+/// A hash being computed, fed by `hash_update` until `hash_digest`.
+enum Hasher {
+	Sha256(digest::Context),
+	Sha512(digest::Context),
+	Blake3(Box<blake3::Hasher>),
+}
+
+impl Resource for Hasher {}
+
+impl Hasher {
+	fn new(algorithm:&str) -> Result<Self, ErrBox> {
+		Ok(match algorithm {
+			"sha256" => Hasher::Sha256(digest::Context::new(&digest::SHA256)),
+			"sha512" => Hasher::Sha512(digest::Context::new(&digest::SHA512)),
+			"blake3" => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+			_ => return Err(unsupported_algorithm(algorithm)),
+		})
+	}
+
+	fn update(&mut self, data:&[u8]) {
+		match self {
+			Hasher::Sha256(context) | Hasher::Sha512(context) => context.update(data),
+			Hasher::Blake3(hasher) => {
+				hasher.update(data);
+			},
+		}
+	}
+
+	fn digest(&self) -> Buf {
+		match self {
+			Hasher::Sha256(context) | Hasher::Sha512(context) => {
+				context.clone().finish().as_ref().to_vec().into_boxed_slice()
+			},
+			Hasher::Blake3(hasher) => hasher.finalize().as_bytes().to_vec().into_boxed_slice(),
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct HashCreateArgs {
+	algorithm:String,
+}
+
+fn op_hash_create(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:HashCreateArgs = serde_json::from_value(args)?;
+	let hasher = Hasher::new(&args.algorithm)?;
+	let mut table = state.lock_resource_table();
+	let rid = table.add("hasher", Box::new(hasher));
+	Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+struct HashArgs {
+	rid:i32,
+}
+
+fn op_hash_update(
+	state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:HashArgs = serde_json::from_value(args)?;
+	let data = zero_copy.ok_or_else(no_buffer_specified)?;
+	let mut table = state.lock_resource_table();
+	let hasher = table.get_mut::<Hasher>(args.rid as u32).ok_or_else(bad_resource)?;
+	hasher.update(&data);
+	Ok(JsonOp::Sync(json!({})))
+}
+
+/// Returns the digest and closes the hasher.
+fn op_hash_digest(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:HashArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let mut table = state.lock_resource_table();
+	let digest = table.get::<Hasher>(rid).ok_or_else(bad_resource)?.digest();
+	table.close(rid);
+	Ok(JsonOp::SyncBuf(digest))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct HmacArgs {
+	algorithm:String,
+	/// The buffer holds the key followed by the message.
+	key_length:usize,
+}
+
+fn op_hmac(
+	_state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:HmacArgs = serde_json::from_value(args)?;
+	let algorithm = match args.algorithm.as_str() {
+		"sha256" => hmac::HMAC_SHA256,
+		"sha512" => hmac::HMAC_SHA512,
+		algorithm => return Err(unsupported_algorithm(algorithm)),
+	};
+	let (key, message) = split_operands(operands(&zero_copy), args.key_length)?;
+	let tag = hmac::sign(&hmac::Key::new(algorithm, key), message);
+	Ok(JsonOp::SyncBuf(tag.as_ref().to_vec().into_boxed_slice()))
+}
+
+#[derive(Deserialize)]
+struct BytesEqualArgs {
+	/// The buffer holds both operands, the first one `length` bytes long.
+	length:usize,
+}
+
+/// Compares in time depending only on the lengths, not on where the
+/// operands differ.
+fn op_bytes_equal(
+	_state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:BytesEqualArgs = serde_json::from_value(args)?;
+	let (a, b) = split_operands(operands(&zero_copy), args.length)?;
+	let equal = constant_time::verify_slices_are_equal(a, b).is_ok();
+	Ok(JsonOp::Sync(json!(equal)))
+}
@@ -9,9 +9,15 @@ pub use serde_json::Value;
 
 pub type AsyncJsonOp = Pin<Box<dyn Future<Output = Result<Value, ErrBox>> + Send>>;
 
+pub type AsyncBufOp = Pin<Box<dyn Future<Output = Result<Buf, ErrBox>> + Send>>;
+
 pub enum JsonOp {
 	Sync(Value),
 	Async(AsyncJsonOp),
+	/// The result is raw bytes, such as file contents, which JS receives as a
+	/// `Uint8Array` rather than having them encoded as JSON.
+	SyncBuf(Buf),
+	AsyncBuf(AsyncBufOp),
 }
 
 fn json_err(err:ErrBox) -> Value {
@@ -34,6 +40,19 @@ fn serialize_result(promise_id:Option<u64>, result:Result<Value, ErrBox>) -> Buf
 	vec.into_boxed_slice()
 }
 
+/// Serializes a successful `JsonOp::SyncBuf` or `JsonOp::AsyncBuf` result. A
+/// zero word, which can't start a JSON response, marks the layout: the length
+/// of the JSON part as a little endian u32, the JSON part, then `data`.
+fn serialize_buf_result(promise_id:Option<u64>, data:Buf) -> Buf {
+	let json = serialize_result(promise_id, Ok(json!({})));
+	let mut vec = Vec::with_capacity(8 + json.len() + data.len());
+	vec.extend_from_slice(&[0, 0, 0, 0]);
+	vec.extend_from_slice(&(json.len() as u32).to_le_bytes());
+	vec.extend_from_slice(&json);
+	vec.extend_from_slice(&data);
+	vec.into_boxed_slice()
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct AsyncArgs {
@@ -70,6 +89,20 @@ where
 					.then(move |result| futures::future::ok(serialize_result(promise_id, result)));
 				CoreOp::Async(fut2.boxed())
 			},
+			Ok(JsonOp::SyncBuf(data)) => {
+				assert!(promise_id.is_none());
+				CoreOp::Sync(serialize_buf_result(promise_id, data))
+			},
+			Ok(JsonOp::AsyncBuf(fut)) => {
+				assert!(promise_id.is_some());
+				let fut2 = fut.then(move |result| {
+					futures::future::ok(match result {
+						Ok(data) => serialize_buf_result(promise_id, data),
+						Err(err) => serialize_result(promise_id, Err(err)),
+					})
+				});
+				CoreOp::Async(fut2.boxed())
+			},
 			Err(sync_err) => {
 				let buf = serialize_result(promise_id, Err(sync_err));
 				if is_sync {
@@ -82,6 +115,9 @@ where
 	}
 }
 
+/// Runs `f` on the calling thread when the op was dispatched without a
+/// `promiseId`, which is how the `*Sync` functions of the JS API call it, and
+/// on a thread pool otherwise.
 pub fn blocking_json<F>(is_sync:bool, f:F) -> Result<JsonOp, ErrBox>
 where
 	F: 'static + Send + FnOnce() -> Result<Value, ErrBox> + Unpin, {
@@ -94,3 +130,16 @@ where
 		Ok(JsonOp::Async(handle.boxed()))
 	}
 }
+
+/// Like `blocking_json()`, for ops returning raw bytes.
+pub fn blocking_buf<F>(is_sync:bool, f:F) -> Result<JsonOp, ErrBox>
+where
+	F: 'static + Send + FnOnce() -> Result<Buf, ErrBox> + Unpin, {
+	if is_sync {
+		Ok(JsonOp::SyncBuf(f()?))
+	} else {
+		let pool = futures::executor::ThreadPool::new().unwrap();
+		let handle = pool.spawn_with_handle(futures::future::lazy(move |_cx| f())).unwrap();
+		Ok(JsonOp::AsyncBuf(handle.boxed()))
+	}
+}
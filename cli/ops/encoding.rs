@@ -0,0 +1,165 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+//! Base64 and hex codecs, which are slow to run in JavaScript on large
+//! buffers.
+use deno::*;
+
+use super::dispatch_json::{Deserialize, JsonOp, Value};
+use crate::{
+	deno_error::{DenoError, ErrorKind},
+	ops::json_op,
+	state::ThreadSafeState,
+};
+
+pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
+	s.register_op(i, "base64_encode", json_op(s.stateful_op(op_base64_encode)));
+	s.register_op(i, "base64_decode", json_op(s.stateful_op(op_base64_decode)));
+	s.register_op(i, "hex_encode", json_op(s.stateful_op(op_hex_encode)));
+	s.register_op(i, "hex_decode", json_op(s.stateful_op(op_hex_decode)));
+}
+
+/// Empty buffers arrive as `None`.
+fn input(zero_copy:&Option<PinnedBuf>) -> &[u8] {
+	zero_copy.as_ref().map(|buf| &buf[..]).unwrap_or(&[])
+}
+
+fn invalid(encoding:&str) -> ErrBox {
+	DenoError::new(ErrorKind::InvalidData, format!("Input is not valid {}", encoding)).into()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Base64Args {
+	#[serde(default)]
+	url_safe:bool,
+	/// Only used for encoding, decoding accepts input with or without padding.
+	pad:Option<bool>,
+	/// The input of `base64_decode`.
+	data:Option<String>,
+}
+
+fn base64_config(url_safe:bool, pad:bool) -> base64::Config {
+	match (url_safe, pad) {
+		(false, true) => base64::STANDARD,
+		(false, false) => base64::STANDARD_NO_PAD,
+		(true, true) => base64::URL_SAFE,
+		(true, false) => base64::URL_SAFE_NO_PAD,
+	}
+}
+
+fn op_base64_encode(
+	_state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:Base64Args = serde_json::from_value(args)?;
+	let config = base64_config(args.url_safe, args.pad.unwrap_or(true));
+	Ok(JsonOp::Sync(json!(base64::encode_config(input(&zero_copy), config))))
+}
+
+pub fn base64_decode(data:&str, url_safe:bool) -> Result<Vec<u8>, ErrBox> {
+	let unpadded = data.trim_end_matches('=');
+	// Padding, when present, must fill the last group of four exactly.
+	let padding = data.len() - unpadded.len();
+	if padding > 2 || (padding > 0 && data.len() % 4 != 0) {
+		return Err(invalid("base64"));
+	}
+	base64::decode_config(unpadded, base64_config(url_safe, false)).map_err(|_| invalid("base64"))
+}
+
+fn op_base64_decode(
+	_state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:Base64Args = serde_json::from_value(args)?;
+	let data = args.data.unwrap_or_default();
+	Ok(JsonOp::SyncBuf(base64_decode(&data, args.url_safe)?.into_boxed_slice()))
+}
+
+const HEX_DIGITS:&[u8; 16] = b"0123456789abcdef";
+
+pub fn hex_encode(bytes:&[u8]) -> String {
+	let mut out = String::with_capacity(bytes.len() * 2);
+	for byte in bytes {
+		out.push(HEX_DIGITS[usize::from(byte >> 4)] as char);
+		out.push(HEX_DIGITS[usize::from(byte & 0x0f)] as char);
+	}
+	out
+}
+
+/// Accepts upper and lower case digits.
+pub fn hex_decode(data:&str) -> Result<Vec<u8>, ErrBox> {
+	let digit = |c:u8| (c as char).to_digit(16).ok_or_else(|| invalid("hex"));
+	let data = data.as_bytes();
+	if data.len() % 2 != 0 {
+		return Err(invalid("hex"));
+	}
+	data.chunks(2).map(|pair| Ok((digit(pair[0])? << 4 | digit(pair[1])?) as u8)).collect()
+}
+
+fn op_hex_encode(
+	_state:&ThreadSafeState,
+	_args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	Ok(JsonOp::Sync(json!(hex_encode(input(&zero_copy)))))
+}
+
+#[derive(Deserialize)]
+struct HexDecodeArgs {
+	data:String,
+}
+
+fn op_hex_decode(
+	_state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:HexDecodeArgs = serde_json::from_value(args)?;
+	Ok(JsonOp::SyncBuf(hex_decode(&args.data)?.into_boxed_slice()))
+}
+
+#[cfg(test)]
+mod tests {
+	use rand::{Rng, RngCore};
+
+	use super::*;
+
+	#[test]
+	fn round_trip_random_buffers() {
+		let mut rng = rand::thread_rng();
+		for _ in 0..500 {
+			let mut bytes = vec![0u8; rng.gen_range(0, 100)];
+			rng.fill_bytes(&mut bytes);
+			assert_eq!(hex_decode(&hex_encode(&bytes)).unwrap(), bytes);
+			assert_eq!(hex_decode(&hex_encode(&bytes).to_uppercase()).unwrap(), bytes);
+			for &url_safe in &[false, true] {
+				for &pad in &[false, true] {
+					let encoded = base64::encode_config(&bytes, base64_config(url_safe, pad));
+					assert_eq!(encoded.ends_with('='), pad && bytes.len() % 3 != 0);
+					assert_eq!(base64_decode(&encoded, url_safe).unwrap(), bytes);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn padding_edge_cases() {
+		assert_eq!(base64::encode_config(b"f", base64_config(false, true)), "Zg==");
+		assert_eq!(base64::encode_config(b"fo", base64_config(false, true)), "Zm8=");
+		assert_eq!(base64::encode_config(b"foo", base64_config(false, true)), "Zm9v");
+		assert_eq!(base64_decode("Zg", false).unwrap(), b"f");
+		assert_eq!(base64_decode("Zm8=", false).unwrap(), b"fo");
+		assert_eq!(base64_decode("", false).unwrap(), b"");
+		assert_eq!(base64_decode("-_8", true).unwrap(), vec![0xfb, 0xff]);
+		assert!(base64_decode("-_8", false).is_err());
+		assert!(base64_decode("Z", false).is_err());
+		assert!(base64_decode("Zg=", false).is_err());
+		assert!(base64_decode("Zg=====", false).is_err());
+		assert!(base64_decode("Zm9v=", false).is_err());
+		assert!(base64_decode("Zm9v====", false).is_err());
+		assert_eq!(hex_decode("00fF").unwrap(), vec![0, 0xff]);
+		assert!(hex_decode("abc").is_err());
+		assert!(hex_decode("zz").is_err());
+	}
+}
@@ -12,8 +12,8 @@ use crate::{
 };
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("apply_source_map", s.core_op(json_op(s.stateful_op(op_apply_source_map))));
-	i.register_op("format_error", s.core_op(json_op(s.stateful_op(op_format_error))));
+	s.register_op(i, "apply_source_map", json_op(s.stateful_op(op_apply_source_map)));
+	s.register_op(i, "format_error", json_op(s.stateful_op(op_format_error)));
 }
 
 #[derive(Deserialize)]
@@ -1,21 +1,43 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
-use std::{self, convert::From};
+use std::{
+	self,
+	convert::From,
+	net::SocketAddr,
+	time::{Duration, Instant},
+};
 
 use deno::*;
 use futures::future::{FutureExt, TryFutureExt};
 use http::{
-	header::{HeaderName, HeaderValue},
+	header::{
+		HeaderName,
+		HeaderValue,
+		ACCEPT_ENCODING,
+		CONTENT_ENCODING,
+		CONTENT_LENGTH,
+		CONTENT_TYPE,
+		HOST,
+	},
 	Method,
 };
 
 use super::{
 	dispatch_json::{Deserialize, JsonOp, Value},
 	io::StreamResource,
+	process::clone_file,
+	resources::cancellable,
+};
+use crate::{
+	deno_error::{DenoError, ErrorKind},
+	http_body::HttpBody,
+	http_util::with_deadline,
+	multipart::{Multipart, Part, PartBody},
+	ops::json_op,
+	state::ThreadSafeState,
 };
-use crate::{http_body::HttpBody, http_util::get_client, ops::json_op, state::ThreadSafeState};
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("fetch", s.core_op(json_op(s.stateful_op(op_fetch))));
+	s.register_op(i, "fetch", json_op(s.stateful_op(op_fetch)));
 }
 
 #[derive(Deserialize)]
@@ -23,6 +45,69 @@ struct FetchArgs {
 	method:Option<String>,
 	url:String,
 	headers:Vec<(String, String)>,
+	/// Milliseconds, overrides `--fetch-timeout`. Zero disables the timeout.
+	timeout:Option<u64>,
+	/// Decode gzip and brotli responses, on unless set to `false`.
+	decompress:Option<bool>,
+	/// A cancel handle that aborts the request, see `AbortSignal`.
+	#[serde(rename = "cancelRid")]
+	cancel_rid:Option<u32>,
+	/// Send a `multipart/form-data` body made of these parts instead of the
+	/// zero copy buffer.
+	multipart:Option<Vec<MultipartPartArgs>>,
+}
+
+/// A part's content is `value`, the file `rid` from its current position, or
+/// the range `data` of the zero copy buffer.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MultipartPartArgs {
+	name:String,
+	value:Option<String>,
+	rid:Option<u32>,
+	data:Option<(usize, usize)>,
+	filename:Option<String>,
+	content_type:Option<String>,
+}
+
+fn multipart_from_args(
+	state:&ThreadSafeState,
+	parts:Vec<MultipartPartArgs>,
+	data:Option<&[u8]>,
+) -> Result<Multipart, ErrBox> {
+	let mut parsed = Vec::with_capacity(parts.len());
+	for part in parts {
+		let body = match (part.value, part.rid, part.data) {
+			(Some(value), None, None) => PartBody::Bytes(value.into_bytes()),
+			(None, Some(rid), None) => PartBody::File(clone_file(rid, state)?),
+			(None, None, Some((start, end))) => {
+				match data.and_then(|data| data.get(start..end)) {
+					Some(bytes) => PartBody::Bytes(bytes.to_vec()),
+					None => {
+						return Err(DenoError::new(
+							ErrorKind::InvalidInput,
+							format!("Part \"{}\" is out of the body's range", part.name),
+						)
+						.into());
+					},
+				}
+			},
+			_ => {
+				return Err(DenoError::new(
+					ErrorKind::InvalidInput,
+					format!("Part \"{}\" needs exactly one of value, rid or data", part.name),
+				)
+				.into());
+			},
+		};
+		parsed.push(Part {
+			name:part.name,
+			filename:part.filename,
+			content_type:part.content_type,
+			body,
+		});
+	}
+	Ok(Multipart::new(parsed))
 }
 
 pub fn op_fetch(
@@ -33,52 +118,176 @@ pub fn op_fetch(
 	let args:FetchArgs = serde_json::from_value(args)?;
 	let url = args.url;
 
-	let client = get_client();
+	let http_config = &state.global_state.http_config;
+	// reqwest decodes gzip itself and strips `Content-Encoding` when it does,
+	// brotli is handled below.
+	let decompress = args.decompress.unwrap_or(true);
+	let client = state.global_state.fetch_clients.get(decompress);
+	let timeout = match args.timeout {
+		Some(0) => None,
+		Some(ms) => Some(Duration::from_millis(ms)),
+		None => http_config.request_timeout,
+	};
+	let deadline = timeout.map(|timeout| Instant::now() + timeout);
 
 	let method = match args.method {
 		Some(method_str) => Method::from_bytes(method_str.as_bytes())?,
 		None => Method::GET,
 	};
 
-	let url_ = url::Url::parse(&url).map_err(ErrBox::from)?;
+	let mut url_ = url::Url::parse(&url).map_err(ErrBox::from)?;
 	state.check_net_url(&url_)?;
+	// With an allowlist the host is resolved and checked here, and the request
+	// is pinned to that address, see `pin_address()`. Proxied requests are
+	// left alone, the proxy does its own resolution.
+	let host = url_.host_str().unwrap_or("").to_string();
+	let proxied = http_config.proxy.proxy_for(&url_).is_some();
+	let resolved = if state.global_state.net_allowlist.is_some() {
+		let port = url_.port_or_known_default().unwrap_or(80);
+		state.resolve_net_target(&host, port)?.map_ok(Some).boxed()
+	} else {
+		futures::future::ok(None).boxed()
+	};
+
+	// reqwest does its own resolution, so overridden hosts are reached by
+	// connecting to the address directly and naming the host in `Host`. That
+	// would fail certificate validation for https, which is rejected instead.
+	let mut host_header = None;
+	let resolver = &state.global_state.resolver;
+	if let Some(ip) = url_.host_str().and_then(|host| resolver.override_for(host)) {
+		if url_.scheme() != "http" {
+			return Err(DenoError::new(
+				ErrorKind::InvalidInput,
+				format!("--host-override doesn't apply to {} requests", url_.scheme()),
+			)
+			.into());
+		}
+		let host = match url_.port() {
+			Some(port) => format!("{}:{}", url_.host_str().unwrap(), port),
+			None => url_.host_str().unwrap().to_string(),
+		};
+		host_header = Some(HeaderValue::from_str(&host).unwrap());
+		url_.set_ip_host(ip).unwrap();
+	}
 
 	let mut request = client.request(method, url_);
 
-	if let Some(buf) = data {
+	let is_multipart = args.multipart.is_some();
+	if let Some(parts) = args.multipart {
+		let multipart = multipart_from_args(state, parts, data.as_ref().map(|buf| &**buf))?;
+		let content_type = HeaderValue::from_str(&multipart.content_type()).unwrap();
+		request = request
+			.header(CONTENT_TYPE, content_type)
+			.header(CONTENT_LENGTH, HeaderValue::from(multipart.content_length()?))
+			.body(multipart.into_body()?);
+	} else if let Some(buf) = data {
 		request = request.body(Vec::from(&*buf));
 	}
 
+	let mut has_accept_encoding = false;
 	for (key, value) in args.headers {
 		let name = HeaderName::from_bytes(key.as_bytes()).unwrap();
 		let v = HeaderValue::from_str(&value).unwrap();
+		has_accept_encoding |= name == ACCEPT_ENCODING;
+		if name == HOST {
+			host_header = None;
+		}
+		// The boundary is chosen here.
+		if is_multipart && (name == CONTENT_TYPE || name == CONTENT_LENGTH) {
+			continue;
+		}
 		request = request.header(name, v);
 	}
+	if !has_accept_encoding {
+		request = request.header(ACCEPT_ENCODING, HeaderValue::from_static("gzip, br"));
+	}
+	if let Some(host) = host_header {
+		request = request.header(HOST, host);
+	}
+	let request = request.build().map_err(ErrBox::from)?;
 	debug!("Before fetch {}", url);
 	let state_ = state.clone();
-	let future = futures::compat::Compat01As03::new(request.send())
-		.map_err(ErrBox::from)
-		.and_then(move |res| {
-			debug!("Fetch response {}", url);
-			let status = res.status();
-			let mut res_headers = Vec::new();
-			for (key, val) in res.headers().iter() {
-				res_headers.push((key.to_string(), val.to_str().unwrap().to_owned()));
+	let send = resolved.and_then(move |addr| {
+		let mut request = request;
+		if let Some(addr) = addr.filter(|_| !proxied) {
+			pin_address(&mut request, addr);
+		}
+		futures::compat::Compat01As03::new(client.execute(request)).map_err(ErrBox::from)
+	});
+	let send = match deadline {
+		Some(deadline) => with_deadline(send.boxed(), deadline).boxed(),
+		None => send.boxed(),
+	};
+	let future = send.and_then(move |res| {
+		// Requests that couldn't be pinned are checked once connected. The
+		// request has been sent by then, but its response is never exposed.
+		if let (Some(allowlist), Some(remote_addr)) =
+			(&state_.global_state.net_allowlist, res.remote_addr())
+		{
+			if !proxied {
+				if let Err(err) = allowlist.check_addr(&host, &remote_addr) {
+					return futures::future::err(err);
+				}
 			}
+		}
+		debug!("Fetch response {}", url);
+		let status = res.status();
+		let brotli = decompress
+			&& res.headers().get(CONTENT_ENCODING).map_or(false, |v| v.as_bytes() == b"br");
+		// One entry per value, so repeated headers are kept. hyper lowercases
+		// the names and groups values by name, which is as close to the order
+		// on the wire as it gets.
+		let mut res_headers = Vec::new();
+		for (key, val) in res.headers().iter() {
+			// Like browsers, don't report the encoding of a body that was decoded.
+			if brotli && (key == CONTENT_ENCODING || key == CONTENT_LENGTH) {
+				continue;
+			}
+			// Values aren't necessarily ASCII, which `to_str()` insists on.
+			let val = String::from_utf8_lossy(val.as_bytes()).into_owned();
+			res_headers.push((key.to_string(), val));
+		}
 
-			let body = HttpBody::from(res.into_body());
-			let mut table = state_.lock_resource_table();
-			let rid = table.add("httpBody", Box::new(StreamResource::HttpBody(Box::new(body))));
-
-			let json_res = json!({
-			  "bodyRid": rid,
-			  "status": status.as_u16(),
-			  "statusText": status.canonical_reason().unwrap_or(""),
-			  "headers": res_headers
-			});
+		let mut body = HttpBody::from(res.into_body());
+		if brotli {
+			body = body.with_brotli();
+		}
+		if let Some(deadline) = deadline {
+			body = body.with_deadline(deadline);
+		}
+		let mut table = state_.lock_resource_table();
+		let rid = table.add("httpBody", Box::new(StreamResource::HttpBody(Box::new(body))));
 
-			futures::future::ok(json_res)
+		let json_res = json!({
+		  "bodyRid": rid,
+		  "status": status.as_u16(),
+		  "statusText": status.canonical_reason().unwrap_or(""),
+		  "headers": res_headers
 		});
 
-	Ok(JsonOp::Async(future.boxed()))
+		futures::future::ok(json_res)
+	});
+
+	Ok(JsonOp::Async(cancellable(state, args.cancel_rid, future.boxed())?))
+}
+
+/// Points a plain http request at `addr`, the checked address of its host,
+/// naming the host in `Host`. Otherwise reqwest resolves the host again when
+/// connecting, and could reach another address if the DNS answer changed in
+/// the meantime. https requests can't be pinned like this as the certificate
+/// is for the name, their connected address is checked after the fact.
+fn pin_address(request:&mut reqwest::r#async::Request, addr:SocketAddr) {
+	let url = request.url();
+	let host = match url.host_str() {
+		Some(host) if url.scheme() == "http" => host,
+		_ => return,
+	};
+	let host = match url.port() {
+		Some(port) => format!("{}:{}", host, port),
+		None => host.to_string(),
+	};
+	if !request.headers().contains_key(HOST) {
+		request.headers_mut().insert(HOST, HeaderValue::from_str(&host).unwrap());
+	}
+	request.url_mut().set_ip_host(addr.ip()).unwrap();
 }
@@ -3,7 +3,7 @@ use std::{
 	self,
 	convert::From,
 	future::Future,
-	io::SeekFrom,
+	io::{Read, SeekFrom},
 	pin::Pin,
 	task::{Context, Poll},
 };
@@ -13,8 +13,9 @@ use futures::future::{FutureExt, TryFutureExt};
 use tokio;
 
 use super::{
-	dispatch_json::{Deserialize, JsonOp, Value},
+	dispatch_json::{blocking_buf, blocking_json, Deserialize, JsonOp, Value},
 	io::StreamResource,
+	process::clone_file,
 };
 use crate::{
 	deno_error::{bad_resource, DenoError, ErrorKind},
@@ -24,9 +25,15 @@ use crate::{
 };
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("open", s.core_op(json_op(s.stateful_op(op_open))));
-	i.register_op("close", s.core_op(json_op(s.stateful_op(op_close))));
-	i.register_op("seek", s.core_op(json_op(s.stateful_op(op_seek))));
+	s.register_op(i, "open", json_op(s.stateful_op(op_open)));
+	s.register_op(i, "close", json_op(s.stateful_op(op_close)));
+	s.register_op(i, "seek", json_op(s.stateful_op(op_seek)));
+	s.register_op(i, "flock", json_op(s.stateful_op(op_flock)));
+	s.register_op(i, "funlock", json_op(s.stateful_op(op_funlock)));
+	s.register_op(i, "ftruncate", json_op(s.stateful_op(op_ftruncate)));
+	s.register_op(i, "fallocate", json_op(s.stateful_op(op_fallocate)));
+	s.register_op(i, "read_file", json_op(s.stateful_op(op_read_file)));
+	s.register_op(i, "write_file", json_op(s.stateful_op(op_write_file)));
 }
 
 #[derive(Deserialize)]
@@ -43,7 +50,7 @@ fn op_open(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:OpenArgs = serde_json::from_value(args)?;
-	let (filename, filename_) = deno_fs::resolve_from_cwd(&args.filename)?;
+	let (filename, filename_) = state.resolve_path(&args.filename)?;
 	let mode = args.mode.as_ref();
 	let state_ = state.clone();
 	let mut open_options = tokio::fs::OpenOptions::new();
@@ -99,6 +106,7 @@ fn op_open(
 	.and_then(move |fs_file| {
 		let mut table = state_.lock_resource_table();
 		let rid = table.add("fsFile", Box::new(StreamResource::FsFile(fs_file)));
+		table.set_details(rid, &filename_);
 		futures::future::ok(json!(rid))
 	});
 
@@ -143,7 +151,13 @@ impl Future for SeekFuture {
 
 		let tokio_file = match resource {
 			StreamResource::FsFile(ref mut file) => file,
-			_ => return Poll::Ready(Err(bad_resource())),
+			_ => {
+				return Poll::Ready(Err(DenoError::new(
+					ErrorKind::BadResource,
+					"Resource is not seekable".to_string(),
+				)
+				.into()));
+			},
 		};
 
 		use tokio::prelude::Async::*;
@@ -161,7 +175,7 @@ impl Future for SeekFuture {
 struct SeekArgs {
 	promise_id:Option<u64>,
 	rid:i32,
-	offset:i32,
+	offset:i64,
 	whence:i32,
 }
 
@@ -176,9 +190,15 @@ fn op_seek(
 	let whence = args.whence as u32;
 	// Translate seek mode to Rust repr.
 	let seek_from = match whence {
+		0 if offset < 0 => {
+			return Err(ErrBox::from(DenoError::new(
+				ErrorKind::InvalidInput,
+				format!("Invalid seek offset: {}", offset),
+			)));
+		},
 		0 => SeekFrom::Start(offset as u64),
-		1 => SeekFrom::Current(i64::from(offset)),
-		2 => SeekFrom::End(i64::from(offset)),
+		1 => SeekFrom::Current(offset),
+		2 => SeekFrom::End(offset),
 		_ => {
 			return Err(ErrBox::from(DenoError::new(
 				ErrorKind::InvalidSeekMode,
@@ -189,7 +209,7 @@ fn op_seek(
 
 	let fut = SeekFuture { state:state.clone(), seek_from, rid };
 
-	let op = fut.and_then(move |_| futures::future::ok(json!({})));
+	let op = fut.and_then(move |position| futures::future::ok(json!({ "position": position })));
 	if args.promise_id.is_none() {
 		let buf = futures::executor::block_on(op)?;
 		Ok(JsonOp::Sync(buf))
@@ -197,3 +217,208 @@ fn op_seek(
 		Ok(JsonOp::Async(op.boxed()))
 	}
 }
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FlockArgs {
+	promise_id:Option<u64>,
+	rid:i32,
+	exclusive:bool,
+	non_blocking:bool,
+}
+
+fn op_flock(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:FlockArgs = serde_json::from_value(args)?;
+	// The lock is taken through a duplicate handle so that a blocking wait
+	// doesn't hold the resource table. It stays with the resource's file and is
+	// released when the resource is closed.
+	let file = clone_file(args.rid as u32, state)?;
+	let exclusive = args.exclusive;
+	let non_blocking = args.non_blocking;
+
+	let is_sync = args.promise_id.is_none();
+	blocking_json(is_sync, move || {
+		debug!("flock rid={} exclusive={} non_blocking={}", args.rid, exclusive, non_blocking);
+		deno_fs::lock_file(&file, exclusive, non_blocking)?;
+		Ok(json!({}))
+	})
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FunlockArgs {
+	promise_id:Option<u64>,
+	rid:i32,
+}
+
+fn op_funlock(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:FunlockArgs = serde_json::from_value(args)?;
+	let file = clone_file(args.rid as u32, state)?;
+
+	let is_sync = args.promise_id.is_none();
+	blocking_json(is_sync, move || {
+		debug!("funlock rid={}", args.rid);
+		deno_fs::unlock_file(&file)?;
+		Ok(json!({}))
+	})
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileLenArgs {
+	promise_id:Option<u64>,
+	rid:i32,
+	len:u64,
+}
+
+fn op_ftruncate(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:FileLenArgs = serde_json::from_value(args)?;
+	let file = clone_file(args.rid as u32, state)?;
+	let len = args.len;
+
+	let is_sync = args.promise_id.is_none();
+	blocking_json(is_sync, move || {
+		debug!("ftruncate rid={} {}", args.rid, len);
+		file.set_len(len)?;
+		Ok(json!({}))
+	})
+}
+
+fn op_fallocate(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:FileLenArgs = serde_json::from_value(args)?;
+	let file = clone_file(args.rid as u32, state)?;
+	let len = args.len;
+
+	let is_sync = args.promise_id.is_none();
+	blocking_json(is_sync, move || {
+		debug!("fallocate rid={} {}", args.rid, len);
+		deno_fs::fallocate(&file, len)?;
+		Ok(json!({}))
+	})
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadFileArgs {
+	promise_id:Option<u64>,
+	filename:String,
+	/// Fail with `ErrorKind::TooLarge` rather than read more bytes.
+	max_size:Option<u64>,
+}
+
+// Opens, reads and closes the file in one op.
+fn op_read_file(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:ReadFileArgs = serde_json::from_value(args)?;
+	let (filename, filename_) = state.resolve_path(&args.filename)?;
+	state.check_read(&filename_)?;
+	let max_size = args.max_size;
+
+	let is_sync = args.promise_id.is_none();
+	blocking_buf(is_sync, move || {
+		debug!("op_read_file {}", filename.display());
+		let too_large = || -> ErrBox {
+			DenoError::new(
+				ErrorKind::TooLarge,
+				format!("\"{}\" is larger than {} bytes", filename_, max_size.unwrap()),
+			)
+			.into()
+		};
+		let file = std::fs::File::open(&filename)?;
+		// The size is only a hint, files in /proc for example report zero.
+		let len = file.metadata()?.len();
+		if max_size.map_or(false, |max| len > max) {
+			return Err(too_large());
+		}
+		let mut buf = Vec::with_capacity(len as usize);
+		match max_size {
+			Some(max) => {
+				file.take(max + 1).read_to_end(&mut buf)?;
+				if buf.len() as u64 > max {
+					return Err(too_large());
+				}
+			},
+			None => {
+				(&file).read_to_end(&mut buf)?;
+			},
+		}
+		Ok(buf.into_boxed_slice())
+	})
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WriteFileArgs {
+	promise_id:Option<u64>,
+	filename:String,
+	create:bool,
+	append:bool,
+	perm:Option<u32>,
+	/// Replace the file by renaming a temporary one over it, see
+	/// `fs::write_file_atomic()`.
+	atomic:bool,
+	/// Flush an atomic write to disk before returning.
+	sync:bool,
+}
+
+// Opens, writes and closes the file in one op.
+fn op_write_file(
+	state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:WriteFileArgs = serde_json::from_value(args)?;
+	let (filename, filename_) = state.resolve_path(&args.filename)?;
+
+	state.check_write(&filename_)?;
+	if args.atomic {
+		if args.append {
+			return Err(DenoError::new(
+				ErrorKind::InvalidInput,
+				"append and atomic can't be used together".to_string(),
+			)
+			.into());
+		}
+		// The temporary file is created in the same directory.
+		if let Some(dir) = filename.parent() {
+			state.check_write(&dir.to_string_lossy())?;
+		}
+	}
+
+	let is_sync = args.promise_id.is_none();
+	blocking_json(is_sync, move || {
+		debug!("op_write_file {}", filename.display());
+		let data:&[u8] = zero_copy.as_ref().map_or(&[], |buf| &**buf);
+		let perm = args.perm.unwrap_or(0o666);
+		if args.atomic {
+			if !args.create {
+				// Fails with NotFound like opening without `create` would.
+				std::fs::metadata(&filename)?;
+			}
+			deno_fs::write_file_atomic(&filename, data, perm, args.sync)?;
+		} else {
+			let update_perm = args.perm.is_some();
+			deno_fs::write_file_2(&filename, data, update_perm, perm, args.create, args.append)?;
+		}
+		Ok(json!({}))
+	})
+}
@@ -3,37 +3,54 @@
 // without it
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::{convert::From, fs, path::PathBuf, time::UNIX_EPOCH};
+use std::{
+	convert::From,
+	fs,
+	path::{Path, PathBuf},
+	sync::{mpsc as std_mpsc, Arc, Mutex},
+	task::Poll,
+	thread,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-use deno::*;
+use deno::{Resource, *};
+use futures::{channel::mpsc, future::FutureExt, stream::StreamExt, task::AtomicWaker};
+use notify::{op::Op as RawOp, DebouncedEvent, RawEvent, RecommendedWatcher, RecursiveMode, Watcher};
 use remove_dir_all::remove_dir_all;
 
 use super::dispatch_json::{blocking_json, Deserialize, JsonOp, Value};
 use crate::{
-	deno_error::{DenoError, ErrorKind},
+	deno_error::{bad_resource, DenoError, ErrorKind},
 	fs as deno_fs,
 	ops::json_op,
 	state::ThreadSafeState,
 };
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("chdir", s.core_op(json_op(s.stateful_op(op_chdir))));
-	i.register_op("mkdir", s.core_op(json_op(s.stateful_op(op_mkdir))));
-	i.register_op("chmod", s.core_op(json_op(s.stateful_op(op_chmod))));
-	i.register_op("chown", s.core_op(json_op(s.stateful_op(op_chown))));
-	i.register_op("remove", s.core_op(json_op(s.stateful_op(op_remove))));
-	i.register_op("copy_file", s.core_op(json_op(s.stateful_op(op_copy_file))));
-	i.register_op("stat", s.core_op(json_op(s.stateful_op(op_stat))));
-	i.register_op("realpath", s.core_op(json_op(s.stateful_op(op_realpath))));
-	i.register_op("read_dir", s.core_op(json_op(s.stateful_op(op_read_dir))));
-	i.register_op("rename", s.core_op(json_op(s.stateful_op(op_rename))));
-	i.register_op("link", s.core_op(json_op(s.stateful_op(op_link))));
-	i.register_op("symlink", s.core_op(json_op(s.stateful_op(op_symlink))));
-	i.register_op("read_link", s.core_op(json_op(s.stateful_op(op_read_link))));
-	i.register_op("truncate", s.core_op(json_op(s.stateful_op(op_truncate))));
-	i.register_op("make_temp_dir", s.core_op(json_op(s.stateful_op(op_make_temp_dir))));
-	i.register_op("cwd", s.core_op(json_op(s.stateful_op(op_cwd))));
-	i.register_op("utime", s.core_op(json_op(s.stateful_op(op_utime))));
+	s.register_op(i, "chdir", json_op(s.stateful_op(op_chdir)));
+	s.register_op(i, "mkdir", json_op(s.stateful_op(op_mkdir)));
+	s.register_op(i, "chmod", json_op(s.stateful_op(op_chmod)));
+	s.register_op(i, "chown", json_op(s.stateful_op(op_chown)));
+	s.register_op(i, "umask", json_op(s.stateful_op(op_umask)));
+	s.register_op(i, "remove", json_op(s.stateful_op(op_remove)));
+	s.register_op(i, "copy_file", json_op(s.stateful_op(op_copy_file)));
+	s.register_op(i, "copy_dir", json_op(s.stateful_op(op_copy_dir)));
+	s.register_op(i, "stat", json_op(s.stateful_op(op_stat)));
+	s.register_op(i, "realpath", json_op(s.stateful_op(op_realpath)));
+	s.register_op(i, "read_dir", json_op(s.stateful_op(op_read_dir)));
+	s.register_op(i, "open_dir", json_op(s.stateful_op(op_open_dir)));
+	s.register_op(i, "read_dir_batch", json_op(s.stateful_op(op_read_dir_batch)));
+	s.register_op(i, "rename", json_op(s.stateful_op(op_rename)));
+	s.register_op(i, "link", json_op(s.stateful_op(op_link)));
+	s.register_op(i, "symlink", json_op(s.stateful_op(op_symlink)));
+	s.register_op(i, "read_link", json_op(s.stateful_op(op_read_link)));
+	s.register_op(i, "truncate", json_op(s.stateful_op(op_truncate)));
+	s.register_op(i, "make_temp_dir", json_op(s.stateful_op(op_make_temp_dir)));
+	s.register_op(i, "make_temp_file", json_op(s.stateful_op(op_make_temp_file)));
+	s.register_op(i, "cwd", json_op(s.stateful_op(op_cwd)));
+	s.register_op(i, "utime", json_op(s.stateful_op(op_utime)));
+	s.register_op(i, "fs_watch", json_op(s.stateful_op(op_fs_watch)));
+	s.register_op(i, "fs_events_poll", json_op(s.stateful_op(op_fs_events_poll)));
 }
 
 #[derive(Deserialize)]
@@ -41,13 +58,15 @@ struct ChdirArgs {
 	directory:String,
 }
 
+// Only changes the directory of the calling worker, the process' working
+// directory stays the same.
 fn op_chdir(
-	_state:&ThreadSafeState,
+	state:&ThreadSafeState,
 	args:Value,
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:ChdirArgs = serde_json::from_value(args)?;
-	std::env::set_current_dir(&args.directory)?;
+	state.set_cwd(&args.directory)?;
 	Ok(JsonOp::Sync(json!({})))
 }
 
@@ -66,7 +85,7 @@ fn op_mkdir(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:MkdirArgs = serde_json::from_value(args)?;
-	let (path, path_) = deno_fs::resolve_from_cwd(args.path.as_ref())?;
+	let (path, path_) = state.resolve_path(args.path.as_ref())?;
 
 	state.check_write(&path_)?;
 
@@ -92,21 +111,20 @@ fn op_chmod(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:ChmodArgs = serde_json::from_value(args)?;
-	let (path, path_) = deno_fs::resolve_from_cwd(args.path.as_ref())?;
+	let (path, path_) = state.resolve_path(args.path.as_ref())?;
 
 	state.check_write(&path_)?;
 
 	let is_sync = args.promise_id.is_none();
 	blocking_json(is_sync, move || {
 		debug!("op_chmod {}", &path_);
-		// Still check file/dir exists on windows
-		let _metadata = fs::metadata(&path)?;
+		let mut permissions = fs::metadata(&path)?.permissions();
 		#[cfg(any(unix))]
-		{
-			let mut permissions = _metadata.permissions();
-			permissions.set_mode(args.mode);
-			fs::set_permissions(&path, permissions)?;
-		}
+		permissions.set_mode(args.mode);
+		// Windows only has a readonly attribute, derived from the owner write bit.
+		#[cfg(not(any(unix)))]
+		permissions.set_readonly(args.mode & 0o200 == 0);
+		fs::set_permissions(&path, permissions)?;
 		Ok(json!({}))
 	})
 }
@@ -126,19 +144,40 @@ fn op_chown(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:ChownArgs = serde_json::from_value(args)?;
+	let (_, path_) = state.resolve_path(args.path.as_ref())?;
 
-	state.check_write(&args.path)?;
+	state.check_write(&path_)?;
 
 	let is_sync = args.promise_id.is_none();
 	blocking_json(is_sync, move || {
-		debug!("op_chown {}", &args.path);
-		match deno_fs::chown(args.path.as_ref(), args.uid, args.gid) {
+		debug!("op_chown {}", &path_);
+		match deno_fs::chown(&path_, args.uid, args.gid) {
 			Ok(_) => Ok(json!({})),
 			Err(e) => Err(e),
 		}
 	})
 }
 
+#[derive(Deserialize)]
+struct UmaskArgs {
+	mask:Option<u32>,
+}
+
+/// Returns the process umask, replacing it first if `mask` is given. Only
+/// replacing it needs write permission.
+fn op_umask(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:UmaskArgs = serde_json::from_value(args)?;
+	if args.mask.is_some() {
+		state.check_umask()?;
+	}
+	let previous = deno_fs::umask(args.mask)?;
+	Ok(JsonOp::Sync(json!({ "previous": previous })))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct RemoveArgs {
@@ -153,7 +192,7 @@ fn op_remove(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:RemoveArgs = serde_json::from_value(args)?;
-	let (path, path_) = deno_fs::resolve_from_cwd(args.path.as_ref())?;
+	let (path, path_) = state.resolve_path(args.path.as_ref())?;
 	let recursive = args.recursive;
 
 	state.check_write(&path_)?;
@@ -188,8 +227,8 @@ fn op_copy_file(
 ) -> Result<JsonOp, ErrBox> {
 	let args:CopyFileArgs = serde_json::from_value(args)?;
 
-	let (from, from_) = deno_fs::resolve_from_cwd(args.from.as_ref())?;
-	let (to, to_) = deno_fs::resolve_from_cwd(args.to.as_ref())?;
+	let (from, from_) = state.resolve_path(args.from.as_ref())?;
+	let (to, to_) = state.resolve_path(args.to.as_ref())?;
 
 	state.check_read(&from_)?;
 	state.check_write(&to_)?;
@@ -209,6 +248,44 @@ fn op_copy_file(
 	})
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CopyDirArgs {
+	promise_id:Option<u64>,
+	from:String,
+	to:String,
+	preserve:bool,
+	overwrite:bool,
+	follow_symlinks:bool,
+}
+
+fn op_copy_dir(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:CopyDirArgs = serde_json::from_value(args)?;
+
+	let (from, from_) = state.resolve_path(args.from.as_ref())?;
+	let (to, to_) = state.resolve_path(args.to.as_ref())?;
+
+	state.check_read(&from_)?;
+	state.check_write(&to_)?;
+
+	let options = deno_fs::CopyDirOptions {
+		preserve:args.preserve,
+		overwrite:args.overwrite,
+		follow_symlinks:args.follow_symlinks,
+	};
+
+	debug!("op_copy_dir {} {}", from.display(), to.display());
+	let is_sync = args.promise_id.is_none();
+	blocking_json(is_sync, move || {
+		deno_fs::copy_dir(&from, &to, options)?;
+		Ok(json!({}))
+	})
+}
+
 macro_rules! to_seconds {
 	($time:expr) => {{
 		// Unwrap is safe here as if the file is before the unix epoch
@@ -225,6 +302,60 @@ fn get_mode(perm:&fs::Permissions) -> u32 { perm.mode() }
 #[cfg(not(any(unix)))]
 fn get_mode(_perm:&fs::Permissions) -> u32 { 0 }
 
+/// Nanoseconds past the whole second, to go with `to_seconds!`.
+fn subsec_nanos(time:std::io::Result<SystemTime>) -> Option<u32> {
+	time.ok().and_then(|t| t.duration_since(UNIX_EPOCH).ok()).map(|d| d.subsec_nanos())
+}
+
+#[cfg(unix)]
+fn unix_stat_json(metadata:&fs::Metadata) -> Value {
+	use std::os::unix::fs::MetadataExt;
+	json!({
+	  "dev": metadata.dev(),
+	  "ino": metadata.ino(),
+	  "nlink": metadata.nlink(),
+	  "uid": metadata.uid(),
+	  "gid": metadata.gid(),
+	  "rdev": metadata.rdev(),
+	  "blksize": metadata.blksize(),
+	  "blocks": metadata.blocks(),
+	})
+}
+
+#[cfg(not(unix))]
+fn unix_stat_json(_metadata:&fs::Metadata) -> Value {
+	json!({
+	  "dev": null,
+	  "ino": null,
+	  "nlink": null,
+	  "uid": null,
+	  "gid": null,
+	  "rdev": null,
+	  "blksize": null,
+	  "blocks": null,
+	})
+}
+
+fn stat_json(metadata:&fs::Metadata) -> Value {
+	let mut json = json!({
+	  "isFile": metadata.is_file(),
+	  "isSymlink": metadata.file_type().is_symlink(),
+	  "len": metadata.len(),
+	  "modified": to_seconds!(metadata.modified()),
+	  "accessed": to_seconds!(metadata.accessed()),
+	  "created": to_seconds!(metadata.created()),
+	  "modifiedNsec": subsec_nanos(metadata.modified()),
+	  "accessedNsec": subsec_nanos(metadata.accessed()),
+	  "createdNsec": subsec_nanos(metadata.created()),
+	  "mode": get_mode(&metadata.permissions()),
+	  "hasMode": cfg!(target_family = "unix"), // false on windows,
+	});
+	if let (Some(stat), Value::Object(unix)) = (json.as_object_mut(), unix_stat_json(metadata)) {
+		stat.extend(unix);
+	}
+	json
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct StatArgs {
@@ -240,7 +371,7 @@ fn op_stat(
 ) -> Result<JsonOp, ErrBox> {
 	let args:StatArgs = serde_json::from_value(args)?;
 
-	let (filename, filename_) = deno_fs::resolve_from_cwd(args.filename.as_ref())?;
+	let (filename, filename_) = state.resolve_path(args.filename.as_ref())?;
 	let lstat = args.lstat;
 
 	state.check_read(&filename_)?;
@@ -251,16 +382,7 @@ fn op_stat(
 		let metadata =
 			if lstat { fs::symlink_metadata(&filename)? } else { fs::metadata(&filename)? };
 
-		Ok(json!({
-		  "isFile": metadata.is_file(),
-		  "isSymlink": metadata.file_type().is_symlink(),
-		  "len": metadata.len(),
-		  "modified":to_seconds!(metadata.modified()),
-		  "accessed":to_seconds!(metadata.accessed()),
-		  "created":to_seconds!(metadata.created()),
-		  "mode": get_mode(&metadata.permissions()),
-		  "hasMode": cfg!(target_family = "unix"), // false on windows,
-		}))
+		Ok(stat_json(&metadata))
 	})
 }
 
@@ -269,6 +391,8 @@ fn op_stat(
 struct RealpathArgs {
 	promise_id:Option<u64>,
 	path:String,
+	/// When `false`, a symlink in the final component isn't resolved.
+	follow_final_symlink:Option<bool>,
 }
 
 fn op_realpath(
@@ -277,15 +401,22 @@ fn op_realpath(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:RealpathArgs = serde_json::from_value(args)?;
-	let (_, path_) = deno_fs::resolve_from_cwd(args.path.as_ref())?;
+	let (path, path_) = state.resolve_path(args.path.as_ref())?;
 	state.check_read(&path_)?;
-	let path = args.path.clone();
+	let follow_final_symlink = args.follow_final_symlink.unwrap_or(true);
+	let state = state.clone();
 	let is_sync = args.promise_id.is_none();
 	blocking_json(is_sync, move || {
-		debug!("op_realpath {}", &path);
+		debug!("op_realpath {}", path.display());
 		// corresponds to the realpath on Unix and
 		// CreateFile and GetFinalPathNameByHandle on Windows
-		let realpath = fs::canonicalize(&path)?;
+		let realpath = if follow_final_symlink {
+			fs::canonicalize(&path)?
+		} else {
+			deno_fs::canonicalize_no_follow(&path)?
+		};
+		// Links may point outside of what the caller was allowed to read.
+		state.check_read(realpath.to_str().unwrap())?;
 		let mut realpath_str = realpath.to_str().unwrap().to_owned().replace("\\", "/");
 		if cfg!(windows) {
 			realpath_str = realpath_str.trim_start_matches("//?/").to_string();
@@ -307,7 +438,7 @@ fn op_read_dir(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:ReadDirArgs = serde_json::from_value(args)?;
-	let (path, path_) = deno_fs::resolve_from_cwd(args.path.as_ref())?;
+	let (path, path_) = state.resolve_path(args.path.as_ref())?;
 
 	state.check_read(&path_)?;
 
@@ -319,19 +450,10 @@ fn op_read_dir(
 			.map(|entry| {
 				let entry = entry.unwrap();
 				let metadata = entry.metadata().unwrap();
-				let file_type = metadata.file_type();
-
-				json!({
-				  "isFile": file_type.is_file(),
-				  "isSymlink": file_type.is_symlink(),
-				  "len": metadata.len(),
-				  "modified": to_seconds!(metadata.modified()),
-				  "accessed": to_seconds!(metadata.accessed()),
-				  "created": to_seconds!(metadata.created()),
-				  "mode": get_mode(&metadata.permissions()),
-				  "name": entry.file_name().to_str().unwrap(),
-				  "hasMode": cfg!(target_family = "unix"), // false on windows,
-				})
+
+				let mut json = stat_json(&metadata);
+				json["name"] = json!(entry.file_name().to_str().unwrap());
+				json
 			})
 			.collect();
 
@@ -339,6 +461,101 @@ fn op_read_dir(
 	})
 }
 
+/// An open directory handle that `read_dir_batch` reads entries from. The
+/// handle is closed once the resource and any batch in flight are dropped.
+struct DirResource {
+	entries:Arc<Mutex<fs::ReadDir>>,
+}
+
+impl Resource for DirResource {}
+
+#[derive(Deserialize)]
+struct OpenDirArgs {
+	path:String,
+}
+
+fn op_open_dir(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:OpenDirArgs = serde_json::from_value(args)?;
+	let (path, path_) = state.resolve_path(args.path.as_ref())?;
+	state.check_read(&path_)?;
+	debug!("op_open_dir {}", path.display());
+	let entries = fs::read_dir(path)?;
+	let mut table = state.lock_resource_table();
+	let rid = table.add("dir", Box::new(DirResource { entries:Arc::new(Mutex::new(entries)) }));
+	Ok(JsonOp::Sync(json!({ "rid": rid })))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadDirBatchArgs {
+	promise_id:Option<u64>,
+	rid:i32,
+	batch_size:Option<usize>,
+	/// Include inode numbers, which come with the entries on Unix.
+	#[serde(default)]
+	inode:bool,
+}
+
+/// Entries per batch unless the caller asks for another size.
+const DEFAULT_DIR_BATCH_SIZE:usize = 1024;
+
+/// Returns up to `batchSize` entries with their names and file types, which
+/// most platforms report without a `stat` call. An empty batch means the end
+/// of the directory.
+fn op_read_dir_batch(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:ReadDirBatchArgs = serde_json::from_value(args)?;
+	let entries = {
+		let table = state.lock_resource_table();
+		let dir = table.get::<DirResource>(args.rid as u32).ok_or_else(bad_resource)?;
+		dir.entries.clone()
+	};
+	let batch_size = args.batch_size.unwrap_or(DEFAULT_DIR_BATCH_SIZE).max(1);
+	let inode = args.inode;
+	let is_sync = args.promise_id.is_none();
+	blocking_json(is_sync, move || {
+		let mut entries = entries.lock().unwrap();
+		let mut batch = Vec::new();
+		for entry in entries.by_ref().take(batch_size) {
+			let entry = entry?;
+			let file_type = entry.file_type()?;
+			let kind = if file_type.is_symlink() {
+				"symlink"
+			} else if file_type.is_dir() {
+				"dir"
+			} else if file_type.is_file() {
+				"file"
+			} else {
+				"other"
+			};
+			let mut json = json!({
+			  "name": entry.file_name().to_string_lossy(),
+			  "type": kind,
+			});
+			if inode {
+				#[cfg(unix)]
+				{
+					use std::os::unix::fs::DirEntryExt;
+					json["ino"] = json!(entry.ino());
+				}
+				#[cfg(not(unix))]
+				{
+					json["ino"] = Value::Null;
+				}
+			}
+			batch.push(json);
+		}
+		Ok(json!({ "entries": batch }))
+	})
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct RenameArgs {
@@ -354,8 +571,8 @@ fn op_rename(
 ) -> Result<JsonOp, ErrBox> {
 	let args:RenameArgs = serde_json::from_value(args)?;
 
-	let (oldpath, oldpath_) = deno_fs::resolve_from_cwd(args.oldpath.as_ref())?;
-	let (newpath, newpath_) = deno_fs::resolve_from_cwd(args.newpath.as_ref())?;
+	let (oldpath, oldpath_) = state.resolve_path(args.oldpath.as_ref())?;
+	let (newpath, newpath_) = state.resolve_path(args.newpath.as_ref())?;
 
 	state.check_read(&oldpath_)?;
 	state.check_write(&oldpath_)?;
@@ -384,8 +601,8 @@ fn op_link(
 ) -> Result<JsonOp, ErrBox> {
 	let args:LinkArgs = serde_json::from_value(args)?;
 
-	let (oldname, oldname_) = deno_fs::resolve_from_cwd(args.oldname.as_ref())?;
-	let (newname, newname_) = deno_fs::resolve_from_cwd(args.newname.as_ref())?;
+	let (oldname, oldname_) = state.resolve_path(args.oldname.as_ref())?;
+	let (newname, newname_) = state.resolve_path(args.newname.as_ref())?;
 
 	state.check_read(&oldname_)?;
 	state.check_write(&newname_)?;
@@ -404,6 +621,10 @@ struct SymlinkArgs {
 	promise_id:Option<u64>,
 	oldname:String,
 	newname:String,
+	/// `"file"` or `"dir"`. Windows needs to know which kind of link to
+	/// create, without a hint it is inferred from `oldname`.
+	#[serde(rename = "type")]
+	type_:Option<String>,
 }
 
 fn op_symlink(
@@ -413,19 +634,42 @@ fn op_symlink(
 ) -> Result<JsonOp, ErrBox> {
 	let args:SymlinkArgs = serde_json::from_value(args)?;
 
-	let (oldname, _oldname_) = deno_fs::resolve_from_cwd(args.oldname.as_ref())?;
-	let (newname, newname_) = deno_fs::resolve_from_cwd(args.newname.as_ref())?;
+	let (oldname, _oldname_) = state.resolve_path(args.oldname.as_ref())?;
+	let (newname, newname_) = state.resolve_path(args.newname.as_ref())?;
 
 	state.check_write(&newname_)?;
-	// TODO Use type for Windows.
-	if cfg!(windows) {
-		return Err(DenoError::new(ErrorKind::Other, "Not implemented".to_string()).into());
-	}
+	let is_dir = match args.type_.as_ref().map(String::as_str) {
+		Some("file") => Some(false),
+		Some("dir") => Some(true),
+		None => None,
+		Some(type_) => {
+			return Err(DenoError::new(
+				ErrorKind::InvalidInput,
+				format!("Invalid symlink type \"{}\", expected \"file\" or \"dir\"", type_),
+			)
+			.into());
+		},
+	};
 	let is_sync = args.promise_id.is_none();
 	blocking_json(is_sync, move || {
 		debug!("op_symlink {} {}", oldname.display(), newname.display());
-		#[cfg(any(unix))]
-		std::os::unix::fs::symlink(&oldname, &newname)?;
+		#[cfg(unix)]
+		{
+			let _ = is_dir;
+			std::os::unix::fs::symlink(&oldname, &newname)?;
+		}
+		#[cfg(windows)]
+		{
+			use std::os::windows::fs::{symlink_dir, symlink_file};
+			// A dangling link without a hint is created as a file link.
+			let is_dir = is_dir
+				.unwrap_or_else(|| fs::metadata(&oldname).map(|m| m.is_dir()).unwrap_or(false));
+			if is_dir {
+				symlink_dir(&oldname, &newname)?;
+			} else {
+				symlink_file(&oldname, &newname)?;
+			}
+		}
 		Ok(json!({}))
 	})
 }
@@ -444,7 +688,7 @@ fn op_read_link(
 ) -> Result<JsonOp, ErrBox> {
 	let args:ReadLinkArgs = serde_json::from_value(args)?;
 
-	let (name, name_) = deno_fs::resolve_from_cwd(args.name.as_ref())?;
+	let (name, name_) = state.resolve_path(args.name.as_ref())?;
 
 	state.check_read(&name_)?;
 
@@ -473,7 +717,7 @@ fn op_truncate(
 ) -> Result<JsonOp, ErrBox> {
 	let args:TruncateArgs = serde_json::from_value(args)?;
 
-	let (filename, filename_) = deno_fs::resolve_from_cwd(args.name.as_ref())?;
+	let (filename, filename_) = state.resolve_path(args.name.as_ref())?;
 	let len = args.len;
 
 	state.check_write(&filename_)?;
@@ -489,7 +733,7 @@ fn op_truncate(
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-struct MakeTempDirArgs {
+struct MakeTempArgs {
 	promise_id:Option<u64>,
 	dir:Option<String>,
 	prefix:Option<String>,
@@ -501,26 +745,39 @@ fn op_make_temp_dir(
 	args:Value,
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
-	let args:MakeTempDirArgs = serde_json::from_value(args)?;
+	make_temp(state, args, deno_fs::make_temp_dir)
+}
+
+fn op_make_temp_file(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	make_temp(state, args, deno_fs::make_temp_file)
+}
 
-	// FIXME
-	state.check_write("make_temp")?;
+fn make_temp(
+	state:&ThreadSafeState,
+	args:Value,
+	create:fn(Option<&Path>, Option<&str>, Option<&str>) -> std::io::Result<PathBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:MakeTempArgs = serde_json::from_value(args)?;
 
-	let dir = args.dir.map(PathBuf::from);
-	let prefix = args.prefix.map(String::from);
-	let suffix = args.suffix.map(String::from);
+	// Writing to the directory the entry is created in is what's checked.
+	let dir = match &args.dir {
+		Some(dir) => state.resolve_path(dir)?.0,
+		None => std::env::temp_dir(),
+	};
+	state.check_write(dir.to_str().unwrap())?;
+	let prefix = args.prefix;
+	let suffix = args.suffix;
 
 	let is_sync = args.promise_id.is_none();
 	blocking_json(is_sync, move || {
 		// TODO(piscisaureus): use byte vector for paths, not a string.
 		// See https://github.com/denoland/deno/issues/627.
 		// We can't assume that paths are always valid utf8 strings.
-		let path = deno_fs::make_temp_dir(
-			// Converting Option<String> to Option<&str>
-			dir.as_ref().map(|x| &**x),
-			prefix.as_ref().map(|x| &**x),
-			suffix.as_ref().map(|x| &**x),
-		)?;
+		let path = create(Some(&dir), prefix.as_ref().map(|x| &**x), suffix.as_ref().map(|x| &**x))?;
 		let path_str = path.to_str().unwrap();
 
 		Ok(json!(path_str))
@@ -552,11 +809,182 @@ fn op_utime(
 }
 
 fn op_cwd(
-	_state:&ThreadSafeState,
+	state:&ThreadSafeState,
 	_args:Value,
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
-	let path = std::env::current_dir()?;
+	let path = state.cwd();
+	// Fails with `NotFound` if the directory was removed since.
+	fs::metadata(&path)?;
 	let path_str = path.into_os_string().into_string().unwrap();
 	Ok(JsonOp::Sync(json!(path_str)))
 }
+
+/// A change reported by `fs_watch`.
+struct FsEvent {
+	kind:&'static str,
+	paths:Vec<PathBuf>,
+}
+
+impl FsEvent {
+	fn new(kind:&'static str, paths:Vec<PathBuf>) -> Option<Result<Self, ErrBox>> {
+		Some(Ok(Self { kind, paths }))
+	}
+
+	fn from_debounced(event:DebouncedEvent) -> Option<Result<Self, ErrBox>> {
+		match event {
+			DebouncedEvent::Create(path) => Self::new("create", vec![path]),
+			DebouncedEvent::Write(path) | DebouncedEvent::Chmod(path) => {
+				Self::new("modify", vec![path])
+			},
+			DebouncedEvent::Remove(path) => Self::new("remove", vec![path]),
+			DebouncedEvent::Rename(from, to) => Self::new("rename", vec![from, to]),
+			DebouncedEvent::Error(e, _) => Some(Err(ErrBox::from(e))),
+			// Notices are followed by the debounced event for the same path.
+			DebouncedEvent::NoticeWrite(_)
+			| DebouncedEvent::NoticeRemove(_)
+			| DebouncedEvent::Rescan => None,
+		}
+	}
+
+	fn from_raw(event:RawEvent) -> Option<Result<Self, ErrBox>> {
+		let op = match event.op {
+			Ok(op) => op,
+			Err(e) => return Some(Err(ErrBox::from(e))),
+		};
+		let paths = event.path.into_iter().collect();
+		if op.contains(RawOp::CREATE) {
+			Self::new("create", paths)
+		} else if op.contains(RawOp::REMOVE) {
+			Self::new("remove", paths)
+		} else if op.contains(RawOp::RENAME) {
+			Self::new("rename", paths)
+		} else if op.intersects(RawOp::WRITE | RawOp::CHMOD | RawOp::CLOSE_WRITE) {
+			Self::new("modify", paths)
+		} else {
+			None
+		}
+	}
+
+	fn to_json(&self) -> Value {
+		let paths:Vec<_> = self.paths.iter().map(|p| p.to_string_lossy()).collect();
+		json!({
+		  "kind": self.kind,
+		  "paths": paths,
+		})
+	}
+}
+
+struct FsWatcherResource {
+	/// Watching stops when this is dropped, which in turn ends the stream.
+	_watcher:RecommendedWatcher,
+	events:mpsc::UnboundedReceiver<Result<FsEvent, ErrBox>>,
+	/// Task of a pending `fs_events_poll`, woken when the watcher is closed.
+	waker:AtomicWaker,
+}
+
+impl Resource for FsWatcherResource {}
+
+impl Drop for FsWatcherResource {
+	fn drop(&mut self) { self.waker.wake(); }
+}
+
+/// notify delivers events on a std channel; move them onto a futures channel
+/// from a helper thread. The thread exits once the watcher is dropped.
+fn forward_events<E, F>(
+	rx:std_mpsc::Receiver<E>,
+	convert:F,
+) -> mpsc::UnboundedReceiver<Result<FsEvent, ErrBox>>
+where
+	E: Send + 'static,
+	F: Fn(E) -> Option<Result<FsEvent, ErrBox>> + Send + 'static, {
+	let (tx, events) = mpsc::unbounded();
+	thread::spawn(move || {
+		for event in rx.iter().filter_map(convert) {
+			if tx.unbounded_send(event).is_err() {
+				break;
+			}
+		}
+	});
+	events
+}
+
+#[derive(Deserialize)]
+struct FsWatchArgs {
+	paths:Vec<String>,
+	recursive:bool,
+	/// Milliseconds to coalesce repeated events for the same path.
+	debounce:Option<u64>,
+}
+
+fn op_fs_watch(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:FsWatchArgs = serde_json::from_value(args)?;
+	let mut paths = Vec::with_capacity(args.paths.len());
+	for path in &args.paths {
+		let (path, path_) = state.resolve_path(path)?;
+		state.check_read(&path_)?;
+		paths.push(path);
+	}
+	let mode =
+		if args.recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+
+	let (mut watcher, events):(RecommendedWatcher, _) = match args.debounce {
+		Some(ms) if ms > 0 => {
+			let (tx, rx) = std_mpsc::channel();
+			let watcher = Watcher::new(tx, Duration::from_millis(ms))?;
+			(watcher, forward_events(rx, FsEvent::from_debounced))
+		},
+		_ => {
+			let (tx, rx) = std_mpsc::channel();
+			let watcher = Watcher::new_raw(tx)?;
+			(watcher, forward_events(rx, FsEvent::from_raw))
+		},
+	};
+	for path in &paths {
+		debug!("op_fs_watch {}", path.display());
+		watcher.watch(path, mode)?;
+	}
+
+	let resource = FsWatcherResource { _watcher:watcher, events, waker:AtomicWaker::new() };
+	let mut table = state.lock_resource_table();
+	let rid = table.add("fsWatcher", Box::new(resource));
+	Ok(JsonOp::Sync(json!({ "rid": rid })))
+}
+
+#[derive(Deserialize)]
+struct FsEventsPollArgs {
+	rid:i32,
+}
+
+fn op_fs_events_poll(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:FsEventsPollArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let state = state.clone();
+
+	let op = futures::future::poll_fn(move |cx| -> Poll<Result<Value, ErrBox>> {
+		let mut table = state.lock_resource_table();
+		let watcher = match table.get_mut::<FsWatcherResource>(rid) {
+			Some(watcher) => watcher,
+			// Closed while we were waiting.
+			None => return Poll::Ready(Ok(json!({ "done": true }))),
+		};
+		watcher.waker.register(cx.waker());
+		watcher.events.poll_next_unpin(cx).map(|maybe_event| {
+			match maybe_event {
+				Some(Ok(event)) => Ok(json!({ "value": event.to_json(), "done": false })),
+				Some(Err(e)) => Err(e),
+				None => Ok(json!({ "done": true })),
+			}
+		})
+	});
+
+	Ok(JsonOp::Async(op.boxed()))
+}
@@ -5,7 +5,12 @@ use std::os::windows::io::FromRawHandle;
 use std::{
 	self,
 	future::Future,
+	io::Write as _,
 	pin::Pin,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Mutex,
+	},
 	task::{Context, Poll},
 };
 
@@ -20,12 +25,15 @@ use tokio::{self, net::TcpStream};
 use tokio_process;
 use tokio_rustls::{client::TlsStream as ClientTlsStream, server::TlsStream as ServerTlsStream};
 
-use super::dispatch_minimal::MinimalOp;
+use super::{
+	dispatch_json::{Deserialize, JsonOp, Value},
+	dispatch_minimal::MinimalOp,
+};
 use crate::{
 	deno_error,
 	deno_error::bad_resource,
 	http_body::HttpBody,
-	ops::minimal_op,
+	ops::{json_op, minimal_op},
 	state::ThreadSafeState,
 };
 
@@ -51,11 +59,142 @@ lazy_static! {
 
 	stdout
   };
+
+  /// Trailing partial lines of stdout and stderr in line buffered mode.
+  static ref PENDING_LINES: Mutex<[Vec<u8>; 2]> = Mutex::new([Vec::new(), Vec::new()]);
+}
+
+static LINE_BUFFERED:AtomicBool = AtomicBool::new(false);
+
+/// A partial line longer than this is written out without waiting for its
+/// newline.
+const MAX_PENDING_LINE:usize = 64 * 1024;
+
+#[derive(Clone, Copy)]
+enum Stdio {
+	Stdout = 0,
+	Stderr = 1,
 }
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("read", s.core_op(minimal_op(s.stateful_minimal_op(op_read))));
-	i.register_op("write", s.core_op(minimal_op(s.stateful_minimal_op(op_write))));
+	s.register_op(i, "read", minimal_op(s.stateful_minimal_op(op_read)));
+	s.register_op(i, "write", minimal_op(s.stateful_minimal_op(op_write)));
+	s.register_op(i, "read_partial", json_op(s.stateful_op(op_read_partial)));
+	s.register_op(i, "print", json_op(s.stateful_op(op_print)));
+}
+
+/// With `--line-buffered`, writes to the stdout and stderr resources and
+/// `console.log` output hold back a trailing partial line until its newline
+/// arrives, so that whoever reads the piped output never sees half a line.
+/// `flush_stdio()` writes what is left on exit.
+pub fn set_line_buffered(enabled:bool) { LINE_BUFFERED.store(enabled, Ordering::SeqCst); }
+
+/// Writes the partial lines held back in line buffered mode.
+pub fn flush_stdio() {
+	let mut pending = PENDING_LINES.lock().unwrap();
+	for stream in &[Stdio::Stdout, Stdio::Stderr] {
+		let pending = &mut pending[*stream as usize];
+		// Nothing sensible can be done about a failure this late.
+		let _ = write_stdio(*stream, pending);
+		pending.clear();
+	}
+}
+
+fn write_stdio(stream:Stdio, buf:&[u8]) -> std::io::Result<()> {
+	if buf.is_empty() {
+		return Ok(());
+	}
+	match stream {
+		Stdio::Stdout => (&*STDOUT_HANDLE).write_all(buf),
+		Stdio::Stderr => std::io::stderr().write_all(buf),
+	}
+}
+
+/// Writes the complete lines of what is pending plus `buf` and keeps the
+/// rest. Reports all of `buf` as written, unless writing failed. Blocks like
+/// `Deno.core.print`, which it stands in for.
+fn write_line_buffered(stream:Stdio, buf:&[u8]) -> std::io::Result<usize> {
+	let mut pending = PENDING_LINES.lock().unwrap();
+	let pending = &mut pending[stream as usize];
+	pending.extend_from_slice(buf);
+	let end = match pending.iter().rposition(|b| *b == b'\n') {
+		Some(pos) => pos + 1,
+		None if pending.len() >= MAX_PENDING_LINE => pending.len(),
+		None => return Ok(buf.len()),
+	};
+	if let Err(err) = write_stdio(stream, &pending[..end]) {
+		// The caller may retry, don't write `buf` twice.
+		let len = pending.len() - buf.len();
+		pending.truncate(len);
+		return Err(err);
+	}
+	pending.drain(..end);
+	Ok(buf.len())
+}
+
+/// Like `write_line_buffered()`, writing through `f` without blocking. As
+/// with any write only part of `buf` may be taken.
+fn poll_write_line_buffered(
+	f:&mut (dyn AsyncWrite + Unpin),
+	cx:&mut Context,
+	stream:Stdio,
+	buf:&[u8],
+) -> Poll<std::io::Result<usize>> {
+	let mut pending = PENDING_LINES.lock().unwrap();
+	let pending = &mut pending[stream as usize];
+	let end = match buf.iter().rposition(|b| *b == b'\n') {
+		Some(pos) => pos + 1,
+		None if pending.len() + buf.len() >= MAX_PENDING_LINE => buf.len(),
+		None => {
+			pending.extend_from_slice(buf);
+			return Poll::Ready(Ok(buf.len()));
+		},
+	};
+	// The held back start of the first line goes out first.
+	while !pending.is_empty() {
+		match Pin::new(&mut *f).poll_write(cx, pending) {
+			Poll::Ready(Ok(0)) => {
+				return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+			},
+			Poll::Ready(Ok(n)) => {
+				pending.drain(..n);
+			},
+			Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+			Poll::Pending => return Poll::Pending,
+		}
+	}
+	match Pin::new(&mut *f).poll_write(cx, &buf[..end]) {
+		Poll::Ready(Ok(n)) if n == end => {
+			pending.extend_from_slice(&buf[end..]);
+			Poll::Ready(Ok(buf.len()))
+		},
+		result => result,
+	}
+}
+
+#[derive(Deserialize)]
+struct PrintArgs {
+	#[serde(default)]
+	stderr:bool,
+}
+
+/// `console.log` output, written like `Deno.core.print` unless line
+/// buffering is on.
+fn op_print(
+	_state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:PrintArgs = serde_json::from_value(args)?;
+	let stream = if args.stderr { Stdio::Stderr } else { Stdio::Stdout };
+	if let Some(buf) = zero_copy {
+		if LINE_BUFFERED.load(Ordering::SeqCst) {
+			write_line_buffered(stream, &buf)?;
+		} else {
+			write_stdio(stream, &buf)?;
+		}
+	}
+	Ok(JsonOp::Sync(json!({})))
 }
 
 pub fn get_stdio() -> (StreamResource, StreamResource, StreamResource) {
@@ -75,16 +214,44 @@ pub enum StreamResource {
 	Stderr(tokio::io::Stderr),
 	FsFile(tokio::fs::File),
 	TcpStream(tokio::net::TcpStream),
+	#[cfg(unix)]
+	UnixStream(tokio::net::UnixStream),
 	ServerTlsStream(Box<ServerTlsStream<TcpStream>>),
 	ClientTlsStream(Box<ClientTlsStream<TcpStream>>),
 	HttpBody(Box<HttpBody>),
 	ChildStdin(tokio_process::ChildStdin),
 	ChildStdout(tokio_process::ChildStdout),
 	ChildStderr(tokio_process::ChildStderr),
+	/// Halves of a stream split by `op_split`, each owning a duplicate of
+	/// the socket.
+	TcpReadHalf(tokio::net::TcpStream),
+	TcpWriteHalf(TcpWriteHalf),
+	#[cfg(unix)]
+	UnixReadHalf(tokio::net::UnixStream),
+	#[cfg(unix)]
+	UnixWriteHalf(UnixWriteHalf),
 }
 
 impl Resource for StreamResource {}
 
+/// The writable half of a split TCP stream. Dropping it shuts the connection
+/// down for writing, so the peer sees EOF while the readable half keeps
+/// receiving.
+pub struct TcpWriteHalf(pub tokio::net::TcpStream);
+
+impl Drop for TcpWriteHalf {
+	fn drop(&mut self) { let _ = self.0.shutdown(std::net::Shutdown::Write); }
+}
+
+/// Like `TcpWriteHalf`, for Unix domain sockets.
+#[cfg(unix)]
+pub struct UnixWriteHalf(pub tokio::net::UnixStream);
+
+#[cfg(unix)]
+impl Drop for UnixWriteHalf {
+	fn drop(&mut self) { let _ = self.0.shutdown(std::net::Shutdown::Write); }
+}
+
 /// `DenoAsyncRead` is the same as the `tokio_io::AsyncRead` trait
 /// but uses an `ErrBox` error instead of `std::io:Error`
 pub trait DenoAsyncRead {
@@ -106,6 +273,11 @@ impl DenoAsyncRead for StreamResource {
 			StreamResource::FsFile(f) => Box::new(AsyncRead01CompatExt::compat(f)),
 			StreamResource::Stdin(f) => Box::new(AsyncRead01CompatExt::compat(f)),
 			StreamResource::TcpStream(f) => Box::new(AsyncRead01CompatExt::compat(f)),
+			#[cfg(unix)]
+			StreamResource::UnixStream(f) => Box::new(AsyncRead01CompatExt::compat(f)),
+			StreamResource::TcpReadHalf(f) => Box::new(AsyncRead01CompatExt::compat(f)),
+			#[cfg(unix)]
+			StreamResource::UnixReadHalf(f) => Box::new(AsyncRead01CompatExt::compat(f)),
 			StreamResource::ClientTlsStream(f) => Box::new(AsyncRead01CompatExt::compat(f)),
 			StreamResource::ServerTlsStream(f) => Box::new(AsyncRead01CompatExt::compat(f)),
 			StreamResource::HttpBody(f) => Box::new(f),
@@ -174,6 +346,7 @@ where
 				Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
 				Poll::Pending => return Poll::Pending,
 			};
+		table.record_read(inner.rid, nread);
 		inner.io_state = IoState::Done;
 		Poll::Ready(Ok(nread as i32))
 	}
@@ -195,6 +368,58 @@ pub fn op_read(
 	fut.boxed()
 }
 
+/// The first `len` bytes of a zero-copy buffer.
+struct CappedBuf {
+	buf:PinnedBuf,
+	len:usize,
+}
+
+impl AsMut<[u8]> for CappedBuf {
+	fn as_mut(&mut self) -> &mut [u8] { &mut self.buf[..self.len] }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReadPartialArgs {
+	rid:i32,
+	max_len:Option<usize>,
+	#[serde(default)]
+	non_blocking:bool,
+}
+
+/// Like `read`, but reads at most `maxLen` bytes and reports EOF explicitly.
+/// With `nonBlocking` a read that can't complete immediately resolves with
+/// `wouldBlock` instead of waiting.
+fn op_read_partial(
+	state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:ReadPartialArgs = serde_json::from_value(args)?;
+	let buf = zero_copy.ok_or_else(deno_error::no_buffer_specified)?;
+	let len = args.max_len.map_or(buf.len(), |max_len| max_len.min(buf.len()));
+	let non_blocking = args.non_blocking;
+	debug!("read_partial rid={} len={}", args.rid, len);
+
+	let mut fut = read(state, args.rid as u32, CappedBuf { buf, len });
+	let op = futures::future::poll_fn(move |cx| -> Poll<Result<Value, ErrBox>> {
+		let nread = match Pin::new(&mut fut).poll(cx) {
+			Poll::Ready(result) => result?,
+			Poll::Pending if non_blocking => {
+				return Poll::Ready(Ok(json!({ "nread": 0, "eof": false, "wouldBlock": true })));
+			},
+			Poll::Pending => return Poll::Pending,
+		};
+		Poll::Ready(Ok(json!({
+		  "nread": nread,
+		  "eof": nread == 0 && len > 0,
+		  "wouldBlock": false,
+		})))
+	});
+
+	Ok(JsonOp::Async(op.boxed()))
+}
+
 /// `DenoAsyncWrite` is the same as the `tokio_io::AsyncWrite` trait
 /// but uses an `ErrBox` error instead of `std::io:Error`
 pub trait DenoAsyncWrite {
@@ -206,11 +431,25 @@ pub trait DenoAsyncWrite {
 impl DenoAsyncWrite for StreamResource {
 	fn poll_write(self: Pin<&mut Self>, cx:&mut Context, buf:&[u8]) -> Poll<Result<usize, ErrBox>> {
 		let inner = self.get_mut();
+		let line_buffered = if LINE_BUFFERED.load(Ordering::SeqCst) {
+			match inner {
+				StreamResource::Stdout(_) => Some(Stdio::Stdout),
+				StreamResource::Stderr(_) => Some(Stdio::Stderr),
+				_ => None,
+			}
+		} else {
+			None
+		};
 		let mut f:Box<dyn AsyncWrite + Unpin> = match inner {
 			StreamResource::FsFile(f) => Box::new(AsyncWrite01CompatExt::compat(f)),
 			StreamResource::Stdout(f) => Box::new(AsyncWrite01CompatExt::compat(f)),
 			StreamResource::Stderr(f) => Box::new(AsyncWrite01CompatExt::compat(f)),
 			StreamResource::TcpStream(f) => Box::new(AsyncWrite01CompatExt::compat(f)),
+			#[cfg(unix)]
+			StreamResource::UnixStream(f) => Box::new(AsyncWrite01CompatExt::compat(f)),
+			StreamResource::TcpWriteHalf(f) => Box::new(AsyncWrite01CompatExt::compat(&mut f.0)),
+			#[cfg(unix)]
+			StreamResource::UnixWriteHalf(f) => Box::new(AsyncWrite01CompatExt::compat(&mut f.0)),
 			StreamResource::ClientTlsStream(f) => Box::new(AsyncWrite01CompatExt::compat(f)),
 			StreamResource::ServerTlsStream(f) => Box::new(AsyncWrite01CompatExt::compat(f)),
 			StreamResource::ChildStdin(f) => Box::new(AsyncWrite01CompatExt::compat(f)),
@@ -219,7 +458,10 @@ impl DenoAsyncWrite for StreamResource {
 			},
 		};
 
-		let r = AsyncWrite::poll_write(Pin::new(&mut f), cx, buf);
+		let r = match line_buffered {
+			Some(stream) => poll_write_line_buffered(&mut *f, cx, stream, buf),
+			None => AsyncWrite::poll_write(Pin::new(&mut f), cx, buf),
+		};
 
 		match r {
 			Poll::Ready(Err(e)) => Poll::Ready(Err(ErrBox::from(e))),
@@ -274,6 +516,7 @@ where
 			Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
 			Poll::Pending => return Poll::Pending,
 		};
+		table.record_write(inner.rid, nwritten);
 		inner.io_state = IoState::Done;
 		Poll::Ready(Ok(nwritten as i32))
 	}
@@ -6,6 +6,9 @@ pub use dispatch_json::{json_op, JsonOp};
 pub use dispatch_minimal::{minimal_op, MinimalOp};
 
 pub mod compiler;
+pub mod compression;
+pub mod crypto;
+pub mod encoding;
 pub mod errors;
 pub mod fetch;
 pub mod files;
@@ -3,9 +3,11 @@ use std::{
 	self,
 	convert::From,
 	future::Future,
+	io,
 	net::{Shutdown, SocketAddr},
 	pin::Pin,
 	task::{Context, Poll},
+	time::Duration,
 };
 
 use deno::{Resource, *};
@@ -15,25 +17,41 @@ use futures::{
 };
 use tokio::{
 	self,
-	net::{tcp::Incoming, TcpListener, TcpStream},
+	net::{tcp::Incoming, TcpListener, TcpStream, UdpSocket},
+	prelude::Async,
 };
 
 use super::{
-	dispatch_json::{Deserialize, JsonOp, Value},
-	io::StreamResource,
+	dispatch_json::{blocking_json, Deserialize, JsonOp, Value},
+	io::{StreamResource, TcpWriteHalf},
+	resources::{cancellable, dup_tcp},
 };
 use crate::{
-	deno_error::bad_resource,
+	deno_error::{bad_resource, no_buffer_specified, DenoError, ErrorKind},
 	ops::json_op,
+	ping::ping,
 	resolve_addr::resolve_addr,
 	state::ThreadSafeState,
 };
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("accept", s.core_op(json_op(s.stateful_op(op_accept))));
-	i.register_op("dial", s.core_op(json_op(s.stateful_op(op_dial))));
-	i.register_op("shutdown", s.core_op(json_op(s.stateful_op(op_shutdown))));
-	i.register_op("listen", s.core_op(json_op(s.stateful_op(op_listen))));
+	s.register_op(i, "accept", json_op(s.stateful_op(op_accept)));
+	s.register_op(i, "dial", json_op(s.stateful_op(op_dial)));
+	s.register_op(i, "shutdown", json_op(s.stateful_op(op_shutdown)));
+	s.register_op(i, "split", json_op(s.stateful_op(op_split)));
+	s.register_op(i, "local_addr", json_op(s.stateful_op(op_local_addr)));
+	s.register_op(i, "peer_addr", json_op(s.stateful_op(op_peer_addr)));
+	s.register_op(i, "listen", json_op(s.stateful_op(op_listen)));
+	s.register_op(i, "set_tcp_options", json_op(s.stateful_op(op_set_tcp_options)));
+	s.register_op(i, "tcp_options", json_op(s.stateful_op(op_tcp_options)));
+	s.register_op(i, "udp_bind", json_op(s.stateful_op(op_udp_bind)));
+	s.register_op(i, "udp_send_to", json_op(s.stateful_op(op_udp_send_to)));
+	s.register_op(i, "udp_recv_from", json_op(s.stateful_op(op_udp_recv_from)));
+	s.register_op(i, "unix_listen", json_op(s.stateful_op(op_unix_listen)));
+	s.register_op(i, "unix_accept", json_op(s.stateful_op(op_unix_accept)));
+	s.register_op(i, "unix_dial", json_op(s.stateful_op(op_unix_dial)));
+	s.register_op(i, "resolve_dns", json_op(s.stateful_op(op_resolve_dns)));
+	s.register_op(i, "ping", json_op(s.stateful_op(op_ping)));
 }
 
 #[derive(Debug, PartialEq)]
@@ -108,10 +126,14 @@ fn op_accept(
 	let rid = args.rid as u32;
 	let state_ = state.clone();
 	let table = state.lock_resource_table();
-	table.get::<TcpListenerResource>(rid).ok_or_else(bad_resource)?;
+	let options = table.get::<TcpListenerResource>(rid).ok_or_else(bad_resource)?.options;
+	drop(table);
 
 	let op = accept(state, rid)
 		.and_then(move |(tcp_stream, _socket_addr)| {
+			if let Err(e) = options.apply(&tcp_stream) {
+				return futures::future::err(e);
+			}
 			let local_addr = match tcp_stream.local_addr() {
 				Ok(v) => v,
 				Err(e) => return futures::future::err(ErrBox::from(e)),
@@ -122,6 +144,7 @@ fn op_accept(
 			};
 			let mut table = state_.lock_resource_table();
 			let rid = table.add("tcpStream", Box::new(StreamResource::TcpStream(tcp_stream)));
+			table.set_details(rid, &remote_addr.to_string());
 			futures::future::ok((rid, local_addr, remote_addr))
 		})
 		.map_err(ErrBox::from)
@@ -136,11 +159,41 @@ fn op_accept(
 	Ok(JsonOp::Async(op.boxed()))
 }
 
+/// Socket options applied to a TCP stream before it enters the resource
+/// table. `None` leaves the OS default untouched.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TcpOptions {
+	no_delay:Option<bool>,
+	/// Keepalive interval in milliseconds, zero turns keepalive off.
+	keep_alive:Option<u64>,
+}
+
+impl TcpOptions {
+	fn apply(&self, stream:&TcpStream) -> Result<(), ErrBox> {
+		if let Some(no_delay) = self.no_delay {
+			stream.set_nodelay(no_delay)?;
+		}
+		if let Some(keep_alive) = self.keep_alive {
+			let interval = match keep_alive {
+				0 => None,
+				ms => Some(Duration::from_millis(ms)),
+			};
+			stream.set_keepalive(interval)?;
+		}
+		Ok(())
+	}
+}
+
 #[derive(Deserialize)]
 struct DialArgs {
 	transport:String,
 	hostname:String,
 	port:u16,
+	#[serde(flatten)]
+	options:TcpOptions,
+	#[serde(rename = "cancelRid")]
+	cancel_rid:Option<u32>,
 }
 
 fn op_dial(
@@ -152,11 +205,15 @@ fn op_dial(
 	assert_eq!(args.transport, "tcp"); // TODO Support others.
 	let state_ = state.clone();
 	state.check_net(&args.hostname, args.port)?;
+	let options = args.options;
 
-	let op = resolve_addr(&args.hostname, args.port).and_then(move |addr| {
+	let op = state.resolve_net_target(&args.hostname, args.port)?.and_then(move |addr| {
 		futures::compat::Compat01As03::new(TcpStream::connect(&addr))
 			.map_err(ErrBox::from)
 			.and_then(move |tcp_stream| {
+				if let Err(e) = options.apply(&tcp_stream) {
+					return futures::future::err(e);
+				}
 				let local_addr = match tcp_stream.local_addr() {
 					Ok(v) => v,
 					Err(e) => return futures::future::err(ErrBox::from(e)),
@@ -167,6 +224,7 @@ fn op_dial(
 				};
 				let mut table = state_.lock_resource_table();
 				let rid = table.add("tcpStream", Box::new(StreamResource::TcpStream(tcp_stream)));
+				table.set_details(rid, &remote_addr.to_string());
 				futures::future::ok((rid, local_addr, remote_addr))
 			})
 			.map_err(ErrBox::from)
@@ -179,7 +237,7 @@ fn op_dial(
 			})
 	});
 
-	Ok(JsonOp::Async(op.boxed()))
+	Ok(JsonOp::Async(cancellable(state, args.cancel_rid, op.boxed())?))
 }
 
 #[derive(Deserialize)]
@@ -188,6 +246,9 @@ struct ShutdownArgs {
 	how:i32,
 }
 
+// Shuts down reading, writing or both, see shutdown(2). The resource stays
+// open: after shutting down writing the peer sees EOF, while data it sends
+// can still be read.
 fn op_shutdown(
 	state:&ThreadSafeState,
 	args:Value,
@@ -201,26 +262,315 @@ fn op_shutdown(
 	let shutdown_mode = match how {
 		0 => Shutdown::Read,
 		1 => Shutdown::Write,
-		_ => unimplemented!(),
+		2 => Shutdown::Both,
+		_ => {
+			return Err(DenoError::new(
+				ErrorKind::InvalidInput,
+				format!("Invalid shutdown mode {}", how),
+			)
+			.into());
+		},
 	};
 
 	let mut table = state.lock_resource_table();
 	let resource = table.get_mut::<StreamResource>(rid).ok_or_else(bad_resource)?;
 	match resource {
-		StreamResource::TcpStream(ref mut stream) => {
+		StreamResource::TcpStream(ref mut stream) | StreamResource::TcpReadHalf(ref mut stream) => {
 			TcpStream::shutdown(stream, shutdown_mode).map_err(ErrBox::from)?;
 		},
+		StreamResource::TcpWriteHalf(ref mut half) => {
+			TcpStream::shutdown(&half.0, shutdown_mode).map_err(ErrBox::from)?;
+		},
+		#[cfg(unix)]
+		StreamResource::UnixStream(ref mut stream)
+		| StreamResource::UnixReadHalf(ref mut stream) => {
+			stream.shutdown(shutdown_mode)?;
+		},
+		#[cfg(unix)]
+		StreamResource::UnixWriteHalf(ref mut half) => half.0.shutdown(shutdown_mode)?,
+		_ => return Err(bad_resource()),
+	}
+
+	Ok(JsonOp::Sync(json!({})))
+}
+
+#[derive(Deserialize)]
+struct SplitArgs {
+	rid:i32,
+}
+
+// Replaces a TCP or Unix stream with a readable and a writable half, each
+// owning a duplicate of the socket, so that a read and a write can be
+// pending at the same time. Closing the writable half sends FIN.
+fn op_split(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:SplitArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+
+	let mut table = state.lock_resource_table();
+	let ((read_name, read_half), (write_name, write_half)) =
+		match table.get::<StreamResource>(rid).ok_or_else(bad_resource)? {
+			StreamResource::TcpStream(stream) => {
+				// Write half last: dropping it after a failed dup would send FIN.
+				let read_half = dup_tcp(stream)?;
+				let write_half = TcpWriteHalf(dup_tcp(stream)?);
+				(
+					("tcpReadHalf", StreamResource::TcpReadHalf(read_half)),
+					("tcpWriteHalf", StreamResource::TcpWriteHalf(write_half)),
+				)
+			},
+			#[cfg(unix)]
+			StreamResource::UnixStream(stream) => {
+				use super::{io::UnixWriteHalf, resources::dup_unix};
+				let read_half = dup_unix(stream)?;
+				let write_half = UnixWriteHalf(dup_unix(stream)?);
+				(
+					("unixReadHalf", StreamResource::UnixReadHalf(read_half)),
+					("unixWriteHalf", StreamResource::UnixWriteHalf(write_half)),
+				)
+			},
+			_ => return Err(bad_resource()),
+		};
+	let details = table.details(rid).map(str::to_string);
+	// Only closes this handle, the halves keep the connection open.
+	table.close(rid);
+	let read_rid = table.add(read_name, Box::new(read_half));
+	let write_rid = table.add(write_name, Box::new(write_half));
+	if let Some(details) = details {
+		table.set_details(read_rid, &details);
+		table.set_details(write_rid, &details);
+	}
+
+	Ok(JsonOp::Sync(json!({
+	  "readRid": read_rid,
+	  "writeRid": write_rid,
+	})))
+}
+
+#[derive(Deserialize)]
+struct AddrArgs {
+	rid:i32,
+}
+
+fn op_local_addr(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:AddrArgs = serde_json::from_value(args)?;
+	Ok(JsonOp::Sync(socket_addr(state, args.rid as u32, false)?))
+}
+
+fn op_peer_addr(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:AddrArgs = serde_json::from_value(args)?;
+	Ok(JsonOp::Sync(socket_addr(state, args.rid as u32, true)?))
+}
+
+fn ip_addr_json(transport:&str, addr:SocketAddr) -> Value {
+	json!({
+	  "transport": transport,
+	  "hostname": addr.ip().to_string(),
+	  "port": addr.port(),
+	  "family": if addr.is_ipv4() { "ipv4" } else { "ipv6" },
+	})
+}
+
+#[cfg(unix)]
+fn unix_addr_json(addr:&std::os::unix::net::SocketAddr) -> Value {
+	// Unnamed sockets, e.g. the dialing end of a connection, have no path.
+	let path = addr.as_pathname().map(|p| p.to_string_lossy().to_string());
+	json!({
+	  "transport": "unix",
+	  "path": path,
+	})
+}
+
+// Returns the address the socket behind `rid` is bound to or, with `peer`,
+// the one it is connected to. Listeners only have the former.
+fn socket_addr(state:&ThreadSafeState, rid:u32, peer:bool) -> Result<Value, ErrBox> {
+	let table = state.lock_resource_table();
+	if let Some(resource) = table.get::<StreamResource>(rid) {
+		let tcp = |stream:&TcpStream| -> Result<Value, ErrBox> {
+			let addr = if peer { stream.peer_addr()? } else { stream.local_addr()? };
+			Ok(ip_addr_json("tcp", addr))
+		};
+		#[cfg(unix)]
+		let unix = |stream:&tokio::net::UnixStream| -> Result<Value, ErrBox> {
+			let addr = if peer { stream.peer_addr()? } else { stream.local_addr()? };
+			Ok(unix_addr_json(&addr))
+		};
+		return match resource {
+			StreamResource::TcpStream(stream) | StreamResource::TcpReadHalf(stream) => tcp(stream),
+			StreamResource::TcpWriteHalf(half) => tcp(&half.0),
+			StreamResource::ClientTlsStream(stream) => tcp(stream.get_ref().0),
+			StreamResource::ServerTlsStream(stream) => tcp(stream.get_ref().0),
+			#[cfg(unix)]
+			StreamResource::UnixStream(stream) | StreamResource::UnixReadHalf(stream) => {
+				unix(stream)
+			},
+			#[cfg(unix)]
+			StreamResource::UnixWriteHalf(half) => unix(&half.0),
+			_ => Err(bad_resource()),
+		};
+	}
+	if !peer {
+		if let Some(listener) = table.get::<TcpListenerResource>(rid) {
+			return Ok(ip_addr_json("tcp", listener.local_addr));
+		}
+		if let Some(resource) = table.get::<UdpSocketResource>(rid) {
+			return Ok(ip_addr_json("udp", resource.socket.local_addr()?));
+		}
+		#[cfg(unix)]
+		{
+			if let Some(resource) = table.get::<UnixListenerResource>(rid) {
+				return Ok(unix_addr_json(&resource.listener.local_addr()?));
+			}
+		}
+	}
+	Err(bad_resource())
+}
+
+#[derive(Deserialize)]
+struct TcpOptionsArgs {
+	rid:i32,
+	#[serde(flatten)]
+	options:TcpOptions,
+}
+
+fn op_set_tcp_options(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:TcpOptionsArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+
+	let mut table = state.lock_resource_table();
+	match table.get_mut::<StreamResource>(rid).ok_or_else(bad_resource)? {
+		StreamResource::TcpStream(ref stream) => args.options.apply(stream)?,
 		_ => return Err(bad_resource()),
 	}
 
 	Ok(JsonOp::Sync(json!({})))
 }
 
+fn op_tcp_options(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:TcpOptionsArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+
+	let mut table = state.lock_resource_table();
+	let stream = match table.get_mut::<StreamResource>(rid).ok_or_else(bad_resource)? {
+		StreamResource::TcpStream(ref stream) => stream,
+		_ => return Err(bad_resource()),
+	};
+	let keep_alive = stream.keepalive()?.map(|d| d.as_millis() as u64);
+
+	Ok(JsonOp::Sync(json!({
+	  "noDelay": stream.nodelay()?,
+	  "keepAlive": keep_alive,
+	})))
+}
+
 #[derive(Deserialize)]
 struct ListenArgs {
 	transport:String,
 	hostname:String,
 	port:u16,
+	#[serde(flatten)]
+	options:TcpOptions,
+	#[serde(flatten)]
+	listener_options:ListenerOptions,
+}
+
+/// Backlog used by `TcpListener::bind()`.
+const DEFAULT_BACKLOG:usize = 1024;
+
+/// Socket options applied before `listen(2)`. Those left out keep the
+/// defaults of `TcpListener::bind()`: `SO_REUSEADDR` on Unix, no
+/// `SO_REUSEPORT`, a backlog of `DEFAULT_BACKLOG` and the system's
+/// `IPV6_V6ONLY` setting.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct ListenerOptions {
+	reuse_address:Option<bool>,
+	reuse_port:Option<bool>,
+	backlog:Option<usize>,
+	/// Whether a listener on an IPv6 address refuses IPv4 connections, which
+	/// otherwise arrive as IPv4-mapped addresses.
+	v6_only:Option<bool>,
+}
+
+impl ListenerOptions {
+	fn is_default(&self) -> bool {
+		self.reuse_address.is_none()
+			&& self.reuse_port.is_none()
+			&& self.backlog.is_none()
+			&& self.v6_only.is_none()
+	}
+}
+
+#[cfg(unix)]
+fn bind_listener(addr:&SocketAddr, options:&ListenerOptions) -> Result<TcpListener, ErrBox> {
+	use std::os::unix::io::FromRawFd;
+
+	use nix::{
+		fcntl::{fcntl, FcntlArg, FdFlag},
+		sys::socket::{self, sockopt, AddressFamily, InetAddr, SockAddr, SockFlag, SockType},
+	};
+
+	if options.is_default() {
+		return Ok(TcpListener::bind(addr)?);
+	}
+	let family = if addr.is_ipv4() { AddressFamily::Inet } else { AddressFamily::Inet6 };
+	let fd = socket::socket(family, SockType::Stream, SockFlag::empty(), None)?;
+	// Owned from here on, so that the socket is closed on errors.
+	let listener = unsafe { std::net::TcpListener::from_raw_fd(fd) };
+	fcntl(fd, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+	socket::setsockopt(fd, sockopt::ReuseAddr, &options.reuse_address.unwrap_or(true))?;
+	if options.reuse_port.unwrap_or(false) {
+		socket::setsockopt(fd, sockopt::ReusePort, &true)?;
+	}
+	if let (SocketAddr::V6(_), Some(v6_only)) = (addr, options.v6_only) {
+		let value = v6_only as libc::c_int;
+		let result = unsafe {
+			libc::setsockopt(
+				fd,
+				libc::IPPROTO_IPV6,
+				libc::IPV6_V6ONLY,
+				&value as *const libc::c_int as *const libc::c_void,
+				std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+			)
+		};
+		if result == -1 {
+			return Err(io::Error::last_os_error().into());
+		}
+	}
+	socket::bind(fd, &SockAddr::new_inet(InetAddr::from_std(addr)))?;
+	socket::listen(fd, options.backlog.unwrap_or(DEFAULT_BACKLOG))?;
+	Ok(TcpListener::from_std(listener, &tokio::reactor::Handle::default())?)
+}
+
+#[cfg(not(unix))]
+fn bind_listener(addr:&SocketAddr, options:&ListenerOptions) -> Result<TcpListener, ErrBox> {
+	if !options.is_default() {
+		return Err(DenoError::new(
+			ErrorKind::OpNotAvailable,
+			"Listener socket options are not supported on this platform".to_string(),
+		)
+		.into());
+	}
+	Ok(TcpListener::bind(addr)?)
 }
 
 #[allow(dead_code)]
@@ -228,6 +578,8 @@ struct TcpListenerResource {
 	listener:Incoming,
 	waker:Option<futures::task::AtomicWaker>,
 	local_addr:SocketAddr,
+	/// Applied to every accepted connection.
+	options:TcpOptions,
 }
 
 impl Resource for TcpListenerResource {}
@@ -285,11 +637,15 @@ fn op_listen(
 	state.check_net(&args.hostname, args.port)?;
 
 	let addr = futures::executor::block_on(resolve_addr(&args.hostname, args.port))?;
-	let listener = TcpListener::bind(&addr)?;
+	let listener = bind_listener(&addr, &args.listener_options)?;
 	let local_addr = listener.local_addr()?;
 	let local_addr_str = local_addr.to_string();
-	let listener_resource =
-		TcpListenerResource { listener:listener.incoming(), waker:None, local_addr };
+	let listener_resource = TcpListenerResource {
+		listener:listener.incoming(),
+		waker:None,
+		local_addr,
+		options:args.options,
+	};
 	let mut table = state.lock_resource_table();
 	let rid = table.add("tcpListener", Box::new(listener_resource));
 	debug!("New listener {} {}", rid, local_addr_str);
@@ -299,3 +655,433 @@ fn op_listen(
 	  "localAddr": local_addr_str,
 	})))
 }
+
+/// Largest payload a UDP datagram can carry.
+const MAX_DATAGRAM_SIZE:usize = 65536;
+
+struct UdpSocketResource {
+	socket:UdpSocket,
+	/// Datagrams are received here first so that truncation can be detected
+	/// when the caller's buffer is too small.
+	recv_buf:Vec<u8>,
+}
+
+impl Resource for UdpSocketResource {}
+
+#[derive(Deserialize)]
+struct UdpBindArgs {
+	hostname:String,
+	port:u16,
+}
+
+fn op_udp_bind(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:UdpBindArgs = serde_json::from_value(args)?;
+
+	state.check_net(&args.hostname, args.port)?;
+
+	let addr = futures::executor::block_on(resolve_addr(&args.hostname, args.port))?;
+	let socket = UdpSocket::bind(&addr)?;
+	// With port 0 the OS picks the port, so report what we actually got.
+	let local_addr = socket.local_addr()?;
+	let resource = UdpSocketResource { socket, recv_buf:vec![0; MAX_DATAGRAM_SIZE] };
+	let mut table = state.lock_resource_table();
+	let rid = table.add("udpSocket", Box::new(resource));
+	debug!("New udp socket {} {}", rid, local_addr);
+
+	Ok(JsonOp::Sync(json!({
+	  "rid": rid,
+	  "hostname": local_addr.ip().to_string(),
+	  "port": local_addr.port(),
+	})))
+}
+
+#[derive(Deserialize)]
+struct UdpSendToArgs {
+	rid:i32,
+	hostname:String,
+	port:u16,
+}
+
+/// Reports an oversized datagram as `TooLarge` rather than a generic OS error.
+fn udp_send_error(e:io::Error) -> ErrBox {
+	#[cfg(unix)]
+	{
+		if e.raw_os_error() == Some(libc::EMSGSIZE) {
+			return DenoError::new(ErrorKind::TooLarge, "datagram too large".to_string()).into();
+		}
+	}
+	ErrBox::from(e)
+}
+
+fn op_udp_send_to(
+	state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:UdpSendToArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let buf = zero_copy.ok_or_else(no_buffer_specified)?;
+
+	state.check_net(&args.hostname, args.port)?;
+	{
+		let table = state.lock_resource_table();
+		table.get::<UdpSocketResource>(rid).ok_or_else(bad_resource)?;
+	}
+
+	let state_ = state.clone();
+	let op = state.resolve_net_target(&args.hostname, args.port)?.and_then(move |addr| {
+		let send = tokio::prelude::future::poll_fn(move || -> Result<Async<usize>, ErrBox> {
+			let mut table = state_.lock_resource_table();
+			let resource = table.get_mut::<UdpSocketResource>(rid).ok_or_else(bad_resource)?;
+			resource.socket.poll_send_to(&buf, &addr).map_err(udp_send_error)
+		});
+		futures::compat::Compat01As03::new(send)
+			.and_then(|nwritten| futures::future::ok(json!({ "nwritten": nwritten })))
+	});
+
+	Ok(JsonOp::Async(op.boxed()))
+}
+
+#[derive(Deserialize)]
+struct UdpRecvFromArgs {
+	rid:i32,
+}
+
+fn op_udp_recv_from(
+	state:&ThreadSafeState,
+	args:Value,
+	zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:UdpRecvFromArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let mut buf = zero_copy.ok_or_else(no_buffer_specified)?;
+	{
+		let table = state.lock_resource_table();
+		table.get::<UdpSocketResource>(rid).ok_or_else(bad_resource)?;
+	}
+
+	let state_ = state.clone();
+	let recv = tokio::prelude::future::poll_fn(
+		move || -> Result<Async<(usize, bool, SocketAddr)>, ErrBox> {
+			let mut table = state_.lock_resource_table();
+			let resource = table.get_mut::<UdpSocketResource>(rid).ok_or_else(bad_resource)?;
+			let UdpSocketResource { socket, recv_buf } = resource;
+			match socket.poll_recv_from(recv_buf)? {
+				Async::Ready((size, remote_addr)) => {
+					let nread = size.min(buf.len());
+					buf[..nread].copy_from_slice(&recv_buf[..nread]);
+					Ok(Async::Ready((nread, size > nread, remote_addr)))
+				},
+				Async::NotReady => Ok(Async::NotReady),
+			}
+		},
+	);
+	let op = futures::compat::Compat01As03::new(recv).and_then(|(nread, truncated, remote_addr)| {
+		futures::future::ok(json!({
+		  "nread": nread,
+		  "truncated": truncated,
+		  "hostname": remote_addr.ip().to_string(),
+		  "port": remote_addr.port(),
+		}))
+	});
+
+	Ok(JsonOp::Async(op.boxed()))
+}
+
+#[cfg(unix)]
+#[derive(Deserialize)]
+struct UnixPathArgs {
+	path:String,
+}
+
+#[cfg(unix)]
+#[derive(Deserialize)]
+struct UnixListenArgs {
+	path:String,
+	backlog:Option<usize>,
+}
+
+#[cfg(unix)]
+#[derive(Deserialize)]
+struct UnixAcceptArgs {
+	rid:i32,
+}
+
+#[cfg(not(unix))]
+fn unix_sockets_unsupported() -> ErrBox {
+	DenoError::new(
+		ErrorKind::OpNotAvailable,
+		"Unix domain sockets are not supported on this platform".to_string(),
+	)
+	.into()
+}
+
+#[cfg(not(unix))]
+fn op_unix_listen(
+	_state:&ThreadSafeState,
+	_args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	Err(unix_sockets_unsupported())
+}
+
+#[cfg(not(unix))]
+fn op_unix_accept(
+	_state:&ThreadSafeState,
+	_args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	Err(unix_sockets_unsupported())
+}
+
+#[cfg(not(unix))]
+fn op_unix_dial(
+	_state:&ThreadSafeState,
+	_args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	Err(unix_sockets_unsupported())
+}
+
+#[cfg(unix)]
+struct UnixListenerResource {
+	listener:tokio::net::UnixListener,
+	path:std::path::PathBuf,
+	task:Option<tokio::prelude::task::Task>,
+}
+
+#[cfg(unix)]
+impl Resource for UnixListenerResource {}
+
+#[cfg(unix)]
+impl Drop for UnixListenerResource {
+	/// Wakes a pending accept so it can observe the close, and removes the
+	/// socket file created by `unix_listen`.
+	fn drop(&mut self) {
+		if let Some(task) = self.task.take() {
+			task.notify();
+		}
+		let _ = std::fs::remove_file(&self.path);
+	}
+}
+
+/// Like `UnixListener::bind()`, which always uses a backlog of 128.
+#[cfg(unix)]
+fn bind_unix_listener(
+	path:&std::path::Path,
+	backlog:usize,
+) -> Result<tokio::net::UnixListener, ErrBox> {
+	use std::os::unix::io::FromRawFd;
+
+	use nix::{
+		fcntl::{fcntl, FcntlArg, FdFlag},
+		sys::socket::{self, AddressFamily, SockAddr, SockFlag, SockType},
+	};
+
+	let fd = socket::socket(AddressFamily::Unix, SockType::Stream, SockFlag::empty(), None)?;
+	let listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(fd) };
+	fcntl(fd, FcntlArg::F_SETFD(FdFlag::FD_CLOEXEC))?;
+	socket::bind(fd, &SockAddr::new_unix(path)?)?;
+	socket::listen(fd, backlog)?;
+	Ok(tokio::net::UnixListener::from_std(listener, &tokio::reactor::Handle::default())?)
+}
+
+#[cfg(unix)]
+fn unix_addr_to_string(addr:&std::os::unix::net::SocketAddr) -> String {
+	addr.as_pathname().map(|p| p.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+#[cfg(unix)]
+fn op_unix_listen(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:UnixListenArgs = serde_json::from_value(args)?;
+	let (path, path_) = state.resolve_path(&args.path)?;
+	state.check_read(&path_)?;
+	state.check_write(&path_)?;
+
+	let listener = match args.backlog {
+		None => tokio::net::UnixListener::bind(&path)?,
+		Some(backlog) => bind_unix_listener(&path, backlog)?,
+	};
+	let resource = UnixListenerResource { listener, path, task:None };
+	let mut table = state.lock_resource_table();
+	let rid = table.add("unixListener", Box::new(resource));
+	debug!("New unix listener {} {}", rid, path_);
+
+	Ok(JsonOp::Sync(json!({
+	  "rid": rid,
+	  "localAddr": path_,
+	})))
+}
+
+#[cfg(unix)]
+fn op_unix_accept(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:UnixAcceptArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	{
+		let table = state.lock_resource_table();
+		table.get::<UnixListenerResource>(rid).ok_or_else(bad_resource)?;
+	}
+
+	let state_ = state.clone();
+	type Accepted = (tokio::net::UnixStream, std::os::unix::net::SocketAddr);
+	let accept = tokio::prelude::future::poll_fn(move || -> Result<Async<Accepted>, ErrBox> {
+		let mut table = state_.lock_resource_table();
+		let resource = table.get_mut::<UnixListenerResource>(rid).ok_or_else(|| {
+			let e = std::io::Error::new(std::io::ErrorKind::Other, "Listener has been closed");
+			ErrBox::from(e)
+		})?;
+		match resource.listener.poll_accept()? {
+			Async::Ready(accepted) => {
+				resource.task = None;
+				Ok(Async::Ready(accepted))
+			},
+			Async::NotReady => {
+				resource.task = Some(tokio::prelude::task::current());
+				Ok(Async::NotReady)
+			},
+		}
+	});
+
+	let state_ = state.clone();
+	let op = futures::compat::Compat01As03::new(accept).and_then(move |(stream, remote_addr)| {
+		let local_addr = match stream.local_addr() {
+			Ok(v) => v,
+			Err(e) => return futures::future::err(ErrBox::from(e)),
+		};
+		// Clients are usually unnamed, so the details name the listener's path.
+		let mut table = state_.lock_resource_table();
+		let rid = table.add("unixStream", Box::new(StreamResource::UnixStream(stream)));
+		table.set_details(rid, &unix_addr_to_string(&local_addr));
+		futures::future::ok(json!({
+		  "rid": rid,
+		  "localAddr": unix_addr_to_string(&local_addr),
+		  "remoteAddr": unix_addr_to_string(&remote_addr),
+		}))
+	});
+
+	Ok(JsonOp::Async(op.boxed()))
+}
+
+#[cfg(unix)]
+fn op_unix_dial(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:UnixPathArgs = serde_json::from_value(args)?;
+	let (path, path_) = state.resolve_path(&args.path)?;
+	state.check_read(&path_)?;
+	state.check_write(&path_)?;
+
+	let state_ = state.clone();
+	let op = futures::compat::Compat01As03::new(tokio::net::UnixStream::connect(&path))
+		.map_err(ErrBox::from)
+		.and_then(move |stream| {
+			let local_addr = match stream.local_addr() {
+				Ok(v) => v,
+				Err(e) => return futures::future::err(ErrBox::from(e)),
+			};
+			let mut table = state_.lock_resource_table();
+			let rid = table.add("unixStream", Box::new(StreamResource::UnixStream(stream)));
+			table.set_details(rid, &path_);
+			futures::future::ok(json!({
+			  "rid": rid,
+			  "localAddr": unix_addr_to_string(&local_addr),
+			  "remoteAddr": path_,
+			}))
+		});
+
+	Ok(JsonOp::Async(op.boxed()))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ResolveDnsArgs {
+	promise_id:Option<u64>,
+	hostname:String,
+	/// `"ipv4"` or `"ipv6"` to return only A or AAAA records.
+	family:Option<String>,
+}
+
+fn op_resolve_dns(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:ResolveDnsArgs = serde_json::from_value(args)?;
+	state.check_net_host(&args.hostname)?;
+	let (want_v4, want_v6) = match args.family.as_ref().map(String::as_str) {
+		None => (true, true),
+		Some("ipv4") => (true, false),
+		Some("ipv6") => (false, true),
+		Some(family) => {
+			return Err(DenoError::new(
+				ErrorKind::InvalidInput,
+				format!("Invalid address family \"{}\"", family),
+			)
+			.into());
+		},
+	};
+	let global_state = state.global_state.clone();
+	let is_sync = args.promise_id.is_none();
+	blocking_json(is_sync, move || {
+		let ips = global_state.resolver.lookup(&args.hostname)?;
+		let records:Vec<Value> = ips
+			.into_iter()
+			.filter(|ip| if ip.is_ipv4() { want_v4 } else { want_v6 })
+			.map(|ip| {
+				json!({
+				  "type": if ip.is_ipv4() { "A" } else { "AAAA" },
+				  "address": ip.to_string(),
+				})
+			})
+			.collect();
+		Ok(json!(records))
+	})
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PingArgs {
+	promise_id:Option<u64>,
+	hostname:String,
+	count:Option<u16>,
+	/// How long to wait for each reply, in milliseconds.
+	timeout:Option<u64>,
+}
+
+fn op_ping(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:PingArgs = serde_json::from_value(args)?;
+	state.check_ping(&args.hostname)?;
+	let target = state.resolve_net_target(&args.hostname, 0)?;
+	let count = args.count.unwrap_or(1);
+	let timeout = Duration::from_millis(args.timeout.unwrap_or(1000));
+	let is_sync = args.promise_id.is_none();
+	blocking_json(is_sync, move || {
+		let addr = futures::executor::block_on(target)?;
+		let rtts:Vec<Option<f64>> = ping(&addr, count, timeout)?
+			.into_iter()
+			.map(|rtt| rtt.map(|rtt| rtt.as_secs_f64() * 1000.0))
+			.collect();
+		Ok(json!({
+		  "address": addr.ip().to_string(),
+		  "rtts": rtts,
+		}))
+	})
+}
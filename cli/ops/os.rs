@@ -11,7 +11,14 @@ use sys_info;
 use url::Url;
 
 use super::dispatch_json::{Deserialize, JsonOp, Value};
-use crate::{colors, fs as deno_fs, ops::json_op, state::ThreadSafeState, version};
+use crate::{
+	colors,
+	deno_error::{DenoError, ErrorKind as DenoErrorKind},
+	fs as deno_fs,
+	ops::json_op,
+	state::ThreadSafeState,
+	version,
+};
 
 /// BUILD_OS and BUILD_ARCH match the values in Deno.build. See js/build.ts.
 #[cfg(target_os = "macos")]
@@ -24,15 +31,20 @@ static BUILD_OS:&str = "win";
 static BUILD_ARCH:&str = "x64";
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("exit", s.core_op(json_op(s.stateful_op(op_exit))));
-	i.register_op("is_tty", s.core_op(json_op(s.stateful_op(op_is_tty))));
-	i.register_op("env", s.core_op(json_op(s.stateful_op(op_env))));
-	i.register_op("exec_path", s.core_op(json_op(s.stateful_op(op_exec_path))));
-	i.register_op("set_env", s.core_op(json_op(s.stateful_op(op_set_env))));
-	i.register_op("get_env", s.core_op(json_op(s.stateful_op(op_get_env))));
-	i.register_op("get_dir", s.core_op(json_op(s.stateful_op(op_get_dir))));
-	i.register_op("hostname", s.core_op(json_op(s.stateful_op(op_hostname))));
-	i.register_op("start", s.core_op(json_op(s.stateful_op(op_start))));
+	s.register_op(i, "exit", json_op(s.stateful_op(op_exit)));
+	s.register_op(i, "is_tty", json_op(s.stateful_op(op_is_tty)));
+	s.register_op(i, "env", json_op(s.stateful_op(op_env)));
+	s.register_op(i, "exec_path", json_op(s.stateful_op(op_exec_path)));
+	s.register_op(i, "set_env", json_op(s.stateful_op(op_set_env)));
+	s.register_op(i, "delete_env", json_op(s.stateful_op(op_delete_env)));
+	s.register_op(i, "get_env", json_op(s.stateful_op(op_get_env)));
+	s.register_op(i, "get_dir", json_op(s.stateful_op(op_get_dir)));
+	s.register_op(i, "hostname", json_op(s.stateful_op(op_hostname)));
+	s.register_op(i, "uptime", json_op(s.stateful_op(op_uptime)));
+	s.register_op(i, "loadavg", json_op(s.stateful_op(op_loadavg)));
+	s.register_op(i, "mem_info", json_op(s.stateful_op(op_mem_info)));
+	s.register_op(i, "resource_usage", json_op(s.stateful_op(op_resource_usage)));
+	s.register_op(i, "start", json_op(s.stateful_op(op_start)));
 }
 
 fn op_start(
@@ -43,7 +55,7 @@ fn op_start(
 	let gs = &state.global_state;
 
 	Ok(JsonOp::Sync(json!({
-	  "cwd": deno_fs::normalize_path(&env::current_dir().unwrap()),
+	  "cwd": deno_fs::normalize_path(&state.cwd()),
 	  "pid": std::process::id(),
 	  "argv": gs.flags.argv,
 	  "mainModule": gs.main_module.as_ref().map(|x| x.to_string()),
@@ -126,6 +138,28 @@ struct SetEnv {
 	value:String,
 }
 
+/// `env::set_var` and `env::remove_var` panic on keys and values the platform
+/// can't represent, so reject those first.
+fn check_env_var(key:&str, value:Option<&str>) -> Result<(), ErrBox> {
+	if key.is_empty() || key.contains('=') || key.contains('\0') {
+		return Err(ErrBox::from(Error::new(
+			ErrorKind::InvalidInput,
+			format!("Invalid environment variable name: {:?}", key),
+		)));
+	}
+	if value.map_or(false, |value| value.contains('\0')) {
+		return Err(ErrBox::from(Error::new(
+			ErrorKind::InvalidInput,
+			format!("Invalid value for environment variable {:?}", key),
+		)));
+	}
+	Ok(())
+}
+
+// The environment is process-global and shared by all workers. std serializes
+// its own reads and writes, and children spawned by ops::process copy it under
+// the same lock, so they always see every completed mutation. Native code
+// calling getenv(3) directly (e.g. from a plugin) is not covered by that lock.
 fn op_set_env(
 	state:&ThreadSafeState,
 	args:Value,
@@ -133,10 +167,28 @@ fn op_set_env(
 ) -> Result<JsonOp, ErrBox> {
 	let args:SetEnv = serde_json::from_value(args)?;
 	state.check_env()?;
+	check_env_var(&args.key, Some(&args.value))?;
 	env::set_var(args.key, args.value);
 	Ok(JsonOp::Sync(json!({})))
 }
 
+#[derive(Deserialize)]
+struct DeleteEnv {
+	key:String,
+}
+
+fn op_delete_env(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:DeleteEnv = serde_json::from_value(args)?;
+	state.check_env()?;
+	check_env_var(&args.key, None)?;
+	env::remove_var(args.key);
+	Ok(JsonOp::Sync(json!({})))
+}
+
 fn op_env(
 	state:&ThreadSafeState,
 	_args:Value,
@@ -177,6 +229,7 @@ fn op_exit(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:Exit = serde_json::from_value(args)?;
+	super::io::flush_stdio();
 	std::process::exit(args.code)
 }
 
@@ -201,3 +254,136 @@ fn op_hostname(
 	let hostname = sys_info::hostname().unwrap_or_else(|_| "".to_owned());
 	Ok(JsonOp::Sync(json!(hostname)))
 }
+
+fn sys_info_error(err:sys_info::Error) -> ErrBox {
+	DenoError::new(DenoErrorKind::Other, err.to_string()).into()
+}
+
+/// Seconds since boot.
+#[cfg(target_os = "linux")]
+fn uptime() -> Result<u64, ErrBox> {
+	let mut info:libc::sysinfo = unsafe { std::mem::zeroed() };
+	if unsafe { libc::sysinfo(&mut info) } != 0 {
+		return Err(Error::last_os_error().into());
+	}
+	Ok(info.uptime as u64)
+}
+
+#[cfg(target_os = "macos")]
+fn uptime() -> Result<u64, ErrBox> {
+	use std::time::{SystemTime, UNIX_EPOCH};
+
+	let boottime = sys_info::boottime().map_err(sys_info_error)?;
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+	Ok(now.as_secs().saturating_sub(boottime.tv_sec as u64))
+}
+
+#[cfg(windows)]
+fn uptime() -> Result<u64, ErrBox> {
+	Ok(unsafe { winapi::um::sysinfoapi::GetTickCount64() } / 1000)
+}
+
+fn op_uptime(
+	_state:&ThreadSafeState,
+	_args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	Ok(JsonOp::Sync(json!(uptime()?)))
+}
+
+fn op_loadavg(
+	_state:&ThreadSafeState,
+	_args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	#[cfg(unix)]
+	{
+		let load = sys_info::loadavg().map_err(sys_info_error)?;
+		Ok(JsonOp::Sync(json!([load.one, load.five, load.fifteen])))
+	}
+	// Windows has no load average.
+	#[cfg(not(unix))]
+	Ok(JsonOp::Sync(json!(null)))
+}
+
+fn op_mem_info(
+	_state:&ThreadSafeState,
+	_args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	// sys_info reports kilobytes.
+	let info = sys_info::mem_info().map_err(sys_info_error)?;
+	Ok(JsonOp::Sync(json!({
+	  "total": info.total * 1024,
+	  "free": info.free * 1024,
+	  "available": info.avail * 1024,
+	  "swapTotal": info.swap_total * 1024,
+	  "swapFree": info.swap_free * 1024,
+	})))
+}
+
+/// CPU time and memory used by this process so far. Times are in
+/// microseconds, `maxRss` in bytes.
+#[cfg(unix)]
+fn resource_usage() -> Result<Value, ErrBox> {
+	let mut usage:libc::rusage = unsafe { std::mem::zeroed() };
+	if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+		return Err(Error::last_os_error().into());
+	}
+	let micros = |t:libc::timeval| t.tv_sec as u64 * 1_000_000 + t.tv_usec as u64;
+	// Linux reports kilobytes, macOS bytes.
+	#[cfg(target_os = "macos")]
+	let max_rss = usage.ru_maxrss as u64;
+	#[cfg(not(target_os = "macos"))]
+	let max_rss = usage.ru_maxrss as u64 * 1024;
+	Ok(json!({
+	  "userTime": micros(usage.ru_utime),
+	  "systemTime": micros(usage.ru_stime),
+	  "maxRss": max_rss,
+	  "pageFaults": (usage.ru_minflt + usage.ru_majflt) as u64,
+	  "majorPageFaults": usage.ru_majflt as u64,
+	}))
+}
+
+#[cfg(windows)]
+fn resource_usage() -> Result<Value, ErrBox> {
+	use winapi::{
+		shared::minwindef::FILETIME,
+		um::{
+			processthreadsapi::{GetCurrentProcess, GetProcessTimes},
+			psapi::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS},
+		},
+	};
+
+	let zero = FILETIME { dwLowDateTime:0, dwHighDateTime:0 };
+	let (mut creation, mut exit, mut kernel, mut user) = (zero, zero, zero, zero);
+	let mut counters:PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+	let size = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+	unsafe {
+		let process = GetCurrentProcess();
+		if GetProcessTimes(process, &mut creation, &mut exit, &mut kernel, &mut user) == 0
+			|| GetProcessMemoryInfo(process, &mut counters, size) == 0
+		{
+			return Err(Error::last_os_error().into());
+		}
+	}
+	// FILETIME counts 100 nanosecond intervals.
+	let micros =
+		|t:FILETIME| ((u64::from(t.dwHighDateTime) << 32) | u64::from(t.dwLowDateTime)) / 10;
+	// Windows doesn't tell hard faults apart from soft ones.
+	Ok(json!({
+	  "userTime": micros(user),
+	  "systemTime": micros(kernel),
+	  "maxRss": counters.PeakWorkingSetSize as u64,
+	  "pageFaults": counters.PageFaultCount as u64,
+	  "majorPageFaults": null,
+	}))
+}
+
+fn op_resource_usage(
+	_state:&ThreadSafeState,
+	_args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	Ok(JsonOp::Sync(resource_usage()?))
+}
@@ -5,9 +5,9 @@ use super::dispatch_json::{Deserialize, JsonOp, Value};
 use crate::{deno_error::type_error, ops::json_op, state::ThreadSafeState};
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("query_permission", s.core_op(json_op(s.stateful_op(op_query_permission))));
-	i.register_op("revoke_permission", s.core_op(json_op(s.stateful_op(op_revoke_permission))));
-	i.register_op("request_permission", s.core_op(json_op(s.stateful_op(op_request_permission))));
+	s.register_op(i, "query_permission", json_op(s.stateful_op(op_query_permission)));
+	s.register_op(i, "revoke_permission", json_op(s.stateful_op(op_revoke_permission)));
+	s.register_op(i, "request_permission", json_op(s.stateful_op(op_request_permission)));
 }
 
 #[derive(Deserialize)]
@@ -38,21 +38,16 @@ pub fn op_revoke_permission(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:PermissionArgs = serde_json::from_value(args)?;
-	let mut permissions = state.permissions.lock().unwrap();
-	match args.name.as_ref() {
-		"run" => permissions.allow_run.revoke(),
-		"read" => permissions.allow_read.revoke(),
-		"write" => permissions.allow_write.revoke(),
-		"net" => permissions.allow_net.revoke(),
-		"env" => permissions.allow_env.revoke(),
-		"plugin" => permissions.allow_plugin.revoke(),
-		"hrtime" => permissions.allow_hrtime.revoke(),
-		_ => {},
+	// Whitelist entries are absolute, so the scope has to be too.
+	let path = match args.path {
+		Some(path) => Some(state.resolve_path(&path)?.1),
+		None => None,
 	};
-	let perm = permissions.get_permission_state(
+	let mut permissions = state.permissions.lock().unwrap();
+	let perm = permissions.revoke(
 		&args.name,
 		&args.url.as_ref().map(String::as_str),
-		&args.path.as_ref().map(String::as_str),
+		&path.as_ref().map(String::as_str),
 	)?;
 	Ok(JsonOp::Sync(json!({ "state": perm.to_string() })))
 }
@@ -72,6 +67,7 @@ pub fn op_request_permission(
 		"env" => Ok(permissions.request_env()),
 		"plugin" => Ok(permissions.request_plugin()),
 		"hrtime" => Ok(permissions.request_hrtime()),
+		"ping" => Ok(permissions.request_ping()),
 		n => Err(type_error(format!("No such permission name: {}", n))),
 	}?;
 	Ok(JsonOp::Sync(json!({ "state": perm.to_string() })))
@@ -1,19 +1,33 @@
-use std::{collections::HashMap, ffi::OsStr, sync::Arc};
+use std::{
+	collections::HashMap,
+	ffi::OsStr,
+	future::Future,
+	path::Path,
+	pin::Pin,
+	sync::Arc,
+	task::{Context, Poll},
+};
 
 use deno::*;
 use dlopen::symbor::Library;
 
 use super::dispatch_json::{Deserialize, JsonOp, Value};
-use crate::{fs as deno_fs, ops::json_op, state::ThreadSafeState};
+use crate::{
+	deno_error::{bad_resource, DenoError, ErrorKind},
+	ops::json_op,
+	state::ThreadSafeState,
+};
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState, r:Arc<deno::OpRegistry>) {
 	let r_ = r.clone();
-	i.register_op(
+	s.register_op(
+		i,
 		"open_plugin",
-		s.core_op(json_op(s.stateful_op(move |state, args, zero_copy| {
+		json_op(s.stateful_op(move |state, args, zero_copy| {
 			op_open_plugin(&r_, state, args, zero_copy)
-		}))),
+		})),
 	);
+	s.register_op(i, "close_plugin", json_op(s.stateful_op(op_close_plugin)));
 }
 
 fn open_plugin<P:AsRef<OsStr>>(lib_path:P) -> Result<Library, ErrBox> {
@@ -22,13 +36,89 @@ fn open_plugin<P:AsRef<OsStr>>(lib_path:P) -> Result<Library, ErrBox> {
 	Library::open(lib_path).map_err(ErrBox::from)
 }
 
+/// Opens a plugin and checks its ABI version. Opening the library runs any
+/// static constructors it has, but a plugin built for another version is
+/// refused before deno calls into it.
+fn load_plugin(filename:&Path, filename_:&str) -> Result<Library, ErrBox> {
+	let lib = open_plugin(filename)?;
+	let abi_version = unsafe { lib.reference::<u32>("deno_plugin_abi_version") }.ok().map(|v| *v);
+	check_abi_version(filename_, abi_version)?;
+	Ok(lib)
+}
+
+// Plugins built against another version of deno may disagree with us about
+// the layout of the types passed across the boundary, so their init function
+// is never called.
+fn check_abi_version(filename:&str, found:Option<u32>) -> Result<(), ErrBox> {
+	let msg = match found {
+		Some(PLUGIN_ABI_VERSION) => return Ok(()),
+		Some(version) => {
+			format!(
+				"Plugin {} was built for plugin ABI version {}, but this deno expects version {}",
+				filename, version, PLUGIN_ABI_VERSION
+			)
+		},
+		None => {
+			format!(
+				"Plugin {} does not export deno_plugin_abi_version; rebuild it with the init_fn! \
+				 macro of this deno version",
+				filename
+			)
+		},
+	};
+	Err(DenoError::new(ErrorKind::InvalidData, msg).into())
+}
+
 struct PluginResource {
-	lib:Library,
+	lib:Arc<Library>,
 	ops:HashMap<String, OpId>,
+	registry:Arc<deno::OpRegistry>,
 }
 
 impl Resource for PluginResource {}
 
+// Closing the resource, by `close_plugin` or any other way, unregisters the
+// plugin's ops. The library itself stays loaded until the last call into it
+// has finished, see `PluginOp`.
+impl Drop for PluginResource {
+	fn drop(&mut self) {
+		for op_id in self.ops.values() {
+			self.registry.unregister(*op_id);
+		}
+	}
+}
+
+/// An op implemented by a plugin. Holds on to the library so that it isn't
+/// unloaded while the dispatcher, or a future it returned, still exists.
+/// Fields are dropped in declaration order, so the plugin's code is released
+/// before the library is.
+struct PluginOp {
+	dispatcher:Box<OpDispatcher>,
+	lib:Arc<Library>,
+}
+
+impl PluginOp {
+	fn call(&self, control:&[u8], zero_copy:Option<PinnedBuf>) -> CoreOp {
+		match (self.dispatcher)(control, zero_copy) {
+			Op::Sync(buf) => Op::Sync(buf),
+			Op::Async(fut) => Op::Async(Box::pin(PluginFuture { fut, _lib:self.lib.clone() })),
+		}
+	}
+}
+
+struct PluginFuture {
+	fut:OpAsyncFuture<CoreError>,
+	_lib:Arc<Library>,
+}
+
+impl Future for PluginFuture {
+	type Output = Result<Buf, CoreError>;
+
+	fn poll(self: Pin<&mut Self>, cx:&mut Context) -> Poll<Self::Output> {
+		self.get_mut().fut.as_mut().poll(cx)
+	}
+}
+
 struct InitContext {
 	ops:HashMap<String, Box<OpDispatcher>>,
 }
@@ -53,12 +143,13 @@ pub fn op_open_plugin(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:OpenPluginArgs = serde_json::from_value(args)?;
-	let (filename, filename_) = deno_fs::resolve_from_cwd(&args.filename)?;
+	let (filename, filename_) = state.resolve_path(&args.filename)?;
 
 	state.check_plugin(&filename_)?;
 
-	let lib = open_plugin(filename)?;
-	let plugin_resource = PluginResource { lib, ops:HashMap::new() };
+	let lib = load_plugin(&filename, &filename_)?;
+	let plugin_resource =
+		PluginResource { lib:Arc::new(lib), ops:HashMap::new(), registry:registry.clone() };
 	let mut table = state.lock_resource_table();
 	let rid = table.add("plugin", Box::new(plugin_resource));
 	let plugin_resource = table.get_mut::<PluginResource>(rid).unwrap();
@@ -72,9 +163,48 @@ pub fn op_open_plugin(
 		// The inclusion of prefix and rid is designed to avoid any
 		// op name collision beyond the bound of a single loaded
 		// plugin instance.
-		let op_id = registry.register(&format!("plugin_{}_{}", rid, op.0), state.core_op(op.1));
+		let plugin_op = PluginOp { dispatcher:op.1, lib:plugin_resource.lib.clone() };
+		let name = format!("plugin_{}_{}", rid, op.0);
+		let op_id = registry.register(
+			&name,
+			state.core_op(&name, move |control, zero_copy| plugin_op.call(control, zero_copy)),
+		);
 		plugin_resource.ops.insert(op.0, op_id);
 	}
 
 	Ok(JsonOp::Sync(json!({ "rid": rid, "ops": plugin_resource.ops })))
 }
+
+#[derive(Deserialize)]
+struct ClosePluginArgs {
+	rid:i32,
+}
+
+// Unregisters the plugin's ops; dispatching one of them afterwards throws
+// "Unknown op id". Ops still running finish first, the library is unloaded
+// once the last of them is done.
+fn op_close_plugin(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:ClosePluginArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let mut table = state.lock_resource_table();
+	table.get::<PluginResource>(rid).ok_or_else(bad_resource)?;
+	table.close(rid).ok_or_else(bad_resource)?;
+	Ok(JsonOp::Sync(json!({})))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn abi_version_mismatch() {
+		assert!(check_abi_version("a.so", Some(PLUGIN_ABI_VERSION)).is_ok());
+		let err = check_abi_version("a.so", Some(PLUGIN_ABI_VERSION + 1)).unwrap_err();
+		assert!(err.to_string().contains("ABI version"));
+		assert!(check_abi_version("a.so", None).is_err());
+	}
+}
@@ -7,7 +7,7 @@ use std::{
 	future::Future,
 	pin::Pin,
 	process::{Command, ExitStatus},
-	task::{Context, Poll},
+	task::{Context, Poll, Waker},
 };
 
 use deno::*;
@@ -22,13 +22,25 @@ use tokio_process::CommandExt;
 use super::{
 	dispatch_json::{Deserialize, JsonOp, Value},
 	io::StreamResource,
+	resources::cancellable,
+};
+use crate::{
+	deno_error::{bad_resource, DenoError, ErrorKind},
+	ops::json_op,
+	priority::{get_priority, set_priority},
+	signal::{kill, signal_stream, SignalStream},
+	state::ThreadSafeState,
 };
-use crate::{deno_error::bad_resource, ops::json_op, signal::kill, state::ThreadSafeState};
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("run", s.core_op(json_op(s.stateful_op(op_run))));
-	i.register_op("run_status", s.core_op(json_op(s.stateful_op(op_run_status))));
-	i.register_op("kill", s.core_op(json_op(s.stateful_op(op_kill))));
+	s.register_op(i, "run", json_op(s.stateful_op(op_run)));
+	s.register_op(i, "run_status", json_op(s.stateful_op(op_run_status)));
+	s.register_op(i, "kill", json_op(s.stateful_op(op_kill)));
+	s.register_op(i, "get_priority", json_op(s.stateful_op(op_get_priority)));
+	s.register_op(i, "set_priority", json_op(s.stateful_op(op_set_priority)));
+	s.register_op(i, "signal_bind", json_op(s.stateful_op(op_signal_bind)));
+	s.register_op(i, "signal_poll", json_op(s.stateful_op(op_signal_poll)));
+	s.register_op(i, "signal_unbind", json_op(s.stateful_op(op_signal_unbind)));
 }
 
 struct CloneFileFuture {
@@ -56,7 +68,7 @@ impl Future for CloneFileFuture {
 	}
 }
 
-fn clone_file(rid:u32, state:&ThreadSafeState) -> Result<std::fs::File, ErrBox> {
+pub fn clone_file(rid:u32, state:&ThreadSafeState) -> Result<std::fs::File, ErrBox> {
 	futures::executor::block_on(CloneFileFuture { rid, state:state.clone() }).map(|f| f.into_std())
 }
 
@@ -85,6 +97,8 @@ struct RunArgs {
 
 struct ChildResource {
 	child:futures::compat::Compat01As03<tokio_process::Child>,
+	/// Set once the child has been reaped; its pid may be reused after that.
+	exited:bool,
 }
 
 impl Resource for ChildResource {}
@@ -108,7 +122,12 @@ fn op_run(
 		let arg = args.get(i).unwrap();
 		c.arg(arg);
 	});
-	cwd.map(|d| c.current_dir(d));
+	// A relative `cwd` is relative to the worker's directory, like other paths.
+	let cwd = match cwd {
+		Some(dir) => state.resolve_path(&dir)?.0,
+		None => state.cwd(),
+	};
+	c.current_dir(cwd);
 	for (key, value) in &env {
 		c.env(key, value);
 	}
@@ -168,8 +187,9 @@ fn op_run(
 		None => None,
 	};
 
-	let child_resource = ChildResource { child:futures::compat::Compat01As03::new(child) };
+	let child_resource = ChildResource { child:futures::compat::Compat01As03::new(child), exited:false };
 	let child_rid = table.add("child", Box::new(child_resource));
+	table.set_details(child_rid, &pid.to_string());
 
 	Ok(JsonOp::Sync(json!({
 	  "rid": child_rid,
@@ -193,7 +213,11 @@ impl Future for ChildStatus {
 		let mut table = inner.state.lock_resource_table();
 		let child_resource = table.get_mut::<ChildResource>(inner.rid).ok_or_else(bad_resource)?;
 		let child = &mut child_resource.child;
-		child.map_err(ErrBox::from).poll_unpin(cx)
+		let result = child.map_err(ErrBox::from).poll_unpin(cx);
+		if let Poll::Ready(Ok(_)) = result {
+			child_resource.exited = true;
+		}
+		result
 	}
 }
 
@@ -201,6 +225,8 @@ impl Future for ChildStatus {
 #[serde(rename_all = "camelCase")]
 struct RunStatusArgs {
 	rid:i32,
+	#[serde(rename = "cancelRid")]
+	cancel_rid:Option<u32>,
 }
 
 fn op_run_status(
@@ -223,6 +249,11 @@ fn op_run_status(
 		#[cfg(not(unix))]
 		let signal = None;
 
+		#[cfg(unix)]
+		let core_dumped = signal.is_some() && libc::WCOREDUMP(run_status.into_raw());
+		#[cfg(not(unix))]
+		let core_dumped = false;
+
 		code.or(signal).expect("Should have either an exit code or a signal.");
 		let got_signal = signal.is_some();
 
@@ -230,18 +261,24 @@ fn op_run_status(
 		   "gotSignal": got_signal,
 		   "exitCode": code.unwrap_or(-1),
 		   "exitSignal": signal.unwrap_or(-1),
+		   "code": code,
+		   "signal": signal,
+		   "coreDumped": core_dumped,
 		}))
 	});
 
 	let pool = futures::executor::ThreadPool::new().unwrap();
 	let handle = pool.spawn_with_handle(future).unwrap();
 
-	Ok(JsonOp::Async(handle.boxed()))
+	// Dropping the handle stops waiting, the child keeps running.
+	Ok(JsonOp::Async(cancellable(state, args.cancel_rid, handle.boxed())?))
 }
 
 #[derive(Deserialize)]
 struct KillArgs {
-	pid:i32,
+	pid:Option<i32>,
+	/// A child spawned by `run`, as an alternative to `pid`.
+	rid:Option<i32>,
 	signo:i32,
 }
 
@@ -253,6 +290,158 @@ fn op_kill(
 	state.check_run()?;
 
 	let args:KillArgs = serde_json::from_value(args)?;
-	kill(args.pid, args.signo)?;
+	let pid = match (args.pid, args.rid) {
+		(Some(pid), _) => pid,
+		(None, Some(rid)) => child_pid(state, rid)?,
+		(None, None) => {
+			return Err(
+				DenoError::new(ErrorKind::InvalidInput, "pid or rid is required".to_string()).into()
+			);
+		},
+	};
+	kill(pid, args.signo)?;
+	Ok(JsonOp::Sync(json!({})))
+}
+
+/// The pid of a child spawned by `run`, unless it has been reaped.
+fn child_pid(state:&ThreadSafeState, rid:i32) -> Result<i32, ErrBox> {
+	let mut table = state.lock_resource_table();
+	let child_resource = table.get_mut::<ChildResource>(rid as u32).ok_or_else(bad_resource)?;
+	if child_resource.exited {
+		return Err(DenoError::new(ErrorKind::NotFound, "No such process".to_string()).into());
+	}
+	Ok(child_resource.child.get_ref().id() as i32)
+}
+
+#[derive(Deserialize)]
+struct PriorityArgs {
+	/// Zero or absent means the current process.
+	pid:Option<i32>,
+	/// A child spawned by `run`, as an alternative to `pid`.
+	rid:Option<i32>,
+	priority:Option<i32>,
+}
+
+impl PriorityArgs {
+	fn pid(&self, state:&ThreadSafeState) -> Result<i32, ErrBox> {
+		match (self.pid, self.rid) {
+			(Some(pid), _) => Ok(pid),
+			(None, Some(rid)) => child_pid(state, rid),
+			(None, None) => Ok(0),
+		}
+	}
+}
+
+fn op_get_priority(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	state.check_run()?;
+
+	let args:PriorityArgs = serde_json::from_value(args)?;
+	let priority = get_priority(args.pid(state)?)?;
+	Ok(JsonOp::Sync(json!(priority)))
+}
+
+fn op_set_priority(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	state.check_run()?;
+
+	let args:PriorityArgs = serde_json::from_value(args)?;
+	let priority = args.priority.ok_or_else(|| -> ErrBox {
+		DenoError::new(ErrorKind::InvalidInput, "priority is required".to_string()).into()
+	})?;
+	set_priority(args.pid(state)?, priority)?;
+	Ok(JsonOp::Sync(json!({})))
+}
+
+struct SignalStreamResource {
+	stream:SignalStream,
+	/// Task of a pending `signal_poll`, woken by `signal_unbind`.
+	waker:Option<Waker>,
+}
+
+impl Resource for SignalStreamResource {}
+
+// Lets a pending `signal_poll` see that the resource is gone however it was
+// closed, e.g. by `close_all`.
+impl Drop for SignalStreamResource {
+	fn drop(&mut self) {
+		if let Some(waker) = self.waker.take() {
+			waker.wake();
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct SignalBindArgs {
+	signo:i32,
+}
+
+fn op_signal_bind(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:SignalBindArgs = serde_json::from_value(args)?;
+	let stream = signal_stream(args.signo)?;
+	let mut table = state.lock_resource_table();
+	let rid = table.add("signal", Box::new(SignalStreamResource { stream, waker:None }));
+	Ok(JsonOp::Sync(json!({ "rid": rid })))
+}
+
+#[derive(Deserialize)]
+struct SignalArgs {
+	rid:i32,
+}
+
+fn op_signal_poll(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:SignalArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let state = state.clone();
+
+	let op = futures::future::poll_fn(move |cx| -> Poll<Result<Value, ErrBox>> {
+		let mut table = state.lock_resource_table();
+		let resource = match table.get_mut::<SignalStreamResource>(rid) {
+			Some(resource) => resource,
+			// Unbound while we were waiting.
+			None => return Poll::Ready(Ok(json!({ "done": true }))),
+		};
+		match resource.stream.poll_next() {
+			Ok(Async::Ready(Some(()))) => Poll::Ready(Ok(json!({ "done": false }))),
+			Ok(Async::Ready(None)) => Poll::Ready(Ok(json!({ "done": true }))),
+			Ok(Async::NotReady) => {
+				resource.waker = Some(cx.waker().clone());
+				Poll::Pending
+			},
+			Err(err) => Poll::Ready(Err(ErrBox::from(err))),
+		}
+	});
+
+	Ok(JsonOp::Async(op.boxed()))
+}
+
+fn op_signal_unbind(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:SignalArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let mut table = state.lock_resource_table();
+	let resource = table.get_mut::<SignalStreamResource>(rid).ok_or_else(bad_resource)?;
+	let waker = resource.waker.take();
+	table.close(rid).ok_or_else(bad_resource)?;
+	if let Some(waker) = waker {
+		waker.wake();
+	}
 	Ok(JsonOp::Sync(json!({})))
 }
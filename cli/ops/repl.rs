@@ -7,8 +7,8 @@ use super::dispatch_json::{blocking_json, Deserialize, JsonOp, Value};
 use crate::{deno_error::bad_resource, ops::json_op, repl, repl::Repl, state::ThreadSafeState};
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("repl_start", s.core_op(json_op(s.stateful_op(op_repl_start))));
-	i.register_op("repl_readline", s.core_op(json_op(s.stateful_op(op_repl_readline))));
+	s.register_op(i, "repl_start", json_op(s.stateful_op(op_repl_start)));
+	s.register_op(i, "repl_readline", json_op(s.stateful_op(op_repl_readline)));
 }
 
 struct ReplResource(Arc<Mutex<Repl>>);
@@ -30,7 +30,9 @@ fn op_repl_start(
 
 	debug!("op_repl_start {}", args.history_file);
 	let history_path = repl::history_path(&state.global_state.dir, &args.history_file);
-	let repl = repl::Repl::new(history_path);
+	let history_size =
+		state.global_state.flags.repl_history_size.unwrap_or(repl::DEFAULT_HISTORY_SIZE);
+	let repl = repl::Repl::new(history_path, history_size);
 	let resource = ReplResource(Arc::new(Mutex::new(repl)));
 	let mut table = state.lock_resource_table();
 	let rid = table.add("repl", Box::new(resource));
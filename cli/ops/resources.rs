@@ -1,19 +1,263 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+use std::{collections::HashMap, mem::ManuallyDrop};
+
 use deno::*;
+use futures::future::{abortable, AbortHandle, Aborted, FutureExt};
+use tokio::{net::TcpStream, reactor::Handle};
 
-use super::dispatch_json::{JsonOp, Value};
-use crate::{ops::json_op, state::ThreadSafeState};
+use super::{
+	dispatch_json::{AsyncJsonOp, Deserialize, JsonOp, Value},
+	io::StreamResource,
+	process::clone_file,
+};
+use crate::{
+	deno_error::{aborted, bad_resource, DenoError, ErrorKind},
+	ops::json_op,
+	state::ThreadSafeState,
+};
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("resources", s.core_op(json_op(s.stateful_op(op_resources))));
+	s.register_op(i, "resources", json_op(s.stateful_op(op_resources)));
+	s.register_op(i, "close_all", json_op(s.stateful_op(op_close_all)));
+	s.register_op(i, "dup", json_op(s.stateful_op(op_dup)));
+	s.register_op(i, "cancel_handle", json_op(s.stateful_op(op_cancel_handle)));
+	s.register_op(i, "cancel", json_op(s.stateful_op(op_cancel)));
+}
+
+#[derive(Deserialize, Default)]
+struct ResourcesArgs {
+	#[serde(default)]
+	details:bool,
+	#[serde(default)]
+	stats:bool,
 }
 
+// Lists everything in the resource table, including resources added by
+// plugins. With `details` names carry extra information where it is known,
+// e.g. "fsFile:/tmp/log.txt", "child:1234" or "tcpStream:127.0.0.1:4500".
+// With `stats` each entry also has the bytes read and written so far.
 fn op_resources(
 	state:&ThreadSafeState,
-	_args:Value,
+	args:Value,
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
+	let args:ResourcesArgs =
+		if args.is_null() { ResourcesArgs::default() } else { serde_json::from_value(args)? };
 	let resource_table = state.lock_resource_table();
-	let serialized_resources = resource_table.entries();
-	Ok(JsonOp::Sync(json!(serialized_resources)))
+	let serialized_resources =
+		if args.details { resource_table.describe() } else { resource_table.entries() };
+	if !args.stats {
+		return Ok(JsonOp::Sync(json!(serialized_resources)));
+	}
+	let with_stats:Vec<Value> = serialized_resources
+		.into_iter()
+		.map(|(rid, name)| {
+			let counters = resource_table.io_counters(rid);
+			let bytes_read = counters.map_or(0, |c| c.bytes_read());
+			let bytes_written = counters.map_or(0, |c| c.bytes_written());
+			json!([rid, name, bytes_read, bytes_written])
+		})
+		.collect();
+	Ok(JsonOp::Sync(json!(with_stats)))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CloseAllArgs {
+	keep_stdio:bool,
+}
+
+// Closing is dropping, which can't fail, so the only result is the count.
+fn op_close_all(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:CloseAllArgs = serde_json::from_value(args)?;
+	let keep:&[ResourceId] = if args.keep_stdio { &[0, 1, 2] } else { &[] };
+	let mut table = state.lock_resource_table();
+	let closed = table.close_all(keep);
+	Ok(JsonOp::Sync(json!({ "closed": closed })))
+}
+
+#[derive(Deserialize)]
+struct DupArgs {
+	rid:i32,
+}
+
+// Like dup(2): the new rid refers to the same open file or socket, which
+// stays open until both rids are closed. Files share their position.
+fn op_dup(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:DupArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let mut table = state.lock_resource_table();
+	let (name, resource) = match table.get::<StreamResource>(rid).ok_or_else(bad_resource)? {
+		StreamResource::FsFile(_) => {
+			// `clone_file()` locks the table itself.
+			drop(table);
+			let file = tokio::fs::File::from_std(clone_file(rid, state)?);
+			table = state.lock_resource_table();
+			("fsFile", StreamResource::FsFile(file))
+		},
+		StreamResource::TcpStream(stream) => {
+			("tcpStream", StreamResource::TcpStream(dup_tcp(stream)?))
+		},
+		#[cfg(unix)]
+		StreamResource::UnixStream(stream) => {
+			("unixStream", StreamResource::UnixStream(dup_unix(stream)?))
+		},
+		_ => {
+			return Err(DenoError::new(
+				ErrorKind::BadResource,
+				"Only files and sockets can be duplicated".to_string(),
+			)
+			.into());
+		},
+	};
+	let details = table.details(rid).map(str::to_string);
+	let new_rid = table.add(name, Box::new(resource));
+	if let Some(details) = details {
+		table.set_details(new_rid, &details);
+	}
+	Ok(JsonOp::Sync(json!(new_rid)))
+}
+
+/// Duplicates the socket like `try_clone()` of `std::net::TcpStream`.
+#[cfg(unix)]
+pub fn dup_tcp(stream:&TcpStream) -> Result<TcpStream, ErrBox> {
+	use std::os::unix::io::{AsRawFd, FromRawFd};
+	// Only borrows the socket, which must not be closed when this is dropped.
+	let std_stream =
+		ManuallyDrop::new(unsafe { std::net::TcpStream::from_raw_fd(stream.as_raw_fd()) });
+	Ok(TcpStream::from_std(std_stream.try_clone()?, &Handle::default())?)
+}
+
+#[cfg(windows)]
+pub fn dup_tcp(stream:&TcpStream) -> Result<TcpStream, ErrBox> {
+	use std::os::windows::io::{AsRawSocket, FromRawSocket};
+	// Only borrows the socket, which must not be closed when this is dropped.
+	let std_stream =
+		ManuallyDrop::new(unsafe { std::net::TcpStream::from_raw_socket(stream.as_raw_socket()) });
+	Ok(TcpStream::from_std(std_stream.try_clone()?, &Handle::default())?)
+}
+
+#[cfg(unix)]
+pub fn dup_unix(stream:&tokio::net::UnixStream) -> Result<tokio::net::UnixStream, ErrBox> {
+	use std::os::unix::{
+		io::{AsRawFd, FromRawFd},
+		net::UnixStream,
+	};
+	let std_stream = ManuallyDrop::new(unsafe { UnixStream::from_raw_fd(stream.as_raw_fd()) });
+	Ok(tokio::net::UnixStream::from_std(std_stream.try_clone()?, &Handle::default())?)
+}
+
+/// Backs an `AbortSignal`. Async ops given the handle's rid fail with
+/// `ErrorKind::Aborted` once it is cancelled, see `cancellable()`.
+#[derive(Default)]
+pub struct CancelHandle {
+	cancelled:bool,
+	/// Ops still running, by an id of this handle. An op removes itself when
+	/// it finishes, so a handle reused for many requests doesn't grow.
+	ops:HashMap<usize, AbortHandle>,
+	next_op_id:usize,
+}
+
+impl Resource for CancelHandle {}
+
+fn op_cancel_handle(
+	state:&ThreadSafeState,
+	_args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let mut table = state.lock_resource_table();
+	let rid = table.add("cancelHandle", Box::new(CancelHandle::default()));
+	Ok(JsonOp::Sync(json!(rid)))
+}
+
+#[derive(Deserialize)]
+struct CancelArgs {
+	rid:i32,
+}
+
+fn op_cancel(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:CancelArgs = serde_json::from_value(args)?;
+	let mut table = state.lock_resource_table();
+	let handle = table.get_mut::<CancelHandle>(args.rid as u32).ok_or_else(bad_resource)?;
+	handle.cancelled = true;
+	for (_, op) in handle.ops.drain() {
+		op.abort();
+	}
+	Ok(JsonOp::Sync(json!({})))
+}
+
+/// Ties `op` to the cancel handle `cancel_rid`, if any. Cancelling drops the
+/// op's future, which releases whatever it holds, e.g. a half open
+/// connection, and rejects its promise. A handle that is already cancelled
+/// fails the op right away.
+pub fn cancellable(
+	state:&ThreadSafeState,
+	cancel_rid:Option<u32>,
+	op:AsyncJsonOp,
+) -> Result<AsyncJsonOp, ErrBox> {
+	let rid = match cancel_rid {
+		Some(rid) => rid,
+		None => return Ok(op),
+	};
+	let mut table = state.lock_resource_table();
+	let handle = table.get_mut::<CancelHandle>(rid).ok_or_else(bad_resource)?;
+	if handle.cancelled {
+		return Err(aborted());
+	}
+	let (op, abort_handle) = abortable(op);
+	let id = handle.next_op_id;
+	handle.next_op_id += 1;
+	handle.ops.insert(id, abort_handle);
+	let state = state.clone();
+	let op = op.map(move |result| {
+		// The handle may have been closed in the meantime.
+		if let Some(handle) = state.lock_resource_table().get_mut::<CancelHandle>(rid) {
+			handle.ops.remove(&id);
+		}
+		result.unwrap_or_else(|Aborted| Err(aborted()))
+	});
+	Ok(op.boxed())
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::{executor::block_on, future};
+
+	use super::*;
+	use crate::deno_error::GetErrorKind;
+
+	#[test]
+	fn cancellable_forgets_finished_ops() {
+		let (int, _ext) = ThreadSafeState::create_channels();
+		let state = ThreadSafeState::mock(vec![String::from("./deno")], int);
+		let handle = Box::new(CancelHandle::default());
+		let rid = state.lock_resource_table().add("cancelHandle", handle);
+		let pending_ops =
+			|| state.lock_resource_table().get_mut::<CancelHandle>(rid).unwrap().ops.len();
+
+		for i in 0..3 {
+			let op = cancellable(&state, Some(rid), future::ok(json!(i)).boxed()).unwrap();
+			assert_eq!(pending_ops(), 1);
+			assert_eq!(block_on(op).unwrap(), json!(i));
+			assert_eq!(pending_ops(), 0);
+		}
+
+		let op = cancellable(&state, Some(rid), future::pending().boxed()).unwrap();
+		assert_eq!(pending_ops(), 1);
+		op_cancel(&state, json!({ "rid": rid }), None).unwrap();
+		assert_eq!(pending_ops(), 0);
+		assert_eq!(block_on(op).unwrap_err().kind(), ErrorKind::Aborted);
+	}
 }
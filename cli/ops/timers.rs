@@ -1,19 +1,31 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
 use std::{
 	self,
+	task::{Poll, Waker},
 	time::{Duration, Instant},
 };
 
 use deno::*;
 use futures::future::FutureExt;
+use tokio::{
+	prelude::{Async, Stream},
+	timer::Interval,
+};
 
 use super::dispatch_json::{Deserialize, JsonOp, Value};
-use crate::{ops::json_op, state::ThreadSafeState};
+use crate::{
+	ops::json_op,
+	resources::{bad_resource, Resource},
+	state::ThreadSafeState,
+};
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("global_timer_stop", s.core_op(json_op(s.stateful_op(op_global_timer_stop))));
-	i.register_op("global_timer", s.core_op(json_op(s.stateful_op(op_global_timer))));
-	i.register_op("now", s.core_op(json_op(s.stateful_op(op_now))));
+	s.register_op(i, "global_timer_stop", json_op(s.stateful_op(op_global_timer_stop)));
+	s.register_op(i, "global_timer", json_op(s.stateful_op(op_global_timer)));
+	s.register_op(i, "now", json_op(s.stateful_op(op_now)));
+	s.register_op(i, "interval_start", json_op(s.stateful_op(op_interval_start)));
+	s.register_op(i, "interval_poll", json_op(s.stateful_op(op_interval_poll)));
+	s.register_op(i, "interval_stop", json_op(s.stateful_op(op_interval_stop)));
 }
 
 fn op_global_timer_stop(
@@ -52,13 +64,20 @@ fn op_global_timer(
 // since the start time of the deno runtime.
 // If the High precision flag is not set, the
 // nanoseconds are rounded on 2ms.
+//
+// `start_time` is an `Instant`, which reads CLOCK_MONOTONIC (or the platform
+// equivalent), so the result never jumps when the wall clock is adjusted.
+// Both fields come from a single reading; sampling the clock twice could pair
+// the seconds of one reading with the nanoseconds of a later one and go
+// backwards across a second boundary.
 fn op_now(
 	state:&ThreadSafeState,
 	_args:Value,
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
-	let seconds = state.start_time.elapsed().as_secs();
-	let mut subsec_nanos = state.start_time.elapsed().subsec_nanos();
+	let elapsed = state.start_time.elapsed();
+	let seconds = elapsed.as_secs();
+	let mut subsec_nanos = elapsed.subsec_nanos();
 	let reduced_time_precision = 2_000_000; // 2ms in nanoseconds
 	let permissions = state.permissions.lock().unwrap();
 
@@ -74,3 +93,97 @@ fn op_now(
 	  "subsecNanos": subsec_nanos,
 	})))
 }
+
+struct IntervalResource {
+	interval:Interval,
+	/// Task of a pending `interval_poll`, woken by `interval_stop`.
+	waker:Option<Waker>,
+}
+
+impl Resource for IntervalResource {}
+
+// Lets a pending `interval_poll` see that the resource is gone however it was
+// closed, e.g. by `close_all`.
+impl Drop for IntervalResource {
+	fn drop(&mut self) {
+		if let Some(waker) = self.waker.take() {
+			waker.wake();
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct IntervalStartArgs {
+	period:u64,
+}
+
+// Ticks are scheduled from the previous deadline rather than from when the
+// last tick was observed, so a busy isolate doesn't make the interval drift.
+fn op_interval_start(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:IntervalStartArgs = serde_json::from_value(args)?;
+	let period = Duration::from_millis(args.period.max(1));
+	let interval = Interval::new(Instant::now() + period, period);
+	let mut table = state.lock_resource_table();
+	let rid = table.add("interval", Box::new(IntervalResource { interval, waker:None }));
+	Ok(JsonOp::Sync(json!({ "rid": rid })))
+}
+
+#[derive(Deserialize)]
+struct IntervalArgs {
+	rid:i32,
+}
+
+fn op_interval_poll(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:IntervalArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let state = state.clone();
+
+	let op = futures::future::poll_fn(move |cx| -> Poll<Result<Value, ErrBox>> {
+		let mut table = state.lock_resource_table();
+		let resource = match table.get_mut::<IntervalResource>(rid) {
+			Some(resource) => resource,
+			// Stopped while we were waiting.
+			None => return Poll::Ready(Ok(json!({ "done": true }))),
+		};
+		match resource.interval.poll() {
+			Ok(Async::Ready(Some(_))) => Poll::Ready(Ok(json!({ "done": false }))),
+			Ok(Async::Ready(None)) => Poll::Ready(Ok(json!({ "done": true }))),
+			Ok(Async::NotReady) => {
+				resource.waker = Some(cx.waker().clone());
+				Poll::Pending
+			},
+			Err(err) => Poll::Ready(Err(ErrBox::from(err))),
+		}
+	});
+
+	Ok(JsonOp::Async(op.boxed()))
+}
+
+// Stopping an interval that was already stopped is not an error, so that
+// clearing a timer twice behaves like clearInterval.
+fn op_interval_stop(
+	state:&ThreadSafeState,
+	args:Value,
+	_zero_copy:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:IntervalArgs = serde_json::from_value(args)?;
+	let rid = args.rid as u32;
+	let mut table = state.lock_resource_table();
+	let waker = match table.get_mut::<IntervalResource>(rid) {
+		Some(resource) => resource.waker.take(),
+		None => return Ok(JsonOp::Sync(json!({}))),
+	};
+	table.close(rid).ok_or_else(bad_resource)?;
+	if let Some(waker) = waker {
+		waker.wake();
+	}
+	Ok(JsonOp::Sync(json!({})))
+}
@@ -1,13 +1,18 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
 use std::{
 	self,
+	collections::HashMap,
 	convert::From,
 	fs::File,
 	future::Future,
 	io::BufReader,
 	net::SocketAddr,
 	pin::Pin,
-	sync::Arc,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc,
+		Mutex,
+	},
 	task::{Context, Poll},
 };
 
@@ -23,11 +28,22 @@ use tokio::{
 use tokio_rustls::{
 	rustls::{
 		internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys},
+		AllowAnyAuthenticatedClient,
 		Certificate,
 		ClientConfig,
+		ClientSessionMemoryCache,
 		NoClientAuth,
+		NoClientSessionStorage,
 		PrivateKey,
+		RootCertStore,
+		ServerCertVerified,
+		ServerCertVerifier,
 		ServerConfig,
+		ServerSessionMemoryCache,
+		Session,
+		TLSError,
+		Ticketer,
+		WebPKIVerifier,
 	},
 	TlsAcceptor,
 	TlsConnector,
@@ -40,6 +56,7 @@ use super::{
 	io::StreamResource,
 };
 use crate::{
+	checksum,
 	deno_error::{bad_resource, DenoError, ErrorKind},
 	ops::json_op,
 	resolve_addr::resolve_addr,
@@ -47,9 +64,9 @@ use crate::{
 };
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("dial_tls", s.core_op(json_op(s.stateful_op(op_dial_tls))));
-	i.register_op("listen_tls", s.core_op(json_op(s.stateful_op(op_listen_tls))));
-	i.register_op("accept_tls", s.core_op(json_op(s.stateful_op(op_accept_tls))));
+	s.register_op(i, "dial_tls", json_op(s.stateful_op(op_dial_tls)));
+	s.register_op(i, "listen_tls", json_op(s.stateful_op(op_listen_tls)));
+	s.register_op(i, "accept_tls", json_op(s.stateful_op(op_accept_tls)));
 }
 
 #[derive(Deserialize)]
@@ -57,7 +74,106 @@ pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
 struct DialTLSArgs {
 	hostname:String,
 	port:u16,
+	/// Additional root CA to trust.
 	cert_file:Option<String>,
+	/// Certificate chain presented to servers that request client auth.
+	client_cert_file:Option<String>,
+	client_key_file:Option<String>,
+	/// Offered during the handshake in order of preference.
+	#[serde(default)]
+	alpn_protocols:Vec<String>,
+	/// Resume sessions from earlier connections to the same host. On unless
+	/// set to `false`.
+	session_resumption:Option<bool>,
+	/// Send the first write as TLS 1.3 early data when resuming. That data can
+	/// be replayed by an attacker, so only idempotent requests should use it.
+	#[serde(default)]
+	early_data:bool,
+}
+
+lazy_static! {
+	/// Session caches of `dial_tls` connections, one per `session_cache_key()`.
+	/// Each is keyed by server name in turn.
+	static ref CLIENT_SESSION_CACHES: Mutex<HashMap<String, Arc<ClientSessionMemoryCache>>> =
+		Mutex::new(HashMap::new());
+}
+
+/// Hashes the trust roots, client certificate and ALPN protocols of a config.
+/// A session is only resumed under the same ones, as resuming skips the
+/// server certificate check and presents the original client identity.
+fn session_cache_key(
+	config:&ClientConfig,
+	client_identity:Option<&(Vec<Certificate>, PrivateKey)>,
+) -> String {
+	let mut parts:Vec<&[u8]> = Vec::new();
+	let anchors:Vec<_> =
+		config.root_store.roots.iter().map(|root| root.to_trust_anchor()).collect();
+	for anchor in &anchors {
+		parts.extend(&[anchor.subject, anchor.spki]);
+		parts.push(anchor.name_constraints.unwrap_or(&[]));
+	}
+	parts.push(b"client");
+	if let Some((certs, key)) = client_identity {
+		parts.extend(certs.iter().map(|cert| &cert.0[..]));
+		parts.push(&key.0);
+	}
+	parts.push(b"alpn");
+	parts.extend(config.alpn_protocols.iter().map(|p| &p[..]));
+
+	// Length prefixed, so that different parts never hash the same.
+	let mut data = Vec::new();
+	for part in parts {
+		data.extend_from_slice(&(part.len() as u64).to_le_bytes());
+		data.extend_from_slice(part);
+	}
+	checksum::gen(vec![&data])
+}
+
+fn client_session_cache(key:String) -> Arc<ClientSessionMemoryCache> {
+	let mut caches = CLIENT_SESSION_CACHES.lock().unwrap();
+	caches.entry(key).or_insert_with(|| ClientSessionMemoryCache::new(256)).clone()
+}
+
+/// The default certificate verifier, recording whether it ran. rustls skips
+/// verification only when the server accepts a resumed session, so a
+/// finished handshake without it was resumed.
+struct TrackedVerifier {
+	inner:WebPKIVerifier,
+	verified:Arc<AtomicBool>,
+}
+
+impl ServerCertVerifier for TrackedVerifier {
+	fn verify_server_cert(
+		&self,
+		roots:&RootCertStore,
+		presented_certs:&[Certificate],
+		dns_name:DNSNameRef,
+		ocsp_response:&[u8],
+	) -> Result<ServerCertVerified, TLSError> {
+		self.verified.store(true, Ordering::SeqCst);
+		self.inner.verify_server_cert(roots, presented_certs, dns_name, ocsp_response)
+	}
+}
+
+/// ALPN protocol and peer certificate fingerprints (SHA-256, leaf first)
+/// negotiated during a handshake.
+fn handshake_info(session:&dyn Session) -> Value {
+	let alpn_protocol =
+		session.get_alpn_protocol().map(|p| String::from_utf8_lossy(p).into_owned());
+	let fingerprints:Vec<String> = session
+		.get_peer_certificates()
+		.unwrap_or_default()
+		.iter()
+		.map(|cert| checksum::gen(vec![&cert.0]))
+		.collect();
+	json!({
+	  "alpnProtocol": alpn_protocol,
+	  "peerCertFingerprints": fingerprints,
+	})
+}
+
+fn alpn_protocols(protocols:&[String]) -> Vec<Vec<u8>> {
+	protocols.iter().map(|p| p.as_bytes().to_vec()).collect()
 }
 
 pub fn op_dial_tls(
@@ -66,19 +182,60 @@ pub fn op_dial_tls(
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
 	let args:DialTLSArgs = serde_json::from_value(args)?;
-	let cert_file = args.cert_file;
 	let state_ = state.clone();
 	state.check_net(&args.hostname, args.port)?;
-	if let Some(path) = cert_file.clone() {
-		state.check_read(&path)?;
+
+	let mut config = ClientConfig::new();
+	config.root_store.add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+	for cert in &state.global_state.http_config.ca_certs {
+		config.root_store.add(cert).map_err(|_| {
+			DenoError::new(ErrorKind::InvalidData, "Invalid CA certificate".to_string())
+		})?;
+	}
+	if let Some(path) = &args.cert_file {
+		let path = resolve_read(state, path)?;
+		add_root_certs(&mut config.root_store, &path)?;
 	}
+	let client_identity = match (&args.client_cert_file, &args.client_key_file) {
+		(Some(cert_file), Some(key_file)) => {
+			let cert_file = resolve_read(state, cert_file)?;
+			let key_file = resolve_read(state, key_file)?;
+			let certs = load_certs(&cert_file)?;
+			let key = load_keys(&key_file)?.remove(0);
+			config.set_single_client_cert(certs.clone(), key.clone());
+			Some((certs, key))
+		},
+		(None, None) => None,
+		_ => {
+			return Err(DenoError::new(
+				ErrorKind::InvalidInput,
+				"clientCertFile and clientKeyFile must be given together".to_string(),
+			)
+			.into());
+		},
+	};
+	config.set_protocols(&alpn_protocols(&args.alpn_protocols));
+	let verified = Arc::new(AtomicBool::new(false));
+	config.dangerous().set_certificate_verifier(Arc::new(TrackedVerifier {
+		inner:WebPKIVerifier::new(),
+		verified:verified.clone(),
+	}));
+	if args.session_resumption.unwrap_or(true) {
+		let key = session_cache_key(&config, client_identity.as_ref());
+		config.session_persistence = client_session_cache(key);
+		config.enable_early_data = args.early_data;
+	} else {
+		config.session_persistence = Arc::new(NoClientSessionStorage {});
+		config.enable_tickets = false;
+	}
+	let tls_connector = TlsConnector::from(Arc::new(config)).early_data(args.early_data);
 
 	let mut domain = args.hostname.clone();
 	if domain.is_empty() {
 		domain.push_str("localhost");
 	}
 
-	let op = resolve_addr(&args.hostname, args.port).and_then(move |addr| {
+	let op = state.resolve_net_target(&args.hostname, args.port)?.and_then(move |addr| {
 		futures::compat::Compat01As03::new(TcpStream::connect(&addr))
 			.and_then(move |tcp_stream| {
 				let local_addr = match tcp_stream.local_addr() {
@@ -89,18 +246,6 @@ pub fn op_dial_tls(
 					Ok(v) => v,
 					Err(e) => return futures::future::err(e),
 				};
-				let mut config = ClientConfig::new();
-				config.root_store.add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
-
-				if let Some(path) = cert_file {
-					let key_file = match File::open(path) {
-						Ok(v) => v,
-						Err(e) => return futures::future::err(e),
-					};
-					let reader = &mut BufReader::new(key_file);
-					config.root_store.add_pem_file(reader).unwrap();
-				}
-				let tls_connector = TlsConnector::from(Arc::new(config));
 				futures::future::ok((tls_connector, tcp_stream, local_addr, remote_addr))
 			})
 			.map_err(ErrBox::from)
@@ -109,6 +254,8 @@ pub fn op_dial_tls(
 				futures::compat::Compat01As03::new(tls_connector.connect(dnsname, tcp_stream))
 					.map_err(ErrBox::from)
 					.and_then(move |tls_stream| {
+						let mut handshake = handshake_info(tls_stream.get_ref().1);
+						handshake["resumed"] = json!(!verified.load(Ordering::SeqCst));
 						let mut table = state_.lock_resource_table();
 						let rid = table.add(
 							"clientTlsStream",
@@ -118,6 +265,7 @@ pub fn op_dial_tls(
 						  "rid": rid,
 						  "localAddr": local_addr.to_string(),
 						  "remoteAddr": remote_addr.to_string(),
+						  "handshake": handshake,
 						}))
 					})
 			})
@@ -126,6 +274,13 @@ pub fn op_dial_tls(
 	Ok(JsonOp::Async(op.boxed()))
 }
 
+/// Resolves `path` against the worker's cwd and checks that it may be read.
+fn resolve_read(state:&ThreadSafeState, path:&str) -> Result<String, ErrBox> {
+	let (_, path) = state.resolve_path(path)?;
+	state.check_read(&path)?;
+	Ok(path)
+}
+
 fn load_certs(path:&str) -> Result<Vec<Certificate>, ErrBox> {
 	let cert_file = File::open(path)?;
 	let reader = &mut BufReader::new(cert_file);
@@ -142,6 +297,20 @@ fn load_certs(path:&str) -> Result<Vec<Certificate>, ErrBox> {
 	Ok(certs)
 }
 
+/// Adds every certificate in the PEM file at `path` to `store`.
+fn add_root_certs(store:&mut RootCertStore, path:&str) -> Result<(), ErrBox> {
+	let cert_file = File::open(path)?;
+	let reader = &mut BufReader::new(cert_file);
+	let (valid, _invalid) = store.add_pem_file(reader).map_err(|_| {
+		DenoError::new(ErrorKind::Other, "Unable to decode certificate".to_string())
+	})?;
+	if valid == 0 {
+		let e = DenoError::new(ErrorKind::Other, "No certificates found in cert file".to_string());
+		return Err(ErrBox::from(e));
+	}
+	Ok(())
+}
+
 fn key_decode_err() -> DenoError {
 	DenoError::new(ErrorKind::Other, "Unable to decode key".to_string())
 }
@@ -241,6 +410,10 @@ struct ListenTlsArgs {
 	port:u16,
 	cert_file:String,
 	key_file:String,
+	/// When set, clients must present a certificate signed by this CA.
+	client_ca_file:Option<String>,
+	#[serde(default)]
+	alpn_protocols:Vec<String>,
 }
 
 fn op_listen_tls(
@@ -251,17 +424,27 @@ fn op_listen_tls(
 	let args:ListenTlsArgs = serde_json::from_value(args)?;
 	assert_eq!(args.transport, "tcp");
 
-	let cert_file = args.cert_file;
-	let key_file = args.key_file;
-
 	state.check_net(&args.hostname, args.port)?;
-	state.check_read(&cert_file)?;
-	state.check_read(&key_file)?;
-
-	let mut config = ServerConfig::new(NoClientAuth::new());
+	let cert_file = resolve_read(state, &args.cert_file)?;
+	let key_file = resolve_read(state, &args.key_file)?;
+
+	let client_auth = match &args.client_ca_file {
+		Some(path) => {
+			let path = resolve_read(state, path)?;
+			let mut store = RootCertStore::empty();
+			add_root_certs(&mut store, &path)?;
+			AllowAnyAuthenticatedClient::new(store)
+		},
+		None => NoClientAuth::new(),
+	};
+	let mut config = ServerConfig::new(client_auth);
 	config
 		.set_single_cert(load_certs(&cert_file)?, load_keys(&key_file)?.remove(0))
 		.expect("invalid key or certificate");
+	config.set_protocols(&alpn_protocols(&args.alpn_protocols));
+	// Let clients resume sessions, with tickets or from the server's cache.
+	config.session_storage = ServerSessionMemoryCache::new(256);
+	config.ticketer = Ticketer::new();
 	let tls_acceptor = TlsAcceptor::from(Arc::new(config));
 	let addr = futures::executor::block_on(resolve_addr(&args.hostname, args.port))?;
 	let listener = TcpListener::bind(&addr)?;
@@ -372,19 +555,21 @@ fn op_accept_tls(
 			futures::compat::Compat01As03::new(resource.tls_acceptor.accept(tcp_stream))
 				.map_err(ErrBox::from)
 				.and_then(move |tls_stream| {
+					let handshake = handshake_info(tls_stream.get_ref().1);
 					let mut table = state2.lock_resource_table();
 					let rid = table.add(
 						"serverTlsStream",
 						Box::new(StreamResource::ServerTlsStream(Box::new(tls_stream))),
 					);
-					futures::future::ok((rid, local_addr, remote_addr))
+					futures::future::ok((rid, local_addr, remote_addr, handshake))
 				})
 		})
-		.and_then(move |(rid, local_addr, remote_addr)| {
+		.and_then(move |(rid, local_addr, remote_addr, handshake)| {
 			futures::future::ok(json!({
 			  "rid": rid,
 			  "localAddr": local_addr.to_string(),
 			  "remoteAddr": remote_addr.to_string(),
+			  "handshake": handshake,
 			}))
 		});
 
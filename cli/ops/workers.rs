@@ -4,7 +4,7 @@ use std::{
 	convert::From,
 	future::Future,
 	pin::Pin,
-	sync::{atomic::Ordering, mpsc},
+	sync::mpsc,
 	task::{Context, Poll},
 };
 
@@ -20,23 +20,27 @@ use super::dispatch_json::{Deserialize, JsonOp, Value};
 use crate::{
 	deno_error::{bad_resource, js_check, DenoError, ErrorKind},
 	ops::json_op,
+	permissions::WorkerPermissions,
 	startup_data,
 	state::ThreadSafeState,
 	worker::Worker,
 };
 
 pub fn init(i:&mut Isolate, s:&ThreadSafeState) {
-	i.register_op("create_worker", s.core_op(json_op(s.stateful_op(op_create_worker))));
-	i.register_op(
-		"host_get_worker_closed",
-		s.core_op(json_op(s.stateful_op(op_host_get_worker_closed))),
-	);
-	i.register_op("host_post_message", s.core_op(json_op(s.stateful_op(op_host_post_message))));
-	i.register_op("host_get_message", s.core_op(json_op(s.stateful_op(op_host_get_message))));
+	s.register_op(i, "create_worker", json_op(s.stateful_op(op_create_worker)));
+	s.register_op(i, "host_get_worker_closed", json_op(s.stateful_op(op_host_get_worker_closed)));
+	s.register_op(i, "host_post_message", json_op(s.stateful_op(op_host_post_message)));
+	s.register_op(i, "host_get_message", json_op(s.stateful_op(op_host_get_message)));
 	// TODO: make sure these two ops are only accessible to appropriate Worker
-	i.register_op("worker_post_message", s.core_op(json_op(s.stateful_op(op_worker_post_message))));
-	i.register_op("worker_get_message", s.core_op(json_op(s.stateful_op(op_worker_get_message))));
-	i.register_op("metrics", s.core_op(json_op(s.stateful_op(op_metrics))));
+	s.register_op(i, "worker_post_message", json_op(s.stateful_op(op_worker_post_message)));
+	s.register_op(i, "worker_get_message", json_op(s.stateful_op(op_worker_get_message)));
+	s.register_op(
+		i,
+		"named_worker_post_message",
+		json_op(s.stateful_op(op_named_worker_post_message)),
+	);
+	s.register_op(i, "named_workers", json_op(s.stateful_op(op_named_workers)));
+	s.register_op(i, "metrics", json_op(s.stateful_op(op_metrics)));
 }
 
 struct GetMessageFuture {
@@ -95,6 +99,10 @@ struct CreateWorkerArgs {
 	include_deno_namespace:bool,
 	has_source_code:bool,
 	source_code:String,
+	name:Option<String>,
+	/// Reduces the worker's permissions to these instead of sharing the
+	/// parent's.
+	permissions:Option<WorkerPermissions>,
 }
 
 /// Create worker as the host
@@ -126,16 +134,25 @@ fn op_create_worker(
 	let (int, ext) = ThreadSafeState::create_channels();
 	let child_state = ThreadSafeState::new(
 		state.global_state.clone(),
-		Some(parent_state.permissions.clone()), // by default share with parent
+		Some(parent_state.child_permissions(args.permissions.as_ref())),
 		Some(module_specifier.clone()),
 		include_deno_namespace,
 		int,
 	)?;
-	// TODO: add a new option to make child worker not sharing permissions
-	// with parent (aka .clone(), requests from child won't reflect in parent)
-	let name = format!("USER-WORKER-{}", specifier);
+	// Relative paths in the child resolve like in its parent until it calls
+	// `Deno.chdir()` itself.
+	child_state.inherit_cwd(&parent_state);
+	let name = match args.name {
+		Some(ref name) => name.clone(),
+		None => format!("USER-WORKER-{}", specifier),
+	};
+	info!("Starting worker \"{}\" for {}", name, module_specifier);
 	let deno_main_call = format!("denoMain({})", include_deno_namespace);
-	let mut worker = Worker::new(name, startup_data::deno_isolate_init(), child_state, ext);
+	let startup_data = startup_data::user_isolate_init(&state.global_state.flags);
+	let mut worker = Worker::new(name, startup_data, child_state, ext);
+	if let Some(ref name) = args.name {
+		state.global_state.register_worker(name, worker.clone())?;
+	}
 	js_check(worker.execute(&deno_main_call));
 	js_check(worker.execute("workerMain()"));
 
@@ -205,15 +222,17 @@ fn op_host_get_worker_closed(
 	let state_ = state.clone();
 
 	let future = GetWorkerClosedFuture { state:state.clone(), rid:id };
-	let op = future.then(move |_result| {
+	let op = future.then(move |result| {
 		let mut workers_table = state_.workers.lock().unwrap();
 		let maybe_worker = workers_table.remove(&id);
 		if let Some(worker) = maybe_worker {
+			state_.global_state.unregister_worker(&worker);
 			let mut channels = worker.state.worker_channels.lock().unwrap();
 			channels.sender.close_channel();
 			channels.receiver.close();
 		};
-		futures::future::ok(json!({}))
+		// Uncaught errors in the worker reject the host's `closed` promise.
+		futures::future::ready(result.map(|_| json!({})))
 	});
 
 	Ok(JsonOp::Async(op.boxed()))
@@ -247,6 +266,40 @@ fn op_host_get_message(
 	Ok(JsonOp::Async(op.boxed()))
 }
 
+#[derive(Deserialize)]
+struct NamedWorkerPostMessageArgs {
+	name:String,
+}
+
+/// Post message to a worker registered by name, which needn't be a child of
+/// the calling worker
+fn op_named_worker_post_message(
+	state:&ThreadSafeState,
+	args:Value,
+	data:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	let args:NamedWorkerPostMessageArgs = serde_json::from_value(args)?;
+	let msg = Vec::from(data.unwrap().as_ref()).into_boxed_slice();
+
+	let worker = state.global_state.named_worker(&args.name).ok_or_else(|| {
+		DenoError::new(ErrorKind::NotFound, format!("No worker named \"{}\"", args.name))
+	})?;
+	// Waiting for room would block this isolate, possibly on itself.
+	worker.try_post_message(msg).map_err(|err| {
+		let kind = if err.is_full() { ErrorKind::WouldBlock } else { ErrorKind::BrokenPipe };
+		DenoError::new(kind, format!("Can't post to worker \"{}\": {}", args.name, err))
+	})?;
+	Ok(JsonOp::Sync(json!({})))
+}
+
+fn op_named_workers(
+	state:&ThreadSafeState,
+	_args:Value,
+	_data:Option<PinnedBuf>,
+) -> Result<JsonOp, ErrBox> {
+	Ok(JsonOp::Sync(json!(state.global_state.worker_names())))
+}
+
 #[derive(Deserialize)]
 struct HostPostMessageArgs {
 	id:i32,
@@ -278,13 +331,14 @@ fn op_metrics(
 	_args:Value,
 	_zero_copy:Option<PinnedBuf>,
 ) -> Result<JsonOp, ErrBox> {
-	let m = &state.metrics;
+	let m = state.metrics.snapshot();
 
 	Ok(JsonOp::Sync(json!({
-	  "opsDispatched": m.ops_dispatched.load(Ordering::SeqCst) as u64,
-	  "opsCompleted": m.ops_completed.load(Ordering::SeqCst) as u64,
-	  "bytesSentControl": m.bytes_sent_control.load(Ordering::SeqCst) as u64,
-	  "bytesSentData": m.bytes_sent_data.load(Ordering::SeqCst) as u64,
-	  "bytesReceived": m.bytes_received.load(Ordering::SeqCst) as u64
+	  "opsDispatched": m.ops_dispatched as u64,
+	  "opsCompleted": m.ops_completed as u64,
+	  "bytesSentControl": m.bytes_sent_control as u64,
+	  "bytesSentData": m.bytes_sent_data as u64,
+	  "bytesReceived": m.bytes_received as u64,
+	  "opsPending": state.pending_ops() as u64
 	})))
 }
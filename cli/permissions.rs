@@ -5,7 +5,12 @@ use std::io;
 use std::sync::atomic::AtomicBool;
 #[cfg(test)]
 use std::sync::atomic::Ordering;
-use std::{collections::HashSet, fmt, path::PathBuf};
+use std::{
+	collections::HashSet,
+	fmt,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
 
 use ansi_term::Style;
 #[cfg(not(test))]
@@ -49,12 +54,21 @@ impl PermissionState {
 		}
 	}
 
-	/// Requests the permission.
-	pub fn request(&mut self, msg:&str) -> PermissionState {
+	/// Requests the permission, asking `prompt` if one is installed and the
+	/// terminal otherwise.
+	pub fn request(
+		&mut self,
+		prompt:&Option<PermissionPrompt>,
+		request:&PermissionRequest,
+	) -> PermissionState {
 		if *self != PermissionState::Ask {
 			return *self;
 		}
-		if permission_prompt(msg) {
+		let granted = match prompt {
+			Some(prompt) => (prompt.0)(request),
+			None => permission_prompt(request.message),
+		};
+		if granted {
 			*self = PermissionState::Allow;
 		} else {
 			*self = PermissionState::Deny;
@@ -92,6 +106,48 @@ impl Default for PermissionState {
 	fn default() -> Self { PermissionState::Ask }
 }
 
+/// A permission request as seen by a `PermissionPrompt`.
+pub struct PermissionRequest<'a> {
+	/// One of the names in `cli/js/permissions.ts`, e.g. "read".
+	pub name:&'a str,
+	/// The path or URL the request is limited to, if any.
+	pub scope:Option<&'a str>,
+	/// What the terminal prompt would ask.
+	pub message:&'a str,
+}
+
+/// Decides permission requests in place of the terminal prompt, for embedders
+/// that have no terminal. Returns whether to grant the request.
+#[derive(Clone)]
+pub struct PermissionPrompt(Arc<dyn Fn(&PermissionRequest) -> bool + Send + Sync>);
+
+impl PermissionPrompt {
+	pub fn new<F>(f:F) -> Self
+	where
+		F: Fn(&PermissionRequest) -> bool + Send + Sync + 'static, {
+		PermissionPrompt(Arc::new(f))
+	}
+}
+
+impl fmt::Debug for PermissionPrompt {
+	fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result { f.write_str("PermissionPrompt") }
+}
+
+/// Permissions kept by a worker created with a reduced set, see
+/// `DenoPermissions::restrict()`. Everything not set here is denied.
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct WorkerPermissions {
+	pub read:bool,
+	pub write:bool,
+	pub net:bool,
+	pub env:bool,
+	pub run:bool,
+	pub plugin:bool,
+	pub hrtime:bool,
+	pub ping:bool,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct DenoPermissions {
 	// Keep in sync with cli/js/permissions.ts
@@ -105,6 +161,9 @@ pub struct DenoPermissions {
 	pub allow_run:PermissionState,
 	pub allow_plugin:PermissionState,
 	pub allow_hrtime:PermissionState,
+	pub allow_ping:PermissionState,
+	/// Answers `request_*` calls instead of the terminal when set.
+	pub prompt:Option<PermissionPrompt>,
 }
 
 impl DenoPermissions {
@@ -120,6 +179,34 @@ impl DenoPermissions {
 			allow_run:PermissionState::from(flags.allow_run),
 			allow_plugin:PermissionState::from(flags.allow_plugin),
 			allow_hrtime:PermissionState::from(flags.allow_hrtime),
+			allow_ping:PermissionState::from(flags.allow_ping),
+			prompt:None,
+		}
+	}
+
+	/// Returns the subset of these permissions named by `keep`. Kept ones are
+	/// copied with their whitelists, so the result is never broader than
+	/// `self`, the others are denied without prompting.
+	pub fn restrict(&self, keep:&WorkerPermissions) -> Self {
+		let state = |keep:bool, state:PermissionState| {
+			if keep { state } else { PermissionState::Deny }
+		};
+		let whitelist = |keep:bool, whitelist:&HashSet<String>| {
+			if keep { whitelist.clone() } else { HashSet::new() }
+		};
+		Self {
+			allow_read:state(keep.read, self.allow_read),
+			read_whitelist:whitelist(keep.read, &self.read_whitelist),
+			allow_write:state(keep.write, self.allow_write),
+			write_whitelist:whitelist(keep.write, &self.write_whitelist),
+			allow_net:state(keep.net, self.allow_net),
+			net_whitelist:whitelist(keep.net, &self.net_whitelist),
+			allow_env:state(keep.env, self.allow_env),
+			allow_run:state(keep.run, self.allow_run),
+			allow_plugin:state(keep.plugin, self.allow_plugin),
+			allow_hrtime:state(keep.hrtime, self.allow_hrtime),
+			allow_ping:state(keep.ping, self.allow_ping),
+			prompt:self.prompt.clone(),
 		}
 	}
 
@@ -156,6 +243,13 @@ impl DenoPermissions {
 		)
 	}
 
+	/// For `Deno.umask()`, which affects every file written. A write whitelist
+	/// doesn't cover it.
+	pub fn check_umask(&self) -> Result<(), ErrBox> {
+		self.allow_write
+			.check("access to change the umask", "run again with the --allow-write flag")
+	}
+
 	fn get_state_net(&self, host:&str, port:Option<u16>) -> PermissionState {
 		if check_host_and_port_whitelist(host, port, &self.net_whitelist) {
 			return PermissionState::Allow;
@@ -180,6 +274,14 @@ impl DenoPermissions {
 		)
 	}
 
+	/// Checks access to `hostname` on any port, e.g. to look it up.
+	pub fn check_net_host(&self, hostname:&str) -> Result<(), ErrBox> {
+		self.get_state_net(hostname, None).check(
+			&format!("network access to \"{}\"", hostname),
+			"run again with the --allow-net flag",
+		)
+	}
+
 	pub fn check_net_url(&self, url:&url::Url) -> Result<(), ErrBox> {
 		self.get_state_net(&format!("{}", url.host().unwrap()), url.port())
 			.check(&format!("network access to \"{}\"", url), "run again with the --allow-net flag")
@@ -197,50 +299,152 @@ impl DenoPermissions {
 		)
 	}
 
+	pub fn check_ping(&self, hostname:&str) -> Result<(), ErrBox> {
+		self.allow_ping.check(
+			&format!("access to send ICMP echo to \"{}\"", hostname),
+			"run again with the --allow-ping flag",
+		)
+	}
+
 	pub fn request_run(&mut self) -> PermissionState {
-		self.allow_run.request("Deno requests to access to run a subprocess.")
+		self.allow_run.request(
+			&self.prompt,
+			&PermissionRequest {
+				name:"run",
+				scope:None,
+				message:"Deno requests to access to run a subprocess.",
+			},
+		)
 	}
 
 	pub fn request_read(&mut self, path:&Option<&str>) -> PermissionState {
 		if check_path_white_list(path, &self.read_whitelist) {
 			return PermissionState::Allow;
 		};
-		self.allow_read.request(&match path {
+		let message = match path {
 			None => "Deno requests read access.".to_string(),
 			Some(path) => format!("Deno requests read access to \"{}\".", path),
-		})
+		};
+		self.allow_read.request(
+			&self.prompt,
+			&PermissionRequest { name:"read", scope:*path, message:&message },
+		)
 	}
 
 	pub fn request_write(&mut self, path:&Option<&str>) -> PermissionState {
 		if check_path_white_list(path, &self.write_whitelist) {
 			return PermissionState::Allow;
 		};
-		self.allow_write.request(&match path {
+		let message = match path {
 			None => "Deno requests write access.".to_string(),
 			Some(path) => format!("Deno requests write access to \"{}\".", path),
-		})
+		};
+		self.allow_write.request(
+			&self.prompt,
+			&PermissionRequest { name:"write", scope:*path, message:&message },
+		)
 	}
 
 	pub fn request_net(&mut self, url:&Option<&str>) -> Result<PermissionState, ErrBox> {
 		if self.get_state_net_url(url)? == PermissionState::Ask {
-			return Ok(self.allow_net.request(&match url {
+			let message = match url {
 				None => "Deno requests network access.".to_string(),
 				Some(url) => format!("Deno requests network access to \"{}\".", url),
-			}));
+			};
+			return Ok(self.allow_net.request(
+				&self.prompt,
+				&PermissionRequest { name:"net", scope:*url, message:&message },
+			));
 		};
 		self.get_state_net_url(url)
 	}
 
 	pub fn request_env(&mut self) -> PermissionState {
-		self.allow_env.request("Deno requests to access to environment variables.")
+		self.allow_env.request(
+			&self.prompt,
+			&PermissionRequest {
+				name:"env",
+				scope:None,
+				message:"Deno requests to access to environment variables.",
+			},
+		)
 	}
 
 	pub fn request_hrtime(&mut self) -> PermissionState {
-		self.allow_hrtime.request("Deno requests to access to high precision time.")
+		self.allow_hrtime.request(
+			&self.prompt,
+			&PermissionRequest {
+				name:"hrtime",
+				scope:None,
+				message:"Deno requests to access to high precision time.",
+			},
+		)
 	}
 
 	pub fn request_plugin(&mut self) -> PermissionState {
-		self.allow_plugin.request("Deno requests to open plugins.")
+		self.allow_plugin.request(
+			&self.prompt,
+			&PermissionRequest {
+				name:"plugin",
+				scope:None,
+				message:"Deno requests to open plugins.",
+			},
+		)
+	}
+
+	pub fn request_ping(&mut self) -> PermissionState {
+		self.allow_ping.request(
+			&self.prompt,
+			&PermissionRequest {
+				name:"ping",
+				scope:None,
+				message:"Deno requests to send ICMP echo.",
+			},
+		)
+	}
+
+	/// Drops a grant for the rest of the process, or until it is requested
+	/// again. With a scope, every grant that covers any part of it is dropped:
+	/// whitelist entries inside or above `path` (or for the host of `url`) and
+	/// the blanket flag, since it covers the scope too.
+	pub fn revoke(
+		&mut self,
+		name:&str,
+		url:&Option<&str>,
+		path:&Option<&str>,
+	) -> Result<PermissionState, ErrBox> {
+		match name {
+			"run" => self.allow_run.revoke(),
+			"read" => revoke_path(&mut self.allow_read, &mut self.read_whitelist, path),
+			"write" => revoke_path(&mut self.allow_write, &mut self.write_whitelist, path),
+			"net" => {
+				match url {
+					None => self.net_whitelist.clear(),
+					Some(url) => {
+						let parsed = Url::parse(url)
+							.map_err(|_| type_error(format!("Invalid url: {}", url)))?;
+						let host = format!("{}", parsed.host().unwrap());
+						let host_port = parsed.port().map(|port| format!("{}:{}", host, port));
+						self.net_whitelist.retain(|entry| {
+							match &host_port {
+								Some(host_port) => entry != &host && entry != host_port,
+								// Without a port every port of the host is revoked.
+								None => {
+									entry != &host && !entry.starts_with(&format!("{}:", host))
+								},
+							}
+						});
+					},
+				}
+				self.allow_net.revoke();
+			},
+			"env" => self.allow_env.revoke(),
+			"plugin" => self.allow_plugin.revoke(),
+			"hrtime" => self.allow_hrtime.revoke(),
+			"ping" => self.allow_ping.revoke(),
+			n => return Err(type_error(format!("No such permission name: {}", n))),
+		};
+		self.get_permission_state(name, url, path)
 	}
 
 	pub fn get_permission_state(
@@ -257,6 +461,7 @@ impl DenoPermissions {
 			"env" => Ok(self.allow_env),
 			"plugin" => Ok(self.allow_plugin),
 			"hrtime" => Ok(self.allow_hrtime),
+			"ping" => Ok(self.allow_ping),
 			n => Err(type_error(format!("No such permission name: {}", n))),
 		}
 	}
@@ -329,6 +534,20 @@ fn check_path_white_list(filename:&Option<&str>, white_list:&HashSet<String>) ->
 	false
 }
 
+fn revoke_path(state:&mut PermissionState, white_list:&mut HashSet<String>, path:&Option<&str>) {
+	match path {
+		None => white_list.clear(),
+		Some(path) => {
+			let path = Path::new(path);
+			white_list.retain(|entry| {
+				let entry = Path::new(entry);
+				!path.starts_with(entry) && !entry.starts_with(path)
+			});
+		},
+	}
+	state.revoke();
+}
+
 fn check_host_and_port_whitelist(host:&str, port:Option<u16>, whitelist:&HashSet<String>) -> bool {
 	whitelist.contains(host)
 		|| (port.is_some() && whitelist.contains(&format!("{}:{}", host, port.unwrap())))
@@ -469,6 +688,59 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn test_permissions_revoke_scoped() {
+		let mut perms = DenoPermissions::from_flags(&DenoFlags {
+			read_whitelist:svec!["/a", "/a/b/c", "/d"],
+			net_whitelist:svec!["deno.land", "github.com:443", "localhost:8000"],
+			..Default::default()
+		});
+
+		// Both the entry covering /a/b and the one inside it go.
+		assert_eq!(perms.revoke("read", &None, &Some("/a/b")).unwrap(), PermissionState::Ask);
+		assert!(perms.check_read("/a/b/c/file").is_err());
+		assert!(perms.check_read("/a/other").is_err());
+		assert!(perms.check_read("/d/file").is_ok());
+
+		assert_eq!(
+			perms.revoke("net", &Some("https://github.com/"), &None).unwrap(),
+			PermissionState::Ask
+		);
+		assert!(perms.check_net("github.com", 443).is_err());
+		assert!(perms.check_net("deno.land", 443).is_ok());
+		assert!(perms.check_net("localhost", 8000).is_ok());
+
+		perms.revoke("read", &None, &None).unwrap();
+		assert!(perms.check_read("/d/file").is_err());
+		assert!(perms.revoke("foo", &None, &None).is_err());
+	}
+
+	#[test]
+	fn test_permissions_revoke_blanket_grant() {
+		let mut perms =
+			DenoPermissions::from_flags(&DenoFlags { allow_write:true, ..Default::default() });
+		assert!(perms.check_write("/tmp/file").is_ok());
+		perms.revoke("write", &None, &Some("/tmp")).unwrap();
+		assert!(perms.check_write("/tmp/file").is_err());
+		assert!(perms.check_write("/etc/file").is_err());
+	}
+
+	#[test]
+	fn test_permissions_request_uses_prompt_callback() {
+		let mut perms = DenoPermissions::from_flags(&DenoFlags { ..Default::default() });
+		perms.prompt = Some(PermissionPrompt::new(|request| {
+			request.name == "read" && request.scope == Some("/allowed")
+		}));
+		// The terminal prompt stub would grant everything.
+		set_prompt_result(true);
+		assert_eq!(perms.request_read(&Some("/allowed")), PermissionState::Allow);
+
+		let mut perms = DenoPermissions::from_flags(&DenoFlags { ..Default::default() });
+		perms.prompt = Some(PermissionPrompt::new(|request| request.name == "read"));
+		assert_eq!(perms.request_write(&Some("/allowed")), PermissionState::Deny);
+		assert_eq!(perms.request_env(), PermissionState::Deny);
+	}
+
 	#[test]
 	fn test_permissions_request_run() {
 		let mut perms0 = DenoPermissions::from_flags(&DenoFlags { ..Default::default() });
@@ -610,4 +882,40 @@ mod tests {
 		set_prompt_result(false);
 		assert_eq!(perms1.request_hrtime(), PermissionState::Deny);
 	}
+
+	#[test]
+	fn test_permissions_request_ping() {
+		let mut perms0 = DenoPermissions::from_flags(&DenoFlags { ..Default::default() });
+		set_prompt_result(true);
+		assert_eq!(perms0.request_ping(), PermissionState::Allow);
+		assert!(perms0.check_ping("192.0.2.1").is_ok());
+
+		let mut perms1 = DenoPermissions::from_flags(&DenoFlags { ..Default::default() });
+		set_prompt_result(false);
+		assert_eq!(perms1.request_ping(), PermissionState::Deny);
+		assert!(perms1.check_ping("192.0.2.1").is_err());
+	}
+
+	#[test]
+	fn restrict() {
+		let perms = DenoPermissions::from_flags(&DenoFlags {
+			allow_net:true,
+			read_whitelist:svec!["/a/specific"],
+			allow_env:true,
+			..Default::default()
+		});
+		let net_only = perms.restrict(&WorkerPermissions { net:true, ..Default::default() });
+		assert!(net_only.check_net("deno.land", 443).is_ok());
+		assert!(net_only.check_read("/a/specific/file").is_err());
+		assert!(net_only.check_env().is_err());
+		// Denied rather than prompted.
+		assert_eq!(net_only.allow_read, PermissionState::Deny);
+
+		// Never broader than the parent.
+		let read = perms.restrict(&WorkerPermissions { read:true, run:true, ..Default::default() });
+		assert!(read.check_read("/a/specific/file").is_ok());
+		assert!(read.check_read("/b").is_err());
+		assert!(read.check_run().is_err());
+		assert!(read.check_net("deno.land", 443).is_err());
+	}
 }
@@ -0,0 +1,257 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+//! ICMP echo ("ping"). Raw sockets need root or `CAP_NET_RAW`, so without them
+//! the unprivileged ICMP datagram sockets of Linux (`net.ipv4.ping_group_range`)
+//! and macOS are used instead.
+use std::{
+	net::SocketAddr,
+	time::{Duration, Instant},
+};
+
+use deno::ErrBox;
+
+use crate::deno_error::{DenoError, ErrorKind};
+
+const ECHO_REQUEST_V4:u8 = 8;
+const ECHO_REPLY_V4:u8 = 0;
+const ECHO_REQUEST_V6:u8 = 128;
+const ECHO_REPLY_V6:u8 = 129;
+
+/// Builds an echo request. The checksum of ICMPv6 covers a pseudo header
+/// only the kernel knows, so it is left for the kernel to fill in.
+fn echo_request(v6:bool, id:u16, seq:u16, payload:&[u8]) -> Vec<u8> {
+	let mut packet = vec![if v6 { ECHO_REQUEST_V6 } else { ECHO_REQUEST_V4 }, 0, 0, 0];
+	packet.extend_from_slice(&id.to_be_bytes());
+	packet.extend_from_slice(&seq.to_be_bytes());
+	packet.extend_from_slice(payload);
+	if !v6 {
+		let sum = checksum(&packet);
+		packet[2..4].copy_from_slice(&sum.to_be_bytes());
+	}
+	packet
+}
+
+/// The Internet checksum of RFC 1071.
+fn checksum(data:&[u8]) -> u16 {
+	let mut sum = data
+		.chunks(2)
+		.map(|pair| u32::from(pair[0]) << 8 | u32::from(*pair.get(1).unwrap_or(&0)))
+		.sum::<u32>();
+	while sum >> 16 != 0 {
+		sum = (sum & 0xffff) + (sum >> 16);
+	}
+	!(sum as u16)
+}
+
+/// Returns whether `packet` answers the request `seq`. Raw IPv4 sockets, and
+/// datagram ones on macOS, receive the IP header too. `id` is not compared
+/// when `None`.
+fn is_reply(v6:bool, packet:&[u8], id:Option<u16>, seq:u16) -> bool {
+	let packet = match packet.first() {
+		Some(b) if !v6 && b >> 4 == 4 => packet.get(usize::from(b & 0x0f) * 4..).unwrap_or(&[]),
+		_ => packet,
+	};
+	if packet.len() < 8 {
+		return false;
+	}
+	let reply = if v6 { ECHO_REPLY_V6 } else { ECHO_REPLY_V4 };
+	packet[0] == reply
+		&& id.map_or(true, |id| packet[4..6] == id.to_be_bytes())
+		&& packet[6..8] == seq.to_be_bytes()
+}
+
+#[cfg(unix)]
+struct IcmpSocket {
+	fd:std::os::unix::io::RawFd,
+	raw:bool,
+}
+
+#[cfg(unix)]
+impl Drop for IcmpSocket {
+	fn drop(&mut self) { unsafe { libc::close(self.fd) }; }
+}
+
+/// Opens a socket that is not inherited by child processes.
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))]
+fn socket_cloexec(
+	family:libc::c_int,
+	ty:libc::c_int,
+	protocol:libc::c_int,
+) -> std::io::Result<libc::c_int> {
+	match unsafe { libc::socket(family, ty | libc::SOCK_CLOEXEC, protocol) } {
+		-1 => Err(std::io::Error::last_os_error()),
+		fd => Ok(fd),
+	}
+}
+
+/// Opens a socket that is not inherited by child processes. There is no
+/// `SOCK_CLOEXEC` here, so the flag is set right after creation.
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android", target_os = "freebsd"))))]
+fn socket_cloexec(
+	family:libc::c_int,
+	ty:libc::c_int,
+	protocol:libc::c_int,
+) -> std::io::Result<libc::c_int> {
+	let fd = unsafe { libc::socket(family, ty, protocol) };
+	if fd == -1 {
+		return Err(std::io::Error::last_os_error());
+	}
+	if unsafe { libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC) } == -1 {
+		let err = std::io::Error::last_os_error();
+		unsafe { libc::close(fd) };
+		return Err(err);
+	}
+	Ok(fd)
+}
+
+#[cfg(unix)]
+impl IcmpSocket {
+	fn open(v6:bool) -> Result<Self, ErrBox> {
+		let (family, protocol) = if v6 {
+			(libc::AF_INET6, libc::IPPROTO_ICMPV6)
+		} else {
+			(libc::AF_INET, libc::IPPROTO_ICMP)
+		};
+		if let Ok(fd) = socket_cloexec(family, libc::SOCK_RAW, protocol) {
+			return Ok(Self { fd, raw:true });
+		}
+		let err = match socket_cloexec(family, libc::SOCK_DGRAM, protocol) {
+			Ok(fd) => return Ok(Self { fd, raw:false }),
+			Err(err) => err,
+		};
+		match err.raw_os_error() {
+			Some(libc::EPERM) | Some(libc::EACCES) => {
+				Err(DenoError::new(
+					ErrorKind::PermissionDenied,
+					"Sending ICMP echo requires root or CAP_NET_RAW, or a group in \
+					 net.ipv4.ping_group_range"
+						.to_string(),
+				)
+				.into())
+			},
+			_ => Err(err.into()),
+		}
+	}
+
+	fn set_timeout(&self, timeout:Duration) -> Result<(), ErrBox> {
+		// A zero timeval would block forever.
+		let timeout = timeout.max(Duration::from_millis(1));
+		let tv = libc::timeval {
+			tv_sec:timeout.as_secs() as libc::time_t,
+			tv_usec:timeout.subsec_micros() as libc::suseconds_t,
+		};
+		let result = unsafe {
+			libc::setsockopt(
+				self.fd,
+				libc::SOL_SOCKET,
+				libc::SO_RCVTIMEO,
+				&tv as *const libc::timeval as *const libc::c_void,
+				std::mem::size_of::<libc::timeval>() as libc::socklen_t,
+			)
+		};
+		if result == -1 {
+			return Err(std::io::Error::last_os_error().into());
+		}
+		Ok(())
+	}
+
+	fn send_to(&self, packet:&[u8], addr:&SocketAddr) -> Result<(), ErrBox> {
+		use nix::sys::socket::{sendto, InetAddr, MsgFlags, SockAddr};
+
+		let addr = SockAddr::new_inet(InetAddr::from_std(addr));
+		sendto(self.fd, packet, &addr, MsgFlags::empty())?;
+		Ok(())
+	}
+
+	/// Returns `None` once the receive timeout passed.
+	fn recv(&self, buf:&mut [u8]) -> Result<Option<usize>, ErrBox> {
+		let n = unsafe { libc::recv(self.fd, buf.as_mut_ptr() as *mut libc::c_void, buf.len(), 0) };
+		if n == -1 {
+			let err = std::io::Error::last_os_error();
+			return match err.kind() {
+				std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut => Ok(None),
+				std::io::ErrorKind::Interrupted => Ok(Some(0)),
+				_ => Err(err.into()),
+			};
+		}
+		Ok(Some(n as usize))
+	}
+}
+
+/// Sends `count` echo requests to `addr` one after another and returns the
+/// round-trip time of each, `None` where no reply came within `timeout`.
+#[cfg(unix)]
+pub fn ping(
+	addr:&SocketAddr,
+	count:u16,
+	timeout:Duration,
+) -> Result<Vec<Option<Duration>>, ErrBox> {
+	let v6 = addr.is_ipv6();
+	let socket = IcmpSocket::open(v6)?;
+	let id = std::process::id() as u16;
+	let mut buf = [0u8; 1500];
+	// Linux replaces the id of datagram sockets with their port.
+	let expected_id = if socket.raw { Some(id) } else { None };
+	let mut rtts = Vec::with_capacity(usize::from(count));
+	for seq in 0..count {
+		let sent_at = Instant::now();
+		socket.send_to(&echo_request(v6, id, seq, b"deno ping"), addr)?;
+		let mut rtt = None;
+		while let Some(remaining) = timeout.checked_sub(sent_at.elapsed()) {
+			socket.set_timeout(remaining)?;
+			match socket.recv(&mut buf)? {
+				None => break,
+				Some(n) if is_reply(v6, &buf[..n], expected_id, seq) => {
+					rtt = Some(sent_at.elapsed());
+					break;
+				},
+				Some(_) => {},
+			}
+		}
+		rtts.push(rtt);
+	}
+	Ok(rtts)
+}
+
+#[cfg(not(unix))]
+pub fn ping(
+	_addr:&SocketAddr,
+	_count:u16,
+	_timeout:Duration,
+) -> Result<Vec<Option<Duration>>, ErrBox> {
+	Err(DenoError::new(
+		ErrorKind::OpNotAvailable,
+		"Sending ICMP echo is not supported on this platform".to_string(),
+	)
+	.into())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn echo_request_checksum() {
+		let packet = echo_request(false, 0x1234, 1, b"abc");
+		assert_eq!(&packet[..2], &[ECHO_REQUEST_V4, 0]);
+		assert_eq!(&packet[4..8], &[0x12, 0x34, 0, 1]);
+		// A packet with its checksum filled in sums to zero.
+		assert_eq!(checksum(&packet), 0);
+		assert_eq!(&echo_request(true, 0x1234, 1, b"abc")[2..4], &[0, 0]);
+	}
+
+	#[test]
+	fn matches_replies() {
+		let mut reply = echo_request(false, 7, 3, b"");
+		reply[0] = ECHO_REPLY_V4;
+		assert!(is_reply(false, &reply, Some(7), 3));
+		assert!(is_reply(false, &reply, None, 3));
+		assert!(!is_reply(false, &reply, Some(8), 3));
+		assert!(!is_reply(false, &reply, Some(7), 4));
+		// With a 20 byte IPv4 header in front.
+		let mut with_header = vec![0x45];
+		with_header.extend_from_slice(&[0; 19]);
+		with_header.extend_from_slice(&reply);
+		assert!(is_reply(false, &with_header, Some(7), 3));
+		assert!(!is_reply(true, &reply, None, 3));
+	}
+}
@@ -0,0 +1,208 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+//! Scheduling priority of processes, as Unix nice values from -20 (highest)
+//! to 19 (lowest). On Linux the priority belongs to each thread, so setting it
+//! changes every thread of the process and reading it returns the main
+//! thread's. On Windows they are mapped to priority classes the way libuv
+//! does it, so a value read back may differ from the one set.
+use deno::ErrBox;
+
+use crate::deno_error::{DenoError, ErrorKind};
+
+pub const PRIORITY_HIGHEST:i32 = -20;
+pub const PRIORITY_LOWEST:i32 = 19;
+
+fn check_range(priority:i32) -> Result<(), ErrBox> {
+	if priority < PRIORITY_HIGHEST || priority > PRIORITY_LOWEST {
+		return Err(DenoError::new(
+			ErrorKind::InvalidInput,
+			format!(
+				"Priority must be between {} and {}, got {}",
+				PRIORITY_HIGHEST, PRIORITY_LOWEST, priority
+			),
+		)
+		.into());
+	}
+	Ok(())
+}
+
+#[cfg(unix)]
+fn last_error(pid:i32) -> ErrBox {
+	use nix::errno::Errno;
+	match Errno::last() {
+		Errno::EACCES => DenoError::new(
+			ErrorKind::PermissionDenied,
+			"Raising the priority requires elevated privileges".to_string(),
+		)
+		.into(),
+		Errno::EPERM => DenoError::new(
+			ErrorKind::PermissionDenied,
+			format!("Not allowed to change the priority of process {}", pid),
+		)
+		.into(),
+		errno => nix::Error::Sys(errno).into(),
+	}
+}
+
+/// `PRIO_PROCESS` takes a thread id on Linux, and the main thread's id is the
+/// process id.
+#[cfg(target_os = "linux")]
+fn main_thread(pid:i32) -> libc::id_t {
+	if pid == 0 { std::process::id() } else { pid as libc::id_t }
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn main_thread(pid:i32) -> libc::id_t { pid as libc::id_t }
+
+/// Ids of the threads of `pid`, see `main_thread()`.
+#[cfg(target_os = "linux")]
+fn threads(pid:i32) -> Result<Vec<libc::id_t>, ErrBox> {
+	let entries = match std::fs::read_dir(format!("/proc/{}/task", main_thread(pid))) {
+		Ok(entries) => entries,
+		Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => {
+			return Err(DenoError::new(ErrorKind::NotFound, "No such process".to_string()).into());
+		},
+		Err(e) => return Err(e.into()),
+	};
+	Ok(entries.filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok()).collect())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn threads(pid:i32) -> Result<Vec<libc::id_t>, ErrBox> { Ok(vec![main_thread(pid)]) }
+
+/// Returns the priority of `pid`, zero meaning the current process.
+#[cfg(unix)]
+pub fn get_priority(pid:i32) -> Result<i32, ErrBox> {
+	// -1 is a valid priority, only errno tells an error apart.
+	nix::errno::Errno::clear();
+	let priority = unsafe { libc::getpriority(libc::PRIO_PROCESS, main_thread(pid)) };
+	if priority == -1 && nix::errno::errno() != 0 {
+		return Err(last_error(pid));
+	}
+	Ok(priority)
+}
+
+/// Sets the priority of `pid`, zero meaning the current process.
+#[cfg(unix)]
+pub fn set_priority(pid:i32, priority:i32) -> Result<(), ErrBox> {
+	check_range(priority)?;
+	for thread in threads(pid)? {
+		if unsafe { libc::setpriority(libc::PRIO_PROCESS, thread, priority) } == -1 {
+			// Threads may exit while we go through them.
+			if thread != main_thread(pid) && nix::errno::Errno::last() == nix::errno::Errno::ESRCH {
+				continue;
+			}
+			return Err(last_error(pid));
+		}
+	}
+	Ok(())
+}
+
+#[cfg(windows)]
+fn with_process<T>(
+	pid:i32,
+	access:winapi::shared::minwindef::DWORD,
+	f:impl FnOnce(winapi::um::winnt::HANDLE) -> std::io::Result<T>,
+) -> Result<T, ErrBox> {
+	use winapi::{
+		shared::{minwindef::FALSE, winerror::ERROR_INVALID_PARAMETER},
+		um::{
+			handleapi::CloseHandle,
+			processthreadsapi::{GetCurrentProcess, OpenProcess},
+		},
+	};
+
+	if pid == 0 {
+		// A pseudo handle that needs no closing.
+		return Ok(f(unsafe { GetCurrentProcess() })?);
+	}
+	let handle = unsafe { OpenProcess(access, FALSE, pid as u32) };
+	if handle.is_null() {
+		let err = std::io::Error::last_os_error();
+		if err.raw_os_error() == Some(ERROR_INVALID_PARAMETER as i32) {
+			return Err(DenoError::new(ErrorKind::NotFound, "No such process".to_string()).into());
+		}
+		return Err(err.into());
+	}
+	let result = f(handle);
+	unsafe { CloseHandle(handle) };
+	Ok(result?)
+}
+
+/// Returns the priority of `pid`, zero meaning the current process.
+#[cfg(windows)]
+pub fn get_priority(pid:i32) -> Result<i32, ErrBox> {
+	use winapi::um::{processthreadsapi::GetPriorityClass, winbase::*, winnt::*};
+
+	let class = with_process(pid, PROCESS_QUERY_LIMITED_INFORMATION, |handle| {
+		match unsafe { GetPriorityClass(handle) } {
+			0 => Err(std::io::Error::last_os_error()),
+			class => Ok(class),
+		}
+	})?;
+	Ok(match class {
+		REALTIME_PRIORITY_CLASS => PRIORITY_HIGHEST,
+		HIGH_PRIORITY_CLASS => -14,
+		ABOVE_NORMAL_PRIORITY_CLASS => -7,
+		BELOW_NORMAL_PRIORITY_CLASS => 10,
+		IDLE_PRIORITY_CLASS => PRIORITY_LOWEST,
+		_ => 0,
+	})
+}
+
+/// Sets the priority of `pid`, zero meaning the current process.
+#[cfg(windows)]
+pub fn set_priority(pid:i32, priority:i32) -> Result<(), ErrBox> {
+	use winapi::{
+		shared::minwindef::FALSE,
+		um::{processthreadsapi::SetPriorityClass, winbase::*, winnt::*},
+	};
+
+	check_range(priority)?;
+	let class = match priority {
+		PRIORITY_HIGHEST => REALTIME_PRIORITY_CLASS,
+		p if p < -7 => HIGH_PRIORITY_CLASS,
+		p if p < 0 => ABOVE_NORMAL_PRIORITY_CLASS,
+		p if p < 10 => NORMAL_PRIORITY_CLASS,
+		p if p < PRIORITY_LOWEST => BELOW_NORMAL_PRIORITY_CLASS,
+		_ => IDLE_PRIORITY_CLASS,
+	};
+	with_process(pid, PROCESS_SET_INFORMATION, |handle| {
+		if unsafe { SetPriorityClass(handle, class) } == FALSE {
+			return Err(std::io::Error::last_os_error());
+		}
+		Ok(())
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::deno_error::GetErrorKind;
+
+	#[test]
+	fn out_of_range() {
+		for priority in &[-21, 20] {
+			let err = set_priority(0, *priority).unwrap_err();
+			assert_eq!(err.kind(), ErrorKind::InvalidInput);
+		}
+	}
+
+	#[test]
+	fn current_process() {
+		let priority = get_priority(0).unwrap();
+		assert!(priority >= PRIORITY_HIGHEST && priority <= PRIORITY_LOWEST);
+	}
+
+	// A child is used, lowering the priority can't be undone without privileges.
+	#[cfg(unix)]
+	#[test]
+	fn lower_child() {
+		let mut child = std::process::Command::new("sleep").arg("10").spawn().unwrap();
+		let pid = child.id() as i32;
+		let priority = (get_priority(pid).unwrap() + 5).min(PRIORITY_LOWEST);
+		set_priority(pid, priority).unwrap();
+		assert_eq!(get_priority(pid).unwrap(), priority);
+		child.kill().unwrap();
+		child.wait().unwrap();
+	}
+}
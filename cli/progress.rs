@@ -1,5 +1,27 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
-use std::sync::{Arc, Mutex};
+use std::{
+	pin::Pin,
+	sync::{Arc, Mutex},
+	task::{Context, Poll},
+};
+
+use futures::{
+	channel::mpsc,
+	stream::{Stream, StreamExt},
+};
+
+/// Events delivered to `Progress::subscribe()` streams.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProgressEvent {
+	DownloadStarted { url:String },
+	/// `bytes` is the size of the downloaded source, zero for a redirect.
+	DownloadCompleted { url:String, bytes:usize },
+	ModuleResolved { url:String },
+	CompileStarted { url:String },
+	CompileFinished { url:String },
+	/// All jobs of the current module graph are done.
+	Done,
+}
 
 #[derive(Clone, Default)]
 pub struct Progress(Arc<Mutex<Inner>>);
@@ -35,8 +57,25 @@ impl Progress {
 	}
 
 	pub fn done(&self) {
-		let s = self.0.lock().unwrap();
+		let mut s = self.0.lock().unwrap();
 		s.maybe_call_callback(true, s.complete, s.job_names.len(), "", "");
+		s.emit(ProgressEvent::Done);
+	}
+
+	/// Returns a stream of progress events. Unlike `set_callback` there can be
+	/// any number of subscribers; dropping the stream unsubscribes.
+	pub fn subscribe(&self) -> ProgressSubscription {
+		let mut s = self.0.lock().unwrap();
+		let (sender, receiver) = mpsc::unbounded();
+		let id = s.next_subscriber_id;
+		s.next_subscriber_id += 1;
+		s.subscribers.push((id, sender));
+		ProgressSubscription { id, receiver, inner:self.0.clone() }
+	}
+
+	pub fn emit(&self, event:ProgressEvent) {
+		let mut s = self.0.lock().unwrap();
+		s.emit(event);
 	}
 }
 
@@ -47,6 +86,8 @@ struct Inner {
 	job_names:Vec<String>,
 	complete:usize,
 	callback:Option<Arc<Callback>>,
+	subscribers:Vec<(usize, mpsc::UnboundedSender<ProgressEvent>)>,
+	next_subscriber_id:usize,
 }
 
 impl Inner {
@@ -68,6 +109,32 @@ impl Inner {
 		let total = self.job_names.len();
 		(self.complete, total)
 	}
+
+	fn emit(&mut self, event:ProgressEvent) {
+		self.subscribers.retain(|(_, sender)| sender.unbounded_send(event.clone()).is_ok());
+	}
+}
+
+pub struct ProgressSubscription {
+	id:usize,
+	receiver:mpsc::UnboundedReceiver<ProgressEvent>,
+	inner:Arc<Mutex<Inner>>,
+}
+
+impl Stream for ProgressSubscription {
+	type Item = ProgressEvent;
+
+	fn poll_next(self: Pin<&mut Self>, cx:&mut Context) -> Poll<Option<Self::Item>> {
+		self.get_mut().receiver.poll_next_unpin(cx)
+	}
+}
+
+impl Drop for ProgressSubscription {
+	fn drop(&mut self) {
+		let mut s = self.inner.lock().unwrap();
+		let id = self.id;
+		s.subscribers.retain(|(subscriber_id, _)| *subscriber_id != id);
+	}
 }
 
 pub struct Job {
@@ -147,6 +214,28 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn subscribe() {
+		let p = Progress::new();
+		let mut events = p.subscribe();
+		p.emit(ProgressEvent::DownloadStarted { url:"a".to_string() });
+		p.emit(ProgressEvent::DownloadCompleted { url:"a".to_string(), bytes:3 });
+		p.done();
+		assert_eq!(
+			futures::executor::block_on(events.by_ref().take(3).collect::<Vec<_>>()),
+			vec![
+				ProgressEvent::DownloadStarted { url:"a".to_string() },
+				ProgressEvent::DownloadCompleted { url:"a".to_string(), bytes:3 },
+				ProgressEvent::Done,
+			]
+		);
+
+		let _other = p.subscribe();
+		assert_eq!(p.0.lock().unwrap().subscribers.len(), 2);
+		drop(events);
+		assert_eq!(p.0.lock().unwrap().subscribers.len(), 1);
+	}
+
 	#[test]
 	fn thread_safe() {
 		fn f<S:Send + Sync>(_:S) {}
@@ -11,6 +11,7 @@ use deno::ErrBox;
 use rustyline;
 #[cfg(not(windows))]
 use rustyline::Editor;
+use rustyline::{Config, EditMode};
 
 use crate::deno_dir::DenoDir;
 
@@ -24,7 +25,9 @@ unsafe impl<T:rustyline::Helper> Send for Editor<T> {}
 
 #[cfg(windows)]
 impl<T:rustyline::Helper> Editor<T> {
-	pub fn new() -> Editor<T> { Editor { inner:rustyline::Editor::<T>::new() } }
+	pub fn with_config(config:Config) -> Editor<T> {
+		Editor { inner:rustyline::Editor::<T>::with_config(config) }
+	}
 }
 
 #[cfg(windows)]
@@ -39,14 +42,26 @@ impl<T:rustyline::Helper> DerefMut for Editor<T> {
 	fn deref_mut(&mut self) -> &mut rustyline::Editor<T> { &mut self.inner }
 }
 
+pub const DEFAULT_HISTORY_SIZE:usize = 1000;
+
 pub struct Repl {
 	editor:Editor<()>,
 	history_file:PathBuf,
 }
 
 impl Repl {
-	pub fn new(history_file:PathBuf) -> Self {
-		let mut repl = Self { editor:Editor::<()>::new(), history_file };
+	/// Ctrl-R searches the history backwards incrementally (emacs mode). Only
+	/// consecutive repeats are collapsed, so an earlier entry is never dropped
+	/// because the same line was typed again later, and lines starting with a
+	/// space are kept out of the history altogether, as in bash.
+	pub fn new(history_file:PathBuf, history_size:usize) -> Self {
+		let config = Config::builder()
+			.edit_mode(EditMode::Emacs)
+			.max_history_size(history_size)
+			.history_ignore_dups(true)
+			.history_ignore_space(true)
+			.build();
+		let mut repl = Self { editor:Editor::<()>::with_config(config), history_file };
 
 		repl.load_history();
 		repl
@@ -75,11 +90,18 @@ impl Repl {
 			})
 	}
 
+	/// rustyline turns on bracketed paste mode, so a pasted block comes back
+	/// from a single call, newlines included, and is evaluated as one unit.
 	pub fn readline(&mut self, prompt:&str) -> Result<String, ErrBox> {
 		self.editor
 			.readline(&prompt)
 			.map(|line| {
-				self.editor.add_history_entry(line.clone());
+				let line = normalize_paste(line);
+				// Saved right away: a script calling Deno.exit() never gets to drop
+				// the Repl.
+				if self.editor.add_history_entry(line.clone()) {
+					self.save_history().ok();
+				}
 				line
 			})
 			.map_err(ErrBox::from)
@@ -96,3 +118,34 @@ pub fn history_path(dir:&DenoDir, history_file:&str) -> PathBuf {
 	p.push(history_file);
 	p
 }
+
+/// Start and end of a bracketed paste. Some terminals send them even when the
+/// mode isn't on, in which case rustyline passes them through.
+const PASTE_START:&str = "\x1b[200~";
+const PASTE_END:&str = "\x1b[201~";
+
+/// Removes paste markers and turns the line endings of a pasted block into
+/// `\n`.
+fn normalize_paste(line:String) -> String {
+	if !line.contains('\r') && !line.contains('\x1b') {
+		return line;
+	}
+	line.replace(PASTE_START, "")
+		.replace(PASTE_END, "")
+		.replace("\r\n", "\n")
+		.replace('\r', "\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn normalize_paste_block() {
+		assert_eq!(normalize_paste("1 + 1".to_string()), "1 + 1");
+		assert_eq!(
+			normalize_paste("\x1b[200~function f() {\r\n  return 1;\r}\x1b[201~".to_string()),
+			"function f() {\n  return 1;\n}"
+		);
+	}
+}
@@ -1,13 +1,15 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
 use std::{
 	future::Future,
-	net::{SocketAddr, ToSocketAddrs},
+	net::{Ipv6Addr, SocketAddr, SocketAddrV6, ToSocketAddrs},
 	pin::Pin,
 	task::{Context, Poll},
 };
 
 use deno::ErrBox;
 
+use crate::deno_error::{DenoError, ErrorKind};
+
 /// Resolve network address. Returns a future.
 pub fn resolve_addr(hostname:&str, port:u16) -> ResolveAddrFuture {
 	ResolveAddrFuture { hostname:hostname.to_string(), port }
@@ -38,6 +40,11 @@ impl Future for ResolveAddrFuture {
 		} else {
 			addr
 		};
+		match parse_scoped_ipv6(addr, inner.port) {
+			Ok(Some(scoped)) => return Poll::Ready(Ok(scoped)),
+			Err(err) => return Poll::Ready(Err(err)),
+			Ok(None) => {},
+		}
 		let addr_port_pair = (addr, inner.port);
 		let r = addr_port_pair.to_socket_addrs().map_err(ErrBox::from);
 
@@ -50,6 +57,49 @@ impl Future for ResolveAddrFuture {
 	}
 }
 
+/// Parses an IPv6 address with a zone id, e.g. `fe80::1%eth0` or
+/// `[fe80::1%2]`, as needed to reach link-local addresses. The zone is an
+/// interface name or index. Returns `None` if `hostname` has no zone id.
+pub fn parse_scoped_ipv6(hostname:&str, port:u16) -> Result<Option<SocketAddr>, ErrBox> {
+	let host = hostname.trim_start_matches('[').trim_end_matches(']');
+	let mut parts = host.splitn(2, '%');
+	let (ip, zone) = match (parts.next(), parts.next()) {
+		(Some(ip), Some(zone)) => (ip, zone),
+		_ => return Ok(None),
+	};
+	let ip = ip.parse::<Ipv6Addr>().map_err(|_| -> ErrBox {
+		DenoError::new(ErrorKind::InvalidInput, format!("Invalid IPv6 address \"{}\"", hostname))
+			.into()
+	})?;
+	let scope_id = match zone.parse::<u32>() {
+		Ok(index) => index,
+		Err(_) => interface_index(zone)?,
+	};
+	Ok(Some(SocketAddr::V6(SocketAddrV6::new(ip, port, 0, scope_id))))
+}
+
+#[cfg(unix)]
+fn interface_index(name:&str) -> Result<u32, ErrBox> {
+	let not_found = || -> ErrBox {
+		DenoError::new(ErrorKind::NotFound, format!("No network interface named \"{}\"", name))
+			.into()
+	};
+	let c_name = std::ffi::CString::new(name).map_err(|_| not_found())?;
+	match unsafe { libc::if_nametoindex(c_name.as_ptr()) } {
+		0 => Err(not_found()),
+		index => Ok(index),
+	}
+}
+
+#[cfg(not(unix))]
+fn interface_index(name:&str) -> Result<u32, ErrBox> {
+	Err(DenoError::new(
+		ErrorKind::InvalidInput,
+		format!("Zone id \"{}\" must be an interface index on this platform", name),
+	)
+	.into())
+}
+
 #[cfg(test)]
 mod tests {
 	use std::net::{Ipv4Addr, Ipv6Addr, SocketAddrV4, SocketAddrV6};
@@ -90,4 +140,29 @@ mod tests {
 		let actual = block_on(resolve_addr("[2001:db8::1]", 8080)).unwrap();
 		assert_eq!(actual, expected);
 	}
+
+	#[test]
+	fn resolve_addr_scoped_ipv6() {
+		let expected = SocketAddr::V6(SocketAddrV6::new(
+			Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1),
+			8080,
+			0,
+			2,
+		));
+		assert_eq!(block_on(resolve_addr("[fe80::1%2]", 8080)).unwrap(), expected);
+		assert_eq!(parse_scoped_ipv6("fe80::1%2", 8080).unwrap(), Some(expected));
+		assert_eq!(parse_scoped_ipv6("[2001:db8::1]", 8080).unwrap(), None);
+		assert!(parse_scoped_ipv6("fe80::1%no-such-interface", 8080).is_err());
+		assert!(parse_scoped_ipv6("example.com%2", 8080).is_err());
+	}
+
+	#[cfg(target_os = "linux")]
+	#[test]
+	fn resolve_addr_scoped_ipv6_interface_name() {
+		let addr = parse_scoped_ipv6("fe80::1%lo", 80).unwrap().unwrap();
+		match addr {
+			SocketAddr::V6(addr) => assert!(addr.scope_id() > 0),
+			_ => unreachable!(),
+		}
+	}
 }
@@ -1,18 +1,123 @@
+#[cfg(unix)]
+use std::{collections::HashMap, sync::Mutex};
+use std::io;
+
 use deno::ErrBox;
+#[cfg(unix)]
+use nix::sys::signal::{sigaction, SaFlags, SigAction, SigHandler, SigSet, Signal};
+use tokio::prelude::{Future, Poll, Stream};
 
 #[cfg(unix)]
 pub fn kill(pid:i32, signo:i32) -> Result<(), ErrBox> {
-	use nix::{
-		sys::signal::{kill as unix_kill, Signal},
-		unistd::Pid,
-	};
+	use nix::{sys::signal::kill as unix_kill, unistd::Pid};
 	let sig = Signal::from_c_int(signo)?;
 	unix_kill(Pid::from_raw(pid), Option::Some(sig)).map_err(ErrBox::from)
 }
 
-#[cfg(not(unix))]
-pub fn kill(_pid:i32, _signal:i32) -> Result<(), ErrBox> {
-	// NOOP
-	// TODO: implement this for windows
+/// Windows has no signals; any signal terminates the process.
+#[cfg(windows)]
+pub fn kill(pid:i32, _signo:i32) -> Result<(), ErrBox> {
+	use winapi::{
+		shared::{minwindef::FALSE, winerror::ERROR_INVALID_PARAMETER},
+		um::{
+			handleapi::CloseHandle,
+			processthreadsapi::{OpenProcess, TerminateProcess},
+			winnt::PROCESS_TERMINATE,
+		},
+	};
+
+	use crate::deno_error::{DenoError, ErrorKind};
+
+	let handle = unsafe { OpenProcess(PROCESS_TERMINATE, FALSE, pid as u32) };
+	if handle.is_null() {
+		let err = io::Error::last_os_error();
+		if err.raw_os_error() == Some(ERROR_INVALID_PARAMETER as i32) {
+			return Err(DenoError::new(ErrorKind::NotFound, "No such process".to_string()).into());
+		}
+		return Err(err.into());
+	}
+	let ok = unsafe { TerminateProcess(handle, 1) };
+	let err = io::Error::last_os_error();
+	unsafe { CloseHandle(handle) };
+	if ok == FALSE {
+		return Err(err.into());
+	}
 	Ok(())
 }
+
+/// Occurrences of a single signal. While at least one stream for a signal is
+/// alive the signal no longer has its default effect; once the last one is
+/// dropped the default disposition is restored.
+pub struct SignalStream {
+	#[cfg(unix)]
+	signo:i32,
+	inner:Box<dyn Stream<Item = (), Error = io::Error> + Send>,
+}
+
+impl SignalStream {
+	/// Futures 0.1 style poll; must be called from within a task.
+	pub fn poll_next(&mut self) -> Poll<Option<()>, io::Error> { self.inner.poll() }
+}
+
+#[cfg(unix)]
+#[derive(Default)]
+struct Listeners {
+	count:usize,
+	/// tokio-signal's handler, set aside while the default disposition is in
+	/// place. tokio-signal only installs its handler once per signal, so it has
+	/// to be put back by hand before the signal is listened to again.
+	saved_action:Option<SigAction>,
+}
+
+#[cfg(unix)]
+lazy_static! {
+	static ref LISTENERS:Mutex<HashMap<i32, Listeners>> = Mutex::new(HashMap::new());
+}
+
+#[cfg(unix)]
+pub fn signal_stream(signo:i32) -> Result<SignalStream, ErrBox> {
+	let signal = Signal::from_c_int(signo)?;
+	let mut listeners = LISTENERS.lock().unwrap();
+	let entry = listeners.entry(signo).or_default();
+	if let Some(action) = entry.saved_action.take() {
+		unsafe { sigaction(signal, &action) }?;
+	}
+	let stream = tokio_signal::unix::Signal::new(signo).wait()?;
+	entry.count += 1;
+	Ok(SignalStream { signo, inner:Box::new(stream.map(|_| ())) })
+}
+
+#[cfg(windows)]
+pub fn signal_stream(signo:i32) -> Result<SignalStream, ErrBox> {
+	use crate::deno_error::{DenoError, ErrorKind};
+
+	// Ctrl-C is the only signal with a Windows equivalent.
+	const SIGINT:i32 = 2;
+	if signo != SIGINT {
+		return Err(DenoError::new(
+			ErrorKind::InvalidInput,
+			format!("Signal {} is not supported on Windows", signo),
+		)
+		.into());
+	}
+	let event = tokio_signal::windows::Event::ctrl_c().wait()?;
+	Ok(SignalStream { inner:Box::new(event) })
+}
+
+#[cfg(unix)]
+impl Drop for SignalStream {
+	fn drop(&mut self) {
+		let mut listeners = LISTENERS.lock().unwrap();
+		let entry = listeners.get_mut(&self.signo).unwrap();
+		entry.count -= 1;
+		if entry.count > 0 {
+			return;
+		}
+		let signal = Signal::from_c_int(self.signo).unwrap();
+		let default = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+		match unsafe { sigaction(signal, &default) } {
+			Ok(previous) => entry.saved_action = Some(previous),
+			Err(err) => debug!("failed to restore default for signal {}: {}", self.signo, err),
+		}
+	}
+}
@@ -6,6 +6,7 @@ use std::{collections::HashMap, str};
 use deno::{StackFrame, V8Exception};
 use serde_json;
 use source_map_mappings::{parse_mappings, Bias, Mappings};
+use url::Url;
 
 pub trait SourceMapGetter {
 	/// Returns the raw source map file.
@@ -80,6 +81,46 @@ fn builtin_source_map(script_name:&str) -> Option<Vec<u8>> {
 	}
 }
 
+/// Extracts the source map embedded in `source_code` by a trailing
+/// `//# sourceMappingURL=data:application/json;base64,...` comment, as emitted
+/// by most bundlers. Relative `sources` are resolved against `script_name` so
+/// remapped frames point somewhere meaningful.
+pub fn inline_source_map(script_name:&str, source_code:&[u8]) -> Option<Vec<u8>> {
+	const PREFIX:&str = "sourceMappingURL=data:";
+
+	let source = str::from_utf8(source_code).ok()?;
+	let comment = source.lines().rev().map(str::trim).find(|line| !line.is_empty())?;
+	if !(comment.starts_with("//# ") || comment.starts_with("//@ ")) {
+		return None;
+	}
+	let data_url = comment[4..].trim_start();
+	if !data_url.starts_with(PREFIX) {
+		return None;
+	}
+	let data_url = &data_url[PREFIX.len()..];
+	let comma = data_url.find(',')?;
+	let (media_type, data) = (&data_url[..comma], &data_url[comma + 1..]);
+	if !media_type.starts_with("application/json") || !media_type.ends_with(";base64") {
+		return None;
+	}
+	let raw = base64::decode(data).ok()?;
+
+	let base = match Url::parse(script_name) {
+		Ok(base) => base,
+		Err(_) => return Some(raw),
+	};
+	let mut map:serde_json::Value = serde_json::from_slice(&raw).ok()?;
+	if let Some(sources) = map.get_mut("sources").and_then(|s| s.as_array_mut()) {
+		for source in sources.iter_mut() {
+			let resolved = source.as_str().and_then(|s| base.join(s).ok());
+			if let Some(resolved) = resolved {
+				*source = serde_json::Value::String(resolved.to_string());
+			}
+		}
+	}
+	serde_json::to_vec(&map).ok()
+}
+
 /// Apply a source map to a V8Exception, returning a V8Exception where the
 /// filenames, the lines and the columns point to their original source
 /// location, not their transpiled location if applicable.
@@ -425,4 +466,23 @@ mod tests {
 			})
 		);
 	}
+
+	#[test]
+	fn inline_source_map_data_url() {
+		let map = r#"{"version":3,"sources":["src/a.ts"],"mappings":"AAAA"}"#;
+		let source = format!(
+			"console.log(1);\n//# sourceMappingURL=data:application/json;base64,{}\n",
+			base64::encode(map)
+		);
+		let actual = inline_source_map("https://example.com/dist/bundle.js", source.as_bytes())
+			.expect("inline map");
+		let actual:serde_json::Value = serde_json::from_slice(&actual).unwrap();
+		assert_eq!(actual["sources"][0], "https://example.com/dist/src/a.ts");
+		assert_eq!(actual["mappings"], "AAAA");
+
+		// Not a data URL, or not the last line.
+		assert!(inline_source_map("file:///a.js", b"//# sourceMappingURL=a.js.map").is_none());
+		let trailing = format!("{}foo();\n", source);
+		assert!(inline_source_map("file:///a.js", trailing.as_bytes()).is_none());
+	}
 }
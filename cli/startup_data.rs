@@ -1,9 +1,19 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+use std::{
+	collections::HashMap,
+	fs,
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
 #[cfg(feature = "no-snapshot-init")]
 use deno::Script;
 use deno::StartupData;
 
-use crate::js::{CLI_SNAPSHOT, COMPILER_SNAPSHOT};
+use crate::{
+	flags::DenoFlags,
+	js::{CLI_SNAPSHOT, COMPILER_SNAPSHOT},
+};
 
 #[cfg(feature = "no-snapshot-init")]
 pub fn deno_isolate_init() -> StartupData<'static> {
@@ -27,6 +37,39 @@ pub fn deno_isolate_init() -> StartupData<'static> {
 	StartupData::Snapshot(data)
 }
 
+/// Startup data for workers running user code. Uses the `--snapshot` file
+/// when it exists, `deno_isolate_init()` otherwise.
+pub fn user_isolate_init(flags:&DenoFlags) -> StartupData<'static> {
+	match flags.snapshot_path.as_ref().and_then(|path| load_snapshot(Path::new(path))) {
+		Some(data) => StartupData::Snapshot(data),
+		None => deno_isolate_init(),
+	}
+}
+
+/// Reads a snapshot once per process. V8 needs the data for as long as any
+/// isolate made from it is alive, so it is never freed.
+fn load_snapshot(path:&Path) -> Option<&'static [u8]> {
+	lazy_static! {
+		static ref SNAPSHOTS:Mutex<HashMap<PathBuf, &'static [u8]>> = Mutex::new(HashMap::new());
+	}
+	let mut snapshots = SNAPSHOTS.lock().unwrap();
+	if let Some(data) = snapshots.get(path) {
+		return Some(data);
+	}
+	match fs::read(path) {
+		Ok(data) => {
+			debug!("Deno isolate init with snapshot {}.", path.display());
+			let data:&'static [u8] = Box::leak(data.into_boxed_slice());
+			snapshots.insert(path.to_owned(), data);
+			Some(data)
+		},
+		Err(err) => {
+			debug!("Snapshot {} not loaded: {}", path.display(), err);
+			None
+		},
+	}
+}
+
 #[cfg(feature = "no-snapshot-init")]
 pub fn compiler_isolate_init() -> StartupData<'static> {
 	debug!("Compiler isolate init without snapshots.");
@@ -48,3 +91,46 @@ pub fn compiler_isolate_init() -> StartupData<'static> {
 
 	StartupData::Snapshot(data)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn user_isolate_init_from_file() {
+		let dir = tempfile::TempDir::new().unwrap();
+		let path = dir.path().join("snapshot.bin");
+		fs::write(&path, CLI_SNAPSHOT).unwrap();
+		let flags = DenoFlags {
+			snapshot_path:Some(path.to_str().unwrap().to_string()),
+			..DenoFlags::default()
+		};
+		let first = match user_isolate_init(&flags) {
+			StartupData::Snapshot(data) => data,
+			_ => panic!("expected a snapshot"),
+		};
+		assert_eq!(first, CLI_SNAPSHOT);
+		// Loaded once, later workers share the data.
+		match user_isolate_init(&flags) {
+			StartupData::Snapshot(data) => assert_eq!(data.as_ptr(), first.as_ptr()),
+			_ => panic!("expected a snapshot"),
+		}
+	}
+
+	#[test]
+	fn user_isolate_init_missing_file() {
+		let flags = DenoFlags {
+			snapshot_path:Some("/nonexistent/snapshot.bin".to_string()),
+			..DenoFlags::default()
+		};
+		assert!(load_snapshot(Path::new("/nonexistent/snapshot.bin")).is_none());
+		// Falls back to the built-in startup data.
+		match (user_isolate_init(&flags), deno_isolate_init()) {
+			(StartupData::Snapshot(a), StartupData::Snapshot(b)) => {
+				assert_eq!(a.as_ptr(), b.as_ptr())
+			},
+			(StartupData::Script(_), StartupData::Script(_)) => {},
+			_ => panic!("expected the built-in startup data"),
+		}
+	}
+}
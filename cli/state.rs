@@ -2,7 +2,10 @@
 use std::{
 	self,
 	collections::HashMap,
+	future::Future,
+	net::SocketAddr,
 	ops::Deref,
+	path::PathBuf,
 	pin::Pin,
 	str,
 	sync::{
@@ -14,22 +17,38 @@ use std::{
 	time::Instant,
 };
 
-use deno::{Buf, CoreOp, ErrBox, Loader, ModuleSpecifier, Op, PinnedBuf, ResourceTable};
+use deno::{
+	Buf,
+	CoreOp,
+	ErrBox,
+	Isolate,
+	Loader,
+	ModuleSpecifier,
+	Op,
+	OpId,
+	PinnedBuf,
+	ResourceTable,
+};
 use futures::{
 	channel::mpsc,
 	future::{FutureExt, TryFutureExt},
+	task::SpawnExt,
 };
-use rand::{rngs::StdRng, SeedableRng};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
 use serde_json::Value;
 
 use crate::{
-	deno_error::permission_denied,
+	deno_error::{permission_denied, too_many_pending_ops, DenoError, ErrorKind},
+	file_fetcher::SchemeLoader,
+	fs as deno_fs,
 	global_state::ThreadSafeGlobalState,
 	global_timer::GlobalTimer,
 	import_map::ImportMap,
 	metrics::Metrics,
 	ops::{JsonOp, MinimalOp},
-	permissions::DenoPermissions,
+	permissions::{DenoPermissions, PermissionPrompt, WorkerPermissions},
+	progress::ProgressEvent,
 	worker::{Worker, WorkerChannels},
 };
 
@@ -49,13 +68,26 @@ pub struct State {
 	/// import map file will be resolved and set.
 	pub import_map:Option<ImportMap>,
 	pub metrics:Metrics,
+	/// JavaScript source of modules that exist only in memory, by specifier.
+	/// Loading them doesn't touch the disk or network.
+	pub virtual_modules:Mutex<HashMap<String, String>>,
 	pub global_timer:Mutex<GlobalTimer>,
 	pub workers:Mutex<HashMap<u32, Worker>>,
 	pub next_worker_id:AtomicUsize,
 	pub start_time:Instant,
-	pub seeded_rng:Option<Mutex<StdRng>>,
+	/// Set by `--seed`. ChaCha20 rather than `StdRng`, whose algorithm may change
+	/// between rand releases, so a seed keeps producing the same stream.
+	pub seeded_rng:Option<Mutex<ChaCha20Rng>>,
 	pub include_deno_namespace:bool,
 	pub resource_table:Mutex<ResourceTable>,
+	/// Async ops dispatched but not completed yet.
+	pending_ops:AtomicUsize,
+	/// Set by `--max-pending-ops`.
+	max_pending_ops:Option<usize>,
+	/// Directory relative paths are resolved against. Starts out as the
+	/// process' working directory and is changed by `Deno.chdir()` without
+	/// affecting other workers.
+	cwd:Mutex<PathBuf>,
 }
 
 impl Clone for ThreadSafeState {
@@ -73,15 +105,19 @@ impl ThreadSafeState {
 		self.resource_table.lock().unwrap()
 	}
 
-	/// Wrap core `OpDispatcher` to collect metrics.
-	pub fn core_op<D>(&self, dispatcher:D) -> impl Fn(&[u8], Option<PinnedBuf>) -> CoreOp
+	/// Wrap core `OpDispatcher` to collect metrics. With `--profile-ops` the
+	/// latency of each call is also recorded under `name`.
+	pub fn core_op<D>(&self, name:&str, dispatcher:D) -> impl Fn(&[u8], Option<PinnedBuf>) -> CoreOp
 	where
 		D: Fn(&[u8], Option<PinnedBuf>) -> CoreOp, {
 		let state = self.clone();
+		let profile_name:Option<Arc<str>> =
+			if state.global_state.flags.profile_ops { Some(name.into()) } else { None };
 
 		move |control:&[u8], zero_copy:Option<PinnedBuf>| -> CoreOp {
 			let bytes_sent_control = control.len();
 			let bytes_sent_zero_copy = zero_copy.as_ref().map(|b| b.len()).unwrap_or(0);
+			let timer = profile_name.clone().map(|name| (name, Instant::now()));
 
 			let op = dispatcher(control, zero_copy);
 			state.metrics_op_dispatched(bytes_sent_control, bytes_sent_zero_copy);
@@ -89,13 +125,24 @@ impl ThreadSafeState {
 			match op {
 				Op::Sync(buf) => {
 					state.metrics_op_completed(buf.len());
+					if let Some((name, start)) = timer {
+						state.metrics.record_op_latency(&name, start.elapsed());
+					}
 					Op::Sync(buf)
 				},
 				Op::Async(fut) => {
 					let state = state.clone();
-					let result_fut = fut.map_ok(move |buf:Buf| {
-						state.clone().metrics_op_completed(buf.len());
-						buf
+					let pending = PendingOp::new(&state);
+					let result_fut = fut.map(move |result| {
+						drop(pending);
+						// Failed ops are timed too.
+						if let Some((name, start)) = timer {
+							state.metrics.record_op_latency(&name, start.elapsed());
+						}
+						result.map(|buf:Buf| {
+							state.metrics_op_completed(buf.len());
+							buf
+						})
 					});
 					Op::Async(result_fut.boxed())
 				},
@@ -103,6 +150,13 @@ impl ThreadSafeState {
 		}
 	}
 
+	/// Registers `dispatcher` as op `name`, wrapped with `core_op()`.
+	pub fn register_op<D>(&self, i:&Isolate, name:&str, dispatcher:D) -> OpId
+	where
+		D: Fn(&[u8], Option<PinnedBuf>) -> CoreOp + Send + Sync + 'static, {
+		i.register_op(name, self.core_op(name, dispatcher))
+	}
+
 	/// This is a special function that provides `state` argument to dispatcher.
 	pub fn stateful_minimal_op<D>(
 		&self,
@@ -131,9 +185,41 @@ impl ThreadSafeState {
 		let state = self.clone();
 
 		move |args:Value, zero_copy:Option<PinnedBuf>| -> Result<JsonOp, ErrBox> {
+			// Rejected before dispatching, so that the op has no side effects yet
+			// and JS can safely retry it.
+			if args.get("promiseId").is_some() {
+				state.check_pending_ops()?;
+			}
 			dispatcher(&state, args, zero_copy)
 		}
 	}
+
+	/// Number of async ops that have been dispatched and haven't completed.
+	pub fn pending_ops(&self) -> usize { self.pending_ops.load(Ordering::SeqCst) }
+
+	/// Fails with `ErrorKind::TooManyPendingOps` once `--max-pending-ops` async
+	/// ops are in flight. Only JSON ops are limited; reads and writes operate
+	/// on resources that are themselves bounded by the ops creating them.
+	pub fn check_pending_ops(&self) -> Result<(), ErrBox> {
+		match self.max_pending_ops {
+			Some(max) if self.pending_ops() >= max => Err(too_many_pending_ops()),
+			_ => Ok(()),
+		}
+	}
+}
+
+/// Counts an async op as pending until it completes or its future is dropped.
+struct PendingOp(ThreadSafeState);
+
+impl PendingOp {
+	fn new(state:&ThreadSafeState) -> Self {
+		state.pending_ops.fetch_add(1, Ordering::SeqCst);
+		PendingOp(state.clone())
+	}
+}
+
+impl Drop for PendingOp {
+	fn drop(&mut self) { self.0.pending_ops.fetch_sub(1, Ordering::SeqCst); }
 }
 
 impl Loader for ThreadSafeState {
@@ -144,15 +230,21 @@ impl Loader for ThreadSafeState {
 		is_main:bool,
 		is_dyn_import:bool,
 	) -> Result<ModuleSpecifier, ErrBox> {
-		if !is_main {
-			if let Some(import_map) = &self.import_map {
-				let result = import_map.resolve(specifier, referrer)?;
-				if let Some(r) = result {
-					return Ok(r);
+		let mapped = match &self.import_map {
+			Some(import_map) if !is_main => import_map.resolve(specifier, referrer)?,
+			_ => None,
+		};
+		// Mapped specifiers go through the same permission check: an import map
+		// must not be a way around --allow-read/--allow-net for dynamic imports.
+		let module_specifier = match mapped {
+			Some(module_specifier) => module_specifier,
+			None => {
+				match self.resolve_with_scheme_loader(specifier, referrer) {
+					Some(result) => result?,
+					None => ModuleSpecifier::resolve_import(specifier, referrer)?,
 				}
-			}
-		}
-		let module_specifier = ModuleSpecifier::resolve_import(specifier, referrer)?;
+			},
+		};
 
 		if is_dyn_import {
 			self.check_dyn_import(&module_specifier)?;
@@ -167,8 +259,20 @@ impl Loader for ThreadSafeState {
 		module_specifier:&ModuleSpecifier,
 		maybe_referrer:Option<ModuleSpecifier>,
 	) -> Pin<Box<deno::SourceCodeInfoFuture>> {
-		self.metrics.resolve_count.fetch_add(1, Ordering::SeqCst);
 		let module_url_specified = module_specifier.to_string();
+		if self.metrics.record_resolve(&module_url_specified) {
+			self.global_state
+				.progress
+				.emit(ProgressEvent::ModuleResolved { url:module_url_specified.clone() });
+		}
+		if let Some(code) = self.virtual_modules.lock().unwrap().get(&module_url_specified) {
+			return futures::future::ok(deno::SourceCodeInfo {
+				code:code.clone(),
+				module_url_found:module_url_specified.clone(),
+				module_url_specified,
+			})
+			.boxed();
+		}
 		let fut = self
 			.global_state
 			.fetch_compiled_module(module_specifier, maybe_referrer)
@@ -209,10 +313,11 @@ impl ThreadSafeState {
 		};
 
 		let seeded_rng = match global_state.flags.seed {
-			Some(seed) => Some(Mutex::new(StdRng::seed_from_u64(seed))),
+			Some(seed) => Some(Mutex::new(ChaCha20Rng::seed_from_u64(seed))),
 			None => None,
 		};
 
+		let max_pending_ops = global_state.flags.max_pending_ops;
 		let modules = Arc::new(Mutex::new(deno::Modules::new()));
 		let permissions = if let Some(perm) = shared_permissions {
 			perm
@@ -228,6 +333,7 @@ impl ThreadSafeState {
 			import_map,
 			worker_channels:Mutex::new(internal_channels),
 			metrics:Metrics::default(),
+			virtual_modules:Mutex::new(HashMap::new()),
 			global_timer:Mutex::new(GlobalTimer::new()),
 			workers:Mutex::new(HashMap::new()),
 			next_worker_id:AtomicUsize::new(0),
@@ -235,11 +341,44 @@ impl ThreadSafeState {
 			seeded_rng,
 			include_deno_namespace,
 			resource_table:Mutex::new(ResourceTable::default()),
+			pending_ops:AtomicUsize::new(0),
+			max_pending_ops,
+			cwd:Mutex::new(std::env::current_dir()?),
 		};
 
 		Ok(ThreadSafeState(Arc::new(state)))
 	}
 
+	/// The working directory of this worker.
+	pub fn cwd(&self) -> PathBuf { self.cwd.lock().unwrap().clone() }
+
+	/// Changes the working directory of this worker. `dir` may be relative to
+	/// the current one and must exist. Symlinks are resolved, as they are by
+	/// `std::env::set_current_dir()`, except on Windows where that would give
+	/// a `\\?\` path.
+	pub fn set_cwd(&self, dir:&str) -> Result<(), ErrBox> {
+		let (path, path_) = self.resolve_path(dir)?;
+		if !std::fs::metadata(&path)?.is_dir() {
+			let message = format!("Not a directory: \"{}\"", path_);
+			return Err(DenoError::new(ErrorKind::InvalidInput, message).into());
+		}
+		let path = if cfg!(windows) { path } else { std::fs::canonicalize(path)? };
+		*self.cwd.lock().unwrap() = path;
+		Ok(())
+	}
+
+	/// Takes over the working directory of `parent`, which is already resolved
+	/// and need not be valid UTF-8.
+	pub fn inherit_cwd(&self, parent:&ThreadSafeState) {
+		*self.cwd.lock().unwrap() = parent.cwd();
+	}
+
+	/// Resolves `path` against the working directory of this worker, see
+	/// `fs::resolve_from()`.
+	pub fn resolve_path(&self, path:&str) -> Result<(PathBuf, String), ErrBox> {
+		deno_fs::resolve_from(&self.cwd.lock().unwrap(), path)
+	}
+
 	pub fn add_child_worker(&self, worker:Worker) -> u32 {
 		let worker_id = self.next_worker_id.fetch_add(1, Ordering::Relaxed) as u32;
 		let mut workers_tl = self.workers.lock().unwrap();
@@ -257,6 +396,11 @@ impl ThreadSafeState {
 		self.permissions.lock().unwrap().check_write(filename)
 	}
 
+	#[inline]
+	pub fn check_umask(&self) -> Result<(), ErrBox> {
+		self.permissions.lock().unwrap().check_umask()
+	}
+
 	#[inline]
 	pub fn check_env(&self) -> Result<(), ErrBox> { self.permissions.lock().unwrap().check_env() }
 
@@ -265,6 +409,11 @@ impl ThreadSafeState {
 		self.permissions.lock().unwrap().check_net(hostname, port)
 	}
 
+	#[inline]
+	pub fn check_net_host(&self, hostname:&str) -> Result<(), ErrBox> {
+		self.permissions.lock().unwrap().check_net_host(hostname)
+	}
+
 	#[inline]
 	pub fn check_net_url(&self, url:&url::Url) -> Result<(), ErrBox> {
 		self.permissions.lock().unwrap().check_net_url(url)
@@ -278,6 +427,55 @@ impl ThreadSafeState {
 		self.permissions.lock().unwrap().check_plugin(filename)
 	}
 
+	#[inline]
+	pub fn check_ping(&self, hostname:&str) -> Result<(), ErrBox> {
+		self.permissions.lock().unwrap().check_ping(hostname)
+	}
+
+	/// Resolves the target of an outbound connection with the global
+	/// `Resolver`, checking it against `--net-allowlist` before resolving and
+	/// checking the resolved address after. The lookup may block, so like the
+	/// blocking ops it runs on a thread pool, see `blocking_json()`.
+	pub fn resolve_net_target(
+		&self,
+		hostname:&str,
+		port:u16,
+	) -> Result<impl Future<Output = Result<SocketAddr, ErrBox>>, ErrBox> {
+		if let Some(allowlist) = &self.global_state.net_allowlist {
+			allowlist.check_host(hostname, port)?;
+		}
+		let global_state = self.global_state.clone();
+		let hostname = hostname.to_string();
+		let resolve = futures::future::lazy(move |_cx| {
+			let addr = global_state.resolver.resolve(&hostname, port)?;
+			if let Some(allowlist) = &global_state.net_allowlist {
+				allowlist.check_addr(&hostname, &addr)?;
+			}
+			Ok(addr)
+		});
+		let pool = futures::executor::ThreadPool::new().unwrap();
+		Ok(pool.spawn_with_handle(resolve).unwrap())
+	}
+
+	/// Answers permission requests with `prompt` instead of asking on the
+	/// terminal. Workers sharing these permissions use it too.
+	pub fn set_permission_prompt(&self, prompt:PermissionPrompt) {
+		self.permissions.lock().unwrap().prompt = Some(prompt);
+	}
+
+	/// Permissions for a worker created by this one. Without `keep` they are
+	/// shared, so grants in either apply to both. With it the worker gets its
+	/// own copy, reduced to the permissions named in `keep`.
+	pub fn child_permissions(
+		&self,
+		keep:Option<&WorkerPermissions>,
+	) -> Arc<Mutex<DenoPermissions>> {
+		match keep {
+			Some(keep) => Arc::new(Mutex::new(self.permissions.lock().unwrap().restrict(keep))),
+			None => self.permissions.clone(),
+		}
+	}
+
 	pub fn check_dyn_import(self: &Self, module_specifier:&ModuleSpecifier) -> Result<(), ErrBox> {
 		let u = module_specifier.as_url();
 		match u.scheme() {
@@ -290,10 +488,32 @@ impl ThreadSafeState {
 				self.check_read(&filename)?;
 				Ok(())
 			},
+			// Embedders that register a loader for a scheme decide what it serves.
+			scheme if self.global_state.file_fetcher.scheme_loader(scheme).is_some() => Ok(()),
 			_ => Err(permission_denied()),
 		}
 	}
 
+	/// Serves modules with URLs of `scheme` from `loader`, for every worker
+	/// sharing this state's global state. See `SchemeLoader`.
+	pub fn register_scheme_loader(
+		&self,
+		scheme:&str,
+		loader:Arc<dyn SchemeLoader>,
+	) -> Result<(), ErrBox> {
+		self.global_state.file_fetcher.register_scheme_loader(scheme, loader)
+	}
+
+	fn resolve_with_scheme_loader(
+		&self,
+		specifier:&str,
+		referrer:&str,
+	) -> Option<Result<ModuleSpecifier, ErrBox>> {
+		let referrer = ModuleSpecifier::resolve_url(referrer).ok()?;
+		let loader = self.global_state.file_fetcher.scheme_loader(referrer.as_url().scheme())?;
+		loader.resolve(specifier, &referrer)
+	}
+
 	#[cfg(test)]
 	pub fn mock(argv:Vec<String>, internal_channels:WorkerChannels) -> ThreadSafeState {
 		let module_specifier = if argv.is_empty() {
@@ -335,3 +555,150 @@ fn thread_safe() {
 		int,
 	));
 }
+
+#[test]
+fn seeded_rng_is_deterministic() {
+	use rand::Rng;
+
+	use crate::{flags::DenoFlags, progress::Progress};
+
+	let random_bytes = |seed:u64| {
+		let flags = DenoFlags { seed:Some(seed), ..DenoFlags::default() };
+		let global_state = ThreadSafeGlobalState::new(flags, Progress::new()).unwrap();
+		let (int, _) = ThreadSafeState::create_channels();
+		let state = ThreadSafeState::new(global_state, None, None, true, int).unwrap();
+		let mut bytes = [0u8; 64];
+		state.seeded_rng.as_ref().unwrap().lock().unwrap().fill(&mut bytes[..]);
+		bytes.to_vec()
+	};
+	assert_eq!(random_bytes(42), random_bytes(42));
+	assert_ne!(random_bytes(42), random_bytes(43));
+}
+
+#[test]
+fn resolve_with_import_map() {
+	use tempfile::TempDir;
+
+	use crate::{flags::DenoFlags, progress::Progress};
+
+	let temp_dir = TempDir::new().unwrap();
+	let import_map_path = temp_dir.path().join("import_map.json");
+	std::fs::write(
+		&import_map_path,
+		r#"{
+      "imports": {
+        "lodash": "./vendor/lodash.js",
+        "std/": "./vendor/std/"
+      },
+      "scopes": {
+        "./vendor/": { "lodash": "./vendor/lodash-es.js" }
+      }
+    }"#,
+	)
+	.unwrap();
+	let flags = DenoFlags {
+		import_map_path:Some(import_map_path.to_string_lossy().to_string()),
+		..DenoFlags::default()
+	};
+	let global_state = ThreadSafeGlobalState::new(flags, Progress::new()).unwrap();
+	let (int, _) = ThreadSafeState::create_channels();
+	let state = ThreadSafeState::new(global_state, None, None, true, int).unwrap();
+
+	let base = ModuleSpecifier::resolve_url_or_path(&temp_dir.path().to_string_lossy()).unwrap();
+	let referrer = format!("{}/main.ts", base);
+	let resolve = |specifier:&str, referrer:&str| {
+		state.resolve(specifier, referrer, false, false).unwrap().to_string()
+	};
+	assert_eq!(resolve("lodash", &referrer), format!("{}/vendor/lodash.js", base));
+	assert_eq!(resolve("std/fs/mod.ts", &referrer), format!("{}/vendor/std/fs/mod.ts", base));
+	let vendored = format!("{}/vendor/app.js", base);
+	assert_eq!(resolve("lodash", &vendored), format!("{}/vendor/lodash-es.js", base));
+}
+
+#[test]
+fn max_pending_ops() {
+	use crate::{deno_error::GetErrorKind, flags::DenoFlags, msg::ErrorKind, progress::Progress};
+
+	let flags = DenoFlags { max_pending_ops:Some(1), ..DenoFlags::default() };
+	let global_state = ThreadSafeGlobalState::new(flags, Progress::new()).unwrap();
+	let (int, _) = ThreadSafeState::create_channels();
+	let state = ThreadSafeState::new(global_state, None, None, true, int).unwrap();
+
+	assert!(state.check_pending_ops().is_ok());
+	let pending = PendingOp::new(&state);
+	assert_eq!(state.pending_ops(), 1);
+	assert_eq!(state.check_pending_ops().unwrap_err().kind(), ErrorKind::TooManyPendingOps);
+	drop(pending);
+	assert_eq!(state.pending_ops(), 0);
+	assert!(state.check_pending_ops().is_ok());
+}
+
+#[test]
+fn profile_ops() {
+	use crate::{flags::DenoFlags, progress::Progress};
+
+	let dispatch = |_control:&[u8], _zero_copy:Option<PinnedBuf>| -> CoreOp {
+		Op::Sync(vec![0u8; 4].into_boxed_slice())
+	};
+	for &profile_ops in &[false, true] {
+		let flags = DenoFlags { profile_ops, ..DenoFlags::default() };
+		let global_state = ThreadSafeGlobalState::new(flags, Progress::new()).unwrap();
+		let (int, _) = ThreadSafeState::create_channels();
+		let state = ThreadSafeState::new(global_state, None, None, true, int).unwrap();
+		let op = state.core_op("test_op", dispatch);
+		op(b"", None);
+		op(b"", None);
+
+		let snapshot = state.metrics.snapshot();
+		assert_eq!(snapshot.ops_completed, 2);
+		if profile_ops {
+			assert_eq!(snapshot.op_latencies["test_op"].count, 2);
+		} else {
+			assert!(snapshot.op_latencies.is_empty());
+		}
+	}
+}
+
+#[test]
+fn profile_failed_async_op() {
+	use crate::{flags::DenoFlags, progress::Progress};
+
+	let dispatch = |_control:&[u8], _zero_copy:Option<PinnedBuf>| -> CoreOp {
+		Op::Async(futures::future::err(()).boxed())
+	};
+	let flags = DenoFlags { profile_ops:true, ..DenoFlags::default() };
+	let global_state = ThreadSafeGlobalState::new(flags, Progress::new()).unwrap();
+	let (int, _) = ThreadSafeState::create_channels();
+	let state = ThreadSafeState::new(global_state, None, None, true, int).unwrap();
+	let op = state.core_op("test_op", dispatch);
+	match op(b"", None) {
+		Op::Async(fut) => assert!(futures::executor::block_on(fut).is_err()),
+		Op::Sync(_) => unreachable!(),
+	}
+
+	let snapshot = state.metrics.snapshot();
+	assert_eq!(snapshot.ops_completed, 0);
+	assert_eq!(snapshot.op_latencies["test_op"].count, 1);
+}
+
+#[test]
+fn child_permissions() {
+	use crate::{flags::DenoFlags, progress::Progress};
+
+	let flags = DenoFlags { allow_read:true, allow_net:true, ..DenoFlags::default() };
+	let global_state = ThreadSafeGlobalState::new(flags, Progress::new()).unwrap();
+	let (int, _) = ThreadSafeState::create_channels();
+	let parent = ThreadSafeState::new(global_state.clone(), None, None, true, int).unwrap();
+	let keep = WorkerPermissions { net:true, ..WorkerPermissions::default() };
+	let (int, _) = ThreadSafeState::create_channels();
+	let permissions = parent.child_permissions(Some(&keep));
+	let child = ThreadSafeState::new(global_state, Some(permissions), None, true, int).unwrap();
+
+	assert!(child.check_net("deno.land", 443).is_ok());
+	assert!(child.check_read("/etc/passwd").is_err());
+	assert!(parent.check_read("/etc/passwd").is_ok());
+	// Revoking in the child leaves the parent alone.
+	child.permissions.lock().unwrap().revoke("net", &None, &None).unwrap();
+	assert!(child.check_net("deno.land", 443).is_err());
+	assert!(parent.check_net("deno.land", 443).is_ok());
+}
@@ -0,0 +1,38 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+use std::{
+	env::consts::{DLL_PREFIX, DLL_SUFFIX},
+	process::Command,
+};
+
+use deno_cli::test_util::*;
+use tempfile::TempDir;
+
+/// test_plugin_abi is a workspace member, so it is built next to deno. It
+/// reports another ABI version, and its init function panics if called.
+#[test]
+fn open_plugin_wrong_abi_version() {
+	let plugin = target_dir().join(format!("{}test_plugin_abi{}", DLL_PREFIX, DLL_SUFFIX));
+	let temp_dir = TempDir::new().unwrap();
+	let script = temp_dir.path().join("open_plugin.js");
+	std::fs::write(
+		&script,
+		r#"
+			try {
+				Deno.openPlugin(Deno.args[Deno.args.length - 1]);
+			} catch (e) {
+				console.log(Deno.ErrorKind[e.kind], e.message.includes("ABI version"));
+			}
+		"#,
+	)
+	.unwrap();
+	let output = Command::new(deno_exe_path())
+		.current_dir(root_path())
+		.arg("run")
+		.arg("--allow-plugin")
+		.arg(&script)
+		.arg(&plugin)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	assert_eq!(String::from_utf8(output.stdout).unwrap(), "InvalidData true\n");
+}
@@ -0,0 +1,149 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+//! `deno fetch --vendor=DIR`: copies the remote modules of a module graph into
+//! a directory that can be committed to source control, along with a lockfile
+//! mapping each module specifier to its file.
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fs,
+	path::Path,
+};
+
+use deno::{Deps, ErrBox, ModuleSpecifier};
+
+use crate::{
+	deno_error::{DenoError, ErrorKind},
+	disk_cache::DiskCache,
+	state::ThreadSafeState,
+};
+
+/// Written to the top of the vendor directory.
+pub const LOCK_FILENAME:&str = "vendor.lock.json";
+
+fn collect(deps:&Deps, names:&mut BTreeSet<String>) {
+	names.insert(deps.name.clone());
+	for dep in deps.deps.iter().flatten() {
+		collect(dep, names);
+	}
+}
+
+/// Writes the remote modules `main_module` imports to `dir`, laid out like the
+/// deps cache, e.g. `https/deno.land/std/fmt/colors.ts`. Local modules are
+/// already part of the project and are left out. The graph must have been
+/// loaded into `state` before, see `Worker::execute_mod_async()`.
+///
+/// The lockfile is sorted and files are overwritten, so vendoring the same
+/// graph again produces the same directory. Returns the lockfile's entries.
+pub fn vendor(
+	state:&ThreadSafeState,
+	main_module:&ModuleSpecifier,
+	dir:&Path,
+) -> Result<BTreeMap<String, String>, ErrBox> {
+	let mut names = BTreeSet::new();
+	{
+		let modules = state.modules.lock().unwrap();
+		let deps = modules.deps(main_module.as_str()).ok_or_else(|| -> ErrBox {
+			DenoError::new(
+				ErrorKind::NotFound,
+				format!("Module graph of \"{}\" is not loaded", main_module),
+			)
+			.into()
+		})?;
+		collect(&deps, &mut names);
+	}
+
+	let out = DiskCache::new(dir);
+	let mut lock = BTreeMap::new();
+	for name in names {
+		let specifier = ModuleSpecifier::resolve_url(&name)?;
+		if specifier.as_url().scheme() == "file" {
+			continue;
+		}
+		let not_cached = || -> ErrBox {
+			DenoError::new(ErrorKind::NotFound, format!("Module \"{}\" is not cached", name)).into()
+		};
+		let file_fetcher = &state.global_state.file_fetcher;
+		let source_file = file_fetcher.fetch_cached_source_file(&specifier).ok_or_else(not_cached)?;
+		let filename = out.get_cache_filename(specifier.as_url());
+		out.set(&filename, &source_file.source_code)?;
+		// Forward slashes, so the lockfile is the same on every platform.
+		lock.insert(name, filename.to_string_lossy().replace('\\', "/"));
+	}
+
+	let json = serde_json::to_string_pretty(&lock)?;
+	fs::write(dir.join(LOCK_FILENAME), json)?;
+	Ok(lock)
+}
+
+#[cfg(test)]
+mod tests {
+	use deno::StartupData;
+	use tempfile::TempDir;
+
+	use super::*;
+	use crate::{
+		flags,
+		global_state::ThreadSafeGlobalState,
+		progress::Progress,
+		tokio_util,
+		worker::Worker,
+	};
+
+	fn load_graph(path:&str) -> (ThreadSafeState, ModuleSpecifier) {
+		let p = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().unwrap().join(path);
+		let module_specifier = ModuleSpecifier::resolve_url_or_path(&p.to_string_lossy()).unwrap();
+		let global_state = ThreadSafeGlobalState::new(
+			flags::DenoFlags {
+				argv:vec![String::from("./deno"), module_specifier.to_string()],
+				..flags::DenoFlags::default()
+			},
+			Progress::new(),
+		)
+		.unwrap();
+		let (int, ext) = ThreadSafeState::create_channels();
+		let state =
+			ThreadSafeState::new(global_state, None, Some(module_specifier.clone()), true, int)
+				.unwrap();
+		let state_ = state.clone();
+		let module_specifier_ = module_specifier.clone();
+		tokio_util::run(async move {
+			let mut worker = Worker::new("TEST".to_string(), StartupData::None, state, ext);
+			worker.execute_mod_async(&module_specifier_, None, true).await.unwrap();
+			Ok(())
+		});
+		(state_, module_specifier)
+	}
+
+	#[test]
+	fn vendor_local_graph() {
+		let (state, module_specifier) = load_graph("tests/esm_imports_a.js");
+		let temp_dir = TempDir::new().unwrap();
+		let lock = vendor(&state, &module_specifier, temp_dir.path()).unwrap();
+		assert!(lock.is_empty());
+		assert!(!temp_dir.path().join("file").exists());
+	}
+
+	#[test]
+	fn vendor_url_imports() {
+		let http_server_guard = crate::test_util::http_server();
+		let (state, module_specifier) = load_graph("tests/006_url_imports.ts");
+		let temp_dir = TempDir::new().unwrap();
+		let lock = vendor(&state, &module_specifier, temp_dir.path()).unwrap();
+		assert_eq!(
+			lock.values().collect::<Vec<_>>(),
+			vec![
+				"http/localhost_PORT4545/tests/subdir/mod2.ts",
+				"http/localhost_PORT4545/tests/subdir/print_hello.ts",
+			]
+		);
+		for path in lock.values() {
+			assert!(temp_dir.path().join(path).is_file());
+		}
+		let lockfile = fs::read_to_string(temp_dir.path().join(LOCK_FILENAME)).unwrap();
+		assert!(!lockfile.contains("006_url_imports.ts"));
+
+		// Vendoring again gives the same result.
+		assert_eq!(vendor(&state, &module_specifier, temp_dir.path()).unwrap(), lock);
+		assert_eq!(fs::read_to_string(temp_dir.path().join(LOCK_FILENAME)).unwrap(), lockfile);
+		drop(http_server_guard);
+	}
+}
@@ -1,22 +1,47 @@
 // Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
 use std::{
-	env,
 	future::Future,
 	pin::Pin,
-	sync::{Arc, Mutex},
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		mpsc as std_mpsc,
+		Arc,
+		Mutex,
+	},
 	task::{Context, Poll},
+	thread,
+	time::{Duration, Instant},
 };
 
-use deno::{self, Buf, ErrBox, ModuleSpecifier, RecursiveLoad, StartupData};
+use deno::{
+	self,
+	Buf,
+	CoreOp,
+	ErrBox,
+	IsolateHandle,
+	ModuleSpecifier,
+	OpId,
+	PinnedBuf,
+	RecursiveLoad,
+	StartupData,
+};
 use futures::{
 	channel::mpsc,
-	future::{FutureExt, TryFutureExt},
+	compat::Compat01As03,
+	future::{Either, FutureExt, TryFutureExt},
 	sink::SinkExt,
-	stream::StreamExt,
+	stream::{Stream, StreamExt},
+	task::AtomicWaker,
 };
+use tokio::timer::Delay;
 use url::Url;
 
-use crate::{fmt_errors::JSError, ops, state::ThreadSafeState};
+use crate::{
+	deno_error::{heap_limit_exceeded, worker_timeout},
+	fmt_errors::JSError,
+	ops,
+	state::ThreadSafeState,
+};
 
 /// Wraps mpsc channels so they can be referenced
 /// from ops and used to facilitate parent-child communication
@@ -26,45 +51,153 @@ pub struct WorkerChannels {
 	pub receiver:mpsc::Receiver<Buf>,
 }
 
+impl WorkerChannels {
+	/// Closes both ends and discards any messages that are still buffered.
+	pub fn close_and_drain(&mut self) {
+		self.sender.close_channel();
+		self.receiver.close();
+		while let Ok(Some(_)) = self.receiver.try_next() {}
+	}
+}
+
+/// How long `Worker::close()` waits for a worker to finish by default.
+const DEFAULT_CLOSE_TIMEOUT:Duration = Duration::from_secs(5);
+
 /// Wraps deno::Isolate to provide source maps, ops for the CLI, and
 /// high-level module loading.
+///
+/// Dropping the last handle to a worker tears it down on the spot: messages
+/// that are still buffered are lost and in-flight ops are abandoned. Use
+/// `close()` to let the worker finish first.
 #[derive(Clone)]
 pub struct Worker {
 	pub name:String,
 	isolate:Arc<Mutex<deno::Isolate>>,
+	isolate_handle:IsolateHandle,
+	terminated:Arc<AtomicBool>,
+	waker:Arc<AtomicWaker>,
+	execution_timeout:Option<Duration>,
+	close_timeout:Duration,
 	pub state:ThreadSafeState,
 	external_channels:Arc<Mutex<WorkerChannels>>,
 }
 
-impl Worker {
-	pub fn new(
-		name:String,
-		startup_data:StartupData,
-		state:ThreadSafeState,
-		external_channels:WorkerChannels,
-	) -> Self {
-		let isolate = Arc::new(Mutex::new(deno::Isolate::new(startup_data, false)));
+/// Groups of ops that a worker can expose to JavaScript.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OpGroup {
+	Compiler,
+	Compression,
+	Crypto,
+	Encoding,
+	Errors,
+	Fetch,
+	Files,
+	Fs,
+	Io,
+	Plugins,
+	Net,
+	Tls,
+	Os,
+	Permissions,
+	Process,
+	Random,
+	Repl,
+	Resources,
+	Timers,
+	Workers,
+}
+
+/// Every op group, in registration order. This is what `Worker::new` uses.
+pub const ALL_OP_GROUPS:&[OpGroup] = &[
+	OpGroup::Compiler,
+	OpGroup::Compression,
+	OpGroup::Crypto,
+	OpGroup::Encoding,
+	OpGroup::Errors,
+	OpGroup::Fetch,
+	OpGroup::Files,
+	OpGroup::Fs,
+	OpGroup::Io,
+	OpGroup::Plugins,
+	OpGroup::Net,
+	OpGroup::Tls,
+	OpGroup::Os,
+	OpGroup::Permissions,
+	OpGroup::Process,
+	OpGroup::Random,
+	OpGroup::Repl,
+	OpGroup::Resources,
+	OpGroup::Timers,
+	OpGroup::Workers,
+];
+
+/// Configures and creates a `Worker`. By default all op groups are
+/// registered, which matches `Worker::new`.
+pub struct WorkerBuilder<'a> {
+	name:String,
+	startup_data:StartupData<'a>,
+	state:ThreadSafeState,
+	external_channels:WorkerChannels,
+	op_groups:Vec<OpGroup>,
+	max_heap_mb:Option<usize>,
+}
+
+impl<'a> WorkerBuilder<'a> {
+	pub fn new(state:ThreadSafeState, external_channels:WorkerChannels) -> Self {
+		let max_heap_mb = state.global_state.flags.max_heap_mb;
+		Self {
+			name:"worker".to_string(),
+			startup_data:StartupData::None,
+			state,
+			external_channels,
+			op_groups:ALL_OP_GROUPS.to_vec(),
+			max_heap_mb,
+		}
+	}
+
+	pub fn name<S:Into<String>>(mut self, name:S) -> Self {
+		self.name = name.into();
+		self
+	}
+
+	pub fn startup_data(mut self, startup_data:StartupData<'a>) -> Self {
+		self.startup_data = startup_data;
+		self
+	}
+
+	/// Registers only the given op groups.
+	pub fn ops(mut self, op_groups:&[OpGroup]) -> Self {
+		self.op_groups = op_groups.to_vec();
+		self
+	}
+
+	/// Removes the given op groups from the set to register.
+	pub fn without_ops(mut self, op_groups:&[OpGroup]) -> Self {
+		self.op_groups.retain(|g| !op_groups.contains(g));
+		self
+	}
+
+	/// Caps the V8 heap of the worker. Defaults to `DenoFlags::max_heap_mb`,
+	/// compiler workers pass `None`.
+	pub fn max_heap_mb(mut self, max_heap_mb:Option<usize>) -> Self {
+		self.max_heap_mb = max_heap_mb;
+		self
+	}
+
+	pub fn build(self) -> Worker {
+		let state = self.state;
+		let mut isolate = match self.max_heap_mb {
+			Some(max_heap_mb) => deno::Isolate::with_heap_limit(self.startup_data, max_heap_mb),
+			None => deno::Isolate::new(self.startup_data, false),
+		};
+		let isolate_handle = isolate.shared_isolate_handle();
+		let isolate = Arc::new(Mutex::new(isolate));
 		{
 			let mut i = isolate.lock().unwrap();
-			let op_registry = i.op_registry.clone();
 
-			ops::compiler::init(&mut i, &state);
-			ops::errors::init(&mut i, &state);
-			ops::fetch::init(&mut i, &state);
-			ops::files::init(&mut i, &state);
-			ops::fs::init(&mut i, &state);
-			ops::io::init(&mut i, &state);
-			ops::plugins::init(&mut i, &state, op_registry);
-			ops::net::init(&mut i, &state);
-			ops::tls::init(&mut i, &state);
-			ops::os::init(&mut i, &state);
-			ops::permissions::init(&mut i, &state);
-			ops::process::init(&mut i, &state);
-			ops::random::init(&mut i, &state);
-			ops::repl::init(&mut i, &state);
-			ops::resources::init(&mut i, &state);
-			ops::timers::init(&mut i, &state);
-			ops::workers::init(&mut i, &state);
+			for group in ALL_OP_GROUPS.iter().filter(|g| self.op_groups.contains(g)) {
+				register_op_group(&mut i, *group, &state);
+			}
 
 			let state_ = state.clone();
 			i.set_dyn_import(move |id, specifier, referrer| {
@@ -84,12 +217,94 @@ impl Worker {
 			})
 		}
 
-		Self { name, isolate, state, external_channels:Arc::new(Mutex::new(external_channels)) }
+		Worker {
+			name:self.name,
+			isolate,
+			isolate_handle,
+			terminated:Arc::new(AtomicBool::new(false)),
+			waker:Arc::new(AtomicWaker::new()),
+			execution_timeout:None,
+			close_timeout:DEFAULT_CLOSE_TIMEOUT,
+			state,
+			external_channels:Arc::new(Mutex::new(self.external_channels)),
+		}
+	}
+}
+
+fn register_op_group(i:&mut deno::Isolate, group:OpGroup, state:&ThreadSafeState) {
+	match group {
+		OpGroup::Compiler => ops::compiler::init(i, state),
+		OpGroup::Compression => ops::compression::init(i, state),
+		OpGroup::Crypto => ops::crypto::init(i, state),
+		OpGroup::Encoding => ops::encoding::init(i, state),
+		OpGroup::Errors => ops::errors::init(i, state),
+		OpGroup::Fetch => ops::fetch::init(i, state),
+		OpGroup::Files => ops::files::init(i, state),
+		OpGroup::Fs => ops::fs::init(i, state),
+		OpGroup::Io => ops::io::init(i, state),
+		OpGroup::Plugins => {
+			let op_registry = i.op_registry.clone();
+			ops::plugins::init(i, state, op_registry)
+		},
+		OpGroup::Net => ops::net::init(i, state),
+		OpGroup::Tls => ops::tls::init(i, state),
+		OpGroup::Os => ops::os::init(i, state),
+		OpGroup::Permissions => ops::permissions::init(i, state),
+		OpGroup::Process => ops::process::init(i, state),
+		OpGroup::Random => ops::random::init(i, state),
+		OpGroup::Repl => ops::repl::init(i, state),
+		OpGroup::Resources => ops::resources::init(i, state),
+		OpGroup::Timers => ops::timers::init(i, state),
+		OpGroup::Workers => ops::workers::init(i, state),
+	}
+}
+
+impl Worker {
+	pub fn new(
+		name:String,
+		startup_data:StartupData,
+		state:ThreadSafeState,
+		external_channels:WorkerChannels,
+	) -> Self {
+		WorkerBuilder::new(state, external_channels)
+			.name(name)
+			.startup_data(startup_data)
+			.build()
+	}
+
+	/// Registers a native op under `name` and returns its id. Ops must be
+	/// registered before any script calls `Deno.core.ops()`, otherwise they
+	/// won't be visible to JavaScript.
+	pub fn register_op<F>(&mut self, name:&str, handler:F) -> OpId
+	where
+		F: Fn(&[u8], Option<PinnedBuf>) -> CoreOp + Send + Sync + 'static, {
+		let isolate = self.isolate.lock().unwrap();
+		isolate.register_op(name, handler)
+	}
+
+	/// Limits the wall-clock time `execute_mod_async` may spend evaluating a
+	/// module. Loading the module graph does not count against the budget.
+	/// When the limit is exceeded the worker is terminated and
+	/// `ErrorKind::WorkerTimeout` is returned.
+	pub fn set_execution_timeout(&mut self, timeout:Option<Duration>) {
+		self.execution_timeout = timeout;
 	}
 
-	/// Same as execute2() but the filename defaults to "$CWD/__anonymous__".
+	/// Sets how long `close()` waits for the worker to finish before it is
+	/// terminated. Defaults to 5 seconds.
+	pub fn set_close_timeout(&mut self, timeout:Duration) { self.close_timeout = timeout; }
+
+	/// Same as execute2() but the filename defaults to "$CWD/__anonymous__",
+	/// where $CWD is the worker's working directory.
 	pub fn execute(&mut self, js_source:&str) -> Result<(), ErrBox> {
-		let path = env::current_dir().unwrap().join("__anonymous__");
+		self.execute_named("__anonymous__", js_source)
+	}
+
+	/// Same as execute() with `name` in place of "__anonymous__", so that
+	/// stack traces tell snippets apart. A relative name is resolved against
+	/// the worker's working directory.
+	pub fn execute_named(&mut self, name:&str, js_source:&str) -> Result<(), ErrBox> {
+		let path = self.state.cwd().join(name);
 		let url = Url::from_file_path(path).unwrap();
 		self.execute2(url.as_str(), js_source)
 	}
@@ -98,7 +313,44 @@ impl Worker {
 	/// is provided only for debugging purposes.
 	pub fn execute2(&mut self, js_filename:&str, js_source:&str) -> Result<(), ErrBox> {
 		let mut isolate = self.isolate.lock().unwrap();
-		isolate.execute(js_filename, js_source)
+		let result = isolate.execute(js_filename, js_source);
+		self.check_heap_limit(&isolate, result)
+	}
+
+	/// Turns the termination error of a script that ran into the heap cap into
+	/// `ErrorKind::HeapLimit`. The isolate can't run scripts after that, so the
+	/// worker is marked as terminated.
+	fn check_heap_limit(
+		&self,
+		isolate:&deno::Isolate,
+		result:Result<(), ErrBox>,
+	) -> Result<(), ErrBox> {
+		match result {
+			Err(_) if isolate.heap_limit_reached() => {
+				warn!("Worker \"{}\" exceeded its heap limit", self.name);
+				self.terminated.store(true, Ordering::SeqCst);
+				Err(heap_limit_exceeded())
+			},
+			result => result,
+		}
+	}
+
+	/// Registers `source` as the JavaScript source of `specifier` and executes
+	/// it as a module. Later imports of `specifier` get the same source, and
+	/// relative imports in it resolve against `specifier`.
+	pub fn execute_virtual_mod(
+		&mut self,
+		specifier:&str,
+		source:String,
+	) -> impl Future<Output = Result<(), ErrBox>> {
+		let module_specifier = ModuleSpecifier::resolve_url_or_path(specifier);
+		let load = module_specifier.map_err(ErrBox::from).map(|module_specifier| {
+			let mut virtual_modules = self.state.virtual_modules.lock().unwrap();
+			virtual_modules.insert(module_specifier.to_string(), source);
+			drop(virtual_modules);
+			self.execute_mod_async(&module_specifier, None, false)
+		});
+		async move { load?.await }
 	}
 
 	/// Executes the provided JavaScript module.
@@ -119,11 +371,70 @@ impl Worker {
 			let id = recursive_load.get_future(isolate).await?;
 			worker.state.global_state.progress.done();
 
-			if !is_prefetch {
+			if is_prefetch || worker.is_terminated() {
+				return Ok(());
+			}
+
+			let timeout = match worker.execution_timeout {
+				Some(timeout) => timeout,
+				None => {
+					let mut isolate = worker.isolate.lock().unwrap();
+					let result = isolate.mod_evaluate(id);
+					return worker.check_heap_limit(&isolate, result);
+				},
+			};
+
+			// The watchdog only needs the isolate handle, so it can fire while
+			// mod_evaluate holds the isolate lock.
+			let (done_tx, done_rx) = std_mpsc::channel::<()>();
+			let watchdog_worker = worker.clone();
+			let watchdog = thread::spawn(move || {
+				match done_rx.recv_timeout(timeout) {
+					Err(std_mpsc::RecvTimeoutError::Timeout) => {
+						let _ = futures::executor::block_on(watchdog_worker.terminate());
+						true
+					},
+					_ => false,
+				}
+			});
+
+			let result = {
 				let mut isolate = worker.isolate.lock().unwrap();
-				return isolate.mod_evaluate(id);
+				let result = isolate.mod_evaluate(id);
+				worker.check_heap_limit(&isolate, result)
+			};
+			let _ = done_tx.send(());
+			if watchdog.join().unwrap() {
+				warn!("Worker \"{}\" timed out after {:?}", worker.name, timeout);
+				return Err(worker_timeout());
 			}
+			result
+		}
+	}
+
+	/// Fetches and compiles the given modules and everything they import,
+	/// without evaluating anything. The module graphs are loaded concurrently;
+	/// dependencies they share are counted once in `resolve_count`.
+	pub fn prefetch_all(
+		&self,
+		module_specifiers:&[ModuleSpecifier],
+	) -> impl Future<Output = Result<(), ErrBox>> {
+		let loads:Vec<_> = module_specifiers
+			.iter()
+			.map(|module_specifier| {
+				RecursiveLoad::prefetch(
+					&module_specifier.to_string(),
+					self.state.clone(),
+					self.state.modules.clone(),
+				)
+				.get_future(self.isolate.clone())
+			})
+			.collect();
+		let worker = self.clone();
 
+		async move {
+			futures::future::try_join_all(loads).await?;
+			worker.state.global_state.progress.done();
 			Ok(())
 		}
 	}
@@ -141,10 +452,88 @@ impl Worker {
 		}
 	}
 
+	/// Post message to worker as a host without waiting for buffer space.
+	///
+	/// Fails if the worker's receive buffer is full or the worker has been
+	/// closed. The original message can be recovered from the error with
+	/// `TrySendError::into_inner`.
+	pub fn try_post_message(&self, buf:Buf) -> Result<(), mpsc::TrySendError<Buf>> {
+		self.external_channels.lock().unwrap().sender.try_send(buf)
+	}
+
 	/// Get message from worker as a host.
 	pub fn get_message(self: &Self) -> WorkerReceiver {
-		WorkerReceiver { channels:self.external_channels.clone() }
+		WorkerReceiver { stream:self.message_stream() }
+	}
+
+	/// Stream of messages sent by the worker to the host. The stream ends when
+	/// the worker's channel is closed.
+	pub fn message_stream(&self) -> WorkerMessageStream {
+		WorkerMessageStream { channels:self.external_channels.clone() }
+	}
+
+	/// Forcibly stops the worker.
+	///
+	/// Any JavaScript that is currently running, including a synchronous loop,
+	/// is interrupted. Both ends of the worker channels are closed and pending
+	/// messages are dropped. Workers spawned by this one are terminated too.
+	/// Afterwards polling the worker resolves to `Ok(())`. Calling this more
+	/// than once is a no-op.
+	pub fn terminate(&self) -> impl Future<Output = Result<(), ErrBox>> {
+		if !self.terminated.swap(true, Ordering::SeqCst) {
+			self.isolate_handle.terminate_execution();
+			self.external_channels.lock().unwrap().close_and_drain();
+			self.state.worker_channels.lock().unwrap().close_and_drain();
+			self.waker.wake();
+			self.state.global_state.unregister_worker(self);
+
+			let children:Vec<Worker> =
+				self.state.workers.lock().unwrap().values().cloned().collect();
+			for child in children {
+				// Terminating is synchronous, the returned future is already ready.
+				let _ = child.terminate();
+			}
+		}
+		futures::future::ok(())
+	}
+
+	/// Stops the worker gracefully.
+	///
+	/// No more messages can be posted to the worker, but those already posted
+	/// are still delivered, after which its message loop ends. The worker then
+	/// runs until its in-flight ops are done and both ends of the worker
+	/// channels are closed. Messages the worker sent can still be received.
+	///
+	/// If the worker doesn't finish within the close timeout, see
+	/// `set_close_timeout()`, it is terminated like with `terminate()` and
+	/// `ErrorKind::WorkerTimeout` is returned.
+	pub fn close(self) -> impl Future<Output = Result<(), ErrBox>> {
+		self.external_channels.lock().unwrap().sender.close_channel();
+		let delay = Compat01As03::new(Delay::new(Instant::now() + self.close_timeout));
+
+		async move {
+			match futures::future::select(self.clone(), delay).await {
+				Either::Left((result, _)) => {
+					if !self.terminated.swap(true, Ordering::SeqCst) {
+						self.state.worker_channels.lock().unwrap().close_and_drain();
+						self.waker.wake();
+					}
+					result
+				},
+				Either::Right(_) => {
+					warn!("Worker \"{}\" did not close within {:?}", self.name, self.close_timeout);
+					self.terminate().await?;
+					Err(worker_timeout())
+				},
+			}
+		}
 	}
+
+	/// Returns true once `terminate()` or `close()` has been called.
+	pub fn is_terminated(&self) -> bool { self.terminated.load(Ordering::SeqCst) }
+
+	/// Returns true if both are handles to the same worker.
+	pub fn ptr_eq(&self, other:&Worker) -> bool { Arc::ptr_eq(&self.terminated, &other.terminated) }
 }
 
 impl Future for Worker {
@@ -152,8 +541,33 @@ impl Future for Worker {
 
 	fn poll(self: Pin<&mut Self>, cx:&mut Context) -> Poll<Self::Output> {
 		let inner = self.get_mut();
+		inner.waker.register(cx.waker());
+		if inner.is_terminated() {
+			return Poll::Ready(Ok(()));
+		}
 		let mut isolate = inner.isolate.lock().unwrap();
-		isolate.poll_unpin(cx)
+		match isolate.poll_unpin(cx) {
+			Poll::Ready(result) => Poll::Ready(inner.check_heap_limit(&isolate, result)),
+			Poll::Pending => Poll::Pending,
+		}
+	}
+}
+
+/// Stream of messages received from a worker. Ends once the worker's
+/// channel has been closed.
+pub struct WorkerMessageStream {
+	channels:Arc<Mutex<WorkerChannels>>,
+}
+
+impl Stream for WorkerMessageStream {
+	type Item = Result<Buf, ErrBox>;
+
+	fn poll_next(self: Pin<&mut Self>, cx:&mut Context) -> Poll<Option<Self::Item>> {
+		let mut channels = self.channels.lock().unwrap();
+		match channels.receiver.poll_next_unpin(cx) {
+			Poll::Ready(v) => Poll::Ready(v.map(Ok)),
+			Poll::Pending => Poll::Pending,
+		}
 	}
 }
 
@@ -161,16 +575,17 @@ impl Future for Worker {
 /// that will return message received from worker or None
 /// if worker's channel has been closed.
 pub struct WorkerReceiver {
-	channels:Arc<Mutex<WorkerChannels>>,
+	stream:WorkerMessageStream,
 }
 
 impl Future for WorkerReceiver {
 	type Output = Result<Option<Buf>, ErrBox>;
 
 	fn poll(self: Pin<&mut Self>, cx:&mut Context) -> Poll<Self::Output> {
-		let mut channels = self.channels.lock().unwrap();
-		match channels.receiver.poll_next_unpin(cx) {
-			Poll::Ready(v) => Poll::Ready(Ok(v)),
+		match self.get_mut().stream.poll_next_unpin(cx) {
+			Poll::Ready(Some(Ok(buf))) => Poll::Ready(Ok(Some(buf))),
+			Poll::Ready(Some(Err(err))) => Poll::Ready(Err(err)),
+			Poll::Ready(None) => Poll::Ready(Ok(None)),
 			Poll::Pending => Poll::Pending,
 		}
 	}
@@ -184,8 +599,10 @@ mod tests {
 
 	use super::*;
 	use crate::{
+		deno_error::{ErrorKind, GetErrorKind},
 		flags,
 		global_state::ThreadSafeGlobalState,
+		permissions::PermissionState,
 		progress::Progress,
 		startup_data,
 		state::ThreadSafeState,
@@ -277,7 +694,8 @@ mod tests {
 		});
 
 		let metrics = &state_.metrics;
-		// TODO  assert_eq!(metrics.resolve_count.load(Ordering::SeqCst), 2);
+		// circular1.ts and circular2.ts, each counted once despite the cycle.
+		assert_eq!(metrics.resolve_count.load(Ordering::SeqCst), 2);
 		// Check that we didn't start the compiler.
 		assert_eq!(metrics.compiler_starts.load(Ordering::SeqCst), 0);
 	}
@@ -325,6 +743,41 @@ mod tests {
 		drop(http_server_guard);
 	}
 
+	#[test]
+	fn prefetch_all_shared_deps() {
+		let tests_dir =
+			std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().unwrap().join("tests");
+		let module_specifiers:Vec<ModuleSpecifier> = ["circular1.ts", "circular2.ts"]
+			.iter()
+			.map(|name| {
+				ModuleSpecifier::resolve_url_or_path(&tests_dir.join(name).to_string_lossy())
+					.unwrap()
+			})
+			.collect();
+		let global_state = ThreadSafeGlobalState::new(
+			flags::DenoFlags {
+				argv:vec![String::from("deno"), module_specifiers[0].to_string()],
+				..flags::DenoFlags::default()
+			},
+			Progress::new(),
+		)
+		.unwrap();
+		let (int, ext) = ThreadSafeState::create_channels();
+		let state =
+			ThreadSafeState::new(global_state, None, Some(module_specifiers[0].clone()), true, int)
+				.unwrap();
+		let state_ = state.clone();
+		tokio_util::run(async move {
+			let worker = Worker::new("TEST".to_string(), StartupData::None, state, ext);
+			worker.prefetch_all(&module_specifiers).await.unwrap();
+			panic_on_error(worker).await
+		});
+
+		// Each module imports the other, but each is only counted once.
+		assert_eq!(state_.metrics.resolve_count.load(Ordering::SeqCst), 2);
+		assert_eq!(state_.metrics.compiler_starts.load(Ordering::SeqCst), 0);
+	}
+
 	fn create_test_worker() -> Worker {
 		let (int, ext) = ThreadSafeState::create_channels();
 		let state =
@@ -336,6 +789,86 @@ mod tests {
 		worker
 	}
 
+	#[test]
+	fn boot_from_user_snapshot() {
+		let dir = tempfile::TempDir::new().unwrap();
+		let path = dir.path().join("snapshot.bin");
+		std::fs::write(&path, crate::js::CLI_SNAPSHOT).unwrap();
+		let flags = flags::DenoFlags {
+			snapshot_path:Some(path.to_str().unwrap().to_string()),
+			..flags::DenoFlags::default()
+		};
+		run_in_task(move || {
+			let (int, ext) = ThreadSafeState::create_channels();
+			let state =
+				ThreadSafeState::mock(vec![String::from("./deno"), String::from("hello.js")], int);
+			let startup_data = startup_data::user_isolate_init(&flags);
+			let mut worker = Worker::new("SNAPSHOT".to_string(), startup_data, state, ext);
+			worker.execute("denoMain()").unwrap();
+			worker
+				.execute(r#"if (typeof Deno.version !== "object") throw new Error("not booted");"#)
+				.unwrap();
+		});
+		drop(dir);
+	}
+
+	#[test]
+	fn builder_without_ops() {
+		run_in_task(|| {
+			let (int, ext) = ThreadSafeState::create_channels();
+			let state =
+				ThreadSafeState::mock(vec![String::from("./deno"), String::from("hello.js")], int);
+			let mut worker = WorkerBuilder::new(state, ext)
+				.name("SANDBOX")
+				.startup_data(startup_data::deno_isolate_init())
+				.without_ops(&[OpGroup::Net, OpGroup::Process])
+				.build();
+			assert_eq!(worker.name, "SANDBOX");
+			worker
+				.execute(
+					r#"
+          const ops = Deno.core.ops();
+          if ("listen" in ops || "run" in ops) throw new Error("unexpected op");
+          if (!("read" in ops)) throw new Error("missing op");
+          "#,
+				)
+				.unwrap();
+		})
+	}
+
+	#[test]
+	fn register_custom_op() {
+		run_in_task(|| {
+			let (int, ext) = ThreadSafeState::create_channels();
+			let state =
+				ThreadSafeState::mock(vec![String::from("./deno"), String::from("hello.js")], int);
+			let mut worker =
+				Worker::new("TEST".to_string(), startup_data::deno_isolate_init(), state, ext);
+
+			let received = Arc::new(Mutex::new(Vec::new()));
+			let received_ = received.clone();
+			worker.register_op("test_reverse", move |control, _zero_copy| {
+				received_.lock().unwrap().extend_from_slice(control);
+				let mut reply = control.to_vec();
+				reply.reverse();
+				CoreOp::Sync(reply.into_boxed_slice())
+			});
+
+			worker
+				.execute(
+					r#"
+          const opId = Deno.core.ops()["test_reverse"];
+          const res = Deno.core.dispatch(opId, new Uint8Array([1, 2, 3]));
+          if (res.length !== 3 || res[0] !== 3 || res[2] !== 1) {
+            throw new Error("bad reply");
+          }
+          "#,
+				)
+				.unwrap();
+			assert_eq!(*received.lock().unwrap(), vec![1, 2, 3]);
+		})
+	}
+
 	#[test]
 	fn test_worker_messages() {
 		run_in_task(|| {
@@ -381,6 +914,235 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn test_worker_transfer() {
+		run_in_task(|| {
+			let mut worker = create_test_worker();
+			let source = r#"
+        onmessage = function(e) {
+          if (e.data == "exit") {
+            delete window.onmessage;
+          } else if (e.data == "send") {
+            const ab = new ArrayBuffer(1 << 20);
+            new Uint8Array(ab)[0] = 42;
+            postMessage({ ab, view: new Uint8Array(ab, 1, 2) }, [ab]);
+            if (ab.byteLength !== 0) throw new Error("not detached");
+          } else {
+            const { ab, view } = e.data;
+            if (!(ab instanceof ArrayBuffer) || ab.byteLength !== 1 << 20) {
+              throw new Error("bad buffer");
+            }
+            if (new Uint8Array(ab)[0] !== 42 || view.buffer !== ab || view.length !== 2) {
+              throw new Error("bad contents");
+            }
+            postMessage("ok");
+          }
+        }
+        "#;
+			worker.execute(source).unwrap();
+
+			let worker_ = worker.clone();
+			let fut = async move {
+				let r = worker.await;
+				r.unwrap();
+				Ok(())
+			};
+			tokio::spawn(fut.boxed().compat());
+
+			let msg = json!("send").to_string().into_boxed_str().into_boxed_bytes();
+			assert!(block_on(worker_.post_message(msg)).is_ok());
+			let transferred = block_on(worker_.get_message()).unwrap().unwrap();
+			// Only an id for the buffer travels, after a zero marker byte.
+			assert_eq!(transferred[0], 0);
+			assert!(transferred.len() < 256);
+
+			// Echo it back so the worker decodes its own message.
+			assert!(block_on(worker_.post_message(transferred)).is_ok());
+			let maybe_msg = block_on(worker_.get_message()).unwrap();
+			assert_eq!(*maybe_msg.unwrap(), *b"\"ok\"");
+
+			let msg = json!("exit").to_string().into_boxed_str().into_boxed_bytes();
+			assert!(block_on(worker_.post_message(msg)).is_ok());
+		})
+	}
+
+	#[test]
+	fn test_nested_worker_messages() {
+		run_in_task(|| {
+			let mut worker = create_test_worker();
+			// The leaf is spawned by the worker itself; messages from the host are
+			// relayed down to it and its replies back up.
+			let source = r#"
+        const leafSource = "onmessage = (e) => postMessage(e.data + '!');";
+        const leaf = new Worker(URL.createObjectURL(new Blob([leafSource])));
+        leaf.onmessage = (e) => postMessage(e.data);
+        onmessage = function(e) {
+          if (e.data == "exit") {
+            delete window.onmessage;
+            return;
+          }
+          leaf.postMessage(e.data);
+        }
+        "#;
+			worker.execute(source).unwrap();
+
+			let worker_ = worker.clone();
+			let fut = async move {
+				let r = worker.await;
+				r.unwrap();
+				Ok(())
+			};
+			tokio::spawn(fut.boxed().compat());
+
+			let msg = json!("hi").to_string().into_boxed_str().into_boxed_bytes();
+			assert!(block_on(worker_.post_message(msg)).is_ok());
+			let maybe_msg = block_on(worker_.get_message()).unwrap();
+			assert_eq!(*maybe_msg.unwrap(), *b"\"hi!\"");
+
+			let children:Vec<Worker> =
+				worker_.state.workers.lock().unwrap().values().cloned().collect();
+			assert_eq!(children.len(), 1);
+			block_on(worker_.terminate()).unwrap();
+			assert!(children[0].is_terminated());
+		})
+	}
+
+	#[test]
+	fn try_post_message_full() {
+		run_in_task(|| {
+			let worker = create_test_worker();
+			// Nobody is polling the worker, so the buffer eventually fills up.
+			let mut result = Ok(());
+			for _ in 0..16 {
+				let msg = json!("hi").to_string().into_boxed_str().into_boxed_bytes();
+				result = worker.try_post_message(msg);
+				if result.is_err() {
+					break;
+				}
+			}
+			let err = result.unwrap_err();
+			assert!(err.is_full());
+			assert_eq!(*err.into_inner(), *b"\"hi\"");
+		})
+	}
+
+	#[test]
+	fn try_post_message_after_terminate() {
+		run_in_task(|| {
+			let worker = create_test_worker();
+			block_on(worker.terminate()).unwrap();
+			let msg = json!("hi").to_string().into_boxed_str().into_boxed_bytes();
+			let err = worker.try_post_message(msg).unwrap_err();
+			assert!(err.is_disconnected());
+		})
+	}
+
+	#[test]
+	fn test_worker_message_stream() {
+		run_in_task(|| {
+			let mut worker = create_test_worker();
+			let source = r#"
+        onmessage = function(e) {
+          if (e.data == "exit") {
+            delete window.onmessage;
+            return;
+          }
+          postMessage(1);
+          postMessage(2);
+          postMessage(3);
+        }
+        "#;
+			worker.execute(source).unwrap();
+
+			let worker_ = worker.clone();
+			tokio::spawn(worker.then(|r| futures::future::ok::<(), ()>(r.unwrap())).compat());
+
+			let msg = json!("hi").to_string().into_boxed_str().into_boxed_bytes();
+			assert!(block_on(worker_.post_message(msg)).is_ok());
+
+			let msgs:Vec<Buf> =
+				block_on(worker_.message_stream().take(3).map(|r| r.unwrap()).collect());
+			let msgs:Vec<&[u8]> = msgs.iter().map(|m| &m[..]).collect();
+			assert_eq!(msgs, vec![&b"1"[..], &b"2"[..], &b"3"[..]]);
+
+			let msg = json!("exit").to_string().into_boxed_str().into_boxed_bytes();
+			assert!(block_on(worker_.post_message(msg)).is_ok());
+		})
+	}
+
+	#[test]
+	fn message_stream_ends_on_terminate() {
+		run_in_task(|| {
+			let worker = create_test_worker();
+			block_on(worker.terminate()).unwrap();
+			let msgs:Vec<Result<Buf, ErrBox>> = block_on(worker.message_stream().collect());
+			assert!(msgs.is_empty());
+		})
+	}
+
+	#[test]
+	fn close_delivers_pending_messages() {
+		tokio_util::run(async {
+			let mut worker = create_test_worker();
+			worker.execute("onmessage = (e) => postMessage(e.data + '!');").unwrap();
+			let worker_ = worker.clone();
+
+			// Nobody polls the worker until it is closed.
+			let msg = json!("hi").to_string().into_boxed_str().into_boxed_bytes();
+			worker.post_message(msg).await.unwrap();
+			worker.close().await.unwrap();
+			assert!(worker_.is_terminated());
+
+			let msgs:Vec<Result<Buf, ErrBox>> = worker_.message_stream().collect().await;
+			assert_eq!(msgs.len(), 1);
+			assert_eq!(**msgs[0].as_ref().unwrap(), *b"\"hi!\"");
+			let msg = json!("late").to_string().into_boxed_str().into_boxed_bytes();
+			assert!(worker_.post_message(msg).await.is_err());
+			Ok(())
+		});
+	}
+
+	#[test]
+	fn close_times_out() {
+		tokio_util::run(async {
+			let mut worker = create_test_worker();
+			worker.execute("setInterval(() => {}, 10);").unwrap();
+			worker.set_close_timeout(Duration::from_millis(100));
+			let worker_ = worker.clone();
+			let err = worker.close().await.unwrap_err();
+			assert_eq!(err.kind(), ErrorKind::WorkerTimeout);
+			assert!(worker_.is_terminated());
+			Ok(())
+		});
+	}
+
+	#[test]
+	fn per_worker_cwd() {
+		run_in_task(|| {
+			let temp_dir = tempfile::TempDir::new().unwrap();
+			let mut workers = Vec::new();
+			for name in &["a", "b"] {
+				let dir = temp_dir.path().join(name);
+				std::fs::create_dir(&dir).unwrap();
+				std::fs::write(dir.join("name.txt"), name).unwrap();
+				let mut worker = create_test_worker();
+				worker.state.permissions.lock().unwrap().allow_read = PermissionState::Allow;
+				worker.state.set_cwd(dir.to_str().unwrap()).unwrap();
+				workers.push((name, worker));
+			}
+			for (name, worker) in &mut workers {
+				let source = format!(
+					r#"
+          const name = new TextDecoder().decode(Deno.readFileSync("name.txt"));
+          if (name !== "{}") throw new Error("read " + name);
+          "#,
+					name
+				);
+				worker.execute(&source).unwrap();
+			}
+		})
+	}
+
 	#[test]
 	fn removed_from_resource_table_on_close() {
 		run_in_task(|| {
@@ -407,6 +1169,146 @@ mod tests {
 		})
 	}
 
+	#[test]
+	fn terminate_sync_loop() {
+		run_in_task(|| {
+			let mut worker = create_test_worker();
+			let worker_ = worker.clone();
+
+			let t = std::thread::spawn(move || {
+				// allow the loop below to start
+				std::thread::sleep(std::time::Duration::from_millis(100));
+				block_on(worker_.terminate()).unwrap();
+			});
+
+			let result = worker.execute("while (true) {}");
+			assert!(result.is_err());
+			t.join().unwrap();
+
+			// Terminating twice is fine.
+			assert!(block_on(worker.terminate()).is_ok());
+			assert!(worker.is_terminated());
+			assert!(block_on(worker.get_message()).unwrap().is_none());
+			assert!(block_on(worker).is_ok());
+		})
+	}
+
+	#[test]
+	fn terminate_before_execute_mod() {
+		run_in_task(|| {
+			let mut worker = create_test_worker();
+			block_on(worker.terminate()).unwrap();
+			let p = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+				.parent()
+				.unwrap()
+				.join("tests/002_hello.ts")
+				.to_owned();
+			let module_specifier =
+				ModuleSpecifier::resolve_url_or_path(&p.to_string_lossy()).unwrap();
+			let result = block_on(worker.execute_mod_async(&module_specifier, None, false));
+			assert!(result.is_ok());
+		})
+	}
+
+	#[test]
+	fn execute_mod_heap_limit() {
+		run_in_task(|| {
+			let (int, ext) = ThreadSafeState::create_channels();
+			let state =
+				ThreadSafeState::mock(vec![String::from("./deno"), String::from("hello.js")], int);
+			let mut worker = WorkerBuilder::new(state, ext)
+				.startup_data(startup_data::deno_isolate_init())
+				.max_heap_mb(Some(64))
+				.build();
+			worker.execute("denoMain()").unwrap();
+			worker.execute("workerMain()").unwrap();
+			let module_specifier =
+				ModuleSpecifier::resolve_url_or_path("./__$deno$heap_limit.js").unwrap();
+			let code = "const a = []; while (true) { a.push(new Array(1024 * 1024).fill(1)); }";
+			let result =
+				block_on(worker.execute_mod_async(&module_specifier, Some(code.to_string()), false));
+			let err = result.unwrap_err();
+			assert_eq!(err.kind(), ErrorKind::HeapLimit);
+			assert!(worker.is_terminated());
+		})
+	}
+
+	#[test]
+	fn execute_virtual_mod_imports_sibling() {
+		run_in_task(|| {
+			let temp_dir = tempfile::TempDir::new().unwrap();
+			std::fs::write(temp_dir.path().join("sibling.js"), "export const value = 42;").unwrap();
+			// Never written to disk.
+			let specifier = Url::from_file_path(temp_dir.path().join("virtual.js")).unwrap();
+			let source = r#"
+        import { value } from "./sibling.js";
+        if (value !== 42) throw new Error("got " + value);
+        "#;
+			let mut worker = create_test_worker();
+			block_on(worker.execute_virtual_mod(specifier.as_str(), source.to_string())).unwrap();
+			let virtual_modules = worker.state.virtual_modules.lock().unwrap();
+			assert!(virtual_modules.contains_key(specifier.as_str()));
+		})
+	}
+
+	#[test]
+	fn execute_mod_timeout() {
+		run_in_task(|| {
+			let mut worker = create_test_worker();
+			worker.set_execution_timeout(Some(Duration::from_millis(100)));
+			let module_specifier =
+				ModuleSpecifier::resolve_url_or_path("./__$deno$timeout.js").unwrap();
+			let result = block_on(worker.execute_mod_async(
+				&module_specifier,
+				Some("while (true) {}".to_string()),
+				false,
+			));
+			let err = result.unwrap_err();
+			assert_eq!(err.kind(), ErrorKind::WorkerTimeout);
+			assert!(worker.is_terminated());
+		})
+	}
+
+	#[test]
+	fn worker_future_surfaces_thrown_error() {
+		run_in_task(|| {
+			let mut worker = create_test_worker();
+			worker
+				.execute("setTimeout(function boom() { throw new Error(\"async boom\"); }, 10);")
+				.unwrap();
+			let err = block_on(worker).unwrap_err();
+			let js_error = err.downcast_ref::<JSError>().expect("expected JSError");
+			let exception = js_error.v8_exception();
+			assert!(exception.message.contains("async boom"));
+			assert_eq!(exception.frames[0].function_name, "boom");
+		})
+	}
+
+	#[test]
+	fn execute_named_in_stack_trace() {
+		run_in_task(|| {
+			let mut worker = create_test_worker();
+			let err = worker.execute_named("snippet-2", "throw new Error(\"boom\");").unwrap_err();
+			let js_error = err.downcast_ref::<JSError>().expect("expected JSError");
+			assert!(js_error.v8_exception().frames[0].script_name.ends_with("/snippet-2"));
+
+			let err = worker.execute("throw new Error(\"boom\");").unwrap_err();
+			let js_error = err.downcast_ref::<JSError>().expect("expected JSError");
+			assert!(js_error.v8_exception().frames[0].script_name.ends_with("/__anonymous__"));
+		})
+	}
+
+	#[test]
+	fn worker_future_surfaces_unhandled_rejection() {
+		run_in_task(|| {
+			let mut worker = create_test_worker();
+			worker.execute("Promise.reject(new Error(\"rejected\"));").unwrap();
+			let err = block_on(worker).unwrap_err();
+			let js_error = err.downcast_ref::<JSError>().expect("expected JSError");
+			assert!(js_error.v8_exception().message.contains("rejected"));
+		})
+	}
+
 	#[test]
 	fn execute_mod_resolve_error() {
 		run_in_task(|| {
@@ -0,0 +1,265 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+use std::{
+	future::Future,
+	sync::{
+		self,
+		atomic::{AtomicUsize, Ordering},
+		Arc,
+	},
+};
+
+use deno::{Buf, ErrBox, ModuleSpecifier};
+use futures::{
+	future::{FutureExt, TryFutureExt},
+	lock::Mutex,
+};
+
+use crate::{
+	deno_error::{DenoError, ErrorKind},
+	global_state::ThreadSafeGlobalState,
+	startup_data,
+	state::ThreadSafeState,
+	worker::Worker,
+};
+
+/// A fixed number of workers running the same module. Requests are
+/// dispatched round-robin, preferring a worker that is not busy with another
+/// request. Each request is answered by exactly one message from the worker.
+pub struct WorkerPool {
+	global_state:ThreadSafeGlobalState,
+	module_specifier:ModuleSpecifier,
+	slots:Vec<Arc<Slot>>,
+	next:AtomicUsize,
+}
+
+struct Slot {
+	worker:sync::Mutex<Worker>,
+	/// Held while a request is in flight, a worker answers one at a time.
+	busy:Mutex<()>,
+}
+
+impl WorkerPool {
+	/// Spawns `size` workers that each evaluate `module_specifier`. Fails if
+	/// `size` is zero.
+	pub fn new(
+		global_state:ThreadSafeGlobalState,
+		module_specifier:ModuleSpecifier,
+		size:usize,
+	) -> impl Future<Output = Result<Self, ErrBox>> {
+		async move {
+			if size == 0 {
+				return Err(DenoError::new(
+					ErrorKind::InvalidInput,
+					"worker pool must have at least one worker".to_string(),
+				)
+				.into());
+			}
+			let mut slots = Vec::with_capacity(size);
+			for index in 0..size {
+				let worker =
+					spawn_worker(global_state.clone(), module_specifier.clone(), index).await?;
+				slots.push(Arc::new(Slot { worker:sync::Mutex::new(worker), busy:Mutex::new(()) }));
+			}
+			Ok(Self { global_state, module_specifier, slots, next:AtomicUsize::new(0) })
+		}
+	}
+
+	/// Number of workers in the pool.
+	pub fn size(&self) -> usize { self.slots.len() }
+
+	/// Sends `buf` to a worker and resolves with its reply. If the worker has
+	/// died it is replaced and the request is retried once on the new worker.
+	pub fn dispatch(&self, buf:Buf) -> impl Future<Output = Result<Buf, ErrBox>> {
+		let start = self.next.fetch_add(1, Ordering::SeqCst) % self.slots.len();
+		let slots = self.slots.clone();
+		let global_state = self.global_state.clone();
+		let module_specifier = self.module_specifier.clone();
+
+		async move {
+			let mut idle = None;
+			for offset in 0..slots.len() {
+				let index = (start + offset) % slots.len();
+				if let Some(guard) = slots[index].busy.try_lock() {
+					idle = Some((index, guard));
+					break;
+				}
+			}
+			let (index, _busy) = match idle {
+				Some(idle) => idle,
+				None => (start, slots[start].busy.lock().await),
+			};
+			let slot = &slots[index];
+
+			// Cloned out of the lock, which is never held across an await.
+			let worker = slot.worker.lock().unwrap().clone();
+			if let Some(reply) = request(worker.clone(), buf.clone()).await {
+				return Ok(reply);
+			}
+
+			debug!("worker pool: respawning worker {}", index);
+			worker.terminate().await?;
+			let worker = spawn_worker(global_state, module_specifier, index).await?;
+			*slot.worker.lock().unwrap() = worker.clone();
+			match request(worker, buf).await {
+				Some(reply) => Ok(reply),
+				None => {
+					Err(DenoError::new(
+						ErrorKind::BrokenPipe,
+						"worker closed before replying".to_string(),
+					)
+					.into())
+				},
+			}
+		}
+	}
+
+	/// Waits for in-flight requests to finish and terminates every worker.
+	pub fn shutdown(&self) -> impl Future<Output = Result<(), ErrBox>> {
+		let slots = self.slots.clone();
+		async move {
+			for slot in slots.iter() {
+				let _busy = slot.busy.lock().await;
+				let worker = slot.worker.lock().unwrap().clone();
+				worker.terminate().await?;
+			}
+			Ok(())
+		}
+	}
+}
+
+/// Posts a message and waits for one reply. Returns `None` if the worker's
+/// channel is closed in either direction.
+async fn request(worker:Worker, buf:Buf) -> Option<Buf> {
+	if worker.post_message(buf).await.is_err() {
+		return None;
+	}
+	worker.get_message().await.ok().and_then(|maybe_msg| maybe_msg)
+}
+
+fn spawn_worker(
+	global_state:ThreadSafeGlobalState,
+	module_specifier:ModuleSpecifier,
+	index:usize,
+) -> impl Future<Output = Result<Worker, ErrBox>> {
+	async move {
+		let (int, ext) = ThreadSafeState::create_channels();
+		let startup_data = startup_data::user_isolate_init(&global_state.flags);
+		let state =
+			ThreadSafeState::new(global_state, None, Some(module_specifier.clone()), true, int)?;
+		let name = format!("POOL-WORKER-{}", index);
+		let mut worker = Worker::new(name, startup_data, state, ext);
+		worker.execute("denoMain()")?;
+		worker.execute("workerMain()")?;
+		worker.execute_mod_async(&module_specifier, None, false).await?;
+
+		let worker_ = worker.clone();
+		let handle = worker.clone();
+		tokio::spawn(
+			worker_
+				.then(move |result| {
+					if let Err(err) = result {
+						debug!("worker pool: worker exited with error {:?}", err);
+						// Closes its channels, so a request waiting for a reply gets
+						// `None` and the worker is respawned.
+						let _ = handle.terminate();
+					}
+					futures::future::ok::<(), ()>(())
+				})
+				.boxed()
+				.compat(),
+		);
+		Ok(worker)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::fs;
+
+	use tempfile::TempDir;
+
+	use super::*;
+	use crate::{deno_error::GetErrorKind, flags, progress::Progress, tokio_util};
+
+	fn create_pool_module(source:&str) -> (TempDir, ModuleSpecifier, ThreadSafeGlobalState) {
+		let temp_dir = TempDir::new().expect("tempdir fail");
+		let path = temp_dir.path().join("pool_worker.js");
+		fs::write(&path, source).unwrap();
+		let module_specifier =
+			ModuleSpecifier::resolve_url_or_path(&path.to_string_lossy()).unwrap();
+		let global_state = ThreadSafeGlobalState::new(
+			flags::DenoFlags {
+				argv:vec![String::from("deno"), module_specifier.to_string()],
+				..flags::DenoFlags::default()
+			},
+			Progress::new(),
+		)
+		.unwrap();
+		(temp_dir, module_specifier, global_state)
+	}
+
+	#[test]
+	fn dispatch_round_robin() {
+		let (_temp_dir, module_specifier, global_state) =
+			create_pool_module("onmessage = (e) => { postMessage(e.data * 2); };");
+		tokio_util::run(async move {
+			let pool = WorkerPool::new(global_state, module_specifier, 2).await.unwrap();
+			assert_eq!(pool.size(), 2);
+			for i in 0..4 {
+				let msg = json!(i).to_string().into_boxed_str().into_boxed_bytes();
+				let reply = pool.dispatch(msg).await.unwrap();
+				assert_eq!(*reply, *json!(i * 2).to_string().as_bytes());
+			}
+			pool.shutdown().await.unwrap();
+			Ok(())
+		});
+	}
+
+	#[test]
+	fn empty_pool() {
+		let (_temp_dir, module_specifier, global_state) = create_pool_module("");
+		tokio_util::run(async move {
+			let err = WorkerPool::new(global_state, module_specifier, 0).await.err().unwrap();
+			assert_eq!(err.kind(), ErrorKind::InvalidInput);
+			Ok(())
+		});
+	}
+
+	#[test]
+	fn dispatch_after_uncaught_error() {
+		let (_temp_dir, module_specifier, global_state) = create_pool_module(
+			r#"onmessage = (e) => {
+			  if (e.data === "throw") throw new Error("boom");
+			  postMessage(e.data);
+			};"#,
+		);
+		tokio_util::run(async move {
+			let pool = WorkerPool::new(global_state, module_specifier, 1).await.unwrap();
+			// The worker dies, and so does its replacement on the retry.
+			let msg = json!("throw").to_string().into_boxed_str().into_boxed_bytes();
+			let err = pool.dispatch(msg).await.err().unwrap();
+			assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+			let msg = json!("hi").to_string().into_boxed_str().into_boxed_bytes();
+			let reply = pool.dispatch(msg).await.unwrap();
+			assert_eq!(*reply, *b"\"hi\"");
+			pool.shutdown().await.unwrap();
+			Ok(())
+		});
+	}
+
+	#[test]
+	fn dispatch_respawns_dead_worker() {
+		let (_temp_dir, module_specifier, global_state) =
+			create_pool_module("onmessage = (e) => { postMessage(e.data); };");
+		tokio_util::run(async move {
+			let pool = WorkerPool::new(global_state, module_specifier, 1).await.unwrap();
+			let worker = pool.slots[0].worker.lock().unwrap().clone();
+			worker.terminate().await.unwrap();
+			let msg = json!("hi").to_string().into_boxed_str().into_boxed_bytes();
+			let reply = pool.dispatch(msg).await.unwrap();
+			assert_eq!(*reply, *b"\"hi\"");
+			pool.shutdown().await.unwrap();
+			Ok(())
+		});
+	}
+}
@@ -193,6 +193,17 @@ impl Isolate {
 	/// startup_data defines the snapshot or script used at startup to
 	/// initialize the isolate.
 	pub fn new(startup_data:StartupData, will_snapshot:bool) -> Self {
+		Self::new_with_heap_limit(startup_data, will_snapshot, 0)
+	}
+
+	/// Like `new`, but caps the V8 heap at `max_heap_mb` megabytes. Close to
+	/// the cap, the running script is terminated rather than the process being
+	/// aborted, and `heap_limit_reached()` starts returning true.
+	pub fn with_heap_limit(startup_data:StartupData, max_heap_mb:usize) -> Self {
+		Self::new_with_heap_limit(startup_data, false, max_heap_mb)
+	}
+
+	fn new_with_heap_limit(startup_data:StartupData, will_snapshot:bool, max_heap_mb:usize) -> Self {
 		DENO_INIT.call_once(|| {
 			unsafe { libdeno::deno_init() };
 		});
@@ -207,6 +218,7 @@ impl Isolate {
 			shared:shared.as_deno_buf(),
 			recv_cb:Self::pre_dispatch,
 			dyn_import_cb:Self::dyn_import,
+			max_heap_size_mb:max_heap_mb,
 		};
 
 		let mut startup_script:Option<OwnedScript> = None;
@@ -276,6 +288,12 @@ impl Isolate {
 		IsolateHandle { shared_libdeno_isolate:self.shared_libdeno_isolate.clone() }
 	}
 
+	/// Returns true once a script was terminated for getting close to the heap
+	/// cap given to `with_heap_limit`.
+	pub fn heap_limit_reached(&self) -> bool {
+		unsafe { libdeno::deno_heap_limit_reached(self.libdeno_isolate) != 0 }
+	}
+
 	/// Executes a bit of built-in JavaScript to provide Deno.sharedQueue.
 	fn shared_init(&mut self) {
 		if self.needs_init {
@@ -1172,6 +1190,21 @@ pub mod tests {
 		t2.join().unwrap();
 	}
 
+	#[test]
+	fn heap_limit() {
+		let mut isolate = Isolate::with_heap_limit(StartupData::None, 16);
+		assert!(!isolate.heap_limit_reached());
+		let res = isolate.execute(
+			"heap_limit.js",
+			r#"
+        const chunks = [];
+        while (true) { chunks.push(new Array(1024 * 1024).fill(1)); }
+      "#,
+		);
+		assert_eq!(res.unwrap_err().to_string(), "Uncaught Error: execution terminated");
+		assert!(isolate.heap_limit_reached());
+	}
+
 	#[test]
 	fn dangling_shared_isolate() {
 		let shared = {
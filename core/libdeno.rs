@@ -191,6 +191,7 @@ pub struct deno_config<'a> {
 	pub shared:deno_buf,
 	pub recv_cb:deno_recv_cb,
 	pub dyn_import_cb:deno_dyn_import_cb,
+	pub max_heap_size_mb:usize,
 }
 
 #[cfg(not(windows))]
@@ -241,6 +242,7 @@ extern {
 		js_source:*const c_char,
 	);
 	pub fn deno_terminate_execution(i:*const isolate);
+	pub fn deno_heap_limit_reached(i:*const isolate) -> c_int;
 	#[allow(dead_code)]
 	pub fn deno_run_microtasks(i:*const isolate, user_data:*const c_void);
 
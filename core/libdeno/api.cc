@@ -51,9 +51,15 @@ Deno* deno_new(deno_config config) {
   if (config.load_snapshot.data_ptr) {
     params.snapshot_blob = &d->snapshot_;
   }
+  if (config.max_heap_size_mb > 0) {
+    params.constraints.set_max_old_space_size(config.max_heap_size_mb);
+  }
 
   v8::Isolate* isolate = v8::Isolate::New(params);
   d->AddIsolate(isolate);
+  if (config.max_heap_size_mb > 0) {
+    isolate->AddNearHeapLimitCallback(deno::NearHeapLimitCallback, d);
+  }
 
   v8::Locker locker(isolate);
   v8::Isolate::Scope isolate_scope(isolate);
@@ -237,6 +243,11 @@ void deno_terminate_execution(Deno* d_) {
   d->isolate_->TerminateExecution();
 }
 
+int deno_heap_limit_reached(Deno* d_) {
+  deno::DenoIsolate* d = reinterpret_cast<deno::DenoIsolate*>(d_);
+  return d->heap_limit_reached_ ? 1 : 0;
+}
+
 void deno_run_microtasks(Deno* d_, void* user_data) {
   deno::DenoIsolate* d = reinterpret_cast<deno::DenoIsolate*>(d_);
 
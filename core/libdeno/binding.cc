@@ -465,6 +465,79 @@ void QueueMicrotask(const v8::FunctionCallbackInfo<v8::Value>& args) {
   isolate->EnqueueMicrotask(args[0].As<v8::Function>());
 }
 
+// ArrayBuffer contents moved out of an isolate by TransferArrayBuffer and not
+// yet taken by ReceiveArrayBuffer, by id. The pins keep the memory alive after
+// the source buffer is detached. Entries whose message is never received are
+// leaked.
+static std::mutex transferred_mutex;
+static std::unordered_map<uint32_t, PinnedBuf> transferred;
+static uint32_t next_transfer_id = 1;
+
+// Detaches an ArrayBuffer and returns an id that ReceiveArrayBuffer, in any
+// isolate, turns back into a buffer with the same memory. Nothing is copied.
+void TransferArrayBuffer(const v8::FunctionCallbackInfo<v8::Value>& args) {
+  v8::Isolate* isolate = args.GetIsolate();
+  DenoIsolate* d = DenoIsolate::FromIsolate(isolate);
+
+  if (!args[0]->IsArrayBuffer()) {
+    ThrowInvalidArgument(isolate);
+    return;
+  }
+  auto ab = args[0].As<v8::ArrayBuffer>();
+  // External buffers aren't owned by the allocator, and the import buffer is
+  // reused for every small op response.
+  if (!ab->IsDetachable() || ab->IsExternal() ||
+      ab->GetBackingStore()->Data() == d->global_import_buf_ptr_) {
+    ThrowInvalidArgument(isolate);
+    return;
+  }
+
+  uint32_t id = 0;
+  if (ab->ByteLength() > 0) {
+    PinnedBuf pin(v8::Uint8Array::New(ab, 0, ab->ByteLength()));
+    std::lock_guard<std::mutex> lock(transferred_mutex);
+    id = next_transfer_id++;
+    if (next_transfer_id == 0) {
+      next_transfer_id = 1;
+    }
+    transferred.emplace(id, std::move(pin));
+  }
+  ab->Detach();
+  args.GetReturnValue().Set(v8::Integer::NewFromUnsigned(isolate, id));
+}
+
+// Takes a buffer moved by TransferArrayBuffer. Each id can be received once.
+void ReceiveArrayBuffer(const v8::FunctionCallbackInfo<v8::Value>& args) {
+  v8::Isolate* isolate = args.GetIsolate();
+
+  if (!args[0]->IsUint32()) {
+    ThrowInvalidArgument(isolate);
+    return;
+  }
+  uint32_t id = args[0].As<v8::Uint32>()->Value();
+  if (id == 0) {
+    args.GetReturnValue().Set(v8::ArrayBuffer::New(isolate, 0));
+    return;
+  }
+
+  PinnedBuf::Raw raw;
+  {
+    std::lock_guard<std::mutex> lock(transferred_mutex);
+    auto it = transferred.find(id);
+    if (it == transferred.end()) {
+      ThrowInvalidArgument(isolate);
+      return;
+    }
+    raw = it->second.IntoRaw();
+    transferred.erase(it);
+  }
+  // The new buffer takes over the pin's reference, the allocator frees the
+  // memory when it is collected.
+  auto ab = v8::ArrayBuffer::New(isolate, raw.pin, raw.data_len,
+                                 v8::ArrayBufferCreationMode::kInternalized);
+  args.GetReturnValue().Set(ab);
+}
+
 void InitializeContext(v8::Isolate* isolate, v8::Local<v8::Context> context) {
   v8::HandleScope handle_scope(isolate);
   v8::Context::Scope context_scope(context);
@@ -501,6 +574,24 @@ void InitializeContext(v8::Isolate* isolate, v8::Local<v8::Context> context) {
   CHECK(core_val->Set(context, deno::v8_str("errorToJSON"), error_to_json_val)
             .FromJust());
 
+  auto transfer_array_buffer_tmpl =
+      v8::FunctionTemplate::New(isolate, TransferArrayBuffer);
+  auto transfer_array_buffer_val =
+      transfer_array_buffer_tmpl->GetFunction(context).ToLocalChecked();
+  CHECK(core_val
+            ->Set(context, deno::v8_str("transferArrayBuffer"),
+                  transfer_array_buffer_val)
+            .FromJust());
+
+  auto receive_array_buffer_tmpl =
+      v8::FunctionTemplate::New(isolate, ReceiveArrayBuffer);
+  auto receive_array_buffer_val =
+      receive_array_buffer_tmpl->GetFunction(context).ToLocalChecked();
+  CHECK(core_val
+            ->Set(context, deno::v8_str("receiveArrayBuffer"),
+                  receive_array_buffer_val)
+            .FromJust());
+
   CHECK(core_val->SetAccessor(context, deno::v8_str("shared"), Shared)
             .FromJust());
 
@@ -581,6 +672,16 @@ v8::MaybeLocal<v8::Promise> HostImportModuleDynamicallyCallback(
   return handle_scope.Escape(promise);
 }
 
+size_t NearHeapLimitCallback(void* data, size_t current_heap_limit,
+                             size_t initial_heap_limit) {
+  auto* d = static_cast<DenoIsolate*>(data);
+  d->heap_limit_reached_ = true;
+  d->isolate_->TerminateExecution();
+  // Give V8 room to unwind the terminated script instead of aborting with an
+  // OOM before the termination takes effect.
+  return current_heap_limit * 2;
+}
+
 void DenoIsolate::AddIsolate(v8::Isolate* isolate) {
   isolate_ = isolate;
   isolate_->SetCaptureStackTraceForUncaughtExceptions(
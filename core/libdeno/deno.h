@@ -63,6 +63,7 @@ typedef struct {
   deno_buf shared;              // Shared buffer to be mapped to libdeno.shared
   deno_recv_cb recv_cb;         // Maps to Deno.core.send() calls.
   deno_dyn_import_cb dyn_import_cb;
+  size_t max_heap_size_mb;  // Default 0, which keeps V8's heap limit.
 } deno_config;
 
 // Create a new deno isolate.
@@ -121,6 +122,10 @@ const char* deno_last_exception(Deno* d);
 
 void deno_terminate_execution(Deno* d);
 
+// Returns 1 if execution was terminated because the isolate got close to
+// config.max_heap_size_mb, 0 otherwise.
+int deno_heap_limit_reached(Deno* d);
+
 void deno_run_microtasks(Deno* d, void* user_data);
 // Module API
 
@@ -42,7 +42,8 @@ class DenoIsolate {
         resolve_cb_(nullptr),
         next_dyn_import_id_(0),
         dyn_import_cb_(config.dyn_import_cb),
-        has_snapshotted_(false) {
+        has_snapshotted_(false),
+        heap_limit_reached_(false) {
     if (config.load_snapshot.data_ptr) {
       snapshot_.data =
           reinterpret_cast<const char*>(config.load_snapshot.data_ptr);
@@ -118,6 +119,7 @@ class DenoIsolate {
   v8::Persistent<v8::ArrayBuffer> global_import_buf_;
   v8::Persistent<v8::SharedArrayBuffer> shared_ab_;
   bool has_snapshotted_;
+  bool heap_limit_reached_;
 };
 
 class UserDataScope {
@@ -163,6 +165,10 @@ void Shared(v8::Local<v8::Name> property,
             const v8::PropertyCallbackInfo<v8::Value>& info);
 void MessageCallback(v8::Local<v8::Message> message, v8::Local<v8::Value> data);
 void QueueMicrotask(const v8::FunctionCallbackInfo<v8::Value>& args);
+void TransferArrayBuffer(const v8::FunctionCallbackInfo<v8::Value>& args);
+void ReceiveArrayBuffer(const v8::FunctionCallbackInfo<v8::Value>& args);
+size_t NearHeapLimitCallback(void* data, size_t current_heap_limit,
+                             size_t initial_heap_limit);
 static intptr_t external_references[] = {
     reinterpret_cast<intptr_t>(Print),
     reinterpret_cast<intptr_t>(Recv),
@@ -172,6 +178,8 @@ static intptr_t external_references[] = {
     reinterpret_cast<intptr_t>(Shared),
     reinterpret_cast<intptr_t>(MessageCallback),
     reinterpret_cast<intptr_t>(QueueMicrotask),
+    reinterpret_cast<intptr_t>(TransferArrayBuffer),
+    reinterpret_cast<intptr_t>(ReceiveArrayBuffer),
     0};
 
 static const deno_buf empty_buf = {nullptr, 0};
@@ -33,7 +33,9 @@ pub type OpDispatcher = dyn Fn(&[u8], Option<PinnedBuf>) -> CoreOp + Send + Sync
 
 #[derive(Default)]
 pub struct OpRegistry {
-	dispatchers:RwLock<Vec<Arc<Box<OpDispatcher>>>>,
+	/// Indexed by `OpId`. Unregistered ops leave a `None` behind so that ids
+	/// are never reused.
+	dispatchers:RwLock<Vec<Option<Arc<Box<OpDispatcher>>>>>,
 	name_to_id:RwLock<HashMap<String, OpId>>,
 }
 
@@ -58,12 +60,32 @@ impl OpRegistry {
 		let existing = name_lock.insert(name.to_string(), op_id);
 		assert!(existing.is_none(), format!("Op already registered: {}", name));
 
-		lock.push(Arc::new(Box::new(op)));
+		lock.push(Some(Arc::new(Box::new(op))));
 		drop(name_lock);
 		drop(lock);
 		op_id
 	}
 
+	/// Removes an op. Later calls with its id behave like calls with an id that
+	/// was never registered. Calls already in progress keep their own reference
+	/// to the dispatcher and complete normally. Returns false if there was no
+	/// such op.
+	pub fn unregister(&self, op_id:OpId) -> bool {
+		// Op 0 is the op map itself.
+		if op_id == 0 {
+			return false;
+		}
+		let mut lock = self.dispatchers.write().unwrap();
+		let removed = match lock.get_mut(op_id as usize) {
+			Some(slot) => slot.take().is_some(),
+			None => false,
+		};
+		if removed {
+			self.name_to_id.write().unwrap().retain(|_name, id| *id != op_id);
+		}
+		removed
+	}
+
 	fn json_map(&self) -> Buf {
 		let lock = self.name_to_id.read().unwrap();
 		let op_map_json = serde_json::to_string(&*lock).unwrap();
@@ -85,7 +107,7 @@ impl OpRegistry {
 			return Some(Op::Sync(self.json_map()));
 		}
 		let lock = self.dispatchers.read().unwrap();
-		if let Some(op) = lock.get(op_id as usize) {
+		if let Some(Some(op)) = lock.get(op_id as usize) {
 			let op_ = Arc::clone(&op);
 			// This should allow for changes to the dispatcher list during a call.
 			drop(lock);
@@ -167,3 +189,25 @@ fn register_op_during_call() {
 	let res = op_registry.call(100, &[], None);
 	assert!(res.is_none());
 }
+
+#[test]
+fn unregister_op() {
+	let op_registry = OpRegistry::new();
+	let test_id = op_registry.register("test", |_, _| CoreOp::Sync(Box::new([])));
+	let other_id = op_registry.register("other", |_, _| CoreOp::Sync(Box::new([])));
+
+	assert!(op_registry.unregister(test_id));
+	assert!(!op_registry.unregister(test_id));
+	assert!(!op_registry.unregister(0));
+	assert!(op_registry.call(test_id, &[], None).is_none());
+	assert!(op_registry.call(other_id, &[], None).is_some());
+
+	let mut expected = HashMap::new();
+	expected.insert("ops".to_string(), 0);
+	expected.insert("other".to_string(), other_id);
+	assert_eq!(*op_registry.name_to_id.read().unwrap(), expected);
+
+	// Ids are not reused.
+	let new_id = op_registry.register("test", |_, _| CoreOp::Sync(Box::new([])));
+	assert!(new_id > other_id);
+}
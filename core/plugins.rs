@@ -2,6 +2,11 @@ use crate::{libdeno::PinnedBuf, ops::CoreOp};
 
 pub type PluginInitFn = fn(context:&mut dyn PluginInitContext);
 
+/// Version of the interface between deno and native plugins. Bump it whenever
+/// `PluginInitContext`, `CoreOp` or anything else a plugin touches changes
+/// layout; plugins exporting a different version are refused at load time.
+pub const PLUGIN_ABI_VERSION:u32 = 1;
+
 pub trait PluginInitContext {
 	fn register_op(
 		&mut self,
@@ -13,6 +18,10 @@ pub trait PluginInitContext {
 #[macro_export]
 macro_rules! init_fn {
 	($fn:path) => {
+		#[no_mangle]
+		#[allow(non_upper_case_globals)]
+		pub static deno_plugin_abi_version:u32 = $crate::PLUGIN_ABI_VERSION;
+
 		#[no_mangle]
 		pub fn deno_plugin_init(context:&mut dyn PluginInitContext) { $fn(context) }
 	};
@@ -6,7 +6,12 @@
 // resources. Resources may or may not correspond to a real operating system
 // file descriptor (hence the different name).
 
-use std::{self, any::Any, collections::HashMap};
+use std::{
+	self,
+	any::Any,
+	collections::HashMap,
+	sync::atomic::{AtomicU64, Ordering},
+};
 
 use downcast_rs::Downcast;
 
@@ -21,9 +26,27 @@ type ResourceMap = HashMap<ResourceId, (String, Box<dyn Resource>)>;
 #[derive(Default)]
 pub struct ResourceTable {
 	map:ResourceMap,
+	/// Optional human-readable details, such as a file path or a pid, shown
+	/// next to the resource's name by `describe`.
+	details:HashMap<ResourceId, String>,
+	/// Bytes moved through each open resource by the read and write ops.
+	counters:HashMap<ResourceId, IoCounters>,
 	next_id:u32,
 }
 
+/// Cumulative bytes read from and written to a resource.
+#[derive(Debug, Default)]
+pub struct IoCounters {
+	bytes_read:AtomicU64,
+	bytes_written:AtomicU64,
+}
+
+impl IoCounters {
+	pub fn bytes_read(&self) -> u64 { self.bytes_read.load(Ordering::Relaxed) }
+
+	pub fn bytes_written(&self) -> u64 { self.bytes_written.load(Ordering::Relaxed) }
+}
+
 impl ResourceTable {
 	pub fn get<T:Resource>(&self, rid:ResourceId) -> Option<&T> {
 		if let Some((_name, resource)) = self.map.get(&rid) {
@@ -52,18 +75,74 @@ impl ResourceTable {
 		let rid = self.next_rid();
 		let r = self.map.insert(rid, (name.to_string(), resource));
 		assert!(r.is_none());
+		self.counters.insert(rid, IoCounters::default());
 		rid
 	}
 
+	/// Adds `n` to the bytes read from `rid`, if it is open.
+	pub fn record_read(&self, rid:ResourceId, n:usize) {
+		if let Some(counters) = self.counters.get(&rid) {
+			counters.bytes_read.fetch_add(n as u64, Ordering::Relaxed);
+		}
+	}
+
+	/// Adds `n` to the bytes written to `rid`, if it is open.
+	pub fn record_write(&self, rid:ResourceId, n:usize) {
+		if let Some(counters) = self.counters.get(&rid) {
+			counters.bytes_written.fetch_add(n as u64, Ordering::Relaxed);
+		}
+	}
+
+	pub fn io_counters(&self, rid:ResourceId) -> Option<&IoCounters> { self.counters.get(&rid) }
+
 	pub fn entries(&self) -> Vec<(ResourceId, String)> {
 		self.map.iter().map(|(key, (name, _resource))| (*key, name.clone())).collect()
 	}
 
+	/// Attaches details to an open resource. Returns None if `rid` isn't open.
+	pub fn set_details(&mut self, rid:ResourceId, details:&str) -> Option<()> {
+		if !self.map.contains_key(&rid) {
+			return None;
+		}
+		self.details.insert(rid, details.to_string());
+		Some(())
+	}
+
+	pub fn details(&self, rid:ResourceId) -> Option<&str> {
+		self.details.get(&rid).map(String::as_str)
+	}
+
+	/// Like `entries`, but each name is followed by the resource's details, if
+	/// any, e.g. "fsFile:/tmp/log.txt" or "child:1234".
+	pub fn describe(&self) -> Vec<(ResourceId, String)> {
+		self.map
+			.iter()
+			.map(|(key, (name, _resource))| {
+				match self.details.get(key) {
+					Some(details) => (*key, format!("{}:{}", name, details)),
+					None => (*key, name.clone()),
+				}
+			})
+			.collect()
+	}
+
 	// close(2) is done by dropping the value. Therefore we just need to remove
 	// the resource from the resource table.
 	pub fn close(&mut self, rid:ResourceId) -> Option<()> {
+		self.details.remove(&rid);
+		self.counters.remove(&rid);
 		self.map.remove(&rid).map(|(_name, _resource)| ())
 	}
+
+	/// Closes every resource except those in `keep`, returning how many were
+	/// closed.
+	pub fn close_all(&mut self, keep:&[ResourceId]) -> usize {
+		// Collect first: dropping a resource may run arbitrary code, and the
+		// map must not be borrowed while it is being modified.
+		let rids:Vec<ResourceId> =
+			self.map.keys().filter(|rid| !keep.contains(rid)).cloned().collect();
+		rids.into_iter().filter_map(|rid| self.close(rid)).count()
+	}
 }
 
 /// Abstract type representing resource in Deno.
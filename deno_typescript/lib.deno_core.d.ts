@@ -58,6 +58,15 @@ declare interface DenoCore {
 	evalContext(code: string): [any, EvalErrorInfo | null];
 
 	errorToJSON: (e: Error) => string;
+
+	/** Detaches `buffer` and returns an id for its contents, which
+	 * `receiveArrayBuffer` in any isolate turns back into a buffer without
+	 * copying. Throws if the buffer can't be transferred. */
+	transferArrayBuffer(buffer: ArrayBuffer): number;
+
+	/** Takes the buffer moved by `transferArrayBuffer` under `id`. Throws if
+	 * there is none, each id can be received once. */
+	receiveArrayBuffer(id: number): ArrayBuffer;
 }
 
 declare interface DenoInterface {
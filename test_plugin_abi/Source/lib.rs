@@ -0,0 +1,13 @@
+// Copyright 2018-2019 the Deno authors. All rights reserved. MIT license.
+// A plugin stamped with the wrong plugin ABI version, which deno must refuse
+// to load. See `cli/ops/plugins.rs`.
+use deno::{PluginInitContext, PLUGIN_ABI_VERSION};
+
+#[no_mangle]
+#[allow(non_upper_case_globals)]
+pub static deno_plugin_abi_version:u32 = PLUGIN_ABI_VERSION + 1;
+
+#[no_mangle]
+pub fn deno_plugin_init(_context:&mut dyn PluginInitContext) {
+	panic!("a plugin built for another ABI version must not be initialized");
+}
@@ -4,12 +4,15 @@
 # root directory.
 from collections import namedtuple
 from contextlib import contextmanager
+import gzip
 import os
 import SimpleHTTPServer
 import SocketServer
 import socket
 import sys
+from StringIO import StringIO
 from time import sleep
+from urlparse import urlparse
 from threading import Thread
 from util import root_path
 
@@ -18,9 +21,30 @@ REDIRECT_PORT = 4546
 ANOTHER_REDIRECT_PORT = 4547
 DOUBLE_REDIRECTS_PORT = 4548
 INF_REDIRECTS_PORT = 4549
+PROXY_PORT = 4555
 
 QUIET = '-v' not in sys.argv and '--verbose' not in sys.argv
 
+ENCODED_BODY = 'Hello from an encoded body!\n'
+
+
+def gzip_encode(data):
+    out = StringIO()
+    with gzip.GzipFile(fileobj=out, mode='wb') as f:
+        f.write(data)
+    return out.getvalue()
+
+
+def brotli_encode(data):
+    # A single uncompressed meta-block, so no brotli module is needed.
+    # Header bits, LSB first: WBITS=16 (0), ISLAST (0), MNIBBLES=4 (00),
+    # MLEN-1 (16 bits), ISUNCOMPRESSED (1), padded to 3 bytes.
+    assert 0 < len(data) <= 0x10000
+    header = ((len(data) - 1) << 4) | (1 << 20)
+    last = chr(0x03)  # ISLAST and ISLASTEMPTY
+    return (chr(header & 0xff) + chr((header >> 8) & 0xff) +
+            chr(header >> 16) + data + last)
+
 
 class QuietSimpleHTTPRequestHandler(SimpleHTTPServer.SimpleHTTPRequestHandler):
     def log_request(self, code='-', size='-'):
@@ -52,6 +76,36 @@ class ContentTypeHandler(QuietSimpleHTTPRequestHandler):
                       '\r\n--boundary--\r\n'
                       'Epilogue'))
             return
+        if "etag_script.ts" in self.path:
+            # Answers conditional requests, unlike SimpleHTTPRequestHandler.
+            etag = '"33a64df5"'
+            self.protocol_version = 'HTTP/1.1'
+            if self.headers.getheader('If-None-Match') == etag:
+                self.send_response(304, 'Not Modified')
+                self.send_header('ETag', etag)
+                self.send_header('Content-Length', '0')
+                self.end_headers()
+                return
+            body = 'export const etag = "33a64df5";\n'
+            self.send_response(200, 'OK')
+            self.send_header('Content-Type', 'application/typescript')
+            self.send_header('ETag', etag)
+            self.send_header('Content-Length', str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+            return
+        if "encoded/gzip" in self.path or "encoded/br" in self.path:
+            encoding = "gzip" if "gzip" in self.path else "br"
+            body = (gzip_encode if encoding == "gzip" else
+                    brotli_encode)(ENCODED_BODY)
+            self.protocol_version = 'HTTP/1.1'
+            self.send_response(200, 'OK')
+            self.send_header('Content-Type', 'text/plain')
+            self.send_header('Content-Encoding', encoding)
+            self.send_header('Content-Length', str(len(body)))
+            self.end_headers()
+            self.wfile.write(body)
+            return
         return SimpleHTTPServer.SimpleHTTPRequestHandler.do_GET(self)
 
     def do_POST(self):
@@ -173,6 +227,22 @@ def inf_redirects_server():
     return base_redirect_server(INF_REDIRECTS_PORT, INF_REDIRECTS_PORT)
 
 
+# plain HTTP proxy that serves every absolute-form request from the deno root
+# directory, as if it had been forwarded to the main server
+def proxy_server():
+    os.chdir(root_path)
+
+    class ProxyHandler(ContentTypeHandler):
+        def do_GET(self):
+            self.path = urlparse(self.path).path
+            return ContentTypeHandler.do_GET(self)
+
+    s = get_socket(PROXY_PORT, ProxyHandler)
+    if not QUIET:
+        print "proxy server http://localhost:%d/" % PROXY_PORT
+    return RunningServer(s, start(s))
+
+
 def start(s):
     thread = Thread(target=s.serve_forever, kwargs={"poll_interval": 0.05})
     thread.daemon = True
@@ -183,7 +253,7 @@ def start(s):
 @contextmanager
 def spawn():
     servers = (server(), redirect_server(), another_redirect_server(),
-               double_redirects_server())
+               double_redirects_server(), proxy_server())
     while any(not s.thread.is_alive() for s in servers):
         sleep(0.01)
     try: